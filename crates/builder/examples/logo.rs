@@ -1,4 +1,3 @@
-use itertools::izip;
 use rsdf_builder::*;
 use rsdf_core::*;
 use std::fs::File;
@@ -84,23 +83,26 @@ fn main() {
 
   let input_filename = "rsdf.png";
   let output_filename = "rsdf_render.png";
-  let image = Image::new(&input_filename, [97, 86]);
+  let image = Image::new(input_filename, [97, 86]);
   gen(image, shape).flush();
-  view(&input_filename, &output_filename);
+  view(input_filename, output_filename);
 }
 
 fn gen(mut image: Image, shape: Shape) -> Image {
+  let max_distance = RasterConfig::default().max_distance;
   let start_time = std::time::Instant::now();
   for y in 0..image.height {
     for x in 0..image.width {
       let point = Point::from((x as f32, y as f32));
       // "single channel"
       // let sample = shape.sample_single_channel(point);
-      // let mut color @ [r, g, b] = [sample; 3].map(|sp| distance_color(sp));
+      // let mut color @ [r, g, b] =
+      //   [sample; 3].map(|sp| distance_color(sp, max_distance));
 
       // multi channel
       let sample = shape.sample(point);
-      let mut color @ [r, g, b] = sample.map(|sp| distance_color(sp));
+      let mut color @ [r, g, b] =
+        sample.map(|distance| distance_color(distance, max_distance));
 
       // clip remaining values when bulk is 0
       let sum = r as u16 + g as u16 + b as u16;
@@ -119,80 +121,32 @@ fn gen(mut image: Image, shape: Shape) -> Image {
 }
 
 fn view(input_filename: &str, output_filename: &str) {
+  let max_distance = RasterConfig::default().max_distance;
   let decoder = png::Decoder::new(File::open(input_filename).unwrap());
   let mut reader = decoder.read_info().unwrap();
   let mut buf = vec![0; reader.output_buffer_size()];
   let info = reader.next_frame(&mut buf).unwrap();
 
   let bytes = &buf[..info.buffer_size()];
-
-  let sdf_width = info.width as usize;
-  let sdf_height = info.height as usize;
-
-  let mut image =
-    Image::new(&output_filename, [sdf_width * 10, sdf_height * 10]);
-
-  for y in 0..image.height {
-    for x in 0..image.width {
-      // normalised coordinates
-      let x_norm = x as f32 / (image.width) as f32;
-      let y_norm = y as f32 / (image.height) as f32;
-
-      // points in sdf coordinate system
-      let x_sdf_p = x_norm * (sdf_width - 1) as f32;
-      let y_sdf_p = y_norm * (sdf_height - 1) as f32;
-
-      // sample from points, bilinear
-      let pixel = {
-        let sample_sdf = |x, y| {
-          let offset = (y * sdf_width + x) * 3;
-          [bytes[offset], bytes[offset + 1], bytes[offset + 2]]
-        };
-
-        let x1 = (x_sdf_p - 0.5).floor();
-        let y1 = (y_sdf_p - 0.5).floor();
-        let x2 = x1 + 1.;
-        let y2 = y1 + 1.;
-        let wx = x_sdf_p - x1 - 0.5;
-        let wy = y_sdf_p - y1 - 0.5;
-
-        let t1 = sample_sdf(x1 as usize, y1 as usize)
-          .map(|v| (1. - wx) * (1. - wy) * v as f32);
-        let t2 = sample_sdf(x2 as usize, y1 as usize)
-          .map(|v| wx * (1. - wy) * v as f32);
-        let t3 = sample_sdf(x1 as usize, y2 as usize)
-          .map(|v| (1. - wx) * wy * v as f32);
-        let t4 =
-          sample_sdf(x2 as usize, y2 as usize).map(|v| wx * wy * v as f32);
-
-        let result: Vec<f32> = izip!(t1, t2, t3, t4)
-          .map(|(v1, v2, v3, v4)| v1 + v2 + v3 + v4)
-          .collect();
-
-        [result[0] as u8, result[1] as u8, result[2] as u8]
-      };
-
-      // find the median value
-      let median = |a, b, c| {
-        if (a <= b && b <= c) || (c <= b && b <= a) {
-          b
-        } else if (a <= c && c <= b) || (b <= c && c <= a) {
-          c
-        } else {
-          a
-        }
-      };
-      let value = median(pixel[0], pixel[1], pixel[2]);
-
-      let (mut r_output, mut g_output, mut b_output) = (13, 17, 23);
-      if value > 123 {
-        r_output = 255;
-        g_output = 255;
-        b_output = 255;
-      }
-
-      image.set_pixel([x, y], [r_output, g_output, b_output]);
-    }
+  let sdf_size = [info.width as usize, info.height as usize];
+  let field = Field::from_samples(
+    sdf_size,
+    bytes
+      .chunks_exact(3)
+      .map(|pixel| {
+        [pixel[0], pixel[1], pixel[2]]
+          .map(|color| color_distance(color, max_distance))
+      })
+      .collect(),
+  );
+
+  let coverage = CpuRenderer.render(&field, sdf_size.map(|n| n * 10));
+
+  let mut image = Image::new(output_filename, coverage.size());
+  for (x, y, [coverage]) in coverage.pixels() {
+    let background = [13., 17., 23.];
+    let color = background.map(|c| (c + (255. - c) * coverage) as u8);
+    image.set_pixel([x, y], color);
   }
   image.flush();
 }
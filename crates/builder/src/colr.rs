@@ -0,0 +1,269 @@
+use super::*;
+use rustybuzz::ttf_parser::colr::{ClipBox, CompositeMode, Paint, Painter};
+use rustybuzz::ttf_parser::{self, GlyphId, RgbaColor, Transform};
+
+/// A layer of a [`LayeredGlyph`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColourLayer<const N: usize> {
+  /// The glyph whose outline makes up the layer
+  pub glyph: u16,
+  /// The layer's colour, from the chosen palette of the font's `CPAL`
+  /// table, or the foreground colour
+  pub colour: RgbaColor,
+  /// The layer's field, on the canvas shared by every layer of the glyph
+  pub field: Field<N>,
+}
+
+/// The layers of a colour glyph, sampled by [`colour_glyph_layers`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayeredGlyph<const N: usize> {
+  /// The layers, bottom first, to be drawn over one another in order
+  pub layers: Vec<ColourLayer<N>>,
+  /// Where the top-left pixel of every layer's field lies relative to the
+  /// glyph's origin on the baseline, in pixels with y pointing down, as for
+  /// [`PreparedGlyph::offset`]
+  pub offset: Vector,
+}
+
+/// Sample a field for each layer of a `COLR` colour glyph
+///
+/// Colour fonts draw such glyphs as a stack of other glyphs, each filled
+/// with a colour from the font's palettes, rather than with the glyph's own
+/// outline. Every layer is framed on the same canvas, big enough for all of
+/// them, so that the fields line up when drawn over one another. Layers are
+/// coloured from the `palette`th palette, or with the `foreground` colour
+/// the text is drawn in.
+///
+/// Returns `None` for a glyph without layers, which should be drawn from its
+/// outline as usual. Only glyphs made of solid colour layers are supported,
+/// such as every `COLR` version 0 glyph: the gradients, transforms & blending
+/// of version 1 glyphs can't be represented as layers of solid colour.
+///
+/// ```no_run
+/// # use rsdf_builder::colour_glyph_layers;
+/// # use rsdf_core::RasterConfig;
+/// use rustybuzz::ttf_parser::RgbaColor;
+///
+/// let data = std::fs::read("emoji.ttf").unwrap();
+/// let face = rustybuzz::Face::from_slice(&data, 0).unwrap();
+/// let glyph = face.glyph_index('😀').unwrap();
+/// let black = RgbaColor::new(0, 0, 0, 255);
+/// let config = RasterConfig::default();
+/// let glyph =
+///   colour_glyph_layers::<3>(&face, glyph.0, 0, black, 64., config).unwrap();
+/// for layer in &glyph.layers {
+///   let RgbaColor { red, green, blue, alpha } = layer.colour;
+/// }
+/// ```
+pub fn colour_glyph_layers<const N: usize>(
+  face: &ttf_parser::Face,
+  glyph: u16,
+  palette: u16,
+  foreground: RgbaColor,
+  pixels_per_em: f32,
+  config: RasterConfig,
+) -> Option<LayeredGlyph<N>>
+where
+  Shape: SampleChannels<N>,
+{
+  let mut painter = LayerPainter::default();
+  face.paint_color_glyph(GlyphId(glyph), palette, foreground, &mut painter)?;
+  let layers = painter.layers?;
+  if layers.is_empty() {
+    return None;
+  }
+  let scale = pixels_per_em / face.units_per_em() as f32;
+  sample_layers(&layers, scale, config, |glyph| {
+    ShapeBuilder::new()
+      .glyph_outline(face, GlyphId(glyph))
+      .build()
+  })
+}
+
+/// Collects the `(glyph, colour)` of each layer of a colour glyph, as
+/// painted by [`ttf_parser::Face::paint_color_glyph`]
+///
+/// A layer is a glyph's outline filled with a solid colour, optionally
+/// clipped to that same outline, as version 1 glyphs do. Anything else
+/// leaves the glyph unsupported, with no layers.
+#[derive(Debug)]
+struct LayerPainter {
+  layers: Option<Vec<(u16, RgbaColor)>>,
+  /// The outline stored for the next paint
+  outline: Option<u16>,
+  /// Whether the painting is clipped to the stored outline
+  clipped: bool,
+}
+
+impl Default for LayerPainter {
+  fn default() -> Self {
+    LayerPainter {
+      layers: Some(vec![]),
+      outline: None,
+      clipped: false,
+    }
+  }
+}
+
+impl LayerPainter {
+  fn unsupported(&mut self) {
+    self.layers = None;
+  }
+}
+
+impl<'a> Painter<'a> for LayerPainter {
+  fn outline_glyph(&mut self, glyph_id: GlyphId) {
+    if self.clipped {
+      return self.unsupported();
+    }
+    self.outline = Some(glyph_id.0);
+  }
+
+  fn paint(&mut self, paint: Paint<'a>) {
+    match (paint, self.outline, &mut self.layers) {
+      (Paint::Solid(colour), Some(glyph), Some(layers)) => {
+        layers.push((glyph, colour));
+      },
+      _ => self.unsupported(),
+    }
+  }
+
+  fn push_clip(&mut self) {
+    if self.clipped || self.outline.is_none() {
+      return self.unsupported();
+    }
+    self.clipped = true;
+  }
+
+  fn push_clip_box(&mut self, _: ClipBox) {
+    self.unsupported();
+  }
+
+  fn pop_clip(&mut self) {
+    self.clipped = false;
+    self.outline = None;
+  }
+
+  fn push_layer(&mut self, _: CompositeMode) {
+    self.unsupported();
+  }
+
+  fn pop_layer(&mut self) {}
+
+  fn push_transform(&mut self, _: Transform) {
+    self.unsupported();
+  }
+
+  fn pop_transform(&mut self) {}
+}
+
+/// Frame the outlines of the layers together, and sample each of them
+fn sample_layers<const N: usize>(
+  layers: &[(u16, RgbaColor)],
+  scale: f32,
+  config: RasterConfig,
+  mut outline: impl FnMut(u16) -> Shape,
+) -> Option<LayeredGlyph<N>>
+where
+  Shape: SampleChannels<N>,
+{
+  let outlines: Vec<Shape> =
+    layers.iter().map(|&(glyph, _)| outline(glyph)).collect();
+  // frame the union of the layers, by preparing a shape holding all of them
+  let mut union = ShapeBuilder::new().build();
+  for shape in &outlines {
+    union.append(shape);
+  }
+  let frame = PreparedGlyph::new(union, scale, config)?;
+
+  let layers = layers
+    .iter()
+    .zip(outlines)
+    .map(|(&(glyph, colour), mut shape)| {
+      shape.scale_translate(scale, -frame.offset);
+      ColourLayer {
+        glyph,
+        colour,
+        field: shape.sample_field(frame.size, config),
+      }
+    })
+    .collect();
+  Some(LayeredGlyph {
+    layers,
+    offset: frame.offset,
+  })
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn layer_painter() {
+    let [red, blue] = [
+      RgbaColor::new(255, 0, 0, 255),
+      RgbaColor::new(0, 0, 255, 255),
+    ];
+    // as painted for a version 0 glyph, then a version 1 glyph
+    let mut painter = LayerPainter::default();
+    painter.outline_glyph(GlyphId(10));
+    painter.paint(Paint::Solid(red));
+    painter.outline_glyph(GlyphId(11));
+    painter.push_clip();
+    painter.paint(Paint::Solid(blue));
+    painter.pop_clip();
+    assert_eq!(painter.layers, Some(vec![(10, red), (11, blue)]));
+
+    // nested clips, transforms & painting without an outline aren't layers
+    let mut painter = LayerPainter::default();
+    painter.outline_glyph(GlyphId(10));
+    painter.push_clip();
+    painter.outline_glyph(GlyphId(11));
+    assert_eq!(painter.layers, None);
+    let mut painter = LayerPainter::default();
+    painter.push_transform(Transform::default());
+    assert_eq!(painter.layers, None);
+    let mut painter = LayerPainter::default();
+    painter.paint(Paint::Solid(red));
+    assert_eq!(painter.layers, None);
+  }
+
+  #[test]
+  fn sample_layers() {
+    // a 10 unit square, and a 4 unit square above it
+    let square = |x0: f32, y0: f32, size: f32| {
+      ShapeBuilder::new()
+        .contour((x0, y0))
+        .line((x0 + size, y0))
+        .line((x0 + size, y0 + size))
+        .line((x0, y0 + size))
+        .end_contour()
+        .build()
+    };
+    let config = RasterConfig {
+      max_distance: 2.,
+      ..RasterConfig::default()
+    };
+    let [red, black] =
+      [RgbaColor::new(255, 0, 0, 255), RgbaColor::new(0, 0, 0, 255)];
+    let layers = [(1, red), (2, black)];
+    let glyph =
+      super::sample_layers::<1>(&layers, 1., config, |glyph| match glyph {
+        1 => square(0., -10., 10.),
+        _ => square(3., -14., 4.),
+      })
+      .unwrap();
+
+    assert_eq!(glyph.offset, Vector::new(-2., -16.));
+    let [base, top] = &glyph.layers[..] else {
+      panic!("expected 2 layers");
+    };
+    assert_eq!([base.colour, top.colour], [red, black]);
+    // both layers share the canvas framing the pair of them
+    assert_eq!(base.field.size(), [14, 18]);
+    assert_eq!(top.field.size(), [14, 18]);
+    assert_eq!(base.field.get([7, 11]), [5.]);
+    assert_eq!(top.field.get([7, 4]), [2.]);
+    assert_eq!(top.field.get([7, 11]), [-5.]);
+  }
+}
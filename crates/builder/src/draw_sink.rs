@@ -0,0 +1,165 @@
+use super::*;
+#[cfg(feature = "rustybuzz")]
+use rustybuzz::ttf_parser;
+
+/// Accumulates an outline from HarfBuzz's draw callbacks, see
+/// [`ShapeBuilder::draw_sink`]
+///
+/// The methods match the callbacks of HarfBuzz's `hb_draw_funcs_t`, so that
+/// the draw funcs implementation of whichever binding is in use can forward
+/// to them one to one. HarfBuzz applies the font's variations, along with
+/// any synthetic slant or emboldening, before calling them, so glyphs drawn
+/// after shaping come out as they would be rendered. HarfBuzz draws in font
+/// units scaled by the font's scale, with y pointing up, see
+/// [`ShapeBuilder::font_outline`].
+///
+/// With the `rustybuzz` feature, rustybuzz, HarfBuzz's Rust port, draws into
+/// the sink too, see `DrawSink::draw_glyph`.
+///
+/// ```
+/// # use rsdf_builder::{DrawSink, ShapeBuilder};
+/// // as called by `hb_font_draw_glyph`
+/// let mut sink = DrawSink::new();
+/// sink.move_to(0., 0.);
+/// sink.line_to(10., 0.);
+/// sink.line_to(10., 10.);
+/// sink.line_to(0., 10.);
+/// sink.close_path();
+/// let shape = ShapeBuilder::new().draw_sink(sink).build();
+/// assert_eq!(shape.sample_single_channel((5., -5.)), 5.);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DrawSink {
+  commands: Vec<PathCommand>,
+}
+
+impl DrawSink {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn move_to(&mut self, x: f32, y: f32) {
+    self.commands.push(PathCommand::MoveTo(Point::new(x, y)));
+  }
+
+  pub fn line_to(&mut self, x: f32, y: f32) {
+    self.commands.push(PathCommand::LineTo(Point::new(x, y)));
+  }
+
+  pub fn quadratic_to(
+    &mut self,
+    control_x: f32,
+    control_y: f32,
+    x: f32,
+    y: f32,
+  ) {
+    self.commands.push(PathCommand::QuadTo(
+      Point::new(control_x, control_y),
+      Point::new(x, y),
+    ));
+  }
+
+  pub fn cubic_to(
+    &mut self,
+    control1_x: f32,
+    control1_y: f32,
+    control2_x: f32,
+    control2_y: f32,
+    x: f32,
+    y: f32,
+  ) {
+    self.commands.push(PathCommand::CubicTo(
+      Point::new(control1_x, control1_y),
+      Point::new(control2_x, control2_y),
+      Point::new(x, y),
+    ));
+  }
+
+  pub fn close_path(&mut self) {
+    self.commands.push(PathCommand::ClosePath);
+  }
+
+  /// The path commands drawn so far, with y pointing up
+  pub fn commands(&self) -> &[PathCommand] {
+    &self.commands
+  }
+
+  /// Draw a glyph's outline with rustybuzz, HarfBuzz's Rust port, which
+  /// draws through [`OutlineBuilder`](ttf_parser::OutlineBuilder) callbacks
+  /// that forward to the sink's methods
+  ///
+  /// The outline is taken from the face's current instance, so for variable
+  /// fonts the axes set with
+  /// [`Face::set_variation`](ttf_parser::Face::set_variation) apply. Returns
+  /// `None` for glyphs without an outline, such as spaces.
+  #[cfg(feature = "rustybuzz")]
+  pub fn draw_glyph(
+    face: &ttf_parser::Face,
+    glyph: ttf_parser::GlyphId,
+  ) -> Option<Self> {
+    let mut sink = DrawSink::new();
+    face.outline_glyph(glyph, &mut sink)?;
+    Some(sink)
+  }
+}
+
+#[cfg(feature = "rustybuzz")]
+impl ttf_parser::OutlineBuilder for DrawSink {
+  fn move_to(&mut self, x: f32, y: f32) {
+    DrawSink::move_to(self, x, y);
+  }
+
+  fn line_to(&mut self, x: f32, y: f32) {
+    DrawSink::line_to(self, x, y);
+  }
+
+  fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+    self.quadratic_to(x1, y1, x, y);
+  }
+
+  fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+    self.cubic_to(x1, y1, x2, y2, x, y);
+  }
+
+  fn close(&mut self) {
+    self.close_path();
+  }
+}
+
+impl ShapeBuilder {
+  /// Add the contours of an outline drawn by HarfBuzz into a [`DrawSink`],
+  /// see [`ShapeBuilder::font_outline`]
+  pub fn draw_sink(self, sink: DrawSink) -> Self {
+    self.font_outline(sink.commands)
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn draw_sink() {
+    let mut sink = DrawSink::new();
+    sink.move_to(0., 0.);
+    sink.line_to(0., 10.);
+    sink.quadratic_to(10., 10., 10., 5.);
+    sink.cubic_to(10., 2., 5., 0., 0., 0.);
+    sink.close_path();
+    test_outline::assert_d(sink.commands());
+  }
+
+  #[cfg(feature = "rustybuzz")]
+  #[test]
+  fn outline_builder() {
+    // as called by rustybuzz
+    let mut sink = DrawSink::new();
+    let builder: &mut dyn ttf_parser::OutlineBuilder = &mut sink;
+    builder.move_to(0., 0.);
+    builder.line_to(0., 10.);
+    builder.quad_to(10., 10., 10., 5.);
+    builder.curve_to(10., 2., 5., 0., 0., 0.);
+    builder.close();
+    test_outline::assert_d(sink.commands());
+  }
+}
@@ -0,0 +1,102 @@
+use super::*;
+use freetype::outline::{Curve, Outline};
+use freetype::Vector as FtVector;
+
+impl ShapeBuilder {
+  /// Add the contours of a glyph outline loaded by FreeType
+  ///
+  /// FreeType gives the outlines of glyphs loaded at a pixel size in 26.6
+  /// fixed point, which are converted to pixels. Outlines loaded with
+  /// [`LoadFlag::NO_SCALE`](freetype::face::LoadFlag::NO_SCALE) are in font
+  /// units instead, so come out 64 times too small. The outline has y pointing
+  /// up, see [`ShapeBuilder::font_outline`].
+  ///
+  /// ```no_run
+  /// # use rsdf_builder::ShapeBuilder;
+  /// use freetype::face::LoadFlag;
+  ///
+  /// let library = freetype::Library::init().unwrap();
+  /// let face = library.new_face("font.ttf", 0).unwrap();
+  /// face.set_pixel_sizes(0, 32).unwrap();
+  /// face.load_char('g' as usize, LoadFlag::NO_HINTING).unwrap();
+  /// let outline = face.glyph().outline().unwrap();
+  /// let shape = ShapeBuilder::new().freetype_outline(&outline).build();
+  /// ```
+  pub fn freetype_outline(self, outline: &Outline) -> Self {
+    let mut sink = OutlineSink(vec![]);
+    for contour in outline.contours_iter() {
+      sink.move_to(*contour.start());
+      for curve in contour {
+        match curve {
+          Curve::Line(to) => sink.line_to(to),
+          Curve::Bezier2(control, to) => sink.conic_to(control, to),
+          Curve::Bezier3(control_1, control_2, to) => {
+            sink.cubic_to(control_1, control_2, to)
+          },
+        }
+      }
+      sink.close();
+    }
+    self.font_outline(sink.0)
+  }
+}
+
+/// Collects the outline of a glyph as path commands, following FreeType's
+/// outline decomposition callbacks
+struct OutlineSink(Vec<PathCommand>);
+
+impl OutlineSink {
+  fn move_to(&mut self, to: FtVector) {
+    self.0.push(PathCommand::MoveTo(point(to)));
+  }
+
+  fn line_to(&mut self, to: FtVector) {
+    self.0.push(PathCommand::LineTo(point(to)));
+  }
+
+  fn conic_to(&mut self, control: FtVector, to: FtVector) {
+    self.0.push(PathCommand::QuadTo(point(control), point(to)));
+  }
+
+  fn cubic_to(
+    &mut self,
+    control_1: FtVector,
+    control_2: FtVector,
+    to: FtVector,
+  ) {
+    self.0.push(PathCommand::CubicTo(
+      point(control_1),
+      point(control_2),
+      point(to),
+    ));
+  }
+
+  fn close(&mut self) {
+    self.0.push(PathCommand::ClosePath);
+  }
+}
+
+/// Convert a point in 26.6 fixed point into pixels
+fn point(vector: FtVector) -> Point {
+  Point::new(vector.x as f32 / 64., vector.y as f32 / 64.)
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn outline_sink() {
+    let v = |x: f32, y: f32| FtVector {
+      x: (x * 64.) as _,
+      y: (y * 64.) as _,
+    };
+    let mut sink = OutlineSink(vec![]);
+    sink.move_to(v(0., 0.));
+    sink.line_to(v(0., 10.));
+    sink.conic_to(v(10., 10.), v(10., 5.));
+    sink.cubic_to(v(10., 2.), v(5., 0.), v(0., 0.));
+    sink.close();
+    test_outline::assert_d(&sink.0);
+  }
+}
@@ -0,0 +1,175 @@
+use super::*;
+use kurbo::PathEl;
+
+impl ShapeBuilder {
+  /// Add the contours of a [`kurbo::BezPath`]
+  ///
+  /// Every subpath is closed, as for [`ShapeBuilder::path`]. The winding of
+  /// the path is kept, so use [`ShapeBuilder::filled_path`] on
+  /// [`kurbo_commands`] instead for paths wound either way.
+  ///
+  /// ```
+  /// # use rsdf_builder::ShapeBuilder;
+  /// let path = kurbo::BezPath::from_svg("M0,0 H10 V10 H0 Z").unwrap();
+  /// let shape = ShapeBuilder::new().kurbo_path(&path).build();
+  /// assert_eq!(shape.sample_single_channel((5., 5.)), 5.);
+  /// ```
+  pub fn kurbo_path(self, path: &kurbo::BezPath) -> Self {
+    self.path(kurbo_commands(path.elements().iter().copied()))
+  }
+
+  /// Add the outline of any [`kurbo::Shape`], such as a circle or rounded
+  /// rectangle, as a filled contour
+  ///
+  /// kurbo approximates curved shapes with beziers within `tolerance`; add
+  /// a [`kurbo::Arc`] with [`arc_commands`] to keep it exact.
+  pub fn kurbo_shape(self, shape: &impl kurbo::Shape, tolerance: f64) -> Self {
+    self.filled_path(kurbo_commands(shape.path_elements(tolerance)))
+  }
+}
+
+impl From<PathEl> for PathCommand {
+  fn from(element: PathEl) -> Self {
+    let point = |p: kurbo::Point| Point::new(p.x as f32, p.y as f32);
+    match element {
+      PathEl::MoveTo(p) => PathCommand::MoveTo(point(p)),
+      PathEl::LineTo(p) => PathCommand::LineTo(point(p)),
+      PathEl::QuadTo(c, p) => PathCommand::QuadTo(point(c), point(p)),
+      PathEl::CurveTo(c1, c2, p) => {
+        PathCommand::CubicTo(point(c1), point(c2), point(p))
+      },
+      PathEl::ClosePath => PathCommand::ClosePath,
+    }
+  }
+}
+
+/// Convert kurbo path elements into path commands
+pub fn kurbo_commands(
+  elements: impl IntoIterator<Item = PathEl>,
+) -> Vec<PathCommand> {
+  elements.into_iter().map(PathCommand::from).collect()
+}
+
+/// Convert a [`kurbo::Arc`] into a move to its start, followed by
+/// elliptical arcs
+///
+/// The arc is kept exact, rather than approximated by beziers. Arcs
+/// sweeping a whole turn or more are split in two, since a single elliptical
+/// arc can't start & end at the same point; anything past a whole turn
+/// retraces the ellipse, so is dropped.
+///
+/// ```
+/// # use rsdf_builder::{arc_commands, ShapeBuilder};
+/// let circle = kurbo::Arc::new((5., 5.), (5., 5.), 0., 7., 0.);
+/// let shape = ShapeBuilder::new().path(arc_commands(&circle)).build();
+/// assert_eq!(shape.contours.len(), 1);
+/// assert!((shape.sample_single_channel((5., 5.)) - 5.).abs() < 1e-4);
+/// ```
+pub fn arc_commands(arc: &kurbo::Arc) -> Vec<PathCommand> {
+  use std::f64::consts::{PI, TAU};
+
+  let point_at = |angle: f64| {
+    let (sin, cos) = angle.sin_cos();
+    let (rot_sin, rot_cos) = arc.x_rotation.sin_cos();
+    let (x, y) = (arc.radii.x * cos, arc.radii.y * sin);
+    Point::new(
+      (arc.center.x + x * rot_cos - y * rot_sin) as f32,
+      (arc.center.y + x * rot_sin + y * rot_cos) as f32,
+    )
+  };
+  let arc_to = |sweep: f64, end: f64| PathCommand::ArcTo {
+    rx: arc.radii.x as f32,
+    ry: arc.radii.y as f32,
+    phi: arc.x_rotation as f32,
+    large_arc: sweep.abs() > PI,
+    sweep: sweep > 0.,
+    end: point_at(end),
+  };
+
+  let start = arc.start_angle;
+  let sweep = arc.sweep_angle.clamp(-TAU, TAU);
+  let mut commands = vec![PathCommand::MoveTo(point_at(start))];
+  // a sweep just short of a whole turn, as left by rounding, would still
+  // end at its start once converted to f32
+  if TAU - sweep.abs() < 1e-4 {
+    commands.push(arc_to(sweep / 2., start + sweep / 2.));
+    commands.push(arc_to(sweep / 2., start + sweep));
+  } else {
+    commands.push(arc_to(sweep, start + sweep));
+  }
+  commands
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+  use float_cmp::assert_approx_eq;
+
+  #[test]
+  fn kurbo_path() {
+    let mut path = kurbo::BezPath::new();
+    path.move_to((0., 0.));
+    path.line_to((10., 0.));
+    path.quad_to((10., 5.), (10., 10.));
+    path.curve_to((7., 10.), (3., 10.), (0., 10.));
+    path.close_path();
+    let commands = kurbo_commands(path.elements().iter().copied());
+    assert_eq!(
+      commands[2],
+      PathCommand::QuadTo(Point::new(10., 5.), Point::new(10., 10.))
+    );
+    assert_eq!(commands[4], PathCommand::ClosePath);
+
+    let shape = ShapeBuilder::new().kurbo_path(&path).build();
+    assert_eq!(shape.segments.len(), 4);
+    assert_approx_eq!(f32, shape.sample_single_channel((5., 5.)), 5.);
+
+    // kurbo's circles wind anticlockwise, but are still filled
+    let circle = kurbo::Circle::new((5., 5.), 5.);
+    let shape = ShapeBuilder::new().kurbo_shape(&circle, 0.01).build();
+    assert!((shape.sample_single_channel((5., 5.)) - 5.).abs() < 0.05);
+  }
+
+  #[test]
+  fn arc_commands() {
+    // a quarter of an ellipse, rotated by a quarter turn
+    let quarter = kurbo::Arc::new(
+      (0., 0.),
+      (4., 2.),
+      0.,
+      std::f64::consts::FRAC_PI_2,
+      std::f64::consts::FRAC_PI_2,
+    );
+    let commands = super::arc_commands(&quarter);
+    assert_eq!(commands.len(), 2);
+    let PathCommand::MoveTo(start) = commands[0] else {
+      panic!("arcs start with a move");
+    };
+    assert_approx_eq!(Point, start, Point::new(0., 4.), epsilon = 1e-6);
+    let PathCommand::ArcTo {
+      large_arc,
+      sweep,
+      end,
+      ..
+    } = commands[1]
+    else {
+      panic!("expected an arc");
+    };
+    assert!(!large_arc && sweep);
+    assert_approx_eq!(Point, end, Point::new(-2., 0.), epsilon = 1e-6);
+
+    // a whole turn backwards is split in two
+    let circle = kurbo::Arc::new((0., 0.), (3., 3.), 0., -10., 0.);
+    let commands = super::arc_commands(&circle);
+    assert_eq!(commands.len(), 3);
+    assert!(matches!(
+      commands[2],
+      PathCommand::ArcTo { sweep: false, .. }
+    ));
+
+    // as is one a rounding error short of a whole turn
+    let almost = std::f64::consts::TAU - 1e-9;
+    let circle = kurbo::Arc::new((0., 0.), (3., 3.), 1., almost, 0.);
+    assert_eq!(super::arc_commands(&circle).len(), 3);
+  }
+}
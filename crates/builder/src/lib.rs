@@ -1,9 +1,90 @@
-#![allow(clippy::new_without_default)]
-
 use rsdf_core::*;
 
+#[cfg(feature = "rustybuzz")]
+mod colr;
+mod draw_sink;
+#[cfg(feature = "freetype")]
+mod freetype_outline;
+#[cfg(feature = "kurbo")]
+mod kurbo_path;
+mod ops;
+#[cfg(feature = "rustybuzz")]
+mod pipeline;
+mod shape_description;
+#[cfg(feature = "rustybuzz")]
+mod shaping;
+#[cfg(feature = "skrifa")]
+mod skrifa_outline;
+#[cfg(any(test, feature = "stress"))]
+pub mod stress;
+mod stroke;
+mod svg_path;
+#[cfg(feature = "swash")]
+mod swash_outline;
+#[cfg(feature = "font-kit")]
+mod system_font;
+#[cfg(test)]
+mod test_outline;
+#[cfg(feature = "tiny-skia")]
+mod tiny_skia_path;
+mod trace;
+#[cfg(feature = "zeno")]
+mod zeno_path;
+
+#[cfg(feature = "rustybuzz")]
+pub use colr::{colour_glyph_layers, ColourLayer, LayeredGlyph};
+pub use draw_sink::DrawSink;
+#[cfg(feature = "kurbo")]
+pub use kurbo_path::{arc_commands, kurbo_commands};
+pub use ops::Op;
+#[cfg(feature = "rustybuzz")]
+pub use pipeline::{
+  generate_glyph_or_placeholder, GeneratedGlyph, GlyphError,
+};
+pub use shape_description::{parse_shape_description, ShapeDescriptionError};
+#[cfg(feature = "rustybuzz")]
+pub use shaping::{shape_run, PlacedGlyph, ShapedRun};
+pub use stroke::{
+  dashed_outline, stroke_outline, LineCap, LineJoin, StrokeStyle, MAX_DASHES,
+};
+pub use svg_path::{parse_svg_path, reverse_path, PathCommand, PathError};
+#[cfg(feature = "font-kit")]
+pub use system_font::{system_font, SystemFontError};
+
+/// The furthest the sides of a [`ShapeBuilder::variable_width_stroke`] are
+/// pushed out at a joint, in half widths, so sharp turns don't spike
+pub const STROKE_MITER_LIMIT: f32 = 4.;
+
+/// Builds a [`Shape`] out of contours, one segment at a time
+///
+/// Each contour starts at a point and is drawn with lines, curves and arcs
+/// from the end of the previous segment, and should finish back at its start
+/// point. Contours wound clockwise (with y pointing down) are filled, so the
+/// distances inside them are positive.
+///
+/// ```
+/// # use rsdf_builder::ShapeBuilder;
+/// // a "D" shape: a vertical line closed by a half circle of radius 5
+/// let shape = ShapeBuilder::new()
+///   .contour((10., 0.))
+///   .elliptical_arc(5., 5., 0., false, true, (10., 10.))
+///   .line((10., 0.))
+///   .end_contour()
+///   .build();
+/// assert_eq!(shape.contours.len(), 1);
+/// assert_eq!(shape.sample_single_channel((12., 5.)), 2.);
+/// assert_eq!(shape.sample_single_channel((8., 5.)), -2.);
+/// ```
+#[must_use = "a builder does nothing until the shape is built"]
 pub struct ShapeBuilder {
   shape: Shape,
+  rearrange: bool,
+}
+
+impl Default for ShapeBuilder {
+  fn default() -> Self {
+    Self::new()
+  }
 }
 
 impl ShapeBuilder {
@@ -15,25 +96,219 @@ impl ShapeBuilder {
         splines: vec![],
         contours: vec![],
       },
+      rearrange: true,
     }
   }
 
+  /// Whether [`build`](Self::build) rotates closed contours to start at a
+  /// corner, which is on by default
+  ///
+  /// This keeps the colouring of a contour independent of where it was
+  /// started, see [`Shape::rearrange_contours`].
+  pub fn rearrange_contours(mut self, rearrange: bool) -> Self {
+    self.rearrange = rearrange;
+    self
+  }
+
+  /// Finish building the shape
+  ///
+  /// Contours that enclose no area are removed, see
+  /// [`Shape::cull_degenerate_contours`], and the rest start at a corner
+  /// unless [`rearrange_contours`](Self::rearrange_contours) is turned off.
+  /// See [`build_with_culled`](Self::build_with_culled) to find out which
+  /// contours were removed.
+  #[must_use]
   pub fn build(self) -> Shape {
-    self.shape
+    self.build_with_culled().0
+  }
+
+  /// Finish building the shape, as for [`build`](Self::build), along with
+  /// the indices of the contours removed for enclosing no area, in the order
+  /// the contours were added
+  ///
+  /// ```
+  /// # use rsdf_builder::ShapeBuilder;
+  /// let (shape, culled) = ShapeBuilder::new()
+  ///   .dot((0., 0.), 1.)
+  ///   .contour((5., 0.))
+  ///   .line((6., 0.))
+  ///   .line((5., 0.))
+  ///   .end_contour()
+  ///   .build_with_culled();
+  /// assert_eq!(shape.contours.len(), 1);
+  /// assert_eq!(culled, [1]);
+  /// ```
+  #[must_use]
+  pub fn build_with_culled(mut self) -> (Shape, Vec<usize>) {
+    let culled = self.shape.cull_degenerate_contours();
+    if self.rearrange {
+      self.shape.rearrange_contours();
+    }
+    (self.shape, culled)
+  }
+
+  /// Finish building the shape, as for [`build`](Self::build), unless it's
+  /// over the [`ShapeLimits`]
+  ///
+  /// The limits are checked before the contours are rearranged, so an
+  /// oversized shape costs no more than it took to describe.
+  ///
+  /// ```
+  /// # use rsdf_builder::ShapeBuilder;
+  /// # use rsdf_core::*;
+  /// let limits = ShapeLimits {
+  ///   max_contours: 1,
+  ///   ..ShapeLimits::default()
+  /// };
+  /// let dots = ShapeBuilder::new().dot((0., 0.), 1.).dot((5., 0.), 1.);
+  /// assert_eq!(
+  ///   dots.try_build(limits).unwrap_err(),
+  ///   LimitError::Contours { limit: 1, found: 2 },
+  /// );
+  /// ```
+  pub fn try_build(
+    mut self,
+    limits: ShapeLimits,
+  ) -> Result<Shape, LimitError> {
+    self.shape.cull_degenerate_contours();
+    self.shape.check_limits(limits)?;
+    if self.rearrange {
+      self.shape.rearrange_contours();
+    }
+    Ok(self.shape)
   }
 
   pub fn contour(self, start_point: impl Into<Point>) -> ContourBuilder {
-    ContourBuilder::new(self.shape, start_point)
+    ContourBuilder::new(self, start_point)
+  }
+
+  /// Add the filled envelope of a variable-width stroke
+  ///
+  /// `points` is a polyline along the centre of the stroke, where each point
+  /// is paired with the width of the stroke at that point, e.g. from the
+  /// pressure of a pen. The sides of the stroke are offset from the
+  /// centreline by half the width, mitred at each joint so they stay
+  /// parallel to the centreline, up to [`STROKE_MITER_LIMIT`] times the half
+  /// width. The ends are closed with round caps.
+  ///
+  /// Repeated points are skipped. A stroke without two distinct points, or
+  /// with a coordinate or width that isn't finite, or a width that isn't
+  /// positive, adds nothing.
+  pub fn variable_width_stroke<P: Into<Point>>(
+    self,
+    points: impl IntoIterator<Item = (P, f32)>,
+  ) -> Self {
+    let mut distinct: Vec<(Point, f32)> = vec![];
+    for (point, width) in points {
+      let point = point.into();
+      let finite = [point.x, point.y, width].iter().all(|v| v.is_finite());
+      if !finite || width <= 0. {
+        return self;
+      }
+      // a repeated point has no direction to offset the sides along
+      if distinct.last().map(|&(last, _)| last) != Some(point) {
+        distinct.push((point, width));
+      }
+    }
+    let points = distinct;
+    if points.len() < 2 {
+      return self;
+    }
+
+    let normal = |a: Point, b: Point| {
+      let Vector { x, y } = (b - a).norm();
+      Vector::new(-y, x)
+    };
+    let last = points.len() - 1;
+    // the direction to offset each point along, scaled by 1 / cos(θ / 2) at
+    // a joint turning by θ, so the sides stay a half width from each segment
+    let normals: Vec<Vector> = (0..points.len())
+      .map(|i| {
+        let before = (i > 0).then(|| normal(points[i - 1].0, points[i].0));
+        let after = (i < last).then(|| normal(points[i].0, points[i + 1].0));
+        match (before, after) {
+          (Some(a), Some(b)) => {
+            let bisector = a + b;
+            // a reversal has no bisector, so its sides follow the way in
+            if bisector.length() < f32::EPSILON {
+              return a;
+            }
+            let bisector = bisector.norm();
+            bisector * (1. / bisector.dot(a)).min(STROKE_MITER_LIMIT)
+          },
+          (Some(n), None) | (None, Some(n)) => n,
+          (None, None) => Vector::ZERO,
+        }
+      })
+      .collect();
+    let offset = |i: usize, side: f32| {
+      let (point, width) = points[i];
+      point + normals[i] * (side * width / 2.)
+    };
+
+    let mut contour = self.contour(offset(0, -1.));
+    for i in 1..=last {
+      contour = contour.line(offset(i, -1.));
+    }
+    let end_radius = points[last].1 / 2.;
+    contour = contour.elliptical_arc(
+      end_radius,
+      end_radius,
+      0.,
+      false,
+      true,
+      offset(last, 1.),
+    );
+    for i in (0..last).rev() {
+      contour = contour.line(offset(i, 1.));
+    }
+    let start_radius = points[0].1 / 2.;
+    contour
+      .elliptical_arc(
+        start_radius,
+        start_radius,
+        0.,
+        false,
+        true,
+        offset(0, -1.),
+      )
+      .end_contour()
+  }
+
+  /// Add a round dot of the given `radius` centred on a point
+  ///
+  /// The dot is a closed contour made of a pair of circular arcs. A dot
+  /// whose radius isn't positive & finite, or whose centre isn't finite,
+  /// adds nothing.
+  pub fn dot(self, centre: impl Into<Point>, radius: f32) -> Self {
+    let centre = centre.into();
+    let finite = [centre.x, centre.y, radius].iter().all(|v| v.is_finite());
+    if !finite || radius <= 0. {
+      return self;
+    }
+    let right = Point::new(centre.x + radius, centre.y);
+    let left = Point::new(centre.x - radius, centre.y);
+    self
+      .contour(right)
+      .elliptical_arc(radius, radius, 0., false, true, left)
+      .elliptical_arc(radius, radius, 0., false, true, right)
+      .end_contour()
   }
 }
 
+#[must_use = "a contour is only added to the shape once it is ended"]
 pub struct ContourBuilder {
   shape: Shape,
+  rearrange: bool,
   current_spline: Spline,
 }
 
 impl ContourBuilder {
-  fn new(mut shape: Shape, start_point: impl Into<Point>) -> Self {
+  fn new(builder: ShapeBuilder, start_point: impl Into<Point>) -> Self {
+    let ShapeBuilder {
+      mut shape,
+      rearrange,
+    } = builder;
     shape.points.push(start_point.into());
     let spline_len = shape.splines.len();
     shape.contours.push(Contour {
@@ -43,6 +318,7 @@ impl ContourBuilder {
     let segments_len = shape.segments.len();
     ContourBuilder {
       shape,
+      rearrange,
       current_spline: Spline {
         segments_range: segments_len..segments_len,
         colour: Colour::Magenta,
@@ -128,15 +404,15 @@ impl ContourBuilder {
   }
 
   pub fn end_contour(mut self) -> ShapeBuilder {
-    // finish spline
-    self.current_spline.segments_range.end = self.shape.segments.len();
-    self.shape.splines.push(self.current_spline.clone());
     let (first_point, last_point) = {
-      // TODO: ensure contour is closed
+      // splines are only added to the shape once they're finished, so the
+      // contour's first spline may still be the current one
       let first_spline_i =
         self.shape.contours.last().unwrap().spline_range.start;
-      let first_segment_i =
-        self.shape.splines[first_spline_i].segments_range.start;
+      let first_segment_i = match self.shape.splines.get(first_spline_i) {
+        Some(spline) => spline.segments_range.start,
+        None => self.current_spline.segments_range.start,
+      };
       let first_segment = self.shape.segments[first_segment_i];
       let first_point = self.shape.get_segment(first_segment).sample(0f32);
       let segments_len = self.shape.segments.len();
@@ -144,11 +420,20 @@ impl ContourBuilder {
       let last_point = self.shape.get_segment(last_segment).sample(1f32);
       (first_point, last_point)
     };
-    let mut shape = if !float_cmp::approx_eq!(Point, first_point, last_point) {
-      self.line(first_point).shape
-    } else {
-      self.shape
-    };
+    // sampling the ends of curves & arcs isn't exact, so the contour counts
+    // as closed if it ends close enough to where it started
+    if !float_cmp::approx_eq!(Point, first_point, last_point, epsilon = 0.0001)
+    {
+      self = self.line(first_point);
+    }
+    // finish spline
+    self.current_spline.segments_range.end = self.shape.segments.len();
+    self.shape.splines.push(self.current_spline);
+    let ContourBuilder {
+      mut shape,
+      rearrange,
+      ..
+    } = self;
 
     // check to see if the first & last spline are continuous
     // if !self.is_sharp_corner(segments_len - 1, first_segment_i) {
@@ -157,8 +442,28 @@ impl ContourBuilder {
 
     let contour = shape.contours.last_mut().unwrap();
     contour.spline_range.end = shape.splines.len();
+    // a smooth contour is a single spline, which needs every channel
+    if let [spline] = &mut shape.splines[contour.spline_range.clone()] {
+      spline.colour = White;
+    }
+
+    ShapeBuilder { shape, rearrange }
+  }
+
+  /// Finish the contour without closing it
+  ///
+  /// Open contours have no inside or outside, so a shape containing them
+  /// should be sampled with [`Shape::sample_unsigned`].
+  pub fn end_open_contour(mut self) -> ShapeBuilder {
+    self.current_spline.segments_range.end = self.shape.segments.len();
+    self.shape.splines.push(self.current_spline);
+    let contour = self.shape.contours.last_mut().unwrap();
+    contour.spline_range.end = self.shape.splines.len();
 
-    ShapeBuilder { shape }
+    ShapeBuilder {
+      shape: self.shape,
+      rearrange: self.rearrange,
+    }
   }
 
   fn is_sharp_corner(
@@ -181,6 +486,11 @@ impl ContourBuilder {
     !float_cmp::approx_eq!(Vector, d1, d2)
   }
 
+  /// Start a new spline if the last segment forms a sharp corner
+  ///
+  /// The colour of the new spline depends only on the colour of the previous
+  /// spline in this contour, so colouring is deterministic and shapes can be
+  /// built on separate threads.
   fn check_for_and_create_new_spline(&mut self) {
     let segments_len = self.shape.segments.len();
     // check we even have more than one segment in this spline yet
@@ -193,11 +503,225 @@ impl ContourBuilder {
       // create new spline
       self.current_spline.segments_range = segments_len - 1..segments_len;
       self.current_spline.colour =
-        if self.current_spline.colour == Colour::Magenta {
-          Colour::Yellow
-        } else {
-          self.current_spline.colour ^ Colour::Magenta
-        }
+        self.current_spline.colour.next_spline_colour();
+    }
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  fn shape(offset: f32) -> Shape {
+    ShapeBuilder::new()
+      .contour((offset, 0.))
+      .line((offset + 4., 0.))
+      .quadratic_bezier((offset + 6., 2.), (offset + 4., 4.))
+      .line((offset, 4.))
+      .end_contour()
+      .contour((offset + 1., 1.))
+      .elliptical_arc(1., 1., 0., false, true, (offset + 3., 1.))
+      .line((offset + 2., 3.))
+      .end_contour()
+      .build()
+  }
+
+  #[test]
+  fn parallel_colouring_is_deterministic() {
+    let serial: Vec<u64> =
+      (0..8).map(|i| shape(i as f32).checksum()).collect();
+
+    let parallel: Vec<u64> = std::thread::scope(|scope| {
+      let handles: Vec<_> = (0..8)
+        .map(|i| scope.spawn(move || shape(i as f32).checksum()))
+        .collect();
+      handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+
+    assert_eq!(serial, parallel);
+  }
+
+  #[test]
+  fn rearrange_contours() {
+    let square = |points: &[(f32, f32)], rearrange| {
+      let mut contour = ShapeBuilder::new()
+        .rearrange_contours(rearrange)
+        .contour(points[0]);
+      for &point in &points[1..] {
+        contour = contour.line(point);
+      }
+      contour.end_contour().build()
+    };
+    let config = RasterConfig::default();
+    let field = |shape: &Shape| shape.sample_field::<3>([12, 12], config);
+
+    // starting partway along the top edge gives the same colouring as
+    // starting at the next corner
+    let from_corner = &[(10., 0.), (10., 10.), (0., 10.), (0., 0.), (10., 0.)];
+    let from_edge = &[
+      (5., 0.),
+      (10., 0.),
+      (10., 10.),
+      (0., 10.),
+      (0., 0.),
+      (5., 0.),
+    ];
+    let (from_corner, kept) =
+      (square(from_corner, true), square(from_edge, false));
+    let from_edge = square(from_edge, true);
+    assert_eq!(from_edge.splines.len(), 4);
+    assert_eq!(field(&from_edge), field(&from_corner));
+
+    assert_eq!(kept.splines.len(), 5);
+    assert_ne!(field(&kept), field(&from_corner));
+  }
+
+  #[test]
+  fn smooth_contour() {
+    // a circle of cubic beziers, started on a smooth join
+    let circle = |rearrange| {
+      ShapeBuilder::new()
+        .rearrange_contours(rearrange)
+        .contour((0., 5.))
+        .cubic_bezier((0., 2.24), (2.24, 0.), (5., 0.))
+        .cubic_bezier((7.76, 0.), (10., 2.24), (10., 5.))
+        .cubic_bezier((10., 7.76), (7.76, 10.), (5., 10.))
+        .cubic_bezier((2.24, 10.), (0., 7.76), (0., 5.))
+        .end_contour()
+        .build()
+    };
+    // a circle of two arcs, which is only one spline once rearranged
+    let arcs = ShapeBuilder::new()
+      .contour((0., 5.))
+      .elliptical_arc(5., 5., 0., false, true, (10., 5.))
+      .elliptical_arc(5., 5., 0., false, true, (0., 5.))
+      .end_contour()
+      .build();
+
+    // a lone spline needs every channel
+    for shape in [circle(false), circle(true), arcs] {
+      assert_eq!(shape.splines.len(), 1);
+      assert!(matches!(shape.splines[0].colour, White));
+      let field = shape.sample_field::<3>([10, 10], RasterConfig::default());
+      let [r, g, b] = field.get([5, 5]);
+      assert!(r > 4.9 && r == g && g == b);
+    }
+  }
+
+  #[test]
+  fn open_contour() {
+    let shape = ShapeBuilder::new()
+      .contour((0., 0.))
+      .line((4., 0.))
+      .line((4., 4.))
+      .end_open_contour()
+      .build();
+
+    assert_eq!(shape.segments.len(), 2);
+    assert_eq!(shape.contours.len(), 1);
+    assert!(!shape.is_contour_closed(&shape.contours[0]));
+    assert_eq!(shape.sample_unsigned(Point::new(2., 1.)), 1.);
+    assert_eq!(shape.sample_unsigned(Point::new(2., 3.)), 2.);
+  }
+
+  #[test]
+  fn dot() {
+    let shape = ShapeBuilder::new()
+      .dot((2., 2.), 1.)
+      .dot((8., 2.), 0.5)
+      .build();
+
+    assert_eq!(shape.contours.len(), 2);
+    float_cmp::assert_approx_eq!(
+      f32,
+      shape.sample_single_channel(Point::new(2., 2.)),
+      1.,
+      epsilon = 0.0001
+    );
+    float_cmp::assert_approx_eq!(
+      f32,
+      shape.sample_single_channel(Point::new(2., 5.)),
+      -2.,
+      epsilon = 0.0001
+    );
+    float_cmp::assert_approx_eq!(
+      f32,
+      shape.sample_single_channel(Point::new(8., 2.)),
+      0.5,
+      epsilon = 0.0001
+    );
+  }
+
+  #[test]
+  fn invalid_dots() {
+    for radius in [0., -1., f32::NAN, f32::INFINITY] {
+      let shape = ShapeBuilder::new().dot((2., 2.), radius).build();
+      assert!(shape.contours.is_empty(), "{radius}");
+    }
+    let shape = ShapeBuilder::new().dot((f32::NAN, 2.), 1.).build();
+    assert!(shape.contours.is_empty());
+  }
+
+  #[test]
+  fn variable_width_stroke() {
+    let shape = ShapeBuilder::new()
+      .variable_width_stroke([((0., 0.), 2.), ((4., 0.), 4.), ((8., 0.), 2.)])
+      .build();
+
+    assert_eq!(shape.contours.len(), 1);
+    let inside = |x: f32, y: f32| shape.sample_single_channel((x, y));
+    // the sides are straight lines between the offset points
+    let expected = 8. / 17f32.sqrt();
+    float_cmp::assert_approx_eq!(
+      f32,
+      inside(4., 0.),
+      expected,
+      epsilon = 0.0001
+    );
+    float_cmp::assert_approx_eq!(f32, inside(4., 3.), -1., epsilon = 0.0001);
+    assert!(inside(2., 1.2) > 0.);
+    assert!(inside(2., 1.8) < 0.);
+    // round caps
+    assert!(inside(-0.5, 0.) > 0.);
+    assert!(inside(-1.5, 0.) < 0.);
+    assert!(inside(8.5, 0.) > 0.);
+  }
+
+  #[test]
+  fn variable_width_stroke_joints() {
+    let shape = ShapeBuilder::new()
+      .variable_width_stroke([((0., 0.), 2.), ((4., 0.), 2.), ((4., 4.), 2.)])
+      .build();
+    // the corner is mitred, so the sides stay a half width from the
+    // centreline all the way along
+    let inside = |x: f32, y: f32| shape.sample_single_channel((x, y));
+    float_cmp::assert_approx_eq!(f32, inside(2., 0.), 1., epsilon = 0.0001);
+    float_cmp::assert_approx_eq!(f32, inside(4., 2.), 1., epsilon = 0.0001);
+
+    // a reversal has no bisector to offset along
+    let shape = ShapeBuilder::new()
+      .variable_width_stroke([((0., 0.), 2.), ((4., 0.), 2.), ((0., 0.), 2.)])
+      .build();
+    let points = &shape.points;
+    assert!(points.iter().all(|p| p.x.is_finite() && p.y.is_finite()));
+  }
+
+  #[test]
+  fn invalid_strokes() {
+    let none: [((f32, f32), f32); 0] = [];
+    let strokes = [
+      &none[..],
+      &[((1., 1.), 2.)],
+      &[((1., 1.), 2.), ((1., 1.), 3.)],
+      &[((0., 0.), 2.), ((4., 0.), 0.)],
+      &[((0., 0.), 2.), ((4., 0.), f32::NAN)],
+      &[((0., 0.), 2.), ((f32::INFINITY, 0.), 2.)],
+    ];
+    for stroke in strokes {
+      let shape = ShapeBuilder::new()
+        .variable_width_stroke(stroke.iter().copied())
+        .build();
+      assert!(shape.contours.is_empty(), "{stroke:?}");
     }
   }
 }
@@ -0,0 +1,311 @@
+use super::*;
+use elliptical_arc::{CentreParam, EndpointParam};
+
+/// A single call on the [`ShapeBuilder`], so the steps building a shape can
+/// be stored, sent between tools & replayed with [`ShapeBuilder::ops`]
+///
+/// With the `serde` feature, ops can be serialized along with shapes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Op {
+  /// Start a contour at a point, as for [`ShapeBuilder::contour`]
+  Contour(Point),
+  Line(Point),
+  QuadraticBezier(Point, Point),
+  CubicBezier(Point, Point, Point),
+  /// An elliptical arc, as for [`ContourBuilder::elliptical_arc`]
+  EllipticalArc {
+    rx: f32,
+    ry: f32,
+    phi: f32,
+    large_arc: bool,
+    sweep_ccw: bool,
+    end: Point,
+  },
+  EndContour,
+  EndOpenContour,
+}
+
+impl ShapeBuilder {
+  /// Replay a sequence of [`Op`]s
+  ///
+  /// Contours without any segments are left out, and a contour that isn't
+  /// ended before the next one starts, or before the ops run out, is closed.
+  /// Segments outside of a contour start one at the origin.
+  ///
+  /// ```
+  /// # use rsdf_builder::{Op, ShapeBuilder};
+  /// let shape = ShapeBuilder::new()
+  ///   .ops([
+  ///     Op::Contour((0., 0.).into()),
+  ///     Op::Line((10., 0.).into()),
+  ///     Op::Line((10., 10.).into()),
+  ///     Op::Line((0., 10.).into()),
+  ///     Op::EndContour,
+  ///   ])
+  ///   .build();
+  /// assert_eq!(shape.sample_single_channel((5., 5.)), 5.);
+  /// ```
+  pub fn ops(self, ops: impl IntoIterator<Item = Op>) -> Self {
+    let mut state = OpState::Idle(self, Point::ZERO);
+    for op in ops {
+      state = match op {
+        Op::Contour(start) => OpState::Idle(state.end_contour(), start),
+        Op::Line(end) => state.draw(|c| c.line(end)),
+        Op::QuadraticBezier(control, end) => {
+          state.draw(|c| c.quadratic_bezier(control, end))
+        },
+        Op::CubicBezier(control_1, control_2, end) => {
+          state.draw(|c| c.cubic_bezier(control_1, control_2, end))
+        },
+        Op::EllipticalArc {
+          rx,
+          ry,
+          phi,
+          large_arc,
+          sweep_ccw,
+          end,
+        } => state
+          .draw(|c| c.elliptical_arc(rx, ry, phi, large_arc, sweep_ccw, end)),
+        Op::EndContour => OpState::Idle(state.end_contour(), Point::ZERO),
+        Op::EndOpenContour => {
+          let builder = match state {
+            OpState::Idle(builder, _) => builder,
+            OpState::Drawing(contour) => contour.end_open_contour(),
+          };
+          OpState::Idle(builder, Point::ZERO)
+        },
+      }
+    }
+    state.end_contour()
+  }
+}
+
+impl Op {
+  /// Record the segments of a shape as the ops that would build it again
+  ///
+  /// Each contour is recorded as a [`Op::Contour`], a segment op each, and
+  /// an [`Op::EndContour`]. Recording the shapes made by a front-end gives
+  /// golden ops to compare it against, without sampling any fields; build
+  /// them with [`ShapeBuilder::rearrange_contours`] turned off to record the
+  /// contours just as they were drawn.
+  ///
+  /// [`SegmentKind`] is non-exhaustive; segments of kinds added to rsdf_core
+  /// that ops can't express yet are recorded as an [`Op::Line`] to their
+  /// end, so the recorded contour still closes.
+  ///
+  /// ```
+  /// # use rsdf_builder::{Op, ShapeBuilder};
+  /// let shape = ShapeBuilder::from_svg_path("M0,0 H10 V10 Z")
+  ///   .unwrap()
+  ///   .rearrange_contours(false)
+  ///   .build();
+  /// assert_eq!(
+  ///   Op::record(&shape),
+  ///   [
+  ///     Op::Contour((0., 0.).into()),
+  ///     Op::Line((10., 0.).into()),
+  ///     Op::Line((10., 10.).into()),
+  ///     Op::Line((0., 0.).into()),
+  ///     Op::EndContour,
+  ///   ],
+  /// );
+  /// ```
+  #[must_use]
+  pub fn record(shape: &Shape) -> Vec<Op> {
+    let mut ops = vec![];
+    for contour in &shape.contours {
+      let splines = &shape.splines[contour.spline_range.clone()];
+      let mut segments = splines
+        .iter()
+        .flat_map(|spline| &shape.segments[spline.segments_range.clone()])
+        .peekable();
+      let Some(first) = segments.peek() else {
+        continue;
+      };
+      let start = match first.kind {
+        SegmentKind::EllipticalArc => shape.points[first.points_index - 1],
+        _ => shape.points[first.points_index],
+      };
+      ops.push(Op::Contour(start));
+      for &segment in segments {
+        let SegmentRef { kind, points_index } = segment;
+        let p = &shape.points[points_index..];
+        ops.push(match kind {
+          SegmentKind::Line => Op::Line(p[1]),
+          SegmentKind::QuadBezier => Op::QuadraticBezier(p[1], p[2]),
+          SegmentKind::CubicBezier => Op::CubicBezier(p[1], p[2], p[3]),
+          SegmentKind::EllipticalArc => {
+            let arc = EndpointParam::from(CentreParam::from_ps(&p[..4]));
+            Op::EllipticalArc {
+              rx: arc.rx,
+              ry: arc.ry,
+              phi: arc.phi,
+              large_arc: arc.large_arc,
+              sweep_ccw: arc.sweep_ccw,
+              // the exact end, rather than the one sampled from the ellipse
+              end: p[4],
+            }
+          },
+          // kinds added to rsdf_core that ops can't express yet
+          _ => Op::Line(shape.get_segment(segment).sample(1.)),
+        });
+      }
+      ops.push(Op::EndContour);
+    }
+    ops
+  }
+}
+
+/// Replay an op as a path command, for sinks that take [`PathCommand`]s
+///
+/// Path commands can't leave a contour open, so [`Op::EndOpenContour`]
+/// closes the contour like [`Op::EndContour`].
+impl From<Op> for PathCommand {
+  fn from(op: Op) -> Self {
+    match op {
+      Op::Contour(start) => PathCommand::MoveTo(start),
+      Op::Line(end) => PathCommand::LineTo(end),
+      Op::QuadraticBezier(control, end) => PathCommand::QuadTo(control, end),
+      Op::CubicBezier(control_1, control_2, end) => {
+        PathCommand::CubicTo(control_1, control_2, end)
+      },
+      Op::EllipticalArc {
+        rx,
+        ry,
+        phi,
+        large_arc,
+        sweep_ccw,
+        end,
+      } => PathCommand::ArcTo {
+        rx,
+        ry,
+        phi,
+        large_arc,
+        sweep: sweep_ccw,
+        end,
+      },
+      Op::EndContour | Op::EndOpenContour => PathCommand::ClosePath,
+    }
+  }
+}
+
+/// Whether [`ShapeBuilder::ops`] is in the middle of a contour
+enum OpState {
+  /// Waiting for the first segment of a contour that starts at the point
+  Idle(ShapeBuilder, Point),
+  Drawing(ContourBuilder),
+}
+
+impl OpState {
+  /// Add a segment to the current contour, starting one if there isn't one
+  /// yet
+  fn draw(
+    self,
+    segment: impl FnOnce(ContourBuilder) -> ContourBuilder,
+  ) -> Self {
+    let contour = match self {
+      OpState::Idle(builder, start) => builder.contour(start),
+      OpState::Drawing(contour) => contour,
+    };
+    OpState::Drawing(segment(contour))
+  }
+
+  /// Close the current contour, if any
+  fn end_contour(self) -> ShapeBuilder {
+    match self {
+      OpState::Idle(builder, _) => builder,
+      OpState::Drawing(contour) => contour.end_contour(),
+    }
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  fn triangle() -> Vec<Op> {
+    vec![
+      Op::Contour((0., 0.).into()),
+      Op::Line((10., 0.).into()),
+      Op::QuadraticBezier((15., 5.).into(), (10., 10.).into()),
+      Op::EllipticalArc {
+        rx: 5.,
+        ry: 5.,
+        phi: 0.,
+        large_arc: false,
+        sweep_ccw: true,
+        end: (0., 10.).into(),
+      },
+      Op::EndContour,
+    ]
+  }
+
+  #[test]
+  fn ops() {
+    let shape = ShapeBuilder::new().ops(triangle()).build();
+    let expected = ShapeBuilder::new()
+      .contour((0., 0.))
+      .line((10., 0.))
+      .quadratic_bezier((15., 5.), (10., 10.))
+      .elliptical_arc(5., 5., 0., false, true, (0., 10.))
+      .end_contour()
+      .build();
+    assert_eq!(shape.checksum(), expected.checksum());
+
+    // unfinished & empty contours
+    let shape = ShapeBuilder::new()
+      .ops([
+        Op::Contour((0., 0.).into()),
+        Op::EndContour,
+        Op::Line((0., 10.).into()),
+        Op::Line((10., 10.).into()),
+        Op::Contour((20., 20.).into()),
+        Op::Line((30., 20.).into()),
+        Op::EndOpenContour,
+        Op::Line((40., 0.).into()),
+        Op::Line((40., 40.).into()),
+      ])
+      .build();
+    assert_eq!(shape.contours.len(), 3);
+  }
+
+  #[test]
+  fn record() {
+    let shape = ShapeBuilder::new()
+      .ops(triangle())
+      .rearrange_contours(false)
+      .build();
+    let ops = Op::record(&shape);
+    assert_eq!(ops[..3], triangle()[..3]);
+    let Op::EllipticalArc { rx, ry, end, .. } = ops[3] else {
+      panic!("expected an arc, found {:?}", ops[3]);
+    };
+    float_cmp::assert_approx_eq!(f32, rx, 5., epsilon = 1e-4);
+    float_cmp::assert_approx_eq!(f32, ry, 5., epsilon = 1e-4);
+    assert_eq!(end, Point::new(0., 10.));
+
+    // replaying the recording, either as ops or path commands, builds the
+    // same shape again
+    let replayed = ShapeBuilder::new().ops(ops.clone()).build();
+    assert_eq!(replayed.checksum(), shape.checksum());
+    let path = ShapeBuilder::new()
+      .path(ops.into_iter().map(PathCommand::from))
+      .build();
+    assert_eq!(path.checksum(), shape.checksum());
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn serde() {
+    let json = serde_json::to_string(&triangle()).unwrap();
+    let ops: Vec<Op> = serde_json::from_str(&json).unwrap();
+    assert_eq!(ops, triangle());
+
+    let shape = ShapeBuilder::new().ops(ops).build();
+    let json = serde_json::to_string(&shape).unwrap();
+    let decoded: Shape = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded.checksum(), shape.checksum());
+    assert_eq!(decoded.splines[0].colour, shape.splines[0].colour);
+  }
+}
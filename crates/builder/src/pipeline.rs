@@ -0,0 +1,222 @@
+use super::*;
+use rustybuzz::ttf_parser::{self, GlyphId};
+
+/// Why [`generate_glyph_or_placeholder`] substituted a placeholder for a
+/// glyph
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum GlyphError {
+  /// The glyph id is beyond the number of glyphs in the font
+  MissingGlyph(u16),
+  /// The outline has a NaN or infinite coordinate
+  NonFiniteOutline,
+  /// The glyph's canvas, of the given size, is too large to allocate a
+  /// field for
+  CanvasTooLarge([usize; 2]),
+  /// The field held the given number of NaN or infinite channels, see
+  /// [`scrub_non_finite`]
+  NonFinite(usize),
+}
+
+impl std::fmt::Display for GlyphError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      GlyphError::MissingGlyph(glyph) => {
+        write!(f, "glyph {glyph} is not in the font")
+      },
+      GlyphError::NonFiniteOutline => {
+        write!(f, "the glyph's outline has a non-finite coordinate")
+      },
+      GlyphError::CanvasTooLarge([width, height]) => {
+        write!(f, "the glyph's {width}x{height} canvas is too large")
+      },
+      GlyphError::NonFinite(count) => {
+        write!(f, "the glyph's field had {count} non-finite channels")
+      },
+    }
+  }
+}
+
+impl std::error::Error for GlyphError {}
+
+/// A field made by [`generate_glyph_or_placeholder`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratedGlyph<const N: usize> {
+  pub field: Field<N>,
+  /// Where the top-left pixel of the field lies relative to the glyph's
+  /// origin on the baseline, in pixels with y pointing down, as for
+  /// [`PreparedGlyph::offset`]
+  pub offset: Vector,
+  /// The error that caused the field to be replaced by a placeholder box
+  pub error: Option<GlyphError>,
+}
+
+/// Sample a glyph's field at `pixels_per_em`, substituting a placeholder box
+/// rather than failing
+///
+/// Batch pipelines can then finish the whole atlas, and report the glyphs
+/// that went wrong from [`GeneratedGlyph::error`] afterwards. The box is the
+/// outline of a rectangle half an em wide and 0.7em tall, standing on the
+/// baseline, like a font's `.notdef` glyph. Glyphs without an outline, such
+/// as spaces, aren't errors, and give an empty field.
+///
+/// The outline and its canvas are checked before the field is sampled.
+/// Panics if `pixels_per_em` isn't finite and positive, since the
+/// placeholder couldn't be drawn either.
+///
+/// ```no_run
+/// # use rsdf_builder::generate_glyph_or_placeholder;
+/// # use rsdf_core::RasterConfig;
+/// let data = std::fs::read("font.ttf").unwrap();
+/// let face = rustybuzz::Face::from_slice(&data, 0).unwrap();
+/// let config = RasterConfig::default();
+/// for glyph in 0..face.number_of_glyphs() {
+///   let generated =
+///     generate_glyph_or_placeholder::<3>(&face, glyph, 32., config);
+///   if let Some(error) = generated.error {
+///     eprintln!("glyph {glyph}: {error}");
+///   }
+/// }
+/// ```
+pub fn generate_glyph_or_placeholder<const N: usize>(
+  face: &ttf_parser::Face,
+  glyph: u16,
+  pixels_per_em: f32,
+  config: RasterConfig,
+) -> GeneratedGlyph<N>
+where
+  Shape: SampleChannels<N>,
+{
+  assert!(
+    pixels_per_em.is_finite() && pixels_per_em > 0.,
+    "invalid pixels per em {pixels_per_em}"
+  );
+  let units_per_em = face.units_per_em() as f32;
+  let scale = pixels_per_em / units_per_em;
+  let substitute = |error| placeholder(units_per_em, scale, config, error);
+  if glyph >= face.number_of_glyphs() {
+    return substitute(GlyphError::MissingGlyph(glyph));
+  }
+
+  let outline = ShapeBuilder::new()
+    .glyph_outline(face, GlyphId(glyph))
+    .build();
+  let Some(glyph) = PreparedGlyph::new(outline, scale, config) else {
+    return GeneratedGlyph {
+      field: Field::new([0, 0]),
+      offset: Vector::ZERO,
+      error: None,
+    };
+  };
+  if let Err(error) = check::<N>(&glyph) {
+    return substitute(error);
+  }
+
+  let mut field = glyph.sample::<N>(config);
+  let scrubbed = scrub_non_finite(field.samples_mut(), config.max_distance);
+  if scrubbed > 0 {
+    return substitute(GlyphError::NonFinite(scrubbed));
+  }
+  GeneratedGlyph {
+    field,
+    offset: glyph.offset,
+    error: None,
+  }
+}
+
+/// Check that a glyph's field can be sampled: that its outline is finite,
+/// and its canvas small enough to allocate
+fn check<const N: usize>(glyph: &PreparedGlyph) -> Result<(), GlyphError> {
+  let points = &glyph.shape.points;
+  if !points.iter().all(|p| p.x.is_finite() && p.y.is_finite()) {
+    return Err(GlyphError::NonFiniteOutline);
+  }
+  let [width, height] = glyph.size;
+  let bytes = (width.checked_mul(height))
+    .and_then(|pixels| pixels.checked_mul(std::mem::size_of::<[f32; N]>()));
+  if bytes.is_none_or(|bytes| bytes > isize::MAX as usize) {
+    return Err(GlyphError::CanvasTooLarge(glyph.size));
+  }
+  Ok(())
+}
+
+/// The field of a `.notdef`-like box, reporting `error`
+fn placeholder<const N: usize>(
+  units_per_em: f32,
+  scale: f32,
+  config: RasterConfig,
+  error: GlyphError,
+) -> GeneratedGlyph<N>
+where
+  Shape: SampleChannels<N>,
+{
+  let [width, height] = [0.5, 0.7].map(|size| size * units_per_em);
+  let stroke = 0.05 * units_per_em;
+  let rectangle = |builder: ShapeBuilder, [x0, y0, x1, y1]: [f32; 4]| {
+    builder
+      .contour((x0, y0))
+      .line((x1, y0))
+      .line((x1, y1))
+      .line((x0, y1))
+      .end_contour()
+  };
+  let outer = [0., -height, width, 0.];
+  let inner = [stroke, -stroke, width - stroke, stroke - height];
+  let shape = rectangle(rectangle(ShapeBuilder::new(), outer), inner).build();
+  // the box always has an outline
+  let glyph = PreparedGlyph::new(shape, scale, config).unwrap();
+  GeneratedGlyph {
+    field: glyph.sample(config),
+    offset: glyph.offset,
+    error: Some(error),
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn check() {
+    let config = RasterConfig::default();
+    let outline = |corner: (f32, f32)| {
+      ShapeBuilder::new()
+        .contour((0., 0.))
+        .line((corner.0, 0.))
+        .line(corner)
+        .line((0., corner.1))
+        .end_contour()
+        .build()
+    };
+    let prepare = |corner| PreparedGlyph::new(outline(corner), 1., config);
+
+    let glyph = prepare((10., -10.)).unwrap();
+    assert_eq!(super::check::<3>(&glyph), Ok(()));
+    let mut glyph = prepare((10., -10.)).unwrap();
+    glyph.shape.points[1].x = f32::NAN;
+    assert_eq!(super::check::<3>(&glyph), Err(GlyphError::NonFiniteOutline));
+    let huge = prepare((1e30, -1e30)).unwrap();
+    assert!(matches!(
+      super::check::<3>(&huge),
+      Err(GlyphError::CanvasTooLarge(_))
+    ));
+  }
+
+  #[test]
+  fn placeholder() {
+    let config = RasterConfig::default();
+    let generated = super::placeholder::<1>(
+      1000.,
+      0.04,
+      config,
+      GlyphError::MissingGlyph(7),
+    );
+    assert_eq!(generated.error, Some(GlyphError::MissingGlyph(7)));
+    // a 20x28 pixel box, framed with a margin of 5 pixels
+    assert_eq!(generated.field.size(), [30, 38]);
+    assert_eq!(generated.offset, Vector::new(-5., -33.));
+    // the box is hollow, with a 2 pixel wide outline
+    assert_eq!(generated.field.get([6, 20]), [1.]);
+    assert!(generated.field.get([15, 20])[0] < 0.);
+  }
+}
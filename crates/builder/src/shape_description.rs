@@ -0,0 +1,440 @@
+use super::*;
+use svg_path::Lexer;
+
+/// An error found while parsing an msdfgen shape description, see
+/// [`parse_shape_description`]
+///
+/// Positions are byte offsets into the description.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ShapeDescriptionError {
+  /// A character that doesn't fit the syntax at that point
+  UnexpectedCharacter(usize),
+  /// A point at the given position was missing its y coordinate
+  MissingCoordinate(usize),
+  /// The description ended inside of a contour
+  UnexpectedEnd,
+}
+
+impl std::fmt::Display for ShapeDescriptionError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      ShapeDescriptionError::UnexpectedCharacter(position) => {
+        write!(f, "unexpected character at {position}")
+      },
+      ShapeDescriptionError::MissingCoordinate(position) => {
+        write!(f, "missing y coordinate for the point at {position}")
+      },
+      ShapeDescriptionError::UnexpectedEnd => {
+        write!(f, "shape description ended inside of a contour")
+      },
+    }
+  }
+}
+
+impl std::error::Error for ShapeDescriptionError {}
+
+/// An edge of a contour, as written in a shape description
+#[derive(Debug, Clone)]
+struct Edge {
+  /// The control points, followed by the end point
+  points: Vec<Point>,
+  colour: Option<Colour>,
+}
+
+/// Parse a shape written in [msdfgen]'s shape description format
+///
+/// Each contour is a list of points in braces, separated by `;`. Points
+/// joined by a plain `;` are joined by a line, and control points in
+/// parentheses between two points make a quadratic or cubic bezier. An edge
+/// can be given a colour with one of `c`, `m`, `y` or `w`, and `#` stands
+/// for the contour's first point:
+///
+/// ```text
+/// { 0, 0; m; 0, 10; (5, 15); 10, 10; y (12, 6; 12, 4); 10, 0; c; # }
+/// ```
+///
+/// If any edge has a colour, the splines are made from runs of edges with
+/// the same colour, with uncoloured edges white. Otherwise the shape is
+/// coloured at its corners like any other built shape.
+///
+/// msdfgen descriptions have y pointing up, so y is negated, unless the
+/// description starts with `@invert-y`, in which case the contours are
+/// reversed instead; either way the inside of the shape is the same as
+/// msdfgen's.
+///
+/// [msdfgen]: https://github.com/Chlumsky/msdfgen
+///
+/// ```
+/// # use rsdf_builder::parse_shape_description;
+/// let shape = parse_shape_description(
+///   "{ 0, 0; m; 0, 10; y; 10, 10; m; 10, 0; y; # }",
+/// )
+/// .unwrap();
+/// assert_eq!(shape.splines.len(), 4);
+/// assert_eq!(shape.sample_single_channel((5., -5.)), 5.);
+/// ```
+pub fn parse_shape_description(
+  text: &str,
+) -> Result<Shape, ShapeDescriptionError> {
+  let mut parser = Parser {
+    lexer: Lexer {
+      bytes: text.as_bytes(),
+      position: 0,
+    },
+  };
+  let invert_y = parser.keyword("@invert-y");
+  let mut contours = vec![];
+  while let Some(byte) = parser.next() {
+    match byte {
+      b'{' => contours.push(parser.contour()?),
+      _ => {
+        let position = parser.lexer.position - 1;
+        return Err(ShapeDescriptionError::UnexpectedCharacter(position));
+      },
+    }
+  }
+
+  let coloured = contours
+    .iter()
+    .flat_map(|(_, edges)| edges)
+    .any(|edge: &Edge| edge.colour.is_some());
+  let mut builder = ShapeBuilder::new();
+  // the number of segments in each contour, as they were added
+  let mut lengths = vec![];
+  for (start, edges) in contours {
+    if edges.is_empty() {
+      continue;
+    }
+    let (start, edges) = match invert_y {
+      true => reverse_edges(start, edges),
+      false => flip_edges(start, edges),
+    };
+    let segments_before = builder.shape.segments.len();
+    let mut contour = builder.contour(start);
+    for edge in &edges {
+      contour = match edge.points[..] {
+        [end] => contour.line(end),
+        [control, end] => contour.quadratic_bezier(control, end),
+        [control_1, control_2, end] => {
+          contour.cubic_bezier(control_1, control_2, end)
+        },
+        _ => unreachable!("edges have at most two control points"),
+      };
+    }
+    builder = contour.end_contour();
+    let colours: Vec<Colour> = edges
+      .iter()
+      .map(|edge| edge.colour.unwrap_or(White))
+      .collect();
+    lengths.push((builder.shape.segments.len() - segments_before, colours));
+  }
+
+  if !coloured {
+    return Ok(builder.build());
+  }
+  // the splines are the runs of edges of the same colour, with the line
+  // closing an open contour coloured like the edge before it
+  let shape = &mut builder.shape;
+  shape.splines.clear();
+  let mut segment = 0;
+  for (contour, (length, colours)) in shape.contours.iter_mut().zip(lengths) {
+    let spline_start = shape.splines.len();
+    for i in 0..length {
+      let colour = colours[i.min(colours.len() - 1)];
+      let continues = shape.splines.len() > spline_start
+        && shape.splines.last().unwrap().colour == colour;
+      if continues {
+        shape.splines.last_mut().unwrap().segments_range.end += 1;
+      } else {
+        shape.splines.push(Spline {
+          segments_range: segment + i..segment + i + 1,
+          colour,
+        });
+      }
+    }
+    segment += length;
+    contour.spline_range = spline_start..shape.splines.len();
+  }
+  Ok(builder.rearrange_contours(false).build())
+}
+
+/// Negate the y coordinates of a contour
+fn flip_edges(start: Point, mut edges: Vec<Edge>) -> (Point, Vec<Edge>) {
+  let flip = |p: &mut Point| p.y = -p.y;
+  for point in edges.iter_mut().flat_map(|edge| &mut edge.points) {
+    flip(point);
+  }
+  let mut start = start;
+  flip(&mut start);
+  (start, edges)
+}
+
+/// Reverse the direction of a contour, keeping each edge's colour
+fn reverse_edges(start: Point, edges: Vec<Edge>) -> (Point, Vec<Edge>) {
+  let end = edges
+    .last()
+    .map_or(start, |edge| *edge.points.last().unwrap());
+  let mut from = start;
+  let mut reversed: Vec<Edge> = edges
+    .into_iter()
+    .map(|edge| {
+      let mut points = edge.points;
+      let to = points.pop().unwrap();
+      points.reverse();
+      points.push(from);
+      from = to;
+      Edge {
+        points,
+        colour: edge.colour,
+      }
+    })
+    .collect();
+  reversed.reverse();
+  (end, reversed)
+}
+
+struct Parser<'a> {
+  lexer: Lexer<'a>,
+}
+
+impl Parser<'_> {
+  /// Take the next character that isn't whitespace
+  fn next(&mut self) -> Option<u8> {
+    let lexer = &mut self.lexer;
+    while lexer.bytes.get(lexer.position)?.is_ascii_whitespace() {
+      lexer.position += 1;
+    }
+    lexer.position += 1;
+    Some(lexer.bytes[lexer.position - 1])
+  }
+
+  /// Take the next character, which must exist since a contour is open
+  fn expect_next(&mut self) -> Result<u8, ShapeDescriptionError> {
+    self.next().ok_or(ShapeDescriptionError::UnexpectedEnd)
+  }
+
+  fn unexpected(&self) -> ShapeDescriptionError {
+    ShapeDescriptionError::UnexpectedCharacter(self.lexer.position - 1)
+  }
+
+  /// Take a keyword, if it comes next
+  fn keyword(&mut self, keyword: &str) -> bool {
+    let lexer = &mut self.lexer;
+    lexer.skip_separators();
+    let found = lexer.bytes[lexer.position..].starts_with(keyword.as_bytes());
+    if found {
+      lexer.position += keyword.len();
+    }
+    found
+  }
+
+  /// Take a point, if one comes next
+  fn point(&mut self) -> Result<Option<Point>, ShapeDescriptionError> {
+    self.lexer.skip_separators();
+    let position = self.lexer.position;
+    let Ok(x) = self.lexer.number() else {
+      return Ok(None);
+    };
+    let y = self
+      .lexer
+      .number()
+      .map_err(|_| ShapeDescriptionError::MissingCoordinate(position))?;
+    Ok(Some(Point::new(x, y)))
+  }
+
+  /// Take the rest of a contour, after its opening brace, returning its
+  /// first point and edges
+  fn contour(&mut self) -> Result<(Point, Vec<Edge>), ShapeDescriptionError> {
+    let Some(start) = self.point()? else {
+      return match self.expect_next()? {
+        b'}' => Ok((Point::ZERO, vec![])),
+        _ => Err(self.unexpected()),
+      };
+    };
+    let mut edges = vec![];
+    loop {
+      match self.expect_next()? {
+        b'}' => return Ok((start, edges)),
+        b';' => {},
+        _ => return Err(self.unexpected()),
+      }
+      if let Some(end) = self.point()? {
+        edges.push(Edge {
+          points: vec![end],
+          colour: None,
+        });
+        continue;
+      }
+
+      let mut colour = None;
+      let mut byte = self.expect_next()?;
+      match byte {
+        b'#' => {
+          edges.push(Edge {
+            points: vec![start],
+            colour,
+          });
+          continue;
+        },
+        // a trailing separator
+        b'}' => return Ok((start, edges)),
+        b'c' | b'C' => colour = Some(Cyan),
+        b'm' | b'M' => colour = Some(Magenta),
+        b'y' | b'Y' => colour = Some(Yellow),
+        b'w' | b'W' => colour = Some(White),
+        b';' | b'(' => {},
+        _ => return Err(self.unexpected()),
+      }
+      if colour.is_some() {
+        byte = self.expect_next()?;
+      }
+      let mut points = vec![];
+      match byte {
+        b';' => {},
+        b'(' => {
+          points = self.control_points()?;
+          if self.expect_next()? != b';' {
+            return Err(self.unexpected());
+          }
+        },
+        _ => return Err(self.unexpected()),
+      }
+      let end = match self.point()? {
+        Some(end) => end,
+        None => match self.expect_next()? {
+          b'#' => start,
+          _ => return Err(self.unexpected()),
+        },
+      };
+      points.push(end);
+      edges.push(Edge { points, colour });
+    }
+  }
+
+  /// Take up to two control points, after an opening parenthesis
+  fn control_points(&mut self) -> Result<Vec<Point>, ShapeDescriptionError> {
+    let mut points = vec![];
+    while points.len() < 2 {
+      let Some(point) = self.point()? else {
+        break;
+      };
+      points.push(point);
+      match self.expect_next()? {
+        b')' => return Ok(points),
+        b';' if points.len() < 2 => {},
+        _ => return Err(self.unexpected()),
+      }
+    }
+    match self.expect_next()? {
+      b')' if points.is_empty() => Ok(points),
+      _ => Err(self.unexpected()),
+    }
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+  use float_cmp::assert_approx_eq;
+
+  #[test]
+  fn lines() {
+    let square = |text| parse_shape_description(text).unwrap();
+    let expected = ShapeBuilder::new()
+      .contour((0., 0.))
+      .line((0., -10.))
+      .line((10., -10.))
+      .line((10., 0.))
+      .line((0., 0.))
+      .end_contour()
+      .build();
+    for text in [
+      "{ 0, 0; 0, 10; 10, 10; 10, 0; # }",
+      "{0,0;0,10;10,10;10,0;0,0}",
+      "{ 0, 0; 0, 10; 10, 10; 10, 0; }",
+      "\n{\n  0 , 0 ;\n  0 , 1e1 ;\n  10, 10;\n  10, 0;\n  #\n}\n",
+    ] {
+      assert_eq!(square(text).checksum(), expected.checksum(), "{text}");
+    }
+    assert!(parse_shape_description("").unwrap().contours.is_empty());
+    assert!(parse_shape_description("{ }").unwrap().contours.is_empty());
+  }
+
+  #[test]
+  fn curves() {
+    let shape = parse_shape_description(
+      "{ 0, 0; (5, -5); 10, 0; (10, 5; 5, 10); 0, 10; # }",
+    )
+    .unwrap();
+    assert_eq!(shape.segments.len(), 3);
+    let kinds: Vec<_> = shape.segments.iter().map(|s| s.kind).collect();
+    assert!(matches!(
+      kinds[..],
+      [
+        SegmentKind::QuadBezier,
+        SegmentKind::CubicBezier,
+        SegmentKind::Line
+      ]
+    ));
+    let quad = shape.get_segment(shape.segments[0]);
+    assert_approx_eq!(Point, quad.sample(0.5), Point::new(5., 2.5));
+  }
+
+  #[test]
+  fn colours() {
+    let shape = parse_shape_description(
+      "{ 0, 0; m; 0, 10; y; 10, 10; y; 10, 0; w (5, -5); # }",
+    )
+    .unwrap();
+    let splines: Vec<_> = shape
+      .splines
+      .iter()
+      .map(|s| (s.segments_range.clone(), s.colour))
+      .collect();
+    assert_eq!(splines, [(0..1, Magenta), (1..3, Yellow), (3..4, White)]);
+
+    // an open contour is closed with the colour of its last edge
+    let shape =
+      parse_shape_description("{ 0, 0; m; 0, 10; c; 10, 10 }").unwrap();
+    assert_eq!(shape.segments.len(), 3);
+    assert_eq!(shape.splines[1].segments_range, 1..3);
+  }
+
+  #[test]
+  fn invert_y() {
+    // the same square, with y pointing down, is still filled
+    let shape = parse_shape_description(
+      "@invert-y { 0, 0; m; 0, 10; y; 10, 10; m; 10, 0; c; # }",
+    )
+    .unwrap();
+    assert_eq!(shape.sample_single_channel((5., 5.)), 5.);
+    assert_eq!(shape.points[0], Point::new(0., 0.));
+    // the colours are kept with their edges
+    let first = shape.get_segment(shape.segments[0]);
+    assert_eq!(first.sample(1.), Point::new(10., 0.));
+    assert_eq!(shape.splines[0].colour, Cyan);
+  }
+
+  #[test]
+  fn round_trip() {
+    let text =
+      "{ 0, 0; m; 0, 10; y (5, 15); 10, 10; y (12, 6; 12, 4); 10, 0; c; # }\n";
+    let shape = parse_shape_description(text).unwrap();
+    assert_eq!(shape.to_shape_description(), text);
+  }
+
+  #[test]
+  fn errors() {
+    use ShapeDescriptionError::*;
+    let error = |text| parse_shape_description(text).err();
+    assert_eq!(error("{ 0, 0; 1 }"), Some(MissingCoordinate(8)));
+    assert_eq!(error("{ 0, 0; x; 1, 1 }"), Some(UnexpectedCharacter(8)));
+    assert_eq!(error("{ 0, 0; 1, 1"), Some(UnexpectedEnd));
+    assert_eq!(error("0, 0"), Some(UnexpectedCharacter(0)));
+    assert_eq!(
+      error("{ 0, 0; (1, 1; 2, 2; 3, 3); 4, 4 }"),
+      Some(UnexpectedCharacter(19))
+    );
+  }
+}
@@ -0,0 +1,249 @@
+use super::*;
+use rustybuzz::ttf_parser::{self, GlyphId};
+use rustybuzz::{Face, Feature, UnicodeBuffer};
+use std::collections::BTreeMap;
+
+impl ShapeBuilder {
+  /// Add the contours of a glyph's outline, in font units
+  ///
+  /// The outline has y pointing up, see [`ShapeBuilder::font_outline`].
+  /// Glyphs without an outline, such as spaces, add nothing.
+  ///
+  /// The outline is taken from the face's current instance, so for variable
+  /// fonts the axes set with
+  /// [`Face::set_variation`](ttf_parser::Face::set_variation) apply.
+  pub fn glyph_outline(self, face: &ttf_parser::Face, glyph: GlyphId) -> Self {
+    match DrawSink::draw_glyph(face, glyph) {
+      Some(sink) => self.draw_sink(sink),
+      None => self,
+    }
+  }
+}
+
+/// A glyph of a [`ShapedRun`], positioned along the run
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlacedGlyph {
+  /// The glyph's id in the font, which is also the key of its field in
+  /// [`ShapedRun::fields`]
+  pub glyph: u16,
+  /// The cluster the glyph was shaped from, as set on the
+  /// [`UnicodeBuffer`]; by default the byte offset of its first character
+  pub cluster: u32,
+  /// Where the top-left pixel of the glyph's field is drawn, in pixels from
+  /// the start of the run on the baseline, with y pointing down
+  pub position: Point,
+}
+
+/// A run of text shaped by rustybuzz, with the field of every glyph in it
+///
+/// See [`shape_run`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShapedRun<const N: usize> {
+  /// The glyphs that have an outline, in the order they were shaped
+  pub glyphs: Vec<PlacedGlyph>,
+  /// The field of each distinct glyph, sampled once however many times the
+  /// glyph appears in the run
+  pub fields: BTreeMap<u16, Field<N>>,
+  /// How far the pen moved over the whole run, in pixels
+  pub advance: Vector,
+}
+
+impl<const N: usize> ShapedRun<N> {
+  /// Add the field of every distinct glyph to an atlas, as glyphs of `font`
+  ///
+  /// Packing the run into an atlas only as wide as the sum of its glyphs
+  /// gives a strip, with every glyph on a single shelf.
+  pub fn pack(&self, atlas: &mut AtlasBuilder<N>, font: usize) {
+    for (&glyph, field) in &self.fields {
+      atlas.insert(GlyphKey { font, glyph }, field.clone());
+    }
+  }
+}
+
+/// Shape a buffer of text with rustybuzz, and sample a field for each of the
+/// glyphs it produces
+///
+/// The run is laid out at `pixels_per_em`, and each glyph's field has a
+/// margin of [`RasterConfig::max_distance`] pixels around its outline, so
+/// that the whole falloff fits. Repeated glyphs share a field.
+///
+/// Variable fonts are shaped & outlined at the instance picked by the
+/// variations set on the face, rather than the default master.
+///
+/// ```no_run
+/// # use rsdf_builder::shape_run;
+/// # use rsdf_core::{Field, RasterConfig};
+/// use rustybuzz::ttf_parser::Tag;
+/// use rustybuzz::Variation;
+///
+/// let data = std::fs::read("font.ttf").unwrap();
+/// let mut face = rustybuzz::Face::from_slice(&data, 0).unwrap();
+/// face.set_variations(&[
+///   Variation {
+///     tag: Tag::from_bytes(b"wght"),
+///     value: 700.,
+///   },
+///   Variation {
+///     tag: Tag::from_bytes(b"wdth"),
+///     value: 75.,
+///   },
+/// ]);
+/// let mut buffer = rustybuzz::UnicodeBuffer::new();
+/// buffer.push_str("hello");
+/// let run = shape_run::<3>(&face, &[], buffer, 32., RasterConfig::default());
+/// // "l" is only sampled once
+/// assert_eq!(run.glyphs.len(), 5);
+/// assert_eq!(run.fields.len(), 4);
+/// ```
+pub fn shape_run<const N: usize>(
+  face: &Face,
+  features: &[Feature],
+  buffer: UnicodeBuffer,
+  pixels_per_em: f32,
+  config: RasterConfig,
+) -> ShapedRun<N>
+where
+  Shape: SampleChannels<N>,
+{
+  let shaped = rustybuzz::shape(face, features, buffer);
+  let glyphs = shaped.glyph_infos().iter().zip(shaped.glyph_positions());
+  let scale = pixels_per_em / face.units_per_em() as f32;
+  layout(
+    glyphs.map(|(info, position)| GlyphPlacement {
+      // rustybuzz only widens ids to fit HarfBuzz's glyph type
+      glyph: info.glyph_id as u16,
+      cluster: info.cluster,
+      advance: [position.x_advance, position.y_advance],
+      offset: [position.x_offset, position.y_offset],
+    }),
+    scale,
+    config,
+    |glyph| {
+      ShapeBuilder::new()
+        .glyph_outline(face, GlyphId(glyph))
+        .build()
+    },
+  )
+}
+
+/// A shaped glyph, with its advance & offset in font units with y up
+struct GlyphPlacement {
+  glyph: u16,
+  cluster: u32,
+  advance: [i32; 2],
+  offset: [i32; 2],
+}
+
+/// Place the shaped glyphs along the run, sampling a field for each distinct
+/// glyph from its outline in font units
+fn layout<const N: usize>(
+  glyphs: impl IntoIterator<Item = GlyphPlacement>,
+  scale: f32,
+  config: RasterConfig,
+  mut outline: impl FnMut(u16) -> Shape,
+) -> ShapedRun<N>
+where
+  Shape: SampleChannels<N>,
+{
+  let mut run = ShapedRun {
+    glyphs: vec![],
+    fields: BTreeMap::new(),
+    advance: Vector::ZERO,
+  };
+  // where each glyph's field is placed relative to its origin, or `None` for
+  // glyphs without an outline
+  let mut offsets: BTreeMap<u16, Option<Vector>> = BTreeMap::new();
+  let pixels = |[x, y]: [i32; 2]| Vector::new(x as f32, -y as f32) * scale;
+
+  for placement in glyphs {
+    let offset = *offsets.entry(placement.glyph).or_insert_with(|| {
+      let glyph = PreparedGlyph::new(outline(placement.glyph), scale, config)?;
+      run.fields.insert(placement.glyph, glyph.sample(config));
+      Some(glyph.offset)
+    });
+    if let Some(offset) = offset {
+      let origin = run.advance + pixels(placement.offset);
+      run.glyphs.push(PlacedGlyph {
+        glyph: placement.glyph,
+        cluster: placement.cluster,
+        position: (origin + offset).as_point(),
+      });
+    }
+    run.advance = run.advance + pixels(placement.advance);
+  }
+  run
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn layout() {
+    // a 10 unit square sitting on the baseline, for every glyph but 0
+    let square = |glyph| match glyph {
+      0 => ShapeBuilder::new().build(),
+      _ => ShapeBuilder::new()
+        .contour((0., 0.))
+        .line((0., -10.))
+        .line((10., -10.))
+        .line((10., 0.))
+        .line((0., 0.))
+        .end_contour()
+        .build(),
+    };
+    let placement = |glyph, cluster, offset| GlyphPlacement {
+      glyph,
+      cluster,
+      advance: [20, 0],
+      offset,
+    };
+    let mut outlines = 0;
+    let config = RasterConfig {
+      max_distance: 2.,
+      ..RasterConfig::default()
+    };
+    let run = super::layout::<1>(
+      [
+        placement(1, 0, [0, 0]),
+        placement(0, 1, [0, 0]),
+        placement(1, 2, [0, 10]),
+        placement(2, 3, [0, 0]),
+      ],
+      0.5,
+      config,
+      |glyph| {
+        outlines += 1;
+        square(glyph)
+      },
+    );
+
+    // each distinct glyph is outlined once; glyph 0 has no outline
+    assert_eq!(outlines, 3);
+    assert_eq!(run.fields.len(), 2);
+    assert_eq!(run.advance, Vector::new(40., 0.));
+    let positions: Vec<_> =
+      run.glyphs.iter().map(|g| (g.cluster, g.position)).collect();
+    assert_eq!(
+      positions,
+      [
+        (0, Point::new(-2., -7.)),
+        (2, Point::new(18., -12.)),
+        (3, Point::new(28., -7.)),
+      ]
+    );
+
+    // the square fills the field, inside its margin
+    let field = &run.fields[&1];
+    assert_eq!(field.size(), [9, 9]);
+    assert_eq!(field.get([4, 4]), [2.]);
+    assert_eq!(field.get([0, 4]), [-2.]);
+
+    let mut atlas = AtlasBuilder::new(20);
+    let font = atlas.add_font("Sans");
+    run.pack(&mut atlas, font);
+    let atlas = atlas.build();
+    assert_eq!(atlas.font_glyphs(font).count(), 2);
+    assert_eq!(atlas.field.size(), [20, 9]);
+  }
+}
@@ -0,0 +1,94 @@
+use super::*;
+use skrifa::outline::{DrawError, DrawSettings, OutlinePen};
+use skrifa::OutlineGlyph;
+
+impl ShapeBuilder {
+  /// Add the contours of a glyph outline drawn by skrifa
+  ///
+  /// The `settings` choose the size, the location in a variable font's
+  /// design space, and whether the outline is hinted; a bare
+  /// [`Size`](skrifa::instance::Size) draws the default instance unhinted.
+  /// Outlines come out in pixels at the given size, or in font units at
+  /// [`Size::unscaled`](skrifa::instance::Size::unscaled). The outline has y
+  /// pointing up, see [`ShapeBuilder::font_outline`].
+  ///
+  /// ```no_run
+  /// # use rsdf_builder::ShapeBuilder;
+  /// use skrifa::instance::{LocationRef, Size};
+  /// use skrifa::{FontRef, MetadataProvider};
+  ///
+  /// let data = std::fs::read("font.ttf").unwrap();
+  /// let font = FontRef::new(&data).unwrap();
+  /// let glyph_id = font.charmap().map('g').unwrap();
+  /// let outlines = font.outline_glyphs();
+  /// let glyph = outlines.get(glyph_id).unwrap();
+  /// let shape = ShapeBuilder::new()
+  ///   .skrifa_glyph(&glyph, (Size::new(32.), LocationRef::default()))
+  ///   .unwrap()
+  ///   .build();
+  /// ```
+  pub fn skrifa_glyph<'a>(
+    self,
+    glyph: &OutlineGlyph<'a>,
+    settings: impl Into<DrawSettings<'a>>,
+  ) -> Result<Self, DrawError> {
+    let mut pen = OutlineCommands(vec![]);
+    glyph.draw(settings, &mut pen)?;
+    Ok(self.font_outline(pen.0))
+  }
+}
+
+/// Collects an outline as path commands
+struct OutlineCommands(Vec<PathCommand>);
+
+impl OutlinePen for OutlineCommands {
+  fn move_to(&mut self, x: f32, y: f32) {
+    self.0.push(PathCommand::MoveTo(Point::new(x, y)));
+  }
+
+  fn line_to(&mut self, x: f32, y: f32) {
+    self.0.push(PathCommand::LineTo(Point::new(x, y)));
+  }
+
+  fn quad_to(&mut self, cx0: f32, cy0: f32, x: f32, y: f32) {
+    self
+      .0
+      .push(PathCommand::QuadTo(Point::new(cx0, cy0), Point::new(x, y)));
+  }
+
+  fn curve_to(
+    &mut self,
+    cx0: f32,
+    cy0: f32,
+    cx1: f32,
+    cy1: f32,
+    x: f32,
+    y: f32,
+  ) {
+    self.0.push(PathCommand::CubicTo(
+      Point::new(cx0, cy0),
+      Point::new(cx1, cy1),
+      Point::new(x, y),
+    ));
+  }
+
+  fn close(&mut self) {
+    self.0.push(PathCommand::ClosePath);
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn outline_commands() {
+    let mut pen = OutlineCommands(vec![]);
+    pen.move_to(0., 0.);
+    pen.line_to(0., 10.);
+    pen.quad_to(10., 10., 10., 5.);
+    pen.curve_to(10., 2., 5., 0., 0., 0.);
+    pen.close();
+    test_outline::assert_d(&pen.0);
+  }
+}
@@ -0,0 +1,331 @@
+//! Procedurally generated pathological shapes, for shaking out edge cases in
+//! the solvers and colouring
+//!
+//! Requires the `stress` feature outside of this crate's own tests.
+
+use crate::*;
+use std::f32::consts::TAU;
+
+/// Small, deterministic pseudo-random number generator (xorshift64*)
+///
+/// The same seed always produces the same corpus, on every platform.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+  pub fn new(seed: u64) -> Self {
+    // xorshift gets stuck on a zero state
+    Rng(seed ^ 0x9e37_79b9_7f4a_7c15)
+  }
+
+  pub fn next_u64(&mut self) -> u64 {
+    self.0 ^= self.0 >> 12;
+    self.0 ^= self.0 << 25;
+    self.0 ^= self.0 >> 27;
+    self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+  }
+
+  /// A uniformly distributed value in `[min, max)`
+  pub fn range(&mut self, min: f32, max: f32) -> f32 {
+    let unit = (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32;
+    min + unit * (max - min)
+  }
+
+  /// A point with coordinates uniformly distributed in `[min, max)`
+  pub fn point(&mut self, min: f32, max: f32) -> Point {
+    Point::new(self.range(min, max), self.range(min, max))
+  }
+}
+
+/// The kinds of pathological shape that can be generated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pathology {
+  /// Curves meeting at an almost (but not quite) smooth corner
+  NearTangent,
+  /// Cubic curves whose derivative vanishes somewhere along them
+  Cusp,
+  /// Cubic curves with a tiny self-intersecting loop
+  TinyLoop,
+  /// An ordinary shape placed very far from the origin
+  HugeCoordinates,
+  /// A random sequence of segments of every kind
+  Random,
+  /// A dense, CJK-like glyph: many brush strokes with hundreds of segments
+  /// between them, and enclosures nested inside each other
+  Ideograph,
+}
+
+impl Pathology {
+  pub const ALL: [Pathology; 6] = [
+    Pathology::NearTangent,
+    Pathology::Cusp,
+    Pathology::TinyLoop,
+    Pathology::HugeCoordinates,
+    Pathology::Random,
+    Pathology::Ideograph,
+  ];
+}
+
+/// Generate a shape exhibiting the given [`Pathology`]
+///
+/// The shapes are roughly centred in a 64x64 canvas at the origin, except
+/// for [`Pathology::HugeCoordinates`].
+pub fn generate(pathology: Pathology, rng: &mut Rng) -> Shape {
+  match pathology {
+    Pathology::NearTangent => {
+      let angle = rng.range(1e-5, 1e-2);
+      let (sin, cos) = angle.sin_cos();
+      let joint = rng.point(24., 40.);
+      ShapeBuilder::new()
+        .contour((8., joint.y))
+        .quadratic_bezier((16., 8.), (joint.x, joint.y))
+        .quadratic_bezier(
+          (joint.x + 8. * cos, joint.y + 8. * sin),
+          (56., joint.y),
+        )
+        .line((32., 60.))
+        .end_contour()
+        .build()
+    },
+    Pathology::Cusp => {
+      let origin = rng.point(4., 16.);
+      let size = rng.range(8., 40.);
+      let at = |x: f32, y: f32| (origin.x + x * size, origin.y + y * size);
+      ShapeBuilder::new()
+        .contour(at(0., 0.))
+        .cubic_bezier(at(1., 1.), at(0., 1.), at(1., 0.))
+        .line(at(0.5, -0.5))
+        .end_contour()
+        .build()
+    },
+    Pathology::TinyLoop => {
+      let origin = rng.point(8., 32.);
+      let size = rng.range(0.01, 1.);
+      let at = |x: f32, y: f32| (origin.x + x * size, origin.y + y * size);
+      ShapeBuilder::new()
+        .contour(at(0., 0.))
+        .cubic_bezier(at(3., 2.), at(-1., 2.), at(2., 0.))
+        .line((origin.x + 20., origin.y + 20.))
+        .line((origin.x, origin.y + 20.))
+        .end_contour()
+        .build()
+    },
+    Pathology::HugeCoordinates => {
+      let origin = rng.point(1e5, 1e7);
+      let size = rng.range(1., 64.);
+      ShapeBuilder::new()
+        .contour((origin.x, origin.y))
+        .line((origin.x + size, origin.y))
+        .quadratic_bezier(
+          (origin.x + size, origin.y + size),
+          (origin.x, origin.y + size),
+        )
+        .end_contour()
+        .build()
+    },
+    Pathology::Random => {
+      let mut contour = ShapeBuilder::new().contour(rng.point(0., 64.));
+      for _ in 0..(rng.next_u64() % 8 + 1) {
+        contour = match rng.next_u64() % 4 {
+          0 => contour.line(rng.point(0., 64.)),
+          1 => {
+            contour.quadratic_bezier(rng.point(0., 64.), rng.point(0., 64.))
+          },
+          2 => contour.cubic_bezier(
+            rng.point(0., 64.),
+            rng.point(0., 64.),
+            rng.point(0., 64.),
+          ),
+          _ => {
+            let radius = rng.range(4., 32.);
+            contour.elliptical_arc(
+              radius,
+              radius * rng.range(0.25, 4.),
+              rng.range(0., TAU),
+              rng.next_u64() % 2 == 0,
+              rng.next_u64() % 2 == 0,
+              rng.point(0., 64.),
+            )
+          },
+        }
+      }
+      contour.end_contour().build()
+    },
+    Pathology::Ideograph => ideograph(rng),
+  }
+}
+
+/// A glyph in the style of a complex CJK character, such as 龍 or 艦
+///
+/// Brush strokes are drawn as wobbly outlines with curved ends, and may
+/// cross each other, while enclosures like 口 are boxes with holes, which
+/// can hold another box in turn, like 回.
+fn ideograph(rng: &mut Rng) -> Shape {
+  let mut builder = ShapeBuilder::new();
+  for _ in 0..(rng.next_u64() % 3 + 1) {
+    let min = rng.point(2., 28.);
+    let size = rng.range(16., 34.);
+    let depth = rng.next_u64() % 2 + 1;
+    builder = enclosure(builder, min, size, depth, rng);
+  }
+  for _ in 0..(rng.next_u64() % 8 + 8) {
+    let (from, to) = match rng.next_u64() % 3 {
+      0 => {
+        let (y, x) = (rng.range(4., 60.), rng.range(2., 30.));
+        ((x, y), (x + rng.range(10., 32.), y + rng.range(-2., 2.)))
+      },
+      1 => {
+        let (x, y) = (rng.range(4., 60.), rng.range(2., 30.));
+        ((x, y), (x + rng.range(-2., 2.), y + rng.range(10., 32.)))
+      },
+      _ => {
+        let from = rng.point(8., 40.);
+        let to = from + Vector::new(rng.range(-12., 12.), rng.range(8., 20.));
+        ((from.x, from.y), (to.x, to.y))
+      },
+    };
+    builder = stroke(builder, from.into(), to.into(), rng);
+  }
+  builder.build()
+}
+
+/// A brush stroke from `from` to `to`, wound clockwise
+fn stroke(
+  builder: ShapeBuilder,
+  from: Point,
+  to: Point,
+  rng: &mut Rng,
+) -> ShapeBuilder {
+  let width = rng.range(1.5, 4.);
+  let direction = Vector::from_points(from, to).norm();
+  let normal = Vector::new(-direction.y, direction.x) * (width / 2.);
+  let cap = direction * width;
+  // each side of the stroke is made of many short, slightly wobbly lines
+  let side = |rng: &mut Rng, start: Point, end: Point| -> Vec<Point> {
+    (1..12)
+      .map(|i| {
+        let t = i as f32 / 12.;
+        let wobble = normal * rng.range(-0.1, 0.1);
+        start + Vector::from_points(start, end) * t + wobble
+      })
+      .collect()
+  };
+
+  let mut contour = builder.contour(from - normal);
+  for point in side(rng, from - normal, to - normal) {
+    contour = contour.line(point);
+  }
+  contour = contour
+    .line(to - normal)
+    .quadratic_bezier(to + cap, to + normal);
+  for point in side(rng, to + normal, from + normal) {
+    contour = contour.line(point);
+  }
+  contour
+    .line(from + normal)
+    .quadratic_bezier(from - cap, from - normal)
+    .end_contour()
+}
+
+/// A square box with a hole, holding up to `depth - 1` smaller boxes
+fn enclosure(
+  builder: ShapeBuilder,
+  min: Point,
+  size: f32,
+  depth: u64,
+  rng: &mut Rng,
+) -> ShapeBuilder {
+  let wall = rng.range(1.5, 3.);
+  let square =
+    |builder: ShapeBuilder, min: Point, size: f32, clockwise: bool| {
+      let mut corners = [
+        min,
+        min + Vector::new(size, 0.),
+        min + Vector::new(size, size),
+        min + Vector::new(0., size),
+      ];
+      if !clockwise {
+        corners.reverse();
+      }
+      corners[1..]
+        .iter()
+        .chain([&corners[0]])
+        .fold(builder.contour(corners[0]), |contour, &corner| {
+          contour.line(corner)
+        })
+        .end_contour()
+    };
+  let inner = (min + Vector::new(wall, wall), size - 2. * wall);
+  let builder = square(builder, min, size, true);
+  let builder = square(builder, inner.0, inner.1, false);
+  let inset = 2. * wall;
+  if depth > 1 && inner.1 > 4. * inset {
+    let (min, size) =
+      (inner.0 + Vector::new(inset, inset), inner.1 - 2. * inset);
+    enclosure(builder, min, size, depth - 1, rng)
+  } else {
+    builder
+  }
+}
+
+/// Generate `count` shapes of every [`Pathology`] from the given `seed`
+pub fn corpus(seed: u64, count: usize) -> Vec<(Pathology, Shape)> {
+  let mut rng = Rng::new(seed);
+  Pathology::ALL
+    .into_iter()
+    .flat_map(|pathology| std::iter::repeat_n(pathology, count))
+    .map(|pathology| (pathology, generate(pathology, &mut rng)))
+    .collect()
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn corpus_is_deterministic() {
+    let a: Vec<u64> = corpus(7, 4).iter().map(|(_, s)| s.checksum()).collect();
+    let b: Vec<u64> = corpus(7, 4).iter().map(|(_, s)| s.checksum()).collect();
+    assert_eq!(a, b);
+    let c: Vec<u64> = corpus(8, 4).iter().map(|(_, s)| s.checksum()).collect();
+    assert_ne!(a, c);
+  }
+
+  #[test]
+  fn sample_ideographs() {
+    let mut rng = Rng::new(1);
+    let config = RasterConfig::default();
+    for _ in 0..4 {
+      let shape = generate(Pathology::Ideograph, &mut rng);
+      assert!(shape.segments.len() > 150, "{}", shape.segments.len());
+      assert!(shape.contours.len() > 10);
+
+      let start = std::time::Instant::now();
+      let field = shape.sample_field::<3>([64, 64], config);
+      // generous, so that only a pathological slowdown fails in debug builds
+      let elapsed = start.elapsed();
+      assert!(elapsed.as_secs() < 20, "sampling took {elapsed:?}");
+      assert!(
+        field.samples().iter().flatten().all(|d| d.is_finite()),
+        "non-finite distance"
+      );
+    }
+  }
+
+  #[test]
+  fn sample_corpus() {
+    for (pathology, shape) in corpus(0, 8) {
+      for y in (-8..72).step_by(8) {
+        for x in (-8..72).step_by(8) {
+          let point = Point::new(x as f32, y as f32);
+          let [r, g, b] = shape.sample(point);
+          let single = shape.sample_single_channel(point);
+          assert!(
+            [r, g, b, single].iter().all(|d| !d.is_nan()),
+            "NaN sampling {pathology:?} at {point:?}"
+          );
+        }
+      }
+    }
+  }
+}
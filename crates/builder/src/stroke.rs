@@ -0,0 +1,747 @@
+use super::*;
+use elliptical_arc::{CentreParam, EndpointParam};
+
+/// The shape drawn at the open ends of a stroke
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum LineCap {
+  /// The stroke ends flush with the end of the path
+  #[default]
+  Butt,
+  /// A half circle, with a diameter of the stroke's width
+  Round,
+  /// The stroke is extended past the end by half its width
+  Square,
+}
+
+/// The shape drawn on the outside of the corners of a stroke
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum LineJoin {
+  /// The sides are extended until they meet, unless that's further than the
+  /// [`StrokeStyle::miter_limit`], in which case the corner is bevelled
+  #[default]
+  Miter,
+  /// A circular arc, centred on the corner
+  Round,
+  /// The ends of the sides are joined by a straight line
+  Bevel,
+}
+
+/// How to stroke a path, see [`ShapeBuilder::stroked_path`]
+///
+/// The defaults are those of SVG: a width of 1, butt caps and mitered
+/// joins with a limit of 4.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StrokeStyle {
+  pub width: f32,
+  pub cap: LineCap,
+  pub join: LineJoin,
+  /// The longest miter allowed, as a multiple of the width
+  pub miter_limit: f32,
+  /// How far the sides of the stroke may stray from the true offset of a
+  /// curve, in the units of the path; curves are stroked as polylines
+  pub tolerance: f32,
+}
+
+impl Default for StrokeStyle {
+  fn default() -> Self {
+    Self {
+      width: 1.,
+      cap: LineCap::Butt,
+      join: LineJoin::Miter,
+      miter_limit: 4.,
+      tolerance: 0.05,
+    }
+  }
+}
+
+impl ShapeBuilder {
+  /// Add the filled outline of a stroke along a sequence of path commands
+  ///
+  /// Unlike [`ShapeBuilder::path`], subpaths are only closed by a
+  /// [`PathCommand::ClosePath`]. An open subpath is outlined by a single
+  /// contour, with caps at its ends, and a closed one by a pair of contours,
+  /// one on either side. The inside of the stroke has positive distances,
+  /// see [`ShapeBuilder::filled_path`].
+  ///
+  /// Where the path crosses itself, or turns too tightly for the width of
+  /// the stroke, the outline overlaps itself, which gives wrong distances
+  /// near the overlap.
+  ///
+  /// ```
+  /// # use rsdf_builder::{LineCap, PathCommand, ShapeBuilder, StrokeStyle};
+  /// let path = [
+  ///   PathCommand::MoveTo((0., 0.).into()),
+  ///   PathCommand::LineTo((20., 0.).into()),
+  /// ];
+  /// let style = StrokeStyle {
+  ///   width: 4.,
+  ///   cap: LineCap::Round,
+  ///   ..StrokeStyle::default()
+  /// };
+  /// let shape = ShapeBuilder::new().stroked_path(path, style).build();
+  /// assert_eq!(shape.sample_single_channel((10., 0.)), 2.);
+  /// assert_eq!(shape.sample_single_channel((10., 5.)), -3.);
+  /// assert_eq!(shape.sample_single_channel((-5., 0.)), -3.);
+  /// ```
+  pub fn stroked_path(
+    self,
+    commands: impl IntoIterator<Item = PathCommand>,
+    style: StrokeStyle,
+  ) -> Self {
+    self.filled_path(stroke_outline(commands, style))
+  }
+}
+
+impl ShapeBuilder {
+  /// Add the filled outlines of a dashed stroke along a sequence of path
+  /// commands
+  ///
+  /// `dashes` alternates between the lengths of the dashes and the gaps
+  /// between them, and repeats along each subpath; a list of odd length is
+  /// repeated twice, as in SVG. The pattern starts `offset` along it. Each
+  /// dash is outlined by a contour of its own, with caps at both ends, as
+  /// for an open subpath of [`ShapeBuilder::stroked_path`]. Without any
+  /// positive lengths the stroke is solid, and so is it when the pattern
+  /// repeats within the style's tolerance, or would split the path into more
+  /// than [`MAX_DASHES`] dashes, since the dashes couldn't be told apart.
+  ///
+  /// ```
+  /// # use rsdf_builder::{PathCommand, ShapeBuilder, StrokeStyle};
+  /// let path = [
+  ///   PathCommand::MoveTo((0., 0.).into()),
+  ///   PathCommand::LineTo((20., 0.).into()),
+  /// ];
+  /// let style = StrokeStyle {
+  ///   width: 2.,
+  ///   ..StrokeStyle::default()
+  /// };
+  /// let shape = ShapeBuilder::new()
+  ///   .dashed_path(path, style, &[4., 2.], 0.)
+  ///   .build();
+  /// // dashes from 0 to 4, 6 to 10, 12 to 16 & 18 to 20
+  /// assert_eq!(shape.contours.len(), 4);
+  /// assert_eq!(shape.sample_single_channel((2., 0.)), 1.);
+  /// assert_eq!(shape.sample_single_channel((5., 0.)), -1.);
+  /// ```
+  pub fn dashed_path(
+    self,
+    commands: impl IntoIterator<Item = PathCommand>,
+    style: StrokeStyle,
+    dashes: &[f32],
+    offset: f32,
+  ) -> Self {
+    self.filled_path(dashed_outline(commands, style, dashes, offset))
+  }
+}
+
+/// The outline of a stroke along a sequence of path commands, as filled by
+/// [`ShapeBuilder::stroked_path`], in either winding direction
+///
+/// Front-ends that transform the stroke after outlining it, such as SVG's
+/// with the width in the coordinates of the element, fill it with
+/// [`ShapeBuilder::filled_path`].
+pub fn stroke_outline(
+  commands: impl IntoIterator<Item = PathCommand>,
+  style: StrokeStyle,
+) -> Vec<PathCommand> {
+  let mut outline = vec![];
+  for (points, closed) in flatten(commands, style.tolerance) {
+    stroke_polyline(&points, closed, style, &mut outline);
+  }
+  outline
+}
+
+/// The outlines of a dashed stroke along a sequence of path commands, as
+/// filled by [`ShapeBuilder::dashed_path`], see [`stroke_outline`]
+pub fn dashed_outline(
+  commands: impl IntoIterator<Item = PathCommand>,
+  style: StrokeStyle,
+  dashes: &[f32],
+  offset: f32,
+) -> Vec<PathCommand> {
+  let mut pattern = dashes.to_vec();
+  if pattern.len() % 2 == 1 {
+    pattern.extend_from_slice(dashes);
+  }
+  let period: f32 = pattern.iter().sum();
+  let polylines = flatten(commands, style.tolerance);
+  let length: f32 = polylines
+    .iter()
+    .map(|(points, closed)| {
+      let closing = closed.then(|| [points[points.len() - 1], points[0]]);
+      points
+        .windows(2)
+        .map(|line| [line[0], line[1]])
+        .chain(closing)
+        .map(|[a, b]| (b - a).abs())
+        .sum::<f32>()
+    })
+    .sum();
+  let dash_count = (length / period).ceil() * pattern.len() as f32 / 2.;
+  let solid = pattern.iter().any(|&length| length < 0.)
+    || !(period >= style.tolerance && period > 0. && period.is_finite())
+    || dash_count > MAX_DASHES as f32;
+
+  let mut outline = vec![];
+  for (mut points, closed) in polylines {
+    if solid {
+      stroke_polyline(&points, closed, style, &mut outline);
+      continue;
+    }
+    if closed {
+      points.push(points[0]);
+    }
+    for dash in dash_polyline(&points, &pattern, offset.rem_euclid(period)) {
+      stroke_polyline(&dash, false, style, &mut outline);
+    }
+  }
+  outline
+}
+
+/// The most dashes [`ShapeBuilder::dashed_path`] splits a path into, beyond
+/// which the stroke is solid
+pub const MAX_DASHES: usize = 1 << 16;
+
+/// Split a polyline into the polylines of its dashes
+///
+/// `pattern` alternates between dash & gap lengths, and has an even length;
+/// `phase` is how far into the pattern the polyline starts.
+fn dash_polyline(
+  points: &[Point],
+  pattern: &[f32],
+  phase: f32,
+) -> Vec<Vec<Point>> {
+  // find where in the pattern the polyline starts
+  let mut index = 0;
+  let mut remaining = pattern[0];
+  let mut phase = phase;
+  while phase >= remaining {
+    phase -= remaining;
+    index = (index + 1) % pattern.len();
+    remaining = pattern[index];
+  }
+
+  // distances are accumulated along the whole polyline, rather than
+  // subtracted from each line, so that every dash & gap moves them on
+  let mut boundary = f64::from(remaining - phase);
+  let mut start = 0f64;
+  let mut dashes = vec![];
+  let mut dash: Vec<Point> = if index % 2 == 0 {
+    vec![points[0]]
+  } else {
+    vec![]
+  };
+  for line in points.windows(2) {
+    let [a, b] = [line[0], line[1]];
+    let length = f64::from((b - a).abs());
+    let end = start + length;
+    // end every dash & gap that finishes along this line
+    while boundary < end {
+      let t = ((boundary - start) / length) as f32;
+      let point = a + (b - a) * t;
+      // either ending a dash, or starting the next one
+      dash.push(point);
+      if index % 2 == 0 {
+        dashes.push(std::mem::take(&mut dash));
+      }
+      index = (index + 1) % pattern.len();
+      boundary += f64::from(pattern[index]);
+    }
+    start = end;
+    if index % 2 == 0 {
+      dash.push(b);
+    }
+  }
+  if index % 2 == 0 && dash.len() > 1 {
+    dashes.push(dash);
+  }
+  dashes
+}
+
+/// Flatten each subpath into a polyline, and whether it was closed
+///
+/// Consecutive duplicate points are left out, and so is the last point of a
+/// closed polyline when it lands back on the first.
+pub(crate) fn flatten(
+  commands: impl IntoIterator<Item = PathCommand>,
+  tolerance: f32,
+) -> Vec<(Vec<Point>, bool)> {
+  let mut polylines = vec![];
+  let mut points: Vec<Point> = vec![];
+  let mut current = Point::ZERO;
+  let push = |points: &mut Vec<Point>, point: Point| {
+    if points.last() != Some(&point) {
+      points.push(point);
+    }
+  };
+  for command in commands {
+    match command {
+      PathCommand::MoveTo(point) => {
+        // a move, with nothing drawn from it, isn't a subpath
+        if points.len() > 1 {
+          polylines.push((std::mem::take(&mut points), false));
+        }
+        points.clear();
+        points.push(point);
+      },
+      PathCommand::LineTo(end) => push(&mut points, end),
+      PathCommand::QuadTo(control, end) => {
+        let deviation = (end - control) - (control - current);
+        let pieces = pieces(deviation.abs() / 8., tolerance);
+        for i in 1..=pieces {
+          let t = i as f32 / pieces as f32;
+          let point =
+            lerp(lerp(current, control, t), lerp(control, end, t), t);
+          push(&mut points, point);
+        }
+      },
+      PathCommand::CubicTo(control_1, control_2, end) => {
+        let deviation = ((control_2 - control_1) - (control_1 - current))
+          .abs()
+          .max(((end - control_2) - (control_2 - control_1)).abs());
+        let pieces = pieces(deviation * 3. / 4., tolerance);
+        for i in 1..=pieces {
+          let t = i as f32 / pieces as f32;
+          let [a, b, c] = [
+            lerp(current, control_1, t),
+            lerp(control_1, control_2, t),
+            lerp(control_2, end, t),
+          ];
+          let point = lerp(lerp(a, b, t), lerp(b, c, t), t);
+          push(&mut points, point);
+        }
+      },
+      PathCommand::ArcTo {
+        rx,
+        ry,
+        phi,
+        large_arc,
+        sweep,
+        end,
+      } => {
+        if end != current && rx != 0. && ry != 0. {
+          let arc = CentreParam::from(EndpointParam {
+            start: current,
+            rx,
+            ry,
+            phi,
+            large_arc,
+            sweep_ccw: sweep,
+            end,
+          });
+          // the sagitta of each piece is within the tolerance
+          let radius = arc.r.max(arc.r * arc.k).abs();
+          let step = 2. * (1. - tolerance / radius).clamp(-1., 1.).acos();
+          let pieces = (arc.delta.abs() / step).ceil().clamp(1., 1024.);
+          for i in 1..pieces as usize {
+            let angle = arc.theta + arc.delta * i as f32 / pieces;
+            push(&mut points, arc.sample_ellipse(angle));
+          }
+        }
+        push(&mut points, end);
+      },
+      PathCommand::ClosePath => {
+        if points.len() > 1 && points.first() == points.last() {
+          points.pop();
+        }
+        let start = points.first().copied();
+        if !points.is_empty() {
+          polylines.push((std::mem::take(&mut points), true));
+        }
+        // a subpath after a close starts where it left off
+        if let Some(start) = start {
+          points.push(start);
+          current = start;
+          continue;
+        }
+      },
+    }
+    current = points.last().copied().unwrap_or(current);
+  }
+  if points.len() > 1 {
+    polylines.push((points, false));
+  }
+  polylines
+}
+
+/// The number of lines to flatten a curve with, given the maximum deviation
+/// of a single line from the curve
+fn pieces(deviation: f32, tolerance: f32) -> usize {
+  (deviation / tolerance).sqrt().ceil().clamp(1., 1024.) as usize
+}
+
+fn lerp(a: Point, b: Point, t: f32) -> Point {
+  a + (b - a) * t
+}
+
+/// Outline a polyline with a stroke, adding the outline's path commands
+fn stroke_polyline(
+  points: &[Point],
+  closed: bool,
+  style: StrokeStyle,
+  outline: &mut Vec<PathCommand>,
+) {
+  let half = style.width / 2.;
+  if points.len() < 2 {
+    // a lone point only shows up with a cap
+    let Some(&point) = points.first() else {
+      return;
+    };
+    let [right, down] = [Vector::new(half, 0.), Vector::new(0., half)];
+    match style.cap {
+      LineCap::Butt => {},
+      LineCap::Round => {
+        outline.push(PathCommand::MoveTo(point - right));
+        outline.push(arc(half, point + right));
+        outline.push(arc(half, point - right));
+        outline.push(PathCommand::ClosePath);
+      },
+      LineCap::Square => {
+        outline.push(PathCommand::MoveTo(point - right - down));
+        outline.push(PathCommand::LineTo(point + right - down));
+        outline.push(PathCommand::LineTo(point + right + down));
+        outline.push(PathCommand::LineTo(point - right + down));
+        outline.push(PathCommand::ClosePath);
+      },
+    }
+    return;
+  }
+
+  let reversed: Vec<Point> = points.iter().rev().copied().collect();
+  if closed {
+    side(points, true, style, outline);
+    outline.push(PathCommand::ClosePath);
+    side(&reversed, true, style, outline);
+    outline.push(PathCommand::ClosePath);
+  } else {
+    side(points, false, style, outline);
+    cap(points, style, outline);
+    let start = outline.len();
+    side(&reversed, false, style, outline);
+    // continue from the cap, rather than starting a new subpath
+    outline.remove(start);
+    cap(&reversed, style, outline);
+    outline.push(PathCommand::ClosePath);
+  }
+}
+
+/// The unit normal of the line from `a` to `b`, to the left of the line as
+/// displayed with y pointing down, which is the side [`side`] offsets to
+fn normal(a: Point, b: Point) -> Vector {
+  let Vector { x, y } = (b - a).norm();
+  Vector::new(y, -x)
+}
+
+/// A circular arc of the given radius, turning clockwise as displayed,
+/// which is the way the outline turns around the outside of a stroke
+fn arc(radius: f32, end: Point) -> PathCommand {
+  PathCommand::ArcTo {
+    rx: radius,
+    ry: radius,
+    phi: 0.,
+    large_arc: false,
+    sweep: true,
+    end,
+  }
+}
+
+/// Add the side of the stroke to the left of a polyline, starting with a
+/// move to its first point
+///
+/// Closed polylines start & end halfway along their first line instead,
+/// after being joined back around to it.
+fn side(
+  points: &[Point],
+  closed: bool,
+  style: StrokeStyle,
+  outline: &mut Vec<PathCommand>,
+) {
+  let half = style.width / 2.;
+  let n = points.len();
+  let lines = if closed { n } else { n - 1 };
+  let line = |i: usize| (points[i % n], points[(i + 1) % n]);
+  let offset = |i: usize| {
+    let (a, b) = line(i);
+    let normal = normal(a, b) * half;
+    (a + normal, b + normal)
+  };
+
+  let (first, second) = offset(0);
+  let middle = lerp(first, second, 0.5);
+  outline.push(PathCommand::MoveTo(if closed { middle } else { first }));
+  for i in 0..lines {
+    let (_, end) = offset(i);
+    if i + 1 == lines && !closed {
+      outline.push(PathCommand::LineTo(end));
+      break;
+    }
+    // join this line to the next, around their shared corner
+    let next = (i + 1) % n;
+    let (a, corner) = line(i);
+    let (_, b) = line(next);
+    let (start, _) = offset(next);
+    let [before, after] = [(corner - a).norm(), (b - corner).norm()];
+    let cos = before.dot(after);
+    // the offset sides meet at `corner + mitre`, when they meet at all
+    let mitre = (normal(a, corner) + normal(corner, b)) * (half / (1. + cos));
+    if before.wedge(after) > 0. {
+      // the outside of a turn to the right
+      match style.join {
+        LineJoin::Miter if (2. / (1. + cos)).sqrt() <= style.miter_limit => {
+          outline.push(PathCommand::LineTo(end));
+          outline.push(PathCommand::LineTo(corner + mitre));
+        },
+        LineJoin::Round => {
+          outline.push(PathCommand::LineTo(end));
+          outline.push(arc(half, start));
+        },
+        _ => outline.push(PathCommand::LineTo(end)),
+      }
+      outline.push(PathCommand::LineTo(start));
+    } else if cos < 1. - f32::EPSILON {
+      // the inside of the corner, where the sides cross, unless the lines
+      // are too short for them to
+      let reach = half * ((1. - cos) / (1. + cos)).sqrt();
+      if reach <= (corner - a).abs().min((b - corner).abs()) {
+        outline.push(PathCommand::LineTo(corner + mitre));
+      } else {
+        outline.push(PathCommand::LineTo(end));
+        outline.push(PathCommand::LineTo(corner));
+        outline.push(PathCommand::LineTo(start));
+      }
+    }
+  }
+  if closed {
+    outline.push(PathCommand::LineTo(middle));
+  }
+}
+
+/// Add the cap at the end of a polyline, from its left side to its right
+fn cap(points: &[Point], style: StrokeStyle, outline: &mut Vec<PathCommand>) {
+  let half = style.width / 2.;
+  let [.., a, end] = points else {
+    return;
+  };
+  let normal = normal(*a, *end) * half;
+  let forward = (*end - *a).norm() * half;
+  match style.cap {
+    LineCap::Butt => outline.push(PathCommand::LineTo(*end - normal)),
+    LineCap::Round => outline.push(arc(half, *end - normal)),
+    LineCap::Square => {
+      outline.push(PathCommand::LineTo(*end + normal + forward));
+      outline.push(PathCommand::LineTo(*end - normal + forward));
+      outline.push(PathCommand::LineTo(*end - normal));
+    },
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  fn style(width: f32, cap: LineCap, join: LineJoin) -> StrokeStyle {
+    StrokeStyle {
+      width,
+      cap,
+      join,
+      ..StrokeStyle::default()
+    }
+  }
+
+  #[test]
+  fn flatten() {
+    let polylines = super::flatten(
+      [
+        PathCommand::MoveTo((0., 0.).into()),
+        PathCommand::LineTo((10., 0.).into()),
+        PathCommand::LineTo((10., 0.).into()),
+        PathCommand::QuadTo((20., 0.).into(), (20., 10.).into()),
+        PathCommand::LineTo((0., 0.).into()),
+        PathCommand::ClosePath,
+        PathCommand::MoveTo((50., 0.).into()),
+        PathCommand::ArcTo {
+          rx: 5.,
+          ry: 5.,
+          phi: 0.,
+          large_arc: false,
+          sweep: true,
+          end: (60., 0.).into(),
+        },
+      ],
+      0.05,
+    );
+    let [(square, true), (arc, false)] = &polylines[..] else {
+      panic!("expected a closed & an open polyline, found {polylines:?}");
+    };
+    assert_eq!(square[..2], [Point::new(0., 0.), Point::new(10., 0.)]);
+    assert_eq!(square.last(), Some(&Point::new(20., 10.)));
+    assert!(square.len() > 4);
+    // every point of the arc is on its circle
+    for point in arc {
+      let radius = (*point - Point::new(55., 0.)).abs();
+      float_cmp::assert_approx_eq!(f32, radius, 5., epsilon = 1e-4);
+    }
+  }
+
+  #[test]
+  fn caps() {
+    let line = [
+      PathCommand::MoveTo((0., 0.).into()),
+      PathCommand::LineTo((20., 0.).into()),
+    ];
+    let sample = |cap, point: (f32, f32)| {
+      let style = style(4., cap, LineJoin::Miter);
+      let shape = ShapeBuilder::new().stroked_path(line, style).build();
+      shape.sample_single_channel(point)
+    };
+    assert_eq!(sample(LineCap::Butt, (10., 1.)), 1.);
+    assert_eq!(sample(LineCap::Butt, (-1., 0.)), -1.);
+    assert_eq!(sample(LineCap::Square, (-1., 0.)), 1.);
+    assert_eq!(sample(LineCap::Square, (23., 0.)), -1.);
+    float_cmp::assert_approx_eq!(
+      f32,
+      sample(LineCap::Round, (21., 0.)),
+      1.,
+      epsilon = 1e-4
+    );
+
+    // a lone point is a dot, with a round cap
+    let dot = [PathCommand::MoveTo((0., 0.).into()), PathCommand::ClosePath];
+    let style = style(4., LineCap::Round, LineJoin::Miter);
+    let shape = ShapeBuilder::new().stroked_path(dot, style).build();
+    float_cmp::assert_approx_eq!(
+      f32,
+      shape.sample_single_channel((0., 1.)),
+      1.,
+      epsilon = 1e-4
+    );
+  }
+
+  #[test]
+  fn joins() {
+    // an "L", turning right then left
+    let path = [
+      PathCommand::MoveTo((0., 0.).into()),
+      PathCommand::LineTo((10., 0.).into()),
+      PathCommand::LineTo((10., 10.).into()),
+      PathCommand::LineTo((20., 10.).into()),
+    ];
+    let sample = |join, point: (f32, f32)| {
+      let style = style(2., LineCap::Butt, join);
+      let shape = ShapeBuilder::new().stroked_path(path, style).build();
+      assert_eq!(shape.contours.len(), 1);
+      shape.sample_single_channel(point)
+    };
+    // the outside of the first corner
+    assert_eq!(sample(LineJoin::Miter, (10.5, -0.5)), 0.5);
+    assert!(sample(LineJoin::Bevel, (10.7, -0.7)) < 0.);
+    assert!(sample(LineJoin::Round, (10.5, -0.5)) > 0.);
+    assert!(sample(LineJoin::Round, (10.9, -0.9)) < 0.);
+    // the inside of both corners is the same for every join
+    for join in [LineJoin::Miter, LineJoin::Round, LineJoin::Bevel] {
+      assert_eq!(sample(join, (8., 2.)), -1.);
+      assert_eq!(sample(join, (10., 5.)), 1.);
+      assert_eq!(sample(join, (12., 8.)), -1.);
+    }
+
+    // a sharp turn is bevelled, beyond the miter limit
+    let spike = [
+      PathCommand::MoveTo((0., 0.).into()),
+      PathCommand::LineTo((20., 1.).into()),
+      PathCommand::LineTo((0., 2.).into()),
+    ];
+    let style = style(2., LineCap::Butt, LineJoin::Miter);
+    let shape = ShapeBuilder::new().stroked_path(spike, style).build();
+    assert!(shape.sample_single_channel((21.5, 1.)) < 0.);
+  }
+
+  #[test]
+  fn dash_polyline() {
+    let points = [
+      Point::new(0., 0.),
+      Point::new(10., 0.),
+      Point::new(10., 10.),
+    ];
+    let dashes = super::dash_polyline(&points, &[4., 2.], 3.);
+    // starting a dash 3 along the pattern, with 1 of it left
+    assert_eq!(
+      dashes,
+      [
+        vec![Point::new(0., 0.), Point::new(1., 0.)],
+        vec![Point::new(3., 0.), Point::new(7., 0.)],
+        vec![Point::new(9., 0.), Point::new(10., 0.), Point::new(10., 3.)],
+        vec![Point::new(10., 5.), Point::new(10., 9.)],
+      ]
+    );
+    // starting in a gap
+    let dashes = super::dash_polyline(&points, &[4., 2.], 5.);
+    assert_eq!(dashes[0], [Point::new(1., 0.), Point::new(5., 0.)]);
+
+    // short dashes still move along a long line
+    let line = [Point::new(0., 0.), Point::new(1000., 0.)];
+    let dashes = super::dash_polyline(&line, &[0.1, 0.1], 0.);
+    assert_eq!(dashes.len(), 5000);
+  }
+
+  #[test]
+  fn dashed_path() {
+    let square = [
+      PathCommand::MoveTo((0., 0.).into()),
+      PathCommand::LineTo((10., 0.).into()),
+      PathCommand::LineTo((10., 10.).into()),
+      PathCommand::LineTo((0., 10.).into()),
+      PathCommand::ClosePath,
+    ];
+    let style = style(2., LineCap::Butt, LineJoin::Miter);
+    // an odd pattern repeats twice, as dashes of 5 & gaps of 5
+    let shape = ShapeBuilder::new()
+      .dashed_path(square, style, &[5.], 0.)
+      .build();
+    assert_eq!(shape.contours.len(), 4);
+    assert_eq!(shape.sample_single_channel((2.5, 0.)), 1.);
+    // the gap ends at the corner, where the next dash heads down
+    assert_eq!(shape.sample_single_channel((7.5, 0.)), -1.5);
+
+    // without a usable pattern, the stroke is solid
+    let solid = ShapeBuilder::new()
+      .dashed_path(square, style, &[0., 0.], 0.)
+      .build();
+    assert_eq!(solid.contours.len(), 2);
+
+    // nor with dashes finer than the tolerance, or too many of them
+    let line = [
+      PathCommand::MoveTo((0., 0.).into()),
+      PathCommand::LineTo((100., 0.).into()),
+    ];
+    for (dashes, tolerance) in [([1e-8, 1e-8], 0.05), ([1e-4, 1e-4], 0.)] {
+      let style = StrokeStyle { tolerance, ..style };
+      let shape = ShapeBuilder::new()
+        .dashed_path(line, style, &dashes, 0.)
+        .build();
+      assert_eq!(shape.contours.len(), 1);
+    }
+  }
+
+  #[test]
+  fn closed() {
+    // the outline of a closed square has a hole
+    let square = ShapeBuilder::new()
+      .stroked_path(
+        [
+          PathCommand::MoveTo((0., 0.).into()),
+          PathCommand::LineTo((10., 0.).into()),
+          PathCommand::LineTo((10., 10.).into()),
+          PathCommand::LineTo((0., 10.).into()),
+          PathCommand::ClosePath,
+        ],
+        style(2., LineCap::Round, LineJoin::Miter),
+      )
+      .build();
+    assert_eq!(square.contours.len(), 2);
+    assert_eq!(square.sample_single_channel((5., 0.)), 1.);
+    assert_eq!(square.sample_single_channel((5., 5.)), -4.);
+    assert_eq!(square.sample_single_channel((-1., -1.)), 0.);
+  }
+}
@@ -0,0 +1,698 @@
+use super::*;
+
+/// An error found while parsing SVG path data, see
+/// [`ShapeBuilder::svg_path`]
+///
+/// Positions are byte offsets into the path data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PathError {
+  /// The path data didn't start with a move-to command
+  MissingMoveTo,
+  /// A command at the given position was missing some of its arguments
+  MissingArguments(usize),
+  /// A character that isn't part of a command, number or separator
+  UnexpectedCharacter(usize),
+  /// A number at the given position, or a coordinate relative to the
+  /// current point, was too large to be finite
+  NonFinite(usize),
+}
+
+impl std::fmt::Display for PathError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      PathError::MissingMoveTo => {
+        write!(f, "path data must start with a move-to command")
+      },
+      PathError::MissingArguments(position) => {
+        write!(f, "missing arguments for the command at {position}")
+      },
+      PathError::UnexpectedCharacter(position) => {
+        write!(f, "unexpected character at {position}")
+      },
+      PathError::NonFinite(position) => {
+        write!(f, "number at {position} isn't finite")
+      },
+    }
+  }
+}
+
+impl std::error::Error for PathError {}
+
+impl ShapeBuilder {
+  /// Create a builder holding the contours described by SVG path data
+  ///
+  /// See [`ShapeBuilder::svg_path`].
+  pub fn from_svg_path(d: &str) -> Result<Self, PathError> {
+    Self::new().svg_path(d)
+  }
+
+  /// Add the contours described by SVG path data, the `d` attribute of a
+  /// `<path>` element
+  ///
+  /// All of the commands are supported, in both their absolute and relative
+  /// forms. Every subpath is closed, as if the path were filled, whether or
+  /// not it ends with a `Z`.
+  ///
+  /// ```
+  /// # use rsdf_builder::ShapeBuilder;
+  /// // a 10x10 square with a rounded top-right corner
+  /// let shape = ShapeBuilder::from_svg_path("M0,0 h6 a4,4 0 0 1 4,4 V10 H0z")
+  ///   .unwrap()
+  ///   .build();
+  /// assert_eq!(shape.sample_single_channel((5., 5.)), 5.);
+  /// ```
+  pub fn svg_path(self, d: &str) -> Result<Self, PathError> {
+    Ok(self.path(parse_svg_path(d)?))
+  }
+
+  /// Add the contours described by a sequence of path commands
+  ///
+  /// Every subpath is closed, as for [`ShapeBuilder::svg_path`].
+  pub fn path(self, commands: impl IntoIterator<Item = PathCommand>) -> Self {
+    let mut state = PathState::Idle(self);
+    let mut start = Point::ZERO;
+    let mut current = Point::ZERO;
+
+    for command in commands {
+      state = match command {
+        PathCommand::MoveTo(point) => {
+          let builder = state.close(start, current);
+          start = point;
+          PathState::Idle(builder)
+        },
+        PathCommand::ClosePath => PathState::Idle(state.close(start, current)),
+        // zero-length segments have no direction, so are left out
+        PathCommand::LineTo(end) if end == current => state,
+        PathCommand::LineTo(end) => state.draw(start, |c| c.line(end)),
+        PathCommand::QuadTo(control, end) => {
+          if [control, end] == [current; 2] {
+            state
+          } else {
+            state.draw(start, |c| c.quadratic_bezier(control, end))
+          }
+        },
+        PathCommand::CubicTo(control_1, control_2, end) => {
+          if [control_1, control_2, end] == [current; 3] {
+            state
+          } else {
+            state.draw(start, |c| c.cubic_bezier(control_1, control_2, end))
+          }
+        },
+        PathCommand::ArcTo {
+          rx,
+          ry,
+          phi,
+          large_arc,
+          sweep,
+          end,
+        } => {
+          if end == current {
+            // an arc between coincident points is omitted
+            state
+          } else if rx == 0. || ry == 0. {
+            // and one with a zero radius is a straight line
+            state.draw(start, |c| c.line(end))
+          } else {
+            state.draw(start, |c| {
+              c.elliptical_arc(rx, ry, phi, large_arc, sweep, end)
+            })
+          }
+        },
+      };
+      current = match command {
+        PathCommand::MoveTo(point)
+        | PathCommand::LineTo(point)
+        | PathCommand::QuadTo(_, point)
+        | PathCommand::CubicTo(_, _, point)
+        | PathCommand::ArcTo { end: point, .. } => point,
+        PathCommand::ClosePath => start,
+      };
+    }
+
+    state.close(start, current)
+  }
+
+  /// Add the contours described by a sequence of path commands, reversing
+  /// them if needed so that the inside of the path has positive distances
+  ///
+  /// Use this for outlines whose winding direction isn't known, such as
+  /// glyphs from both TrueType & CFF fonts. The direction is decided for the
+  /// path as a whole, see [`Shape::winding_sign`], so holes stay holes.
+  pub fn filled_path(
+    self,
+    commands: impl IntoIterator<Item = PathCommand>,
+  ) -> Self {
+    let commands: Vec<PathCommand> = commands.into_iter().collect();
+    let sign = ShapeBuilder::new()
+      .path(commands.iter().copied())
+      .build()
+      .winding_sign();
+    if sign < 0. {
+      self.path(reverse_path(&commands))
+    } else {
+      self.path(commands)
+    }
+  }
+
+  /// Add the contours of a glyph's outline, drawn by a font library
+  ///
+  /// Font outlines have y pointing up, so y is negated: the baseline lies
+  /// along `y = 0`, with ascenders above it at negative y. The inside of the
+  /// glyph has positive distances whichever way the font winds its contours,
+  /// see [`ShapeBuilder::filled_path`].
+  pub fn font_outline(
+    self,
+    commands: impl IntoIterator<Item = PathCommand>,
+  ) -> Self {
+    let flip = |point: Point| Point::new(point.x, -point.y);
+    self.filled_path(commands.into_iter().map(|command| match command {
+      PathCommand::MoveTo(to) => PathCommand::MoveTo(flip(to)),
+      PathCommand::LineTo(to) => PathCommand::LineTo(flip(to)),
+      PathCommand::QuadTo(control, to) => {
+        PathCommand::QuadTo(flip(control), flip(to))
+      },
+      PathCommand::CubicTo(control_1, control_2, to) => {
+        PathCommand::CubicTo(flip(control_1), flip(control_2), flip(to))
+      },
+      // mirroring an arc reverses its rotation & its direction
+      PathCommand::ArcTo {
+        rx,
+        ry,
+        phi,
+        large_arc,
+        sweep,
+        end,
+      } => PathCommand::ArcTo {
+        rx,
+        ry,
+        phi: -phi,
+        large_arc,
+        sweep: !sweep,
+        end: flip(end),
+      },
+      PathCommand::ClosePath => PathCommand::ClosePath,
+    }))
+  }
+}
+
+/// The same path drawn backwards, which reverses the winding of its
+/// contours
+pub fn reverse_path(commands: &[PathCommand]) -> Vec<PathCommand> {
+  let mut reversed = vec![];
+  let mut subpath = vec![];
+  let mut start = Point::ZERO;
+  let mut current = Point::ZERO;
+  for &command in commands {
+    match command {
+      PathCommand::MoveTo(point) => {
+        reverse_subpath(current, &subpath, false, &mut reversed);
+        subpath.clear();
+        (start, current) = (point, point);
+        continue;
+      },
+      // drawing may carry on from the start of a closed subpath
+      PathCommand::ClosePath => {
+        reverse_subpath(current, &subpath, true, &mut reversed);
+        subpath.clear();
+        current = start;
+        continue;
+      },
+      _ => {},
+    }
+    // each segment is paired with the point it starts from
+    subpath.push((current, command));
+    current = match command {
+      PathCommand::LineTo(end)
+      | PathCommand::QuadTo(_, end)
+      | PathCommand::CubicTo(_, _, end)
+      | PathCommand::ArcTo { end, .. } => end,
+      PathCommand::MoveTo(_) | PathCommand::ClosePath => unreachable!(),
+    };
+  }
+  reverse_subpath(current, &subpath, false, &mut reversed);
+  reversed
+}
+
+fn reverse_subpath(
+  end: Point,
+  subpath: &[(Point, PathCommand)],
+  closed: bool,
+  reversed: &mut Vec<PathCommand>,
+) {
+  if subpath.is_empty() {
+    return;
+  }
+  reversed.push(PathCommand::MoveTo(end));
+  for &(start, command) in subpath.iter().rev() {
+    reversed.push(match command {
+      PathCommand::QuadTo(control, _) => PathCommand::QuadTo(control, start),
+      PathCommand::CubicTo(control_1, control_2, _) => {
+        PathCommand::CubicTo(control_2, control_1, start)
+      },
+      PathCommand::ArcTo {
+        rx,
+        ry,
+        phi,
+        large_arc,
+        sweep,
+        ..
+      } => PathCommand::ArcTo {
+        rx,
+        ry,
+        phi,
+        large_arc,
+        sweep: !sweep,
+        end: start,
+      },
+      _ => PathCommand::LineTo(start),
+    });
+  }
+  if closed {
+    reversed.push(PathCommand::ClosePath);
+  }
+}
+
+/// The builder, with or without a contour in progress
+enum PathState {
+  Idle(ShapeBuilder),
+  Drawing(ContourBuilder),
+}
+
+impl PathState {
+  /// Add a segment to the current contour, starting one at `start` if there
+  /// isn't one yet
+  fn draw(
+    self,
+    start: Point,
+    segment: impl FnOnce(ContourBuilder) -> ContourBuilder,
+  ) -> Self {
+    let contour = match self {
+      PathState::Idle(builder) => builder.contour(start),
+      PathState::Drawing(contour) => contour,
+    };
+    PathState::Drawing(segment(contour))
+  }
+
+  /// Finish the current contour, if any, with a line back to its start
+  fn close(self, start: Point, current: Point) -> ShapeBuilder {
+    match self {
+      PathState::Idle(builder) => builder,
+      PathState::Drawing(contour) if current != start => {
+        contour.line(start).end_contour()
+      },
+      PathState::Drawing(contour) => contour.end_contour(),
+    }
+  }
+}
+
+/// A path command, with its coordinates made absolute and any shorthand
+/// expanded
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathCommand {
+  MoveTo(Point),
+  LineTo(Point),
+  QuadTo(Point, Point),
+  CubicTo(Point, Point, Point),
+  /// An elliptical arc, as for [`ContourBuilder::elliptical_arc`]
+  ArcTo {
+    rx: f32,
+    ry: f32,
+    /// The rotation of the ellipse's x-axis, in radians
+    phi: f32,
+    large_arc: bool,
+    /// Whether the arc is drawn in the direction of increasing angle, which
+    /// is clockwise with y pointing down
+    sweep: bool,
+    end: Point,
+  },
+  ClosePath,
+}
+
+/// Parse SVG path data into a list of [`PathCommand`]s, see
+/// [`ShapeBuilder::svg_path`]
+pub fn parse_svg_path(d: &str) -> Result<Vec<PathCommand>, PathError> {
+  let mut lexer = Lexer {
+    bytes: d.as_bytes(),
+    position: 0,
+  };
+  let mut commands = vec![];
+  let mut start = Point::ZERO;
+  let mut current = Point::ZERO;
+  // the control point of the previous segment, for the smooth curves
+  let mut last_cubic_control = None;
+  let mut last_quad_control = None;
+  let mut previous: Option<u8> = None;
+
+  while !lexer.skip_separators() {
+    let position = lexer.position;
+    let letter = match lexer.command() {
+      Some(letter) => letter,
+      // arguments following a command repeat it, with a move-to's
+      // followed by line-tos
+      None => match previous {
+        Some(b'M') => b'L',
+        Some(b'm') => b'l',
+        Some(b'Z' | b'z') | None => {
+          return Err(PathError::UnexpectedCharacter(position))
+        },
+        Some(letter) => letter,
+      },
+    };
+    if previous.is_none() && !matches!(letter, b'M' | b'm') {
+      return Err(PathError::MissingMoveTo);
+    }
+    previous = Some(letter);
+
+    let missing = |_| PathError::MissingArguments(position);
+    let origin = if letter.is_ascii_lowercase() {
+      current.as_vector()
+    } else {
+      Vector::ZERO
+    };
+    // a number, offset by a coordinate of the current point if relative
+    let number = |lexer: &mut Lexer, offset: f32| -> Result<f32, PathError> {
+      lexer.skip_separators();
+      let position = lexer.position;
+      let number = lexer.number().map_err(missing)? + offset;
+      match number.is_finite() {
+        true => Ok(number),
+        false => Err(PathError::NonFinite(position)),
+      }
+    };
+    let point = |lexer: &mut Lexer| -> Result<Point, PathError> {
+      let x = number(lexer, origin.x)?;
+      let y = number(lexer, origin.y)?;
+      Ok(Point::new(x, y))
+    };
+    let reflect = |control: Option<Point>| match control {
+      Some(control) => current + (current - control),
+      None => current,
+    };
+
+    let command = match letter.to_ascii_uppercase() {
+      b'M' => {
+        let point = point(&mut lexer)?;
+        start = point;
+        PathCommand::MoveTo(point)
+      },
+      b'L' => PathCommand::LineTo(point(&mut lexer)?),
+      b'H' => {
+        let x = number(&mut lexer, origin.x)?;
+        PathCommand::LineTo(Point::new(x, current.y))
+      },
+      b'V' => {
+        let y = number(&mut lexer, origin.y)?;
+        PathCommand::LineTo(Point::new(current.x, y))
+      },
+      b'C' => PathCommand::CubicTo(
+        point(&mut lexer)?,
+        point(&mut lexer)?,
+        point(&mut lexer)?,
+      ),
+      b'S' => {
+        let control_1 = reflect(last_cubic_control);
+        PathCommand::CubicTo(control_1, point(&mut lexer)?, point(&mut lexer)?)
+      },
+      b'Q' => PathCommand::QuadTo(point(&mut lexer)?, point(&mut lexer)?),
+      b'T' => {
+        PathCommand::QuadTo(reflect(last_quad_control), point(&mut lexer)?)
+      },
+      b'A' => {
+        let rx = number(&mut lexer, 0.)?;
+        let ry = number(&mut lexer, 0.)?;
+        let degrees = number(&mut lexer, 0.)?;
+        let large_arc = lexer.flag().map_err(missing)?;
+        let sweep = lexer.flag().map_err(missing)?;
+        PathCommand::ArcTo {
+          rx: rx.abs(),
+          ry: ry.abs(),
+          phi: degrees.to_radians(),
+          large_arc,
+          sweep,
+          end: point(&mut lexer)?,
+        }
+      },
+      b'Z' => PathCommand::ClosePath,
+      _ => return Err(PathError::UnexpectedCharacter(position)),
+    };
+
+    (last_cubic_control, last_quad_control) = match command {
+      PathCommand::CubicTo(_, control, _) => (Some(control), None),
+      PathCommand::QuadTo(control, _) => (None, Some(control)),
+      _ => (None, None),
+    };
+    current = match command {
+      PathCommand::MoveTo(point)
+      | PathCommand::LineTo(point)
+      | PathCommand::QuadTo(_, point)
+      | PathCommand::CubicTo(_, _, point)
+      | PathCommand::ArcTo { end: point, .. } => point,
+      PathCommand::ClosePath => start,
+    };
+    commands.push(command);
+  }
+
+  Ok(commands)
+}
+
+pub(crate) struct Lexer<'a> {
+  pub(crate) bytes: &'a [u8],
+  pub(crate) position: usize,
+}
+
+impl Lexer<'_> {
+  /// Skip whitespace and commas, returning whether the end has been reached
+  pub(crate) fn skip_separators(&mut self) -> bool {
+    while let Some(byte) = self.bytes.get(self.position) {
+      if !(byte.is_ascii_whitespace() || *byte == b',') {
+        return false;
+      }
+      self.position += 1;
+    }
+    true
+  }
+
+  /// Take a command letter, if there is one next
+  fn command(&mut self) -> Option<u8> {
+    let byte = *self.bytes.get(self.position)?;
+    // `e` can only appear inside of a number
+    (byte.is_ascii_alphabetic() && !matches!(byte, b'e' | b'E')).then(|| {
+      self.position += 1;
+      byte
+    })
+  }
+
+  /// Take a number, which may run straight on from the previous one, as in
+  /// `1-2.5.5`
+  pub(crate) fn number(&mut self) -> Result<f32, ()> {
+    self.skip_separators();
+    let start = self.position;
+    let digits = |lexer: &mut Self| {
+      let from = lexer.position;
+      while lexer
+        .bytes
+        .get(lexer.position)
+        .is_some_and(u8::is_ascii_digit)
+      {
+        lexer.position += 1;
+      }
+      lexer.position > from
+    };
+    let sign = |lexer: &mut Self| {
+      if let Some(b'+' | b'-') = lexer.bytes.get(lexer.position) {
+        lexer.position += 1;
+      }
+    };
+
+    sign(self);
+    let mut mantissa = digits(self);
+    if self.bytes.get(self.position) == Some(&b'.') {
+      self.position += 1;
+      mantissa |= digits(self);
+    }
+    if !mantissa {
+      self.position = start;
+      return Err(());
+    }
+    if let Some(b'e' | b'E') = self.bytes.get(self.position) {
+      let before_exponent = self.position;
+      self.position += 1;
+      sign(self);
+      if !digits(self) {
+        self.position = before_exponent;
+      }
+    }
+
+    std::str::from_utf8(&self.bytes[start..self.position])
+      .unwrap()
+      .parse()
+      .map_err(|_| ())
+  }
+
+  /// Take an arc flag, which needn't be separated from what follows it
+  fn flag(&mut self) -> Result<bool, ()> {
+    self.skip_separators();
+    let flag = match self.bytes.get(self.position) {
+      Some(b'0') => false,
+      Some(b'1') => true,
+      _ => return Err(()),
+    };
+    self.position += 1;
+    Ok(flag)
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+  use float_cmp::assert_approx_eq;
+
+  fn path(d: &str) -> Shape {
+    ShapeBuilder::from_svg_path(d).unwrap().build()
+  }
+
+  #[test]
+  fn lines() {
+    let square = ShapeBuilder::new()
+      .contour((10., 10.))
+      .line((20., 10.))
+      .line((20., 20.))
+      .line((10., 20.))
+      .line((10., 10.))
+      .end_contour()
+      .build();
+
+    for d in [
+      "M 10 10 L 20 10 L 20 20 L 10 20 Z",
+      "M10,10 20,10 20,20 10,20",
+      "m10 10 h10 v10 h-10 z",
+      "M10 10H20V20H10V10Z",
+      // empty subpaths are ignored
+      "M 0 0 M 10 10 l 10 0 0 10 -10 0 Z M 5 5 Z",
+    ] {
+      assert_eq!(path(d).checksum(), square.checksum(), "{d}");
+    }
+  }
+
+  #[test]
+  fn curves() {
+    let expected = ShapeBuilder::new()
+      .contour((0., 0.))
+      .cubic_bezier((0., 10.), (10., 10.), (10., 0.))
+      .cubic_bezier((10., -10.), (20., -10.), (20., 0.))
+      .quadratic_bezier((25., 5.), (30., 0.))
+      .quadratic_bezier((35., -5.), (40., 0.))
+      .line((40., 20.))
+      .line((0., 20.))
+      .line((0., 0.))
+      .end_contour()
+      .build();
+
+    for d in [
+      "M0 0 C0 10 10 10 10 0 S20 -10 20 0 Q25 5 30 0 T40 0 V20 H0 Z",
+      "m0 0 c0 10 10 10 10 0 s10-10 10 0 q5 5 10 0 t10 0 v20 h-40 z",
+    ] {
+      assert_eq!(path(d).checksum(), expected.checksum(), "{d}");
+    }
+  }
+
+  #[test]
+  fn arcs() {
+    // sweeping in the positive angle direction, which is clockwise with y
+    // pointing down
+    let shape = path("M0 0 A5 5 0 0 1 10 0 Z");
+    let arc = shape.get_segment(shape.segments[0]);
+    assert_approx_eq!(Point, arc.sample(0.5), Point::new(5., -5.));
+    assert!(matches!(shape.segments[1].kind, SegmentKind::Line));
+
+    // rotations are given in degrees & flags needn't be separated
+    let shape = path("M0 0a10 5 90 0110 0z");
+    let arc = shape.get_segment(shape.segments[0]);
+    assert_approx_eq!(Point, arc.sample(0.5), Point::new(5., -10.));
+
+    // arcs with no radius are straight lines
+    let shape = path("M0 0 A0 5 0 0 1 10 0 L5 5 Z");
+    assert!(matches!(shape.segments[0].kind, SegmentKind::Line));
+  }
+
+  #[test]
+  fn numbers() {
+    let shape = path("M1-2.5.5e1 3 4E-1.5z");
+    assert_eq!(
+      shape.points,
+      [
+        Point::new(1., -2.5),
+        Point::new(5., 3.),
+        Point::new(0.4, 0.5),
+        Point::new(1., -2.5),
+      ]
+    );
+  }
+
+  #[test]
+  fn reverse_path() {
+    use PathCommand::*;
+    let p = |x, y| Point::new(x, y);
+    let arc = |sweep, end| ArcTo {
+      rx: 1.,
+      ry: 2.,
+      phi: 0.5,
+      large_arc: true,
+      sweep,
+      end,
+    };
+    let path = [
+      MoveTo(p(0., 0.)),
+      LineTo(p(1., 0.)),
+      QuadTo(p(2., 0.), p(2., 1.)),
+      ClosePath,
+      MoveTo(p(5., 5.)),
+      CubicTo(p(6., 5.), p(7., 6.), p(7., 7.)),
+      arc(true, p(9., 9.)),
+    ];
+    assert_eq!(
+      super::reverse_path(&path),
+      [
+        MoveTo(p(2., 1.)),
+        QuadTo(p(2., 0.), p(1., 0.)),
+        LineTo(p(0., 0.)),
+        ClosePath,
+        MoveTo(p(9., 9.)),
+        arc(false, p(7., 7.)),
+        CubicTo(p(7., 6.), p(6., 5.), p(5., 5.)),
+      ]
+    );
+
+    // either winding fills the inside
+    let square = parse_svg_path("M0 0 V10 H10 V0 Z").unwrap();
+    for path in [square.clone(), super::reverse_path(&square)] {
+      let shape = ShapeBuilder::new().filled_path(path).build();
+      assert_eq!(shape.sample_single_channel((5., 5.)), 5.);
+    }
+  }
+
+  #[test]
+  fn errors() {
+    let error = |d| ShapeBuilder::from_svg_path(d).err();
+    assert_eq!(error("L 1 1"), Some(PathError::MissingMoveTo));
+    assert_eq!(error("M 1"), Some(PathError::MissingArguments(0)));
+    assert_eq!(
+      error("M 1 1 L 2 2 C 3"),
+      Some(PathError::MissingArguments(12))
+    );
+    assert_eq!(
+      error("M 1 1 A 1 1 0 2 0 1 1"),
+      Some(PathError::MissingArguments(6))
+    );
+    assert_eq!(error("M 1 1 X"), Some(PathError::UnexpectedCharacter(6)));
+    assert_eq!(error("M 1 1 Z 2"), Some(PathError::UnexpectedCharacter(8)));
+    assert_eq!(error("M 1 1e999"), Some(PathError::NonFinite(4)));
+    assert_eq!(
+      error("M 0 0 A 1e39 1 0 0 0 1 1"),
+      Some(PathError::NonFinite(8))
+    );
+    assert_eq!(error("m 3e38 0 h 3e38"), Some(PathError::NonFinite(11)));
+    assert_eq!(error(""), None);
+  }
+}
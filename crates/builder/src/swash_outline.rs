@@ -0,0 +1,75 @@
+use super::*;
+use swash::scale::outline::Outline;
+use swash::zeno::{Point as ZenoPoint, Verb};
+
+impl ShapeBuilder {
+  /// Add the contours of a glyph outline produced by a swash
+  /// [`Scaler`](swash::scale::Scaler)
+  ///
+  /// The outline is used as scaled, so font variations, hinting and
+  /// emboldening applied by swash are kept. The outline has y pointing up,
+  /// see [`ShapeBuilder::font_outline`].
+  ///
+  /// ```no_run
+  /// # use rsdf_builder::ShapeBuilder;
+  /// use swash::scale::ScaleContext;
+  ///
+  /// let data = std::fs::read("font.ttf").unwrap();
+  /// let font = swash::FontRef::from_index(&data, 0).unwrap();
+  /// let mut context = ScaleContext::new();
+  /// let mut scaler = context
+  ///   .builder(font)
+  ///   .size(32.)
+  ///   .variations(&[("wght", 700.)])
+  ///   .build();
+  /// let glyph_id = font.charmap().map('g');
+  /// let outline = scaler.scale_outline(glyph_id).unwrap();
+  /// let shape = ShapeBuilder::new().swash_outline(&outline).build();
+  /// ```
+  pub fn swash_outline(self, outline: &Outline) -> Self {
+    self.font_outline(outline_commands(outline.points(), outline.verbs()))
+  }
+}
+
+/// Convert the points & verbs of an outline into path commands
+fn outline_commands(points: &[ZenoPoint], verbs: &[Verb]) -> Vec<PathCommand> {
+  let mut points = points.iter().map(|p| Point::new(p.x, p.y));
+  let mut next = || points.next().expect("outline verbs need more points");
+  verbs
+    .iter()
+    .map(|verb| match verb {
+      Verb::MoveTo => PathCommand::MoveTo(next()),
+      Verb::LineTo => PathCommand::LineTo(next()),
+      Verb::QuadTo => PathCommand::QuadTo(next(), next()),
+      Verb::CurveTo => PathCommand::CubicTo(next(), next(), next()),
+      Verb::Close => PathCommand::ClosePath,
+    })
+    .collect()
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn outline_commands() {
+    let p = |x, y| ZenoPoint::new(x, y);
+    let points = [
+      p(0., 0.),
+      p(0., 10.),
+      p(10., 10.),
+      p(10., 5.),
+      p(10., 2.),
+      p(5., 0.),
+      p(0., 0.),
+    ];
+    let verbs = [
+      Verb::MoveTo,
+      Verb::LineTo,
+      Verb::QuadTo,
+      Verb::CurveTo,
+      Verb::Close,
+    ];
+    test_outline::assert_d(&super::outline_commands(&points, &verbs));
+  }
+}
@@ -0,0 +1,145 @@
+use super::*;
+use font_kit::error::{FontLoadingError, GlyphLoadingError, SelectionError};
+use font_kit::family_name::FamilyName;
+use font_kit::font::Font;
+use font_kit::hinting::HintingOptions;
+use font_kit::outline::OutlineSink;
+use font_kit::properties::Properties;
+use font_kit::source::SystemSource;
+use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::vector::Vector2F;
+
+/// Why [`system_font`] couldn't load a font
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SystemFontError {
+  /// No installed font matched, or the system's fonts couldn't be listed
+  Selection(SelectionError),
+  /// The matching font's file couldn't be loaded
+  Loading(FontLoadingError),
+}
+
+impl std::fmt::Display for SystemFontError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      SystemFontError::Selection(error) => {
+        write!(f, "couldn't select a font: {error}")
+      },
+      SystemFontError::Loading(error) => {
+        write!(f, "couldn't load the font: {error}")
+      },
+    }
+  }
+}
+
+impl std::error::Error for SystemFontError {}
+
+/// Load the installed font that best matches a `family` name and the style
+/// `properties`, such as its weight
+///
+/// `family` may also be one of the generic families, `serif`, `sans-serif`,
+/// `monospace`, `cursive` or `fantasy`, for the system's default.
+///
+/// ```no_run
+/// # use rsdf_builder::{system_font, ShapeBuilder};
+/// # use rsdf_core::*;
+/// use font_kit::properties::{Properties, Weight};
+///
+/// let font =
+///   system_font("Noto Serif JP", Properties::new().weight(Weight::BOLD))
+///     .unwrap();
+/// let glyph = font.glyph_for_char('あ').unwrap();
+/// let shape = ShapeBuilder::new()
+///   .font_kit_glyph(&font, glyph)
+///   .unwrap()
+///   .build();
+/// let scale = 32. / font.metrics().units_per_em as f32;
+/// let config = RasterConfig::default();
+/// let glyph = PreparedGlyph::new(shape, scale, config).unwrap();
+/// let field = glyph.sample::<3>(config);
+/// ```
+pub fn system_font(
+  family: &str,
+  properties: &Properties,
+) -> Result<Font, SystemFontError> {
+  let family = match family {
+    "serif" => FamilyName::Serif,
+    "sans-serif" => FamilyName::SansSerif,
+    "monospace" => FamilyName::Monospace,
+    "cursive" => FamilyName::Cursive,
+    "fantasy" => FamilyName::Fantasy,
+    family => FamilyName::Title(family.to_string()),
+  };
+  SystemSource::new()
+    .select_best_match(&[family], properties)
+    .map_err(SystemFontError::Selection)?
+    .load()
+    .map_err(SystemFontError::Loading)
+}
+
+impl ShapeBuilder {
+  /// Add the contours of a glyph's outline from a font loaded by font-kit,
+  /// in font units
+  ///
+  /// The outline has y pointing up, see [`ShapeBuilder::font_outline`]. See
+  /// [`system_font`] to find a font by name.
+  pub fn font_kit_glyph(
+    self,
+    font: &Font,
+    glyph: u32,
+  ) -> Result<Self, GlyphLoadingError> {
+    let mut sink = OutlineCommands(vec![]);
+    font.outline(glyph, HintingOptions::None, &mut sink)?;
+    Ok(self.font_outline(sink.0))
+  }
+}
+
+/// Collects an outline as path commands
+struct OutlineCommands(Vec<PathCommand>);
+
+impl OutlineSink for OutlineCommands {
+  fn move_to(&mut self, to: Vector2F) {
+    self.0.push(PathCommand::MoveTo(point(to)));
+  }
+
+  fn line_to(&mut self, to: Vector2F) {
+    self.0.push(PathCommand::LineTo(point(to)));
+  }
+
+  fn quadratic_curve_to(&mut self, control: Vector2F, to: Vector2F) {
+    self.0.push(PathCommand::QuadTo(point(control), point(to)));
+  }
+
+  fn cubic_curve_to(&mut self, controls: LineSegment2F, to: Vector2F) {
+    self.0.push(PathCommand::CubicTo(
+      point(controls.from()),
+      point(controls.to()),
+      point(to),
+    ));
+  }
+
+  fn close(&mut self) {
+    self.0.push(PathCommand::ClosePath);
+  }
+}
+
+fn point(vector: Vector2F) -> Point {
+  Point::new(vector.x(), vector.y())
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn outline_commands() {
+    let v = Vector2F::new;
+    let mut sink = OutlineCommands(vec![]);
+    sink.move_to(v(0., 0.));
+    sink.line_to(v(0., 10.));
+    sink.quadratic_curve_to(v(10., 10.), v(10., 5.));
+    sink.cubic_curve_to(LineSegment2F::new(v(10., 2.), v(5., 0.)), v(0., 0.));
+    sink.close();
+    test_outline::assert_d(&sink.0);
+  }
+}
@@ -0,0 +1,28 @@
+//! The outline the font adapters' tests draw into their sinks
+
+use super::*;
+
+/// A "D", with y pointing up as fonts draw it: a vertical line closed by a
+/// quadratic & a cubic curve
+pub fn d() -> Vec<PathCommand> {
+  let p = Point::new;
+  vec![
+    PathCommand::MoveTo(p(0., 0.)),
+    PathCommand::LineTo(p(0., 10.)),
+    PathCommand::QuadTo(p(10., 10.), p(10., 5.)),
+    PathCommand::CubicTo(p(10., 2.), p(5., 0.), p(0., 0.)),
+    PathCommand::ClosePath,
+  ]
+}
+
+/// Check that a sink collected the "D" of [`d`], and that it comes out
+/// filled whichever way it's wound
+pub fn assert_d(commands: &[PathCommand]) {
+  assert_eq!(commands, d());
+  for commands in [commands.to_vec(), reverse_path(commands)] {
+    let shape = ShapeBuilder::new().font_outline(commands).build();
+    assert_eq!(shape.contours.len(), 1);
+    assert_eq!(shape.sample_single_channel((1., -5.)), 1.);
+    assert_eq!(shape.sample_single_channel((-2., -5.)), -2.);
+  }
+}
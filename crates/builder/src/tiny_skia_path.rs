@@ -0,0 +1,66 @@
+use super::*;
+use ::tiny_skia_path::{Path, PathSegment};
+
+impl ShapeBuilder {
+  /// Add the contours of a [`tiny_skia::Path`](tiny_skia_path::Path)
+  ///
+  /// tiny-skia fills paths whichever way they're wound, so the path is
+  /// added as for [`ShapeBuilder::filled_path`], with the inside of the path
+  /// at positive distances. Holes need to be wound against the rest of the
+  /// path, as for tiny-skia's non-zero fill rule. Every subpath is closed.
+  ///
+  /// ```
+  /// # use rsdf_builder::ShapeBuilder;
+  /// use ::tiny_skia_path::{PathBuilder, Rect};
+  ///
+  /// let rect = Rect::from_xywh(0., 0., 10., 10.).unwrap();
+  /// let path = PathBuilder::from_rect(rect);
+  /// let shape = ShapeBuilder::new().tiny_skia_path(&path).build();
+  /// assert_eq!(shape.sample_single_channel((5., 5.)), 5.);
+  /// ```
+  pub fn tiny_skia_path(self, path: &Path) -> Self {
+    self.filled_path(path.segments().map(PathCommand::from))
+  }
+}
+
+impl From<PathSegment> for PathCommand {
+  fn from(segment: PathSegment) -> Self {
+    let point = |p: ::tiny_skia_path::Point| Point::new(p.x, p.y);
+    match segment {
+      PathSegment::MoveTo(p) => PathCommand::MoveTo(point(p)),
+      PathSegment::LineTo(p) => PathCommand::LineTo(point(p)),
+      PathSegment::QuadTo(c, p) => PathCommand::QuadTo(point(c), point(p)),
+      PathSegment::CubicTo(c1, c2, p) => {
+        PathCommand::CubicTo(point(c1), point(c2), point(p))
+      },
+      PathSegment::Close => PathCommand::ClosePath,
+    }
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+  use ::tiny_skia_path::PathBuilder;
+
+  #[test]
+  fn tiny_skia_path() {
+    // a circle, and a square wound the other way, are both filled
+    let mut builder = PathBuilder::new();
+    builder.push_circle(10., 10., 8.);
+    let circle = builder.finish().unwrap();
+    let mut builder = PathBuilder::new();
+    builder.move_to(0., 0.);
+    builder.line_to(0., 10.);
+    builder.line_to(10., 10.);
+    builder.line_to(10., 0.);
+    builder.close();
+    let square = builder.finish().unwrap();
+
+    let circle = ShapeBuilder::new().tiny_skia_path(&circle).build();
+    assert_eq!(circle.contours.len(), 1);
+    assert!((circle.sample_single_channel((10., 10.)) - 8.).abs() < 0.01);
+    let square = ShapeBuilder::new().tiny_skia_path(&square).build();
+    assert_eq!(square.sample_single_channel((5., 2.)), 2.);
+  }
+}
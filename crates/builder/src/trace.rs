@@ -0,0 +1,208 @@
+use super::*;
+use std::collections::BTreeMap;
+
+impl ShapeBuilder {
+  /// Add the contours tracing the edges of a bitmap
+  ///
+  /// `bitmap` holds whether each pixel is inside the shape, in row-major
+  /// order, `width` pixels to a row; threshold the bitmaps of raster icons
+  /// first. Pixel `(x, y)` covers the square from `(x, y)` to
+  /// `(x + 1, y + 1)`, so the shape's coordinates match those of the bitmap.
+  /// Pixels that touch only at a corner are kept apart.
+  ///
+  /// Without `smooth` the contours follow the pixels' edges exactly, as a
+  /// staircase. With `smooth` the staircases are fitted with quadratic
+  /// Beziers, while the corners between edges longer than a pixel stay sharp.
+  ///
+  /// ```
+  /// # use rsdf_builder::ShapeBuilder;
+  /// // a 4x4 square with a 2x2 hole in the middle
+  /// let bitmap = [
+  ///   1, 1, 1, 1, //
+  ///   1, 0, 0, 1, //
+  ///   1, 0, 0, 1, //
+  ///   1, 1, 1, 1, //
+  /// ]
+  /// .map(|pixel| pixel == 1);
+  /// let shape = ShapeBuilder::new().trace_bitmap(&bitmap, 4, false).build();
+  /// assert_eq!(shape.contours.len(), 2);
+  /// assert_eq!(shape.sample_single_channel((0.5, 2.)), 0.5);
+  /// assert_eq!(shape.sample_single_channel((2., 2.)), -1.);
+  /// ```
+  pub fn trace_bitmap(
+    self,
+    bitmap: &[bool],
+    width: usize,
+    smooth: bool,
+  ) -> Self {
+    let mut commands = vec![];
+    for outline in trace_outlines(bitmap, width) {
+      let corners = outline_corners(&outline);
+      if smooth {
+        smooth_commands(&corners, &mut commands);
+      } else {
+        commands.push(PathCommand::MoveTo(corners[0]));
+        commands.extend(corners[1..].iter().map(|&c| PathCommand::LineTo(c)));
+        commands.push(PathCommand::ClosePath);
+      }
+    }
+    self.path(commands)
+  }
+}
+
+/// Follow the edges between the pixels inside & outside of the bitmap into
+/// closed outlines, of one vertex per pixel corner
+///
+/// The edges of each pixel are wound clockwise (with y pointing down), so
+/// the outlines of holes come out wound the other way.
+fn trace_outlines(bitmap: &[bool], width: usize) -> Vec<Vec<[isize; 2]>> {
+  let inside = |x: isize, y: isize| {
+    x >= 0
+      && y >= 0
+      && (x as usize) < width
+      && bitmap.get(y as usize * width + x as usize) == Some(&true)
+  };
+  let height = bitmap.len().div_ceil(width.max(1));
+
+  // the outgoing edges of each vertex, as steps
+  let mut edges: BTreeMap<[isize; 2], Vec<[isize; 2]>> = BTreeMap::new();
+  for y in 0..height as isize {
+    for x in 0..width as isize {
+      if !inside(x, y) {
+        continue;
+      }
+      let sides = [
+        (inside(x, y - 1), [x, y], [1, 0]),
+        (inside(x + 1, y), [x + 1, y], [0, 1]),
+        (inside(x, y + 1), [x + 1, y + 1], [-1, 0]),
+        (inside(x - 1, y), [x, y + 1], [0, -1]),
+      ];
+      for (neighbour, start, step) in sides {
+        if !neighbour {
+          edges.entry(start).or_default().push(step);
+        }
+      }
+    }
+  }
+
+  let mut outlines = vec![];
+  while let Some((&start, _)) = edges.iter().next() {
+    let mut outline = vec![start];
+    let mut vertex = start;
+    let mut step = [1, 0];
+    while let Some(steps) = edges.get_mut(&vertex) {
+      // where two pixels meet at a corner, keep turning clockwise around the
+      // pixel being traced
+      let turn = [-step[1], step[0]];
+      let i = steps.iter().position(|&s| s == turn).unwrap_or(0);
+      step = steps.swap_remove(i);
+      if steps.is_empty() {
+        edges.remove(&vertex);
+      }
+      vertex = [vertex[0] + step[0], vertex[1] + step[1]];
+      if vertex == start {
+        break;
+      }
+      outline.push(vertex);
+    }
+    outlines.push(outline);
+  }
+  outlines
+}
+
+/// The vertices of an outline where it changes direction
+fn outline_corners(outline: &[[isize; 2]]) -> Vec<Point> {
+  let n = outline.len();
+  (0..n)
+    .filter(|&i| {
+      let [previous, vertex, next] =
+        [outline[(i + n - 1) % n], outline[i], outline[(i + 1) % n]];
+      let incoming = [vertex[0] - previous[0], vertex[1] - previous[1]];
+      let outgoing = [next[0] - vertex[0], next[1] - vertex[1]];
+      incoming != outgoing
+    })
+    .map(|i| Point::new(outline[i][0] as f32, outline[i][1] as f32))
+    .collect()
+}
+
+/// Fit a closed polygon with quadratic Beziers between the midpoints of its
+/// edges, with the corners as control points
+///
+/// Corners between a pair of edges longer than a pixel are kept sharp.
+fn smooth_commands(corners: &[Point], commands: &mut Vec<PathCommand>) {
+  let n = corners.len();
+  let edge = |i: usize| (corners[i], corners[(i + 1) % n]);
+  let midpoint = |i: usize| {
+    let (a, b) = edge(i);
+    Point::new((a.x + b.x) / 2., (a.y + b.y) / 2.)
+  };
+  let long = |i: usize| {
+    let (a, b) = edge(i);
+    (b - a).abs() > 1.
+  };
+
+  commands.push(PathCommand::MoveTo(midpoint(n - 1)));
+  for (i, &corner) in corners.iter().enumerate() {
+    if long((i + n - 1) % n) && long(i) {
+      commands.push(PathCommand::LineTo(corner));
+      commands.push(PathCommand::LineTo(midpoint(i)));
+    } else {
+      commands.push(PathCommand::QuadTo(corner, midpoint(i)));
+    }
+  }
+  commands.push(PathCommand::ClosePath);
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  fn bitmap<const N: usize>(rows: [&str; N]) -> (Vec<bool>, usize) {
+    let bitmap = rows.iter().flat_map(|row| row.bytes().map(|b| b == b'#'));
+    (bitmap.collect(), rows[0].len())
+  }
+
+  #[test]
+  fn trace_outlines() {
+    // a pair of pixels touching at a corner stay separate
+    let (pixels, width) = bitmap(["#.", ".#"]);
+    let outlines = super::trace_outlines(&pixels, width);
+    assert_eq!(outlines.len(), 2);
+    assert!(outlines.iter().all(|outline| outline.len() == 4));
+
+    let (pixels, width) = bitmap(["###", "#.#", "###"]);
+    let outlines = super::trace_outlines(&pixels, width);
+    assert_eq!(outlines.len(), 2);
+    let corners = outline_corners(&outlines[0]);
+    assert_eq!(corners.len(), 4);
+  }
+
+  #[test]
+  fn trace_bitmap() {
+    let (pixels, width) = bitmap([
+      "..........",
+      "...####...",
+      "..######..",
+      ".########.",
+      ".########.",
+      "..######..",
+      "...####...",
+      "..........",
+    ]);
+    let stairs = ShapeBuilder::new()
+      .trace_bitmap(&pixels, width, false)
+      .build();
+    assert_eq!(stairs.contours.len(), 1);
+    assert_eq!(stairs.sample_single_channel((5., 1.5)), 0.5);
+    assert_eq!(stairs.sample_single_channel((5., 0.5)), -0.5);
+
+    // the staircase is smoothed into curves, cutting inside its corners
+    let smooth = ShapeBuilder::new()
+      .trace_bitmap(&pixels, width, true)
+      .build();
+    assert_eq!(smooth.contours.len(), 1);
+    assert!(smooth.sample_single_channel((5., 4.)) > 2.5);
+    assert!(smooth.sample_single_channel((3., 1.)) < 0.);
+    assert!(stairs.sample_single_channel((3., 1.)) >= 0.);
+  }
+}
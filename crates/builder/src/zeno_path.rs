@@ -0,0 +1,45 @@
+use super::*;
+use zeno::Command;
+
+impl ShapeBuilder {
+  /// Add the contours described by [`zeno::Command`]s, as produced by the
+  /// crates of the swash & zeno ecosystem
+  ///
+  /// The commands are converted as they're read, without collecting them
+  /// first. Every subpath is closed, as for [`ShapeBuilder::path`], and the
+  /// winding of the path is kept; use [`ShapeBuilder::filled_path`] with
+  /// [`PathCommand::from`] instead for paths wound either way.
+  ///
+  /// ```
+  /// # use rsdf_builder::ShapeBuilder;
+  /// use zeno::{Command, Point};
+  ///
+  /// let square = [
+  ///   Command::MoveTo(Point::new(0., 0.)),
+  ///   Command::LineTo(Point::new(10., 0.)),
+  ///   Command::LineTo(Point::new(10., 10.)),
+  ///   Command::LineTo(Point::new(0., 10.)),
+  ///   Command::Close,
+  /// ];
+  /// let shape = ShapeBuilder::new().zeno_path(square).build();
+  /// assert_eq!(shape.sample_single_channel((5., 5.)), 5.);
+  /// ```
+  pub fn zeno_path(self, commands: impl IntoIterator<Item = Command>) -> Self {
+    self.path(commands.into_iter().map(PathCommand::from))
+  }
+}
+
+impl From<Command> for PathCommand {
+  fn from(command: Command) -> Self {
+    let point = |p: zeno::Point| Point::new(p.x, p.y);
+    match command {
+      Command::MoveTo(p) => PathCommand::MoveTo(point(p)),
+      Command::LineTo(p) => PathCommand::LineTo(point(p)),
+      Command::QuadTo(c, p) => PathCommand::QuadTo(point(c), point(p)),
+      Command::CurveTo(c1, c2, p) => {
+        PathCommand::CubicTo(point(c1), point(c2), point(p))
+      },
+      Command::Close => PathCommand::ClosePath,
+    }
+  }
+}
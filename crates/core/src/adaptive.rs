@@ -0,0 +1,257 @@
+use crate::*;
+
+/// The size of the coarse cells the canvas is first sampled on, in pixels
+const ADAPTIVE_CELL: usize = 16;
+/// The largest cells interpolated within the max distance of the edges
+const MAX_BAND_CELL: usize = 4;
+
+impl Shape {
+  /// Sample the shape into a field of the given `size`, as
+  /// [`Shape::sample_field`] does, sampling only the pixels where the field
+  /// isn't smooth
+  ///
+  /// **Experimental**, requires the `unstable` feature.
+  ///
+  /// The canvas is sampled at the corners of a coarse grid of cells first.
+  /// A cell is refined, as a quadtree, whenever the samples at its centre &
+  /// the middles of its sides disagree with its corners by more than
+  /// `tolerance` pixels, or the samples differ in sign. Otherwise its pixels
+  /// are interpolated from its corners. Distances are clamped to the
+  /// [`max_distance`](RasterConfig::max_distance) before being compared, so
+  /// the empty parts of a large canvas cost a handful of samples. The
+  /// interpolated distances are only approximate beyond the `max_distance`,
+  /// which quantization clamps away anyway. [`RasterConfig::fast_fill`] is
+  /// ignored.
+  ///
+  /// ```
+  /// # use rsdf_builder::ShapeBuilder;
+  /// # use rsdf_core::*;
+  /// // a small dot on a large, mostly empty canvas
+  /// let dot = ShapeBuilder::new().dot((32., 32.), 8.).build();
+  /// let config = RasterConfig::default();
+  /// let field: Field<3> = dot.sample_field_adaptive([256, 256], config, 0.1);
+  /// assert_eq!(field.get([32, 32]).map(|d| d > 5.), [true; 3]);
+  /// assert_eq!(field.get([200, 200]).map(|d| d < -5.), [true; 3]);
+  /// ```
+  #[must_use]
+  pub fn sample_field_adaptive<const N: usize>(
+    &self,
+    size: [usize; 2],
+    config: RasterConfig,
+    tolerance: f32,
+  ) -> Field<N>
+  where
+    Self: SampleChannels<N>,
+  {
+    let mut sampler = AdaptiveSampler {
+      shape: self,
+      size,
+      config,
+      tolerance,
+      field: Field::new(size),
+      exact: vec![false; size[0] * size[1]],
+    };
+    sampler.sample_cells();
+    sampler.field
+  }
+}
+
+struct AdaptiveSampler<'a, const N: usize> {
+  shape: &'a Shape,
+  size: [usize; 2],
+  config: RasterConfig,
+  tolerance: f32,
+  field: Field<N>,
+  /// Which pixels have been sampled, rather than interpolated
+  exact: Vec<bool>,
+}
+
+impl<const N: usize> AdaptiveSampler<'_, N>
+where
+  Shape: SampleChannels<N>,
+{
+  fn sample_cells(&mut self) {
+    let [width, height] = self.size;
+    for y0 in (0..height).step_by(ADAPTIVE_CELL) {
+      for x0 in (0..width).step_by(ADAPTIVE_CELL) {
+        let x1 = (x0 + ADAPTIVE_CELL).min(width - 1);
+        let y1 = (y0 + ADAPTIVE_CELL).min(height - 1);
+        self.refine([x0, y0], [x1, y1]);
+      }
+    }
+  }
+
+  /// Sample a pixel, unless it was already
+  fn sample(&mut self, [x, y]: [usize; 2]) -> [f32; N] {
+    let index = y * self.size[0] + x;
+    let row = self.row(y);
+    if !self.exact[index] {
+      let sample =
+        self
+          .shape
+          .sample_pixel_channels([x, y], self.size, self.config);
+      self.field.set([x, row], sample);
+      self.exact[index] = true;
+    }
+    self.field.get([x, row])
+  }
+
+  fn row(&self, y: usize) -> usize {
+    if self.config.flip_y {
+      self.size[1] - 1 - y
+    } else {
+      y
+    }
+  }
+
+  /// Fill the cell with the corners `min` & `max`, inclusive
+  fn refine(&mut self, min: [usize; 2], max: [usize; 2]) {
+    let [x0, y0] = min;
+    let [x1, y1] = max;
+    let corners =
+      [[x0, y0], [x1, y0], [x0, y1], [x1, y1]].map(|c| self.sample(c));
+    if x1 - x0 <= 1 && y1 - y0 <= 1 {
+      return;
+    }
+    // the corners of the quarters the cell would be refined into
+    let [xm, ym] = [(x0 + x1) / 2, (y0 + y1) / 2];
+    let probes = [[xm, ym], [xm, y0], [xm, y1], [x0, ym], [x1, ym]];
+    let samples = probes.map(|probe| self.sample(probe));
+
+    let max_distance = self.config.max_distance;
+    let clamp = |d: f32| d.clamp(-max_distance, max_distance);
+    // ridges in the field can hide between the samples of large cells,
+    // unless every pixel is beyond the max distance: distances change by at
+    // most a pixel per pixel, & no pixel is further than a quarter of the
+    // cell's diagonal from a sample
+    let small = x1 - x0 <= MAX_BAND_CELL && y1 - y0 <= MAX_BAND_CELL;
+    let reach = ((x1 - x0) as f32).hypot((y1 - y0) as f32) / 4.;
+    let smooth = (0..N).all(|channel| {
+      let saturated = corners
+        .iter()
+        .chain(&samples)
+        .all(|sample| sample[channel].abs() >= max_distance + reach);
+      let inside = corners[0][channel] > 0.;
+      corners
+        .iter()
+        .chain(&samples)
+        .all(|sample| (sample[channel] > 0.) == inside)
+        && (small || saturated)
+        && probes.iter().zip(&samples).all(|(&probe, sample)| {
+          let expected = bilinear(corners, min, max, probe, channel);
+          (clamp(expected) - clamp(sample[channel])).abs() <= self.tolerance
+        })
+    });
+    if smooth {
+      for y in y0..=y1 {
+        for x in x0..=x1 {
+          if !self.exact[y * self.size[0] + x] {
+            let sample = std::array::from_fn(|channel| {
+              bilinear(corners, min, max, [x, y], channel)
+            });
+            let row = self.row(y);
+            self.field.set([x, row], sample);
+          }
+        }
+      }
+      return;
+    }
+    self.refine([x0, y0], [xm, ym]);
+    self.refine([xm, y0], [x1, ym]);
+    self.refine([x0, ym], [xm, y1]);
+    self.refine([xm, ym], [x1, y1]);
+  }
+}
+
+/// Interpolate a channel of the samples at the corners of a cell
+fn bilinear<const N: usize>(
+  corners: [[f32; N]; 4],
+  [x0, y0]: [usize; 2],
+  [x1, y1]: [usize; 2],
+  [x, y]: [usize; 2],
+  channel: usize,
+) -> f32 {
+  let fraction = |v: usize, v0: usize, v1: usize| {
+    if v1 > v0 {
+      (v - v0) as f32 / (v1 - v0) as f32
+    } else {
+      0.
+    }
+  };
+  let [tx, ty] = [fraction(x, x0, x1), fraction(y, y0, y1)];
+  let [a, b, c, d] = corners.map(|corner| corner[channel]);
+  let top = a + (b - a) * tx;
+  let bottom = c + (d - c) * tx;
+  top + (bottom - top) * ty
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn sample_field_adaptive() {
+    // a curved triangle, in two splines
+    let shape = Shape {
+      points: vec![
+        (20., 20.).into(),
+        (60., 0.).into(),
+        (80., 40.).into(),
+        (30., 70.).into(),
+        (20., 20.).into(),
+      ],
+      segments: vec![
+        SegmentRef {
+          kind: SegmentKind::QuadBezier,
+          points_index: 0,
+        },
+        SegmentRef {
+          kind: SegmentKind::Line,
+          points_index: 2,
+        },
+        SegmentRef {
+          kind: SegmentKind::Line,
+          points_index: 3,
+        },
+      ],
+      splines: vec![
+        Spline {
+          segments_range: 0..1,
+          colour: Magenta,
+        },
+        Spline {
+          segments_range: 1..3,
+          colour: Yellow,
+        },
+      ],
+      contours: vec![Contour { spline_range: 0..2 }],
+    };
+    let size = [97, 83];
+    let config = RasterConfig {
+      flip_y: true,
+      ..Default::default()
+    };
+    let exact = shape.sample_field::<3>(size, config);
+    let adaptive = shape.sample_field_adaptive::<3>(size, config, 0.05);
+    let max = config.max_distance;
+    for (exact, adaptive) in exact.samples().iter().zip(adaptive.samples()) {
+      for (exact, adaptive) in exact.iter().zip(adaptive) {
+        let error = exact.clamp(-max, max) - adaptive.clamp(-max, max);
+        assert!(error.abs() < 0.1, "{exact} != {adaptive}");
+      }
+    }
+
+    // most of a large canvas is far from the shape
+    let mut sampler = AdaptiveSampler::<3> {
+      shape: &shape,
+      size: [512, 512],
+      config,
+      tolerance: 0.05,
+      field: Field::new([512, 512]),
+      exact: vec![false; 512 * 512],
+    };
+    sampler.sample_cells();
+    let sampled = sampler.exact.iter().filter(|&&exact| exact).count();
+    assert!(sampled < 512 * 512 / 10, "{sampled} pixels were sampled");
+  }
+}
@@ -0,0 +1,829 @@
+use crate::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Identifies a glyph in an [`Atlas`]
+///
+/// Glyph ids are only unique within a font, so glyphs are namespaced by the
+/// index of their font, as returned by [`AtlasBuilder::add_font`]. This lets
+/// glyphs from several fonts, such as a UI font and an icon font, share one
+/// atlas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GlyphKey {
+  /// The index of the glyph's font in [`Atlas::fonts`]
+  pub font: usize,
+  /// The glyph's id within its font
+  pub glyph: u16,
+}
+
+/// Where a glyph was placed in an [`Atlas`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtlasGlyph {
+  /// The pixels of [`Atlas::field`] holding the glyph's field
+  pub rect: Rect,
+  /// Where the top-left pixel of the glyph's field lies relative to the
+  /// glyph's origin on the baseline, in pixels with y pointing down, see
+  /// [`PreparedGlyph::offset`]
+  ///
+  /// Zero for fields added without an offset.
+  pub offset: Vector,
+  /// The [`RasterConfig::max_distance`] the glyph's field was sampled with,
+  /// which it's quantized by, and which the shader needs to map its
+  /// quantized channels back to pixels
+  ///
+  /// Glyphs can each have their own range, e.g. a small one for tiny
+  /// punctuation and a larger one for complex glyphs.
+  pub max_distance: f32,
+}
+
+/// Glyph fields packed into a single field, along with the metadata needed
+/// to find them again
+#[derive(Debug, Clone, PartialEq)]
+pub struct Atlas<const N: usize> {
+  pub field: Field<N>,
+  /// The names of the fonts the glyphs come from, indexed by
+  /// [`GlyphKey::font`]
+  pub fonts: Vec<String>,
+  pub glyphs: BTreeMap<GlyphKey, AtlasGlyph>,
+  /// How the atlas was generated, see [`AtlasBuilder::provenance`]
+  pub provenance: Provenance,
+}
+
+impl<const N: usize> Atlas<N> {
+  /// The index of the font with the given name
+  pub fn font_index(&self, name: &str) -> Option<usize> {
+    self.fonts.iter().position(|font| font == name)
+  }
+
+  pub fn get(&self, key: GlyphKey) -> Option<&AtlasGlyph> {
+    self.glyphs.get(&key)
+  }
+
+  /// The quality report recorded for a glyph, see
+  /// [`AtlasBuilder::record_quality`]
+  pub fn quality(&self, key: GlyphKey) -> Option<QualityReport> {
+    let font = self.fonts.get(key.font)?;
+    let record = format!("{QUALITY_RECORD}{font}.{}", key.glyph);
+    self.provenance.get(&record)?.parse().ok()
+  }
+
+  /// Whether a glyph's multi-channel field was replaced by a single channel,
+  /// see [`AtlasBuilder::insert_prepared_checked`]
+  pub fn is_single_channel(&self, key: GlyphKey) -> bool {
+    let Some(font) = self.fonts.get(key.font) else {
+      return false;
+    };
+    let record = format!("{SINGLE_CHANNEL_RECORD}{font}.{}", key.glyph);
+    self.provenance.get(&record) == Some("true")
+  }
+
+  /// The glyphs of a single font, in order of glyph id
+  pub fn font_glyphs(
+    &self,
+    font: usize,
+  ) -> impl Iterator<Item = (u16, &AtlasGlyph)> + '_ {
+    self
+      .glyphs
+      .range(
+        GlyphKey { font, glyph: 0 }..=GlyphKey {
+          font,
+          glyph: u16::MAX,
+        },
+      )
+      .map(|(key, glyph)| (key.glyph, glyph))
+  }
+
+  /// The atlas' field quantized as [`Field::quantized_le`] does, with each
+  /// glyph quantized by its own range, and the background by the `config`
+  pub(crate) fn quantized_le(&self, config: RasterConfig) -> Vec<u8> {
+    let mut bytes = self.field.quantized_le(config);
+    let texel_size = N * config.quantization.channel_size();
+    let row_size = self.field.width() * texel_size;
+    for glyph in self.glyphs.values() {
+      let Rect { x, y, width, .. } = glyph.rect;
+      if width == 0 {
+        continue;
+      }
+      let glyph_config = RasterConfig {
+        max_distance: glyph.max_distance,
+        ..config
+      };
+      let field = self.field.view(glyph.rect).to_field();
+      let texels = field.quantized_le(glyph_config);
+      for (row, row_texels) in
+        texels.chunks_exact(width * texel_size).enumerate()
+      {
+        let start = (y + row) * row_size + x * texel_size;
+        bytes[start..start + row_texels.len()].copy_from_slice(row_texels);
+      }
+    }
+    bytes
+  }
+}
+
+/// Packs glyph fields into an [`Atlas`]
+///
+/// Glyphs are placed on shelves, tallest first, in an atlas of a fixed
+/// width that grows downwards as needed.
+///
+/// ```
+/// # use rsdf_core::*;
+/// let mut builder = AtlasBuilder::<1>::new(64);
+/// let text = builder.add_font("Sans");
+/// let icons = builder.add_font("Icons");
+/// builder.insert(GlyphKey { font: text, glyph: 3 }, Field::new([10, 12]));
+/// builder.insert(GlyphKey { font: icons, glyph: 3 }, Field::new([16, 16]));
+/// let atlas = builder.build();
+///
+/// // the same glyph id is a different glyph in each font
+/// let icon = atlas.get(GlyphKey { font: icons, glyph: 3 }).unwrap();
+/// assert_eq!([icon.rect.width, icon.rect.height], [16, 16]);
+/// assert_eq!(atlas.font_glyphs(text).count(), 1);
+/// ```
+#[derive(Debug, Clone)]
+#[must_use = "an atlas builder does nothing until the atlas is built"]
+pub struct AtlasBuilder<const N: usize> {
+  width: usize,
+  padding: usize,
+  max_distance: f32,
+  background: Option<f32>,
+  fonts: Vec<String>,
+  /// Each glyph's field, with its offset & range
+  glyphs: BTreeMap<GlyphKey, (Field<N>, Vector, f32)>,
+  provenance: Provenance,
+  /// The checksums of the glyphs added by
+  /// [`AtlasBuilder::insert_prepared`]
+  checksums: BTreeMap<GlyphKey, u64>,
+  /// The fields of a previous atlas' glyphs, by font name & glyph id, with
+  /// the checksums they were recorded with & where they were placed
+  previous: BTreeMap<(String, u16), (u64, Field<N>, Rect)>,
+  /// The checksums of the previous atlas' fonts, see
+  /// [`Provenance::font_checksum`]
+  previous_fonts: BTreeMap<String, u64>,
+  /// Where the glyphs reused from the previous atlas were placed, which they
+  /// keep if they can
+  pinned: BTreeMap<GlyphKey, Rect>,
+  /// The quality reports recorded by [`AtlasBuilder::record_quality`]
+  qualities: BTreeMap<GlyphKey, QualityReport>,
+  /// The glyphs whose multi-channel field was replaced by a single channel,
+  /// see [`AtlasBuilder::insert_prepared_checked`]
+  single_channel: BTreeSet<GlyphKey>,
+}
+
+/// The prefix of the [`Provenance`] records holding glyphs' checksums, as
+/// `checksum.<font name>.<glyph id>`
+const CHECKSUM_RECORD: &str = "checksum.";
+/// The prefix of the [`Provenance`] records holding glyphs' quality reports,
+/// as `quality.<font name>.<glyph id>`
+const QUALITY_RECORD: &str = "quality.";
+/// The prefix of the [`Provenance`] records marking the glyphs that fell back
+/// to a single channel, as `single_channel.<font name>.<glyph id>`
+const SINGLE_CHANNEL_RECORD: &str = "single_channel.";
+
+impl<const N: usize> AtlasBuilder<N> {
+  /// Start an atlas `width` pixels wide
+  pub fn new(width: usize) -> Self {
+    AtlasBuilder {
+      width,
+      padding: 1,
+      max_distance: RasterConfig::default().max_distance,
+      background: None,
+      fonts: vec![],
+      glyphs: BTreeMap::new(),
+      provenance: Provenance::default(),
+      checksums: BTreeMap::new(),
+      previous: BTreeMap::new(),
+      previous_fonts: BTreeMap::new(),
+      pinned: BTreeMap::new(),
+      qualities: BTreeMap::new(),
+      single_channel: BTreeSet::new(),
+    }
+  }
+
+  /// The number of pixels left empty between glyphs, which stops
+  /// neighbouring glyphs bleeding into each other when the atlas is filtered
+  ///
+  /// Defaults to `1`.
+  pub fn padding(mut self, padding: usize) -> Self {
+    self.padding = padding;
+    self
+  }
+
+  /// The [`RasterConfig::max_distance`] the glyphs are sampled with, unless
+  /// given their own by [`AtlasBuilder::insert_with_range`]
+  ///
+  /// Defaults to that of [`RasterConfig::default`].
+  pub fn max_distance(mut self, max_distance: f32) -> Self {
+    self.max_distance = max_distance;
+    self
+  }
+
+  /// The distance written to pixels not covered by any glyph
+  ///
+  /// Defaults to `-max_distance`, fully outside of any shape once quantized,
+  /// see [`AtlasBuilder::max_distance`]. The background is quantized by the
+  /// config passed to [`Atlas::to_ktx2`] & co., not by any glyph's range.
+  pub fn background(mut self, distance: f32) -> Self {
+    self.background = Some(distance);
+    self
+  }
+
+  /// Record how the atlas was generated in its metadata
+  ///
+  /// Defaults to an empty [`Provenance`].
+  pub fn provenance(mut self, provenance: Provenance) -> Self {
+    self.provenance = provenance;
+    self
+  }
+
+  /// Reuse the fields of a previous atlas' glyphs that haven't changed since
+  ///
+  /// Glyphs added by [`AtlasBuilder::insert_prepared`] are copied from
+  /// `previous` when it holds the same glyph, of a font of the same name,
+  /// recorded with the same checksum. Only the changed glyphs are sampled,
+  /// and the reused ones keep their place in the atlas, as long as it's no
+  /// wider than the previous one, with the rest packed around them. So only
+  /// the regions of the changed glyphs, and the metadata, need rewriting in
+  /// the texture built from the previous atlas.
+  ///
+  /// An atlas written by an earlier run can be reused by loading it with
+  /// [`Atlas::from_bytes`].
+  ///
+  /// Fonts whose checksum in the builder's [provenance] differs from the one
+  /// recorded in `previous`, e.g. after a [`FontHandle`] was reloaded, have
+  /// none of their glyphs reused.
+  ///
+  /// [provenance]: AtlasBuilder::provenance
+  ///
+  /// ```
+  /// # use rsdf_builder::ShapeBuilder;
+  /// # use rsdf_core::*;
+  /// let config = RasterConfig::default();
+  /// let square = |size: f32| {
+  ///   let outline = ShapeBuilder::new()
+  ///     .contour((0., 0.))
+  ///     .line((0., -size))
+  ///     .line((size, -size))
+  ///     .line((size, 0.))
+  ///     .line((0., 0.))
+  ///     .end_contour()
+  ///     .build();
+  ///   PreparedGlyph::new(outline, 1., config).unwrap()
+  /// };
+  ///
+  /// let mut builder = AtlasBuilder::<3>::new(64);
+  /// let font = builder.add_font("Sans");
+  /// builder.insert_prepared(GlyphKey { font, glyph: 1 }, &square(8.), config);
+  /// builder.insert_prepared(GlyphKey { font, glyph: 2 }, &square(4.), config);
+  /// let bytes = builder.build().to_bytes();
+  ///
+  /// // only the glyph that changed is sampled again
+  /// let previous = Atlas::from_bytes(&bytes).unwrap();
+  /// let mut builder = AtlasBuilder::<3>::new(64).reuse(&previous);
+  /// let font = builder.add_font("Sans");
+  /// let key = |glyph| GlyphKey { font, glyph };
+  /// assert!(!builder.insert_prepared(key(1), &square(8.), config));
+  /// assert!(builder.insert_prepared(key(2), &square(6.), config));
+  /// let atlas = builder.build();
+  /// assert_eq!(atlas.get(key(1)), previous.get(key(1)));
+  /// ```
+  pub fn reuse(mut self, previous: &Atlas<N>) -> Self {
+    for (record, value) in &previous.provenance.records {
+      let glyph = record
+        .strip_prefix(CHECKSUM_RECORD)
+        .and_then(|name_glyph| name_glyph.rsplit_once('.'));
+      let Some((name, glyph)) = glyph else { continue };
+      let checksum = u64::from_str_radix(value, 16).ok();
+      let glyph = glyph.parse().ok();
+      let font = previous.font_index(name);
+      let (Some(checksum), Some(glyph), Some(font)) = (checksum, glyph, font)
+      else {
+        continue;
+      };
+      if let Some(atlas_glyph) = previous.get(GlyphKey { font, glyph }) {
+        let rect = atlas_glyph.rect;
+        let field = previous.field.view(rect).to_field();
+        self
+          .previous
+          .insert((name.into(), glyph), (checksum, field, rect));
+      }
+    }
+    for name in &previous.fonts {
+      if let Some(checksum) = previous.provenance.font_checksum(name) {
+        self.previous_fonts.insert(name.clone(), checksum);
+      }
+    }
+    self
+  }
+
+  /// The field of the glyph in the reused atlas, and where it was placed, if
+  /// it has the `checksum`
+  pub(crate) fn reusable(
+    &self,
+    key: GlyphKey,
+    checksum: u64,
+  ) -> Option<(Field<N>, Rect)> {
+    let name = self.fonts.get(key.font)?;
+    let font = self.provenance.font_checksum(name);
+    if font.is_some() && font != self.previous_fonts.get(name).copied() {
+      return None;
+    }
+    match self.previous.get(&(name.clone(), key.glyph)) {
+      Some((previous, field, rect)) if *previous == checksum => {
+        Some((field.clone(), *rect))
+      },
+      _ => None,
+    }
+  }
+
+  /// Keep the glyph last inserted for `key` at `rect`, where it was placed in
+  /// the reused atlas
+  pub(crate) fn pin(&mut self, key: GlyphKey, rect: Rect) {
+    self.pinned.insert(key, rect);
+  }
+
+  /// Record the checksum of the glyph last inserted for `key`
+  pub(crate) fn record_checksum(&mut self, key: GlyphKey, checksum: u64) {
+    self.checksums.insert(key, checksum);
+  }
+
+  /// Record the quality of the glyph last inserted for `key`, see
+  /// [`Shape::quality_report`]
+  ///
+  /// The report is kept in the atlas' provenance, so pipelines can flag the
+  /// glyphs that need attention, see [`Atlas::quality`]. Inserting another
+  /// field for the key drops it.
+  pub fn record_quality(&mut self, key: GlyphKey, report: QualityReport) {
+    self.qualities.insert(key, report);
+  }
+
+  /// Mark the glyph last inserted for `key` as having fallen back to a
+  /// single channel
+  pub(crate) fn record_single_channel(&mut self, key: GlyphKey) {
+    self.single_channel.insert(key);
+  }
+
+  /// Register a font, returning its index for use in [`GlyphKey`]s
+  ///
+  /// Adding a font with a name that was already added returns the existing
+  /// index.
+  pub fn add_font(&mut self, name: impl Into<String>) -> usize {
+    let name = name.into();
+    match self.fonts.iter().position(|font| *font == name) {
+      Some(index) => index,
+      None => {
+        self.fonts.push(name);
+        self.fonts.len() - 1
+      },
+    }
+  }
+
+  /// Add a glyph's field, replacing any field already added for the key
+  ///
+  /// Panics if the key's font hasn't been added, or the field is wider than
+  /// the atlas.
+  pub fn insert(&mut self, key: GlyphKey, field: Field<N>) {
+    self.insert_with_offset(key, field, Vector::ZERO);
+  }
+
+  /// Add a glyph's field, as in [`AtlasBuilder::insert`], along with where
+  /// it lies relative to the glyph's origin, see [`AtlasGlyph::offset`]
+  pub fn insert_with_offset(
+    &mut self,
+    key: GlyphKey,
+    field: Field<N>,
+    offset: Vector,
+  ) {
+    self.insert_with_range(key, field, offset, self.max_distance);
+  }
+
+  /// Add a glyph's field, as in [`AtlasBuilder::insert_with_offset`], along
+  /// with the [`RasterConfig::max_distance`] it was sampled with, see
+  /// [`AtlasGlyph::max_distance`]
+  ///
+  /// ```
+  /// # use rsdf_core::*;
+  /// let mut builder = AtlasBuilder::<1>::new(32);
+  /// let font = builder.add_font("Sans");
+  /// let key = |glyph| GlyphKey { font, glyph };
+  /// builder.insert_with_range(key(1), Field::new([4, 4]), Vector::ZERO, 2.);
+  /// builder.insert(key(2), Field::new([8, 8]));
+  /// let atlas = builder.build();
+  /// assert_eq!(atlas.get(key(1)).unwrap().max_distance, 2.);
+  /// assert_eq!(atlas.get(key(2)).unwrap().max_distance, 5.);
+  /// ```
+  pub fn insert_with_range(
+    &mut self,
+    key: GlyphKey,
+    field: Field<N>,
+    offset: Vector,
+    max_distance: f32,
+  ) {
+    assert!(key.font < self.fonts.len(), "unknown font {}", key.font);
+    assert!(field.width() <= self.width, "glyph wider than the atlas");
+    self.glyphs.insert(key, (field, offset, max_distance));
+    self.checksums.remove(&key);
+    self.pinned.remove(&key);
+    self.qualities.remove(&key);
+    self.single_channel.remove(&key);
+  }
+
+  /// Pack the glyphs into an atlas
+  #[must_use]
+  pub fn build(self) -> Atlas<N> {
+    let mut glyphs = BTreeMap::new();
+    // reused glyphs stay where they were, if they still fit
+    let mut pinned = vec![];
+    for (key, &rect) in &self.pinned {
+      let (field, offset, max_distance) = &self.glyphs[key];
+      if rect.x + rect.width <= self.width
+        && field.size() == [rect.width, rect.height]
+      {
+        pinned.push(rect);
+        glyphs.insert(
+          *key,
+          AtlasGlyph {
+            rect,
+            offset: *offset,
+            max_distance: *max_distance,
+          },
+        );
+      }
+    }
+
+    let mut order: Vec<&GlyphKey> = (self.glyphs.keys())
+      .filter(|key| !glyphs.contains_key(*key))
+      .collect();
+    // tallest first, so each shelf wastes little space above its glyphs;
+    // the sort is stable, so ties are kept in key order
+    order.sort_by_key(|key| std::cmp::Reverse(self.glyphs[key].0.height()));
+
+    let padding = self.padding;
+    let (mut x, mut y, mut shelf_height) = (0, 0, 0);
+    for &key in &order {
+      let (field, offset, max_distance) = &self.glyphs[key];
+      let [width, height] = field.size();
+      let rect = loop {
+        if x > 0 && x + width > self.width {
+          (x, y) = (0, y + (shelf_height + padding).max(1));
+          shelf_height = 0;
+        }
+        let rect = Rect {
+          x,
+          y,
+          width,
+          height,
+        };
+        // skip past any pinned glyph in the way, and keep the next shelf
+        // clear of it
+        match pinned.iter().find(|&&other| overlaps(rect, other, padding)) {
+          Some(other) => {
+            x = other.x + other.width + padding;
+            shelf_height = shelf_height.max(other.y + other.height - y);
+          },
+          None => break rect,
+        }
+      };
+      glyphs.insert(
+        *key,
+        AtlasGlyph {
+          rect,
+          offset: *offset,
+          max_distance: *max_distance,
+        },
+      );
+      x += width + self.padding;
+      shelf_height = shelf_height.max(height);
+    }
+
+    let height = glyphs
+      .values()
+      .map(|glyph: &AtlasGlyph| glyph.rect.y + glyph.rect.height)
+      .max()
+      .unwrap_or(0);
+    let background = self.background.unwrap_or(-self.max_distance);
+    let mut field = Field::from_samples(
+      [self.width, height],
+      vec![[background; N]; self.width * height],
+    );
+    for (key, glyph) in &glyphs {
+      paste(&mut field, &self.glyphs[key].0, glyph.rect);
+    }
+
+    let mut provenance = self.provenance;
+    let glyph_record = |prefix: &str, key: &GlyphKey| {
+      format!("{prefix}{}.{}", self.fonts[key.font], key.glyph)
+    };
+    for (key, checksum) in &self.checksums {
+      let record = glyph_record(CHECKSUM_RECORD, key);
+      provenance = provenance.record(record, format!("{checksum:016x}"));
+    }
+    for (key, report) in &self.qualities {
+      let record = glyph_record(QUALITY_RECORD, key);
+      provenance = provenance.record(record, report.to_string());
+    }
+    for key in &self.single_channel {
+      let record = glyph_record(SINGLE_CHANNEL_RECORD, key);
+      provenance = provenance.record(record, "true");
+    }
+
+    Atlas {
+      field,
+      fonts: self.fonts,
+      glyphs,
+      provenance,
+    }
+  }
+}
+
+/// Fields of a single size laid out on a grid, in rows of a fixed number of
+/// cells
+///
+/// Unlike an [`Atlas`], no metadata is needed to find a field: the cell of
+/// the field at `index` is found arithmetically, which suits monospace and
+/// terminal fonts indexed by character, or debugging. The fields must all be
+/// sampled at the size of a cell. Cells left over in the last row are
+/// filled with `-max_distance`, fully outside of any shape once quantized.
+///
+/// ```
+/// # use rsdf_core::*;
+/// let glyphs = (0..10).map(|_| Field::<3>::new([8, 16]));
+/// let max_distance = RasterConfig::default().max_distance;
+/// let grid = GridAtlas::new([8, 16], 4, glyphs, max_distance);
+/// assert_eq!(grid.field.size(), [32, 48]);
+/// let rect = grid.rect(5).unwrap();
+/// assert_eq!([rect.x, rect.y], [8, 16]);
+/// assert!(grid.rect(10).is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridAtlas<const N: usize> {
+  pub field: Field<N>,
+  /// The size of every cell, in pixels
+  pub cell: [usize; 2],
+  /// The number of cells in each row
+  pub columns: usize,
+  /// The number of fields in the grid
+  pub len: usize,
+}
+
+impl<const N: usize> GridAtlas<N> {
+  /// Lay out the fields in order, left to right then top to bottom
+  ///
+  /// `max_distance` is the [`RasterConfig::max_distance`] the fields were
+  /// sampled with. Panics if `columns` is `0`, or any field isn't the size
+  /// of a cell.
+  pub fn new(
+    cell: [usize; 2],
+    columns: usize,
+    fields: impl IntoIterator<Item = Field<N>>,
+    max_distance: f32,
+  ) -> Self {
+    assert!(columns > 0, "a grid needs at least one column");
+    let fields: Vec<Field<N>> = fields.into_iter().collect();
+    let rows = fields.len().div_ceil(columns);
+    let size = [cell[0] * columns, cell[1] * rows];
+    let mut grid = GridAtlas {
+      field: Field::from_samples(
+        size,
+        vec![[-max_distance; N]; size[0] * size[1]],
+      ),
+      cell,
+      columns,
+      len: fields.len(),
+    };
+    for (index, source) in fields.iter().enumerate() {
+      assert_eq!(source.size(), cell, "field {index} isn't the cell size");
+      let rect = grid.cell_rect(index);
+      paste(&mut grid.field, source, rect);
+    }
+    grid
+  }
+
+  /// The pixels of the cell holding the field at `index`, if there is one
+  pub fn rect(&self, index: usize) -> Option<Rect> {
+    (index < self.len).then(|| self.cell_rect(index))
+  }
+
+  /// The field at `index`, if there is one
+  pub fn get(&self, index: usize) -> Option<FieldView<'_, N>> {
+    self.rect(index).map(|rect| self.field.view(rect))
+  }
+
+  fn cell_rect(&self, index: usize) -> Rect {
+    let [width, height] = self.cell;
+    Rect {
+      x: index % self.columns * width,
+      y: index / self.columns * height,
+      width,
+      height,
+    }
+  }
+}
+
+/// Whether the rects are closer than `padding` to each other
+fn overlaps(a: Rect, b: Rect, padding: usize) -> bool {
+  a.x < b.x + b.width + padding
+    && b.x < a.x + a.width + padding
+    && a.y < b.y + b.height + padding
+    && b.y < a.y + a.height + padding
+}
+
+/// Copy `source` into the pixels of `field` covered by `rect`
+pub(crate) fn paste<const N: usize>(
+  field: &mut Field<N>,
+  source: &Field<N>,
+  rect: Rect,
+) {
+  for (x, y, sample) in source.pixels() {
+    field.set([rect.x + x, rect.y + y], sample);
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  /// A field filled with a single distance
+  fn filled(size: [usize; 2], distance: f32) -> Field<1> {
+    Field::from_samples(size, vec![[distance]; size[0] * size[1]])
+  }
+
+  #[test]
+  fn fonts() {
+    let mut builder = AtlasBuilder::new(32).padding(2);
+    let sans = builder.add_font("Sans");
+    let icons = builder.add_font("Icons");
+    assert_eq!(builder.add_font("Sans"), sans);
+
+    let key = |font, glyph| GlyphKey { font, glyph };
+    builder.insert(key(sans, 1), filled([10, 8], 1.));
+    builder.insert(key(sans, 2), filled([10, 12], 2.));
+    builder.insert(key(icons, 1), filled([16, 16], 3.));
+    builder.insert(key(icons, 2), filled([6, 4], 4.));
+    let atlas = builder.build();
+
+    assert_eq!(atlas.fonts, ["Sans", "Icons"]);
+    assert_eq!(atlas.font_index("Icons"), Some(icons));
+    assert_eq!(atlas.font_index("Serif"), None);
+    assert_eq!(atlas.glyphs.len(), 4);
+    let ids: Vec<u16> = atlas.font_glyphs(icons).map(|(id, _)| id).collect();
+    assert_eq!(ids, [1, 2]);
+
+    // every glyph's field is copied into its own rect
+    let rects: Vec<Rect> = atlas.glyphs.values().map(|g| g.rect).collect();
+    for (glyph_key, distance) in
+      [(key(sans, 1), 1.), (key(sans, 2), 2.), (key(icons, 1), 3.)]
+    {
+      let rect = atlas.get(glyph_key).unwrap().rect;
+      let view = atlas.field.view(rect);
+      assert!(view.rows().flatten().all(|&[d]| d == distance));
+    }
+    for (i, a) in rects.iter().enumerate() {
+      for b in &rects[i + 1..] {
+        let apart = a.x + a.width + 2 <= b.x
+          || b.x + b.width + 2 <= a.x
+          || a.y + a.height + 2 <= b.y
+          || b.y + b.height + 2 <= a.y;
+        assert!(apart, "{a:?} overlaps {b:?}");
+      }
+    }
+    // the shelves are as tall as their tallest glyph
+    assert_eq!(atlas.get(key(icons, 1)).unwrap().rect.y, 0);
+    assert_eq!(atlas.get(key(icons, 2)).unwrap().rect.y, 18);
+    assert_eq!(atlas.get(key(sans, 2)).unwrap().rect.x, 18);
+    assert_eq!(atlas.field.size(), [32, 26]);
+    assert_eq!(atlas.field.get([31, 25]), [-5.]);
+  }
+
+  #[test]
+  fn mixed_ranges() {
+    let mut builder = AtlasBuilder::new(8).padding(0).max_distance(4.);
+    let font = builder.add_font("Sans");
+    let key = |glyph| GlyphKey { font, glyph };
+    builder.insert_with_range(key(1), filled([2, 2], 1.), Vector::ZERO, 2.);
+    builder.insert_with_range(key(2), filled([3, 1], 1.), Vector::ZERO, 8.);
+    let atlas = builder.build();
+    assert_eq!(atlas.field.get([7, 1]), [-4.]);
+
+    let config = RasterConfig::default();
+    let texels = atlas.quantized_le(config);
+    let texel = |glyph| {
+      let rect = atlas.get(key(glyph)).unwrap().rect;
+      texels[rect.y * 8 + rect.x]
+    };
+    // the same distance, quantized by each glyph's own range
+    assert_eq!(texel(1), distance_color(1., 2.));
+    assert_eq!(texel(2), distance_color(1., 8.));
+    assert_eq!(texels[8 + 7], distance_color(-4., config.max_distance));
+    let rgba = atlas.to_rgba(config, AlphaMode::default());
+    let rect = atlas.get(key(2)).unwrap().rect;
+    assert_eq!(rgba.get_pixel([rect.x, rect.y])[0], texel(2));
+  }
+
+  #[test]
+  fn reuse() {
+    let config = RasterConfig::default();
+    let glyph = |size: f32| {
+      let shape = Shape::rectangle((0., 0.), (size, size), &[White]);
+      PreparedGlyph::new(shape, 1., config).unwrap()
+    };
+
+    let mut builder = AtlasBuilder::<1>::new(64);
+    let font = builder.add_font("Sans");
+    let key = |glyph| GlyphKey { font, glyph };
+    assert!(builder.insert_prepared(key(1), &glyph(4.), config));
+    assert!(builder.insert_prepared(key(2), &glyph(6.), config));
+    let mut previous = builder.build();
+    let record = previous.provenance.get("checksum.Sans.1").unwrap();
+    assert_eq!(record, format!("{:016x}", glyph(4.).checksum(config)));
+    // mark the previous fields, to tell copied fields from sampled ones
+    let marker = -42.;
+    previous.field = filled(previous.field.size(), marker);
+
+    let mut builder = AtlasBuilder::<1>::new(64).reuse(&previous);
+    let icons = builder.add_font("Icons");
+    let font = builder.add_font("Sans");
+    let key = |glyph| GlyphKey { font, glyph };
+    assert!(!builder.insert_prepared(key(1), &glyph(4.), config));
+    assert!(builder.insert_prepared(key(2), &glyph(5.), config));
+    let icon = GlyphKey {
+      font: icons,
+      glyph: 1,
+    };
+    assert!(builder.insert_prepared(icon, &glyph(4.), config));
+    // a glyph inserted without a checksum isn't reused later
+    builder.insert(key(3), filled([2, 2], 0.));
+    let atlas = builder.build();
+
+    let samples = |key| {
+      let rect = atlas.get(key).unwrap().rect;
+      atlas.field.view(rect).to_field()
+    };
+    assert!(samples(key(1)).samples().iter().all(|&[d]| d == marker));
+    assert!(samples(key(2)).samples().iter().all(|&[d]| d != marker));
+    assert!(samples(icon).samples().iter().all(|&[d]| d != marker));
+    assert!(atlas.provenance.get("checksum.Sans.3").is_none());
+
+    // the reused glyph stays put, with the others packed around it
+    let previous_key = GlyphKey { font: 0, glyph: 1 };
+    assert_eq!(atlas.get(key(1)), previous.get(previous_key));
+    let rects: Vec<Rect> = atlas.glyphs.values().map(|g| g.rect).collect();
+    for (i, &a) in rects.iter().enumerate() {
+      for &b in &rects[i + 1..] {
+        assert!(!overlaps(a, b, 1), "{a:?} overlaps {b:?}");
+      }
+    }
+
+    // nothing is reused from a font whose data has changed
+    previous.provenance = previous.provenance.font("Sans", b"v1");
+    let provenance = Provenance::default().font("Sans", b"v2");
+    let mut builder = AtlasBuilder::<1>::new(64)
+      .provenance(provenance)
+      .reuse(&previous);
+    let font = builder.add_font("Sans");
+    let key = GlyphKey { font, glyph: 1 };
+    assert!(builder.insert_prepared(key, &glyph(4.), config));
+  }
+
+  #[test]
+  fn quality() {
+    let mut builder = AtlasBuilder::new(16);
+    let font = builder.add_font("Sans");
+    let key = |glyph| GlyphKey { font, glyph };
+    let report = QualityReport {
+      pixels: 4,
+      artifacts: 1,
+      deviating: 2,
+      ..QualityReport::default()
+    };
+    builder.insert(key(1), filled([2, 2], 1.));
+    builder.record_quality(key(1), report);
+    builder.insert(key(2), filled([2, 2], 1.));
+    builder.record_quality(key(2), report);
+    // replacing the field drops its report
+    builder.insert(key(2), filled([2, 2], 1.));
+    let atlas = builder.build();
+    assert_eq!(atlas.quality(key(1)), Some(report));
+    assert_eq!(atlas.quality(key(2)), None);
+    let record = atlas.provenance.get("quality.Sans.1").unwrap();
+    assert_eq!(
+      record,
+      "pixels=4 artifacts=1 non_finite=0 deviating=2 corrected=0"
+    );
+  }
+
+  #[test]
+  fn grid() {
+    let fields = (0..5).map(|i| filled([3, 2], i as f32));
+    let grid = GridAtlas::new([3, 2], 2, fields, 3.);
+    assert_eq!(grid.field.size(), [6, 6]);
+    assert_eq!(grid.len, 5);
+    for index in 0..5 {
+      let view = grid.get(index).unwrap();
+      assert!(view.rows().flatten().all(|&[d]| d == index as f32));
+    }
+    assert_eq!(grid.rect(3).map(|r| [r.x, r.y]), Some([3, 2]));
+    assert!(grid.get(5).is_none());
+    // the empty cell at the end of the last row
+    assert_eq!(grid.field.get([3, 4]), [-3.]);
+  }
+}
@@ -0,0 +1,256 @@
+use crate::*;
+use std::fmt::Write;
+
+/// Why an [`Atlas`] couldn't be described by
+/// [`Atlas::to_msdf_atlas_json`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AtlasJsonError {
+  /// msdf-atlas-gen has no atlases of this many channels, only of 1, 3 or 4
+  Channels(usize),
+  /// A metric, advance, bound or range isn't finite, which JSON can't hold
+  NonFinite,
+}
+
+impl std::fmt::Display for AtlasJsonError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      AtlasJsonError::Channels(channels) => {
+        write!(f, "msdf-atlas-gen has no atlases of {channels} channels")
+      },
+      AtlasJsonError::NonFinite => write!(f, "a number isn't finite"),
+    }
+  }
+}
+
+impl std::error::Error for AtlasJsonError {}
+
+/// The value, if JSON can hold it
+fn finite(value: f32) -> Result<f32, AtlasJsonError> {
+  match value.is_finite() {
+    true => Ok(value),
+    false => Err(AtlasJsonError::NonFinite),
+  }
+}
+
+impl<const N: usize> Atlas<N> {
+  /// Describe the glyphs of one of the atlas' fonts in the JSON layout
+  /// written by msdf-atlas-gen, so that text renderers built around it can
+  /// load the atlas unchanged
+  ///
+  /// The atlas `type` is `sdf`, `msdf` or `mtsdf` by the number of channels,
+  /// and its `distanceRange` is the width of the range the field was
+  /// quantized in, twice the [`RasterConfig::max_distance`]. As in
+  /// msdf-atlas-gen's default output, the `yOrigin` is at the bottom, so y
+  /// points up in both the `atlasBounds`, in pixels, & the `planeBounds`,
+  /// in ems relative to the glyph's origin on the baseline. Both bounds are
+  /// inset by half a pixel, so bilinear samples at their edges stay within
+  /// the glyph's field. Characters of the `layout` whose glyphs aren't in
+  /// the atlas, such as spaces, have only an advance.
+  ///
+  /// Fails for atlases of 2 channels, which msdf-atlas-gen doesn't know, or
+  /// if any number to be written isn't finite.
+  ///
+  /// Each glyph in the atlas also has its own `distanceRange`, which
+  /// msdf-atlas-gen doesn't write, from its [`AtlasGlyph::max_distance`], as
+  /// glyphs are quantized by their own ranges by [`Atlas::to_ktx2`] & co.
+  /// Consumers that only read the atlas' range can use atlases whose glyphs
+  /// all share the `config`'s.
+  ///
+  /// ```
+  /// # use rsdf_core::*;
+  /// let mut builder = AtlasBuilder::<3>::new(64);
+  /// let font = builder.add_font("Sans");
+  /// let key = GlyphKey { font, glyph: 1 };
+  /// let offset = Vector::new(-4., -14.);
+  /// builder.insert_with_offset(key, Field::new([18, 20]), offset);
+  /// let atlas = builder.build();
+  ///
+  /// let mut layout = FontLayout { size: 16., ..Default::default() };
+  /// layout.chars.insert('A', CharLayout { glyph: 1, advance: 11. });
+  /// let config = RasterConfig::default();
+  /// let json = atlas.to_msdf_atlas_json(font, &layout, config).unwrap();
+  /// assert!(json.starts_with(r#"{"atlas":{"type":"msdf","#));
+  /// ```
+  pub fn to_msdf_atlas_json(
+    &self,
+    font: usize,
+    layout: &FontLayout,
+    config: RasterConfig,
+  ) -> Result<String, AtlasJsonError> {
+    let atlas_type = match N {
+      1 => "sdf",
+      3 => "msdf",
+      4 => "mtsdf",
+      _ => return Err(AtlasJsonError::Channels(N)),
+    };
+    let [width, height] = self.field.size();
+    let em = |pixels: f32| finite(pixels / layout.size);
+    let mut json = String::new();
+    let _ = write!(
+      json,
+      concat!(
+        r#"{{"atlas":{{"type":"{}","distanceRange":{},"#,
+        r#""distanceRangeMiddle":0,"size":{},"width":{},"height":{},"#,
+        r#""yOrigin":"bottom"}}"#,
+      ),
+      atlas_type,
+      finite(2. * config.max_distance)?,
+      finite(layout.size)?,
+      width,
+      height,
+    );
+    let _ = write!(
+      json,
+      concat!(
+        r#","metrics":{{"emSize":1,"lineHeight":{},"ascender":{},"#,
+        r#""descender":{},"underlineY":{},"underlineThickness":{}}}"#,
+      ),
+      em(layout.line_height)?,
+      em(layout.ascender)?,
+      -em(layout.descender)?,
+      -em(layout.underline_position)?,
+      em(layout.underline_thickness)?,
+    );
+
+    json.push_str(r#","glyphs":["#);
+    for (i, (&char, char_layout)) in layout.chars.iter().enumerate() {
+      if i > 0 {
+        json.push(',');
+      }
+      let _ = write!(
+        json,
+        r#"{{"unicode":{},"advance":{}"#,
+        char as u32,
+        em(char_layout.advance)?,
+      );
+      let key = GlyphKey {
+        font,
+        glyph: char_layout.glyph,
+      };
+      if let Some(glyph) = self.get(key) {
+        let size =
+          Vector::new(glyph.rect.width as f32, glyph.rect.height as f32);
+        let [left, top] = [glyph.offset.x + 0.5, -glyph.offset.y - 0.5];
+        let [right, bottom] = [
+          glyph.offset.x + size.x - 0.5,
+          -glyph.offset.y - size.y + 0.5,
+        ];
+        let _ = write!(
+          json,
+          r#","planeBounds":{{"left":{},"bottom":{},"right":{},"top":{}}}"#,
+          em(left)?,
+          em(bottom)?,
+          em(right)?,
+          em(top)?,
+        );
+        let rect = glyph.rect;
+        let [left, right] =
+          [rect.x as f32 + 0.5, (rect.x + rect.width) as f32 - 0.5];
+        let [bottom, top] = [
+          (height - rect.y - rect.height) as f32 + 0.5,
+          (height - rect.y) as f32 - 0.5,
+        ];
+        let _ = write!(
+          json,
+          r#","atlasBounds":{{"left":{},"bottom":{},"right":{},"top":{}}}"#,
+          left, bottom, right, top,
+        );
+        let range = finite(2. * glyph.max_distance)?;
+        let _ = write!(json, r#","distanceRange":{range}"#);
+      }
+      json.push('}');
+    }
+
+    json.push_str(r#"],"kerning":["#);
+    for (i, (&(first, second), &amount)) in layout.kerning.iter().enumerate() {
+      if i > 0 {
+        json.push(',');
+      }
+      let _ = write!(
+        json,
+        r#"{{"unicode1":{},"unicode2":{},"advance":{}}}"#,
+        first as u32,
+        second as u32,
+        em(amount)?,
+      );
+    }
+    json.push_str("]}");
+    Ok(json)
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn to_msdf_atlas_json() {
+    let mut builder = AtlasBuilder::<4>::new(32);
+    let font = builder.add_font("Sans");
+    let key = GlyphKey { font, glyph: 1 };
+    builder.insert_with_offset(key, Field::new([8, 10]), (-2., -9.).into());
+    let atlas = builder.build();
+
+    let mut layout = FontLayout {
+      size: 10.,
+      line_height: 12.,
+      ascender: 8.,
+      descender: 2.,
+      ..Default::default()
+    };
+    layout.chars.insert(
+      'V',
+      CharLayout {
+        glyph: 1,
+        advance: 7.,
+      },
+    );
+    layout.chars.insert(
+      ' ',
+      CharLayout {
+        glyph: 0,
+        advance: 3.,
+      },
+    );
+    layout.kerning.insert(('V', ' '), -1.);
+    let config = RasterConfig {
+      max_distance: 3.,
+      ..Default::default()
+    };
+
+    let json = atlas.to_msdf_atlas_json(font, &layout, config).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(json["atlas"]["type"], "mtsdf");
+    assert_eq!(json["atlas"]["distanceRange"], 6.);
+    assert_eq!(json["atlas"]["height"], 10);
+    assert_eq!(json["metrics"]["lineHeight"], 1.2);
+    assert_eq!(json["metrics"]["descender"], -0.2);
+    let glyphs = json["glyphs"].as_array().unwrap();
+    assert_eq!(glyphs[0]["unicode"], 32);
+    assert!(glyphs[0].get("planeBounds").is_none());
+    let [plane, atlas_bounds] = ["planeBounds", "atlasBounds"].map(|bounds| {
+      ["left", "bottom", "right", "top"]
+        .map(|side| glyphs[1][bounds][side].as_f64().unwrap() as f32)
+    });
+    assert_eq!(plane, [-0.15, -0.05, 0.55, 0.85]);
+    assert_eq!(atlas_bounds, [0.5, 0.5, 7.5, 9.5]);
+    assert_eq!(glyphs[1]["distanceRange"], 10.);
+    assert_eq!(json["kerning"][0]["advance"], -0.1);
+
+    let layout = FontLayout {
+      line_height: f32::NAN,
+      ..layout
+    };
+    let json = atlas.to_msdf_atlas_json(font, &layout, config);
+    assert_eq!(json, Err(AtlasJsonError::NonFinite));
+  }
+
+  #[test]
+  fn unsupported_channels() {
+    let atlas = AtlasBuilder::<2>::new(8).build();
+    let json =
+      atlas.to_msdf_atlas_json(0, &FontLayout::default(), Default::default());
+    assert_eq!(json, Err(AtlasJsonError::Channels(2)));
+  }
+}
@@ -0,0 +1,107 @@
+use crate::*;
+use arrayvec::ArrayVec;
+use shape::{clip::crossing_times, primitives::Segment};
+
+impl Shape {
+  /// Sample the signed distances from the given [`Point`] to the edges of the
+  /// shape along the x & y axes
+  ///
+  /// **Experimental**, requires the `unstable` feature.
+  ///
+  /// The first distance is to the nearest crossing of the horizontal line
+  /// through the point with the shape's edges, and the second to the nearest
+  /// crossing of the vertical line. Both have the sign of
+  /// [`Shape::sample_single_channel`], and are infinite when the line doesn't
+  /// cross the shape at all. Shaders for UI borders & 9-slice scaling can
+  /// stretch each axis independently with these.
+  ///
+  /// ```
+  /// # use rsdf_builder::ShapeBuilder;
+  /// // a 30x10 button, with its top-left corner at the origin
+  /// let button = ShapeBuilder::from_svg_path("M0,0 H30 V10 H0 Z")
+  ///   .unwrap()
+  ///   .build();
+  /// assert_eq!(button.sample_axis_distances((12., 4.)), [12., 4.]);
+  /// // above the button, it's only crossed vertically
+  /// let [x, y] = button.sample_axis_distances((15., -5.));
+  /// assert_eq!([x, y], [f32::NEG_INFINITY, -5.]);
+  /// ```
+  #[must_use]
+  pub fn sample_axis_distances(&self, point: impl Into<Point>) -> [f32; 2] {
+    let point = point.into();
+    let sign = self.sample_single_channel(point).signum();
+    let mut nearest = [f32::INFINITY; 2];
+    for &segment_ref in &self.segments {
+      let segment = self.get_segment(segment_ref);
+      for (axis, nearest) in nearest.iter_mut().enumerate() {
+        // a horizontal line crosses where y is the point's y, and so on
+        let across = 1 - axis;
+        for crossing in crossings(segment, across, coord(point, across)) {
+          *nearest = nearest.min((crossing - coord(point, axis)).abs());
+        }
+      }
+    }
+    nearest.map(|distance| distance * sign)
+  }
+
+  /// Sample the distances along the x & y axes, as in
+  /// [`Shape::sample_axis_distances`], for every pixel on a canvas of the
+  /// given `size`
+  ///
+  /// **Experimental**, requires the `unstable` feature.
+  #[must_use]
+  pub fn sample_axis_field(&self, size: [usize; 2]) -> Field<2> {
+    let mut field = Field::new(size);
+    for y in 0..size[1] {
+      for x in 0..size[0] {
+        let point = Point::from((x as f32, y as f32));
+        field.set([x, y], self.sample_axis_distances(point));
+      }
+    }
+    field
+  }
+}
+
+fn coord(point: Point, axis: usize) -> f32 {
+  [point.x, point.y][axis]
+}
+
+/// Where a segment crosses the line along which the coordinate on `axis` is
+/// `value`, as the other coordinate of each crossing
+fn crossings(segment: Segment, axis: usize, value: f32) -> ArrayVec<f32, 3> {
+  let normal = [Vector::new(1., 0.), Vector::new(0., 1.)][axis];
+  crossing_times(segment, normal, value)
+    .into_iter()
+    .map(|t| coord(segment.sample(t), 1 - axis))
+    .collect()
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn crossings() {
+    // a quarter circle of radius 5 about the origin, from (5, 0) to (0, 5)
+    let arc = elliptical_arc::CentreParam {
+      centre: Point::ZERO,
+      r: 5.,
+      k: 1.,
+      phi: 0.,
+      theta: 0.,
+      delta: std::f32::consts::FRAC_PI_2,
+    }
+    .to_ps();
+    let segment = Segment::EllipticalArc(&arc);
+    let xs = super::crossings(segment, 1, 3.);
+    assert_eq!(xs.len(), 1);
+    float_cmp::assert_approx_eq!(f32, xs[0], 4., epsilon = 1e-5);
+    assert_eq!(super::crossings(segment, 1, -3.).len(), 0);
+
+    let quad = [(0., 0.), (5., 10.), (10., 0.)].map(Point::from);
+    let mut xs = super::crossings(Segment::QuadBezier(&quad), 1, 2.5);
+    xs.sort_by(f32::total_cmp);
+    float_cmp::assert_approx_eq!(f32, xs[0], 1.464_466, epsilon = 1e-4);
+    float_cmp::assert_approx_eq!(f32, xs[1], 8.535_534, epsilon = 1e-4);
+  }
+}
@@ -0,0 +1,659 @@
+use crate::*;
+
+/// Every encoding starts with these bytes
+const MAGIC: &[u8; 4] = b"RSDF";
+const SHAPE_KIND: u8 = b'S';
+const FIELD_KIND: u8 = b'F';
+#[cfg(feature = "atlas")]
+const ATLAS_KIND: u8 = b'A';
+/// The version written into new encodings
+///
+/// Bump this when the layout changes, and keep decoding the older versions,
+/// so that existing caches stay readable.
+const VERSION: u16 = 1;
+
+/// An error found while decoding a [`Shape`], [`Field`] or `Atlas` from its
+/// binary encoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DecodeError {
+  /// The data doesn't start with the rsdf header
+  NotRsdf,
+  /// The data encodes a different kind of value, e.g. a field was decoded as
+  /// a shape
+  WrongKind,
+  /// The data was written by a newer version of rsdf
+  UnsupportedVersion(u16),
+  /// The data ended early
+  Truncated,
+  /// There is data left over after the encoded value
+  TrailingBytes,
+  /// A field has a different number of channels than requested
+  WrongChannels { expected: usize, found: usize },
+  /// A segment kind or colour that doesn't exist, a reference to a point,
+  /// segment, spline or font that is out of range, or a glyph outside of its
+  /// atlas
+  Invalid,
+}
+
+impl std::fmt::Display for DecodeError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      DecodeError::NotRsdf => write!(f, "not an rsdf binary encoding"),
+      DecodeError::WrongKind => {
+        write!(f, "the data encodes a different kind of value")
+      },
+      DecodeError::UnsupportedVersion(version) => {
+        write!(f, "unsupported encoding version {version}")
+      },
+      DecodeError::Truncated => write!(f, "the data ended early"),
+      DecodeError::TrailingBytes => {
+        write!(f, "unexpected data after the encoded value")
+      },
+      DecodeError::WrongChannels { expected, found } => {
+        write!(
+          f,
+          "expected a field with {expected} channels, found {found}"
+        )
+      },
+      DecodeError::Invalid => write!(f, "invalid encoded value"),
+    }
+  }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl Shape {
+  /// Encode the shape in a compact binary format, for fast loading from
+  /// caches
+  ///
+  /// The encoding starts with a versioned header, and numbers are stored in
+  /// little-endian order, so it can be shared between platforms. Decode it
+  /// with [`Shape::from_bytes`].
+  #[must_use]
+  pub fn to_bytes(&self) -> Vec<u8> {
+    let mut writer = Writer::new(SHAPE_KIND);
+    writer.len(self.points.len());
+    for point in &self.points {
+      writer.f32(point.x);
+      writer.f32(point.y);
+    }
+    writer.len(self.segments.len());
+    for segment in &self.segments {
+      writer.0.push(segment_kind_tag(segment.kind));
+      writer.len(segment.points_index);
+    }
+    writer.len(self.splines.len());
+    for spline in &self.splines {
+      writer.len(spline.segments_range.start);
+      writer.len(spline.segments_range.end);
+      writer.0.push(spline.colour as u8);
+    }
+    writer.len(self.contours.len());
+    for contour in &self.contours {
+      writer.len(contour.spline_range.start);
+      writer.len(contour.spline_range.end);
+    }
+    writer.0
+  }
+
+  /// Decode a shape encoded by [`Shape::to_bytes`]
+  ///
+  /// Every reference in the shape is checked, so a decoded shape can be
+  /// sampled without panicking even if the data was corrupted.
+  pub fn from_bytes(bytes: &[u8]) -> Result<Shape, DecodeError> {
+    let mut reader = Reader::new(bytes, SHAPE_KIND)?;
+
+    let points = (0..reader.len()?)
+      .map(|_| Ok(Point::new(reader.f32()?, reader.f32()?)))
+      .collect::<Result<Vec<_>, _>>()?;
+    let segments = (0..reader.len()?)
+      .map(|_| {
+        let kind = segment_kind(reader.u8()?).ok_or(DecodeError::Invalid)?;
+        let segment = SegmentRef {
+          kind,
+          points_index: reader.len()?,
+        };
+        check_segment(&segment, points.len())?;
+        Ok(segment)
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+    let splines = (0..reader.len()?)
+      .map(|_| {
+        let segments_range = reader.range(segments.len())?;
+        let colour =
+          Colour::from_bits(reader.u8()?).ok_or(DecodeError::Invalid)?;
+        Ok(Spline {
+          segments_range,
+          colour,
+        })
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+    let contours = (0..reader.len()?)
+      .map(|_| {
+        let spline_range = reader.range(splines.len())?;
+        Ok(Contour { spline_range })
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+
+    reader.finish()?;
+    Ok(Shape {
+      points,
+      segments,
+      splines,
+      contours,
+    })
+  }
+}
+
+impl<const N: usize> Field<N> {
+  /// Encode the field in a compact binary format, for fast loading from
+  /// caches
+  ///
+  /// The encoding is versioned and little-endian, as for
+  /// [`Shape::to_bytes`]. Decode it with [`Field::from_bytes`].
+  #[must_use]
+  pub fn to_bytes(&self) -> Vec<u8> {
+    let mut writer = Writer::new(FIELD_KIND);
+    writer.field(self);
+    writer.0
+  }
+
+  /// Decode a field encoded by [`Field::to_bytes`]
+  ///
+  /// Fails if the field was encoded with a different number of channels.
+  pub fn from_bytes(bytes: &[u8]) -> Result<Field<N>, DecodeError> {
+    let mut reader = Reader::new(bytes, FIELD_KIND)?;
+    let field = reader.field()?;
+    reader.finish()?;
+    Ok(field)
+  }
+}
+
+#[cfg(feature = "atlas")]
+impl<const N: usize> Atlas<N> {
+  /// Encode the atlas, including its field's unquantized samples and all of
+  /// its metadata, so that it can be loaded again later
+  ///
+  /// The encoding is versioned and little-endian, as for
+  /// [`Shape::to_bytes`]. Decode it with [`Atlas::from_bytes`], e.g. to
+  /// [reuse](AtlasBuilder::reuse) the glyphs of an atlas written by an
+  /// earlier run.
+  #[must_use]
+  pub fn to_bytes(&self) -> Vec<u8> {
+    let mut writer = Writer::new(ATLAS_KIND);
+    writer.field(&self.field);
+    writer.len(self.fonts.len());
+    for font in &self.fonts {
+      writer.str(font);
+    }
+    writer.len(self.glyphs.len());
+    for (key, glyph) in &self.glyphs {
+      writer.len(key.font);
+      writer.len(key.glyph.into());
+      let Rect {
+        x,
+        y,
+        width,
+        height,
+      } = glyph.rect;
+      for value in [x, y, width, height] {
+        writer.len(value);
+      }
+      writer.f32(glyph.offset.x);
+      writer.f32(glyph.offset.y);
+      writer.f32(glyph.max_distance);
+    }
+    writer.len(self.provenance.records.len());
+    for (key, value) in &self.provenance.records {
+      writer.str(key);
+      writer.str(value);
+    }
+    writer.0
+  }
+
+  /// Decode an atlas encoded by [`Atlas::to_bytes`]
+  ///
+  /// Every glyph is checked to be of a known font and to lie within the
+  /// atlas' field, so a decoded atlas can be used without panicking even if
+  /// the data was corrupted.
+  ///
+  /// ```
+  /// # use rsdf_core::*;
+  /// let mut builder = AtlasBuilder::<3>::new(32);
+  /// let font = builder.add_font("Sans");
+  /// builder.insert(GlyphKey { font, glyph: 1 }, Field::new([8, 8]));
+  /// let atlas = builder.build();
+  /// assert_eq!(Atlas::from_bytes(&atlas.to_bytes()), Ok(atlas));
+  /// ```
+  pub fn from_bytes(bytes: &[u8]) -> Result<Atlas<N>, DecodeError> {
+    let mut reader = Reader::new(bytes, ATLAS_KIND)?;
+    let field: Field<N> = reader.field()?;
+    let fonts = (0..reader.len()?)
+      .map(|_| reader.str())
+      .collect::<Result<Vec<_>, _>>()?;
+    let glyphs = (0..reader.len()?)
+      .map(|_| {
+        let font = reader.len()?;
+        let glyph =
+          u16::try_from(reader.len()?).map_err(|_| DecodeError::Invalid)?;
+        let [x, y, width, height] =
+          [reader.len()?, reader.len()?, reader.len()?, reader.len()?];
+        let fits = |start: usize, len: usize, max: usize| {
+          start.checked_add(len).is_some_and(|end| end <= max)
+        };
+        if font >= fonts.len()
+          || !fits(x, width, field.width())
+          || !fits(y, height, field.height())
+        {
+          return Err(DecodeError::Invalid);
+        }
+        let offset = Vector::new(reader.f32()?, reader.f32()?);
+        let max_distance = reader.f32()?;
+        let rect = Rect {
+          x,
+          y,
+          width,
+          height,
+        };
+        Ok((
+          GlyphKey { font, glyph },
+          AtlasGlyph {
+            rect,
+            offset,
+            max_distance,
+          },
+        ))
+      })
+      .collect::<Result<_, _>>()?;
+    let records = (0..reader.len()?)
+      .map(|_| Ok((reader.str()?, reader.str()?)))
+      .collect::<Result<_, _>>()?;
+
+    reader.finish()?;
+    Ok(Atlas {
+      field,
+      fonts,
+      glyphs,
+      provenance: Provenance { records },
+    })
+  }
+}
+
+fn segment_kind_tag(kind: SegmentKind) -> u8 {
+  match kind {
+    SegmentKind::Line => 0,
+    SegmentKind::QuadBezier => 1,
+    SegmentKind::CubicBezier => 2,
+    SegmentKind::EllipticalArc => 3,
+  }
+}
+
+fn segment_kind(tag: u8) -> Option<SegmentKind> {
+  Some(match tag {
+    0 => SegmentKind::Line,
+    1 => SegmentKind::QuadBezier,
+    2 => SegmentKind::CubicBezier,
+    3 => SegmentKind::EllipticalArc,
+    _ => return None,
+  })
+}
+
+/// The number of points read by a segment of the given kind, see
+/// [`Shape::get_segment`]
+fn segment_len(kind: SegmentKind) -> usize {
+  match kind {
+    SegmentKind::Line => 2,
+    SegmentKind::QuadBezier => 3,
+    SegmentKind::CubicBezier | SegmentKind::EllipticalArc => 4,
+  }
+}
+
+/// Check that a segment's points are within a buffer of `points` points
+///
+/// Shared with the serde implementations, so that deserialized shapes are
+/// checked the same way as decoded ones.
+pub(crate) fn check_segment(
+  segment: &SegmentRef,
+  points: usize,
+) -> Result<(), DecodeError> {
+  let end = segment.points_index.checked_add(segment_len(segment.kind));
+  if end.is_none_or(|end| end > points) {
+    return Err(DecodeError::Invalid);
+  }
+  Ok(())
+}
+
+/// Check that a range of indices is within a buffer of length `max`
+pub(crate) fn check_range(
+  range: &std::ops::Range<usize>,
+  max: usize,
+) -> Result<(), DecodeError> {
+  if range.start > range.end || range.end > max {
+    return Err(DecodeError::Invalid);
+  }
+  Ok(())
+}
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+  fn new(kind: u8) -> Self {
+    let mut bytes = MAGIC.to_vec();
+    bytes.push(kind);
+    bytes.extend(VERSION.to_le_bytes());
+    Writer(bytes)
+  }
+
+  /// Lengths and indices are stored as `u32`s, which is plenty for a shape
+  fn len(&mut self, value: usize) {
+    let value = u32::try_from(value).expect("too large to encode");
+    self.0.extend(value.to_le_bytes());
+  }
+
+  fn f32(&mut self, value: f32) {
+    self.0.extend(value.to_le_bytes());
+  }
+
+  fn field<const N: usize>(&mut self, field: &Field<N>) {
+    self.len(N);
+    self.len(field.width());
+    self.len(field.height());
+    self.0.reserve(field.samples().len() * N * 4);
+    for sample in field.samples() {
+      for &channel in sample {
+        self.f32(channel);
+      }
+    }
+  }
+
+  /// Strings are stored as their length, followed by their UTF-8 bytes
+  #[cfg(feature = "atlas")]
+  fn str(&mut self, value: &str) {
+    self.len(value.len());
+    self.0.extend(value.as_bytes());
+  }
+}
+
+struct Reader<'a> {
+  bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+  /// Check the header, which must be for the given kind of value
+  fn new(bytes: &'a [u8], kind: u8) -> Result<Self, DecodeError> {
+    let mut reader = Reader { bytes };
+    if reader.take(4).ok() != Some(MAGIC.as_slice()) {
+      return Err(DecodeError::NotRsdf);
+    }
+    if reader.u8()? != kind {
+      return Err(DecodeError::WrongKind);
+    }
+    let version = u16::from_le_bytes(reader.array()?);
+    if version != VERSION {
+      return Err(DecodeError::UnsupportedVersion(version));
+    }
+    Ok(reader)
+  }
+
+  fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+    if self.bytes.len() < len {
+      return Err(DecodeError::Truncated);
+    }
+    let (taken, rest) = self.bytes.split_at(len);
+    self.bytes = rest;
+    Ok(taken)
+  }
+
+  fn array<const L: usize>(&mut self) -> Result<[u8; L], DecodeError> {
+    Ok(self.take(L)?.try_into().unwrap())
+  }
+
+  fn u8(&mut self) -> Result<u8, DecodeError> {
+    Ok(self.take(1)?[0])
+  }
+
+  fn len(&mut self) -> Result<usize, DecodeError> {
+    Ok(u32::from_le_bytes(self.array()?) as usize)
+  }
+
+  fn f32(&mut self) -> Result<f32, DecodeError> {
+    Ok(f32::from_le_bytes(self.array()?))
+  }
+
+  /// A field's channel count & size, followed by its samples
+  fn field<const N: usize>(&mut self) -> Result<Field<N>, DecodeError> {
+    let channels = self.len()?;
+    if channels != N {
+      return Err(DecodeError::WrongChannels {
+        expected: N,
+        found: channels,
+      });
+    }
+    let width = self.len()?;
+    let height = self.len()?;
+    // check the length up front, rather than trusting the size to allocate
+    let expected = width
+      .checked_mul(height)
+      .and_then(|pixels| pixels.checked_mul(N * 4))
+      .ok_or(DecodeError::Invalid)?;
+    if self.bytes.len() < expected {
+      return Err(DecodeError::Truncated);
+    }
+
+    let mut samples = Vec::with_capacity(width * height);
+    for _ in 0..width * height {
+      let mut sample = [0.; N];
+      for channel in &mut sample {
+        *channel = self.f32()?;
+      }
+      samples.push(sample);
+    }
+    Ok(Field::from_samples([width, height], samples))
+  }
+
+  #[cfg(feature = "atlas")]
+  fn str(&mut self) -> Result<String, DecodeError> {
+    let len = self.len()?;
+    let bytes = self.take(len)?;
+    String::from_utf8(bytes.to_vec()).map_err(|_| DecodeError::Invalid)
+  }
+
+  /// A range of indices into a buffer of length `max`
+  fn range(
+    &mut self,
+    max: usize,
+  ) -> Result<std::ops::Range<usize>, DecodeError> {
+    let range = self.len()?..self.len()?;
+    check_range(&range, max)?;
+    Ok(range)
+  }
+
+  fn finish(self) -> Result<(), DecodeError> {
+    if self.bytes.is_empty() {
+      Ok(())
+    } else {
+      Err(DecodeError::TrailingBytes)
+    }
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  /// A line and an elliptical arc, in two splines
+  fn shape() -> Shape {
+    Shape {
+      points: vec![
+        (0., 0.).into(),
+        (2., 0.).into(),
+        (1., 0.).into(),
+        (1., 1.).into(),
+        (0., 0.).into(),
+        (0., std::f32::consts::PI).into(),
+        (0., 0.).into(),
+      ],
+      segments: vec![
+        SegmentRef {
+          kind: SegmentKind::Line,
+          points_index: 0,
+        },
+        SegmentRef {
+          kind: SegmentKind::EllipticalArc,
+          points_index: 2,
+        },
+      ],
+      splines: vec![
+        Spline {
+          segments_range: 0..1,
+          colour: Magenta,
+        },
+        Spline {
+          segments_range: 1..2,
+          colour: Yellow,
+        },
+      ],
+      contours: vec![Contour { spline_range: 0..2 }],
+    }
+  }
+
+  #[test]
+  fn round_trip() {
+    let shape = shape();
+    let decoded = Shape::from_bytes(&shape.to_bytes()).unwrap();
+    assert_eq!(decoded.checksum(), shape.checksum());
+
+    let field = Field::from_samples(
+      [3, 2],
+      (0..6).map(|i| [i as f32, -0.5, f32::INFINITY]).collect(),
+    );
+    assert_eq!(Field::<3>::from_bytes(&field.to_bytes()), Ok(field.clone()));
+    assert_eq!(
+      Field::<1>::from_bytes(&field.to_bytes()),
+      Err(DecodeError::WrongChannels {
+        expected: 1,
+        found: 3
+      })
+    );
+    assert_eq!(
+      Field::<3>::from_bytes(&Field::<3>::new([0, 0]).to_bytes()),
+      Ok(Field::new([0, 0]))
+    );
+  }
+
+  /// Encodings written by version 1, which must keep decoding to the same
+  /// values
+  #[test]
+  fn version_1() {
+    #[rustfmt::skip]
+    let shape_bytes: &[u8] = &[
+      b'R', b'S', b'D', b'F', b'S', 1, 0,
+      // points
+      7, 0, 0, 0,
+      0, 0, 0, 0, 0, 0, 0, 0,
+      0, 0, 0, 64, 0, 0, 0, 0,
+      0, 0, 128, 63, 0, 0, 0, 0,
+      0, 0, 128, 63, 0, 0, 128, 63,
+      0, 0, 0, 0, 0, 0, 0, 0,
+      0, 0, 0, 0, 219, 15, 73, 64,
+      0, 0, 0, 0, 0, 0, 0, 0,
+      // segments
+      2, 0, 0, 0,
+      0, 0, 0, 0, 0,
+      3, 2, 0, 0, 0,
+      // splines
+      2, 0, 0, 0,
+      0, 0, 0, 0, 1, 0, 0, 0, 5,
+      1, 0, 0, 0, 2, 0, 0, 0, 3,
+      // contours
+      1, 0, 0, 0,
+      0, 0, 0, 0, 2, 0, 0, 0,
+    ];
+    #[rustfmt::skip]
+    let field_bytes: &[u8] = &[
+      b'R', b'S', b'D', b'F', b'F', 1, 0,
+      // channels, width, height
+      1, 0, 0, 0, 2, 0, 0, 0, 1, 0, 0, 0,
+      0, 0, 0, 63, 0, 0, 128, 191,
+    ];
+
+    let decoded = Shape::from_bytes(shape_bytes).unwrap();
+    assert_eq!(decoded.checksum(), shape().checksum());
+    assert_eq!(
+      Field::<1>::from_bytes(field_bytes),
+      Ok(Field::from_samples([2, 1], vec![[0.5], [-1.]]))
+    );
+    // while the version is unchanged, the encoding shouldn't change either
+    assert_eq!(shape().to_bytes(), shape_bytes);
+  }
+
+  #[test]
+  fn errors() {
+    let bytes = shape().to_bytes();
+    let error = |bytes: &[u8]| Shape::from_bytes(bytes).err();
+    assert_eq!(error(b"nope"), Some(DecodeError::NotRsdf));
+    assert_eq!(Field::<1>::from_bytes(&bytes), Err(DecodeError::WrongKind));
+    let mut newer = bytes.clone();
+    newer[5] = 2;
+    assert_eq!(error(&newer), Some(DecodeError::UnsupportedVersion(2)));
+    for len in [7, 20, bytes.len() - 1] {
+      assert_eq!(error(&bytes[..len]), Some(DecodeError::Truncated));
+    }
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert_eq!(error(&trailing), Some(DecodeError::TrailingBytes));
+
+    // the arc would read past the end of the points
+    let mut shape = shape();
+    shape.segments[1].points_index = 4;
+    assert_eq!(error(&shape.to_bytes()), Some(DecodeError::Invalid));
+
+    let mut field = Field::<1>::new([2, 2]).to_bytes();
+    field.pop();
+    assert_eq!(Field::<1>::from_bytes(&field), Err(DecodeError::Truncated));
+  }
+
+  #[cfg(feature = "atlas")]
+  #[test]
+  fn atlas() {
+    let mut builder = AtlasBuilder::<1>::new(16)
+      .provenance(Provenance::default().record("pipeline", "test"));
+    let font = builder.add_font("Sans");
+    let field = Field::from_samples([2, 1], vec![[0.5], [-1.]]);
+    builder.insert_with_range(
+      GlyphKey { font, glyph: 7 },
+      field,
+      Vector::new(-1., 2.),
+      3.,
+    );
+    let atlas = builder.build();
+    let bytes = atlas.to_bytes();
+    assert_eq!(Atlas::from_bytes(&bytes), Ok(atlas.clone()));
+    assert_eq!(
+      Atlas::<3>::from_bytes(&bytes),
+      Err(DecodeError::WrongChannels {
+        expected: 3,
+        found: 1
+      })
+    );
+    assert_eq!(
+      Atlas::<1>::from_bytes(&bytes[..bytes.len() - 1]),
+      Err(DecodeError::Truncated)
+    );
+
+    // a glyph outside of the field, or of an unknown font
+    let mut outside = atlas.clone();
+    outside.glyphs.values_mut().next().unwrap().rect.x = 15;
+    assert_eq!(
+      Atlas::<1>::from_bytes(&outside.to_bytes()),
+      Err(DecodeError::Invalid)
+    );
+    let mut unknown = atlas;
+    unknown.fonts.clear();
+    assert_eq!(
+      Atlas::<1>::from_bytes(&unknown.to_bytes()),
+      Err(DecodeError::Invalid)
+    );
+  }
+}
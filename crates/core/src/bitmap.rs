@@ -0,0 +1,79 @@
+/// A grid of pixels with `N` channels of type `T`, to rasterize into
+///
+/// Pixels are stored in row-major order as contiguous `[T; N]` arrays, so
+/// [`pixels`](Self::pixels) can be uploaded as a texture as is, e.g. with
+/// `as_flattened`. The bitmap is independent of any image format;
+/// [`Bitmap::write_png`] & [`Image`](crate::Image) encode it as a PNG with
+/// the `png` feature.
+///
+/// Rows are stored top-down, as they're set. Rasterize with
+/// [`RasterConfig::flip_y`](crate::RasterConfig::flip_y) for consumers that
+/// expect a bottom-left origin.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bitmap<T, const N: usize> {
+  pixels: Vec<[T; N]>,
+  pub width: usize,
+  pub height: usize,
+}
+
+impl<T: Copy + Default, const N: usize> Bitmap<T, N> {
+  /// Create a bitmap of the given `size`, with every channel set to the
+  /// default, e.g. `0`
+  #[must_use]
+  pub fn new(size: [usize; 2]) -> Self {
+    let [width, height] = size;
+    Self {
+      pixels: vec![[T::default(); N]; width * height],
+      width,
+      height,
+    }
+  }
+
+  pub fn size(&self) -> [usize; 2] {
+    [self.width, self.height]
+  }
+
+  /// Set the pixel at the coordinates to the given value
+  #[inline]
+  pub fn set_pixel(&mut self, coords: [usize; 2], val: [T; N]) {
+    let index = self.index(coords);
+    self.pixels[index] = val;
+  }
+
+  /// The pixel at the coordinates, as set by [`Bitmap::set_pixel`]
+  #[inline]
+  pub fn get_pixel(&self, coords: [usize; 2]) -> [T; N] {
+    self.pixels[self.index(coords)]
+  }
+
+  /// The pixels in row-major order
+  pub fn pixels(&self) -> &[[T; N]] {
+    &self.pixels
+  }
+
+  pub fn into_pixels(self) -> Vec<[T; N]> {
+    self.pixels
+  }
+
+  fn index(&self, coords: [usize; 2]) -> usize {
+    debug_assert!(
+      coords[0] < self.width && coords[1] < self.height,
+      "coordinates given were outside the dimensions of the image"
+    );
+    coords[1] * self.width + coords[0]
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn set_pixel() {
+    let mut bitmap = Bitmap::<u16, 2>::new([2, 3]);
+    bitmap.set_pixel([1, 2], [1, 2]);
+    assert_eq!(bitmap.get_pixel([1, 2]), [1, 2]);
+    assert_eq!(bitmap.pixels()[5], [1, 2]);
+    assert_eq!(bitmap.into_pixels().iter().flatten().sum::<u16>(), 3);
+  }
+}
@@ -0,0 +1,302 @@
+use crate::*;
+use std::fmt::Write;
+
+impl<const N: usize> Atlas<N> {
+  /// Describe the glyphs of one of the atlas' fonts in AngelCode's BMFont
+  /// text format, for the many renderers that consume `.fnt` files
+  ///
+  /// `page` is the file name the atlas' field is saved under. Characters of
+  /// the `layout` whose glyphs aren't in the atlas, such as spaces, are
+  /// described with an empty rect, so that their advance is kept. Values
+  /// are rounded to whole pixels, as the format expects. Each character
+  /// reads all channels of the atlas, as multi-channel renderers expect.
+  ///
+  /// Each character also has a `distanceRange` attribute, which BMFont
+  /// doesn't define, holding the width of the glyph's range in pixels,
+  /// twice its [`AtlasGlyph::max_distance`]. Parsers ignore attributes they
+  /// don't know, while shaders of atlases mixing ranges can look it up.
+  ///
+  /// ```
+  /// # use rsdf_core::*;
+  /// let mut builder = AtlasBuilder::<3>::new(64);
+  /// let font = builder.add_font("Sans");
+  /// let offset = Vector::new(-4., -14.);
+  /// builder.insert_with_offset(GlyphKey { font, glyph: 1 }, Field::new([18, 20]), offset);
+  /// let atlas = builder.build();
+  ///
+  /// let mut layout = FontLayout {
+  ///   size: 16.,
+  ///   line_height: 20.,
+  ///   ascender: 15.,
+  ///   descender: 5.,
+  ///   ..Default::default()
+  /// };
+  /// layout.chars.insert('A', CharLayout { glyph: 1, advance: 11. });
+  /// layout.chars.insert(' ', CharLayout { glyph: 0, advance: 4. });
+  /// let fnt = atlas.to_bmfont(font, &layout, "atlas.png");
+  /// assert!(fnt.contains(
+  ///   "char id=65 x=0 y=0 width=18 height=20 xoffset=-4 yoffset=1 \
+  ///    xadvance=11 page=0 chnl=15"
+  /// ));
+  /// ```
+  #[must_use]
+  pub fn to_bmfont(
+    &self,
+    font: usize,
+    layout: &FontLayout,
+    page: &str,
+  ) -> String {
+    let mut fnt = String::new();
+    for (tag, attributes) in self.bmfont_lines(font, layout, page) {
+      let _ = write!(fnt, "{tag}");
+      for (name, value) in attributes {
+        let _ = match value {
+          Value::Int(value) => write!(fnt, " {name}={value}"),
+          Value::Float(value) => write!(fnt, " {name}={value}"),
+          Value::Text(value) => write!(fnt, " {name}=\"{value}\""),
+        };
+      }
+      fnt.push('\n');
+    }
+    fnt
+  }
+
+  /// Describe the glyphs of one of the atlas' fonts in BMFont's XML format,
+  /// as [`Atlas::to_bmfont`] does in its text format
+  #[must_use]
+  pub fn to_bmfont_xml(
+    &self,
+    font: usize,
+    layout: &FontLayout,
+    page: &str,
+  ) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\"?>\n<font>\n");
+    let mut open = None;
+    for (tag, attributes) in self.bmfont_lines(font, layout, page) {
+      // the repeated elements are grouped under a parent element
+      let group = match tag {
+        "page" => Some("pages"),
+        "char" => Some("chars"),
+        "kerning" => Some("kernings"),
+        _ => None,
+      };
+      if let Some(group) = open.filter(|&open| Some(open) != group) {
+        let _ = writeln!(xml, "  </{group}>");
+        open = None;
+      }
+      let indent = match group {
+        Some(_) => "    ",
+        None => "  ",
+      };
+      let element = match tag {
+        "chars" | "kernings" => {
+          open = Some(tag);
+          let _ = write!(xml, "  <{tag}");
+          for (name, value) in attributes {
+            let _ = write!(xml, " {name}=\"{}\"", value.to_xml());
+          }
+          xml.push_str(">\n");
+          continue;
+        },
+        "page" if open.is_none() => {
+          open = Some("pages");
+          xml.push_str("  <pages>\n");
+          tag
+        },
+        tag => tag,
+      };
+      let _ = write!(xml, "{indent}<{element}");
+      for (name, value) in attributes {
+        let _ = write!(xml, " {name}=\"{}\"", value.to_xml());
+      }
+      xml.push_str("/>\n");
+    }
+    if let Some(group) = open {
+      let _ = writeln!(xml, "  </{group}>");
+    }
+    xml.push_str("</font>\n");
+    xml
+  }
+
+  /// The tags of the lines of a BMFont descriptor, with their attributes
+  fn bmfont_lines(
+    &self,
+    font: usize,
+    layout: &FontLayout,
+    page: &str,
+  ) -> Vec<(&'static str, Vec<(&'static str, Value)>)> {
+    let int = |value: f32| Value::Int(value.round() as i64);
+    let [width, height] = self.field.size();
+    let mut lines = vec![
+      (
+        "info",
+        vec![
+          ("face", Value::Text(self.fonts[font].clone())),
+          ("size", int(layout.size)),
+          ("bold", Value::Int(0)),
+          ("italic", Value::Int(0)),
+          ("charset", Value::Text(String::new())),
+          ("unicode", Value::Int(1)),
+          ("stretchH", Value::Int(100)),
+          ("smooth", Value::Int(1)),
+          ("aa", Value::Int(1)),
+          ("padding", Value::Text("0,0,0,0".into())),
+          ("spacing", Value::Text("0,0".into())),
+        ],
+      ),
+      (
+        "common",
+        vec![
+          ("lineHeight", int(layout.line_height)),
+          ("base", int(layout.ascender)),
+          ("scaleW", Value::Int(width as i64)),
+          ("scaleH", Value::Int(height as i64)),
+          ("pages", Value::Int(1)),
+          ("packed", Value::Int(0)),
+        ],
+      ),
+      (
+        "page",
+        vec![
+          ("id", Value::Int(0)),
+          ("file", Value::Text(page.to_string())),
+        ],
+      ),
+      (
+        "chars",
+        vec![("count", Value::Int(layout.chars.len() as i64))],
+      ),
+    ];
+
+    for (&char, char_layout) in &layout.chars {
+      let key = GlyphKey {
+        font,
+        glyph: char_layout.glyph,
+      };
+      let (rect, offset, max_distance) = match self.get(key) {
+        Some(glyph) => (glyph.rect, glyph.offset, glyph.max_distance),
+        None => (Rect::default(), Vector::ZERO, 0.),
+      };
+      lines.push((
+        "char",
+        vec![
+          ("id", Value::Int(char as i64)),
+          ("x", Value::Int(rect.x as i64)),
+          ("y", Value::Int(rect.y as i64)),
+          ("width", Value::Int(rect.width as i64)),
+          ("height", Value::Int(rect.height as i64)),
+          ("xoffset", int(offset.x)),
+          // from the top of the line, rather than the baseline
+          ("yoffset", int(layout.ascender + offset.y)),
+          ("xadvance", int(char_layout.advance)),
+          ("page", Value::Int(0)),
+          ("chnl", Value::Int(15)),
+          ("distanceRange", Value::Float(2. * max_distance)),
+        ],
+      ));
+    }
+
+    if !layout.kerning.is_empty() {
+      let count = layout.kerning.len() as i64;
+      lines.push(("kernings", vec![("count", Value::Int(count))]));
+      for (&(first, second), &amount) in &layout.kerning {
+        lines.push((
+          "kerning",
+          vec![
+            ("first", Value::Int(first as i64)),
+            ("second", Value::Int(second as i64)),
+            ("amount", int(amount)),
+          ],
+        ));
+      }
+    }
+    lines
+  }
+}
+
+/// The value of an attribute of a BMFont descriptor
+enum Value {
+  Int(i64),
+  Float(f32),
+  /// Quoted in the text format
+  Text(String),
+}
+
+impl Value {
+  fn to_xml(&self) -> String {
+    match self {
+      Value::Int(value) => value.to_string(),
+      Value::Float(value) => value.to_string(),
+      Value::Text(value) => value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;"),
+    }
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn to_bmfont() {
+    let mut builder = AtlasBuilder::<1>::new(32);
+    let font = builder.add_font("A & B");
+    let key = |glyph| GlyphKey { font, glyph };
+    builder.insert_with_offset(key(1), Field::new([8, 10]), (-2., -9.).into());
+    let offset = Vector::new(-1., -5.);
+    builder.insert_with_range(key(2), Field::new([6, 6]), offset, 1.5);
+    let atlas = builder.build();
+
+    let mut layout = FontLayout {
+      size: 12.,
+      line_height: 14.,
+      ascender: 11.,
+      descender: 3.,
+      ..Default::default()
+    };
+    layout.chars.insert(
+      'V',
+      CharLayout {
+        glyph: 1,
+        advance: 7.4,
+      },
+    );
+    layout.chars.insert(
+      'a',
+      CharLayout {
+        glyph: 2,
+        advance: 5.6,
+      },
+    );
+    layout.kerning.insert(('V', 'a'), -0.8);
+
+    let fnt = atlas.to_bmfont(font, &layout, "atlas.png");
+    let lines: Vec<_> = fnt.lines().collect();
+    assert_eq!(lines.len(), 8);
+    assert!(lines[0].starts_with("info face=\"A & B\" size=12"));
+    assert_eq!(
+      lines[1],
+      "common lineHeight=14 base=11 scaleW=32 scaleH=10 pages=1 packed=0"
+    );
+    assert_eq!(lines[2], "page id=0 file=\"atlas.png\"");
+    assert_eq!(lines[3], "chars count=2");
+    assert_eq!(
+      lines[5],
+      "char id=97 x=9 y=0 width=6 height=6 xoffset=-1 yoffset=6 xadvance=6 \
+       page=0 chnl=15 distanceRange=3"
+    );
+    assert!(lines[4].ends_with(" distanceRange=10"));
+    assert_eq!(lines[7], "kerning first=86 second=97 amount=-1");
+
+    let xml = atlas.to_bmfont_xml(font, &layout, "atlas.png");
+    assert!(xml.contains("<info face=\"A &amp; B\""));
+    assert!(xml.contains(
+      "  <pages>\n    <page id=\"0\" file=\"atlas.png\"/>\n  </pages>\n"
+    ));
+    assert!(xml.contains("  <chars count=\"2\">\n    <char id=\"86\""));
+    assert!(xml.ends_with("  </kernings>\n</font>\n"));
+  }
+}
@@ -0,0 +1,173 @@
+use crate::*;
+
+const MAGIC: &[u8; 4] = b"DDS ";
+/// `DDSD_CAPS | DDSD_HEIGHT | DDSD_WIDTH | DDSD_PITCH | DDSD_PIXELFORMAT`
+const HEADER_FLAGS: u32 = 0x1 | 0x2 | 0x4 | 0x8 | 0x1000;
+/// `DDPF_FOURCC`, with the format given by the DX10 header
+const PIXEL_FORMAT_FLAGS: u32 = 0x4;
+/// `DDSCAPS_TEXTURE`
+const CAPS: u32 = 0x1000;
+const TEXTURE_2D: u32 = 3;
+
+impl<const N: usize> Field<N> {
+  /// Encode the field as a DDS texture, quantized as
+  /// [`Shape::rasterize_into`] would by the [`RasterConfig::quantization`]
+  /// & [`RasterConfig::max_distance`]
+  ///
+  /// The texture has a DX10 header, with an `R`, `RG` or `RGBA` DXGI format
+  /// of the quantization, e.g. `DXGI_FORMAT_R8G8B8A8_UNORM` for a
+  /// multi-channel field in [`Quantization::U8`]. DXGI has no 3 channel
+  /// formats besides `DXGI_FORMAT_R32G32B32_FLOAT`, so multi-channel fields
+  /// are otherwise padded with an opaque alpha channel. Game pipelines that
+  /// expect font textures as DDS can load the texture of a glyph, or an
+  /// [`Atlas`]'s field, directly.
+  ///
+  /// ```
+  /// # use rsdf_core::*;
+  /// let field = Field::from_samples([2, 1], vec![[-5., 0., 5.]; 2]);
+  /// let dds = field.to_dds(RasterConfig::default());
+  /// assert!(dds.starts_with(b"DDS "));
+  /// // the headers, followed by 2 RGBA pixels
+  /// assert_eq!(dds.len(), 4 + 124 + 20 + 2 * 4);
+  /// ```
+  #[must_use]
+  pub fn to_dds(&self, config: RasterConfig) -> Vec<u8> {
+    dds::<N>(self.size(), config, self.quantized_le(config))
+  }
+}
+
+#[cfg(feature = "atlas")]
+impl<const N: usize> Atlas<N> {
+  /// Encode the atlas' field as a DDS texture, as [`Field::to_dds`] does,
+  /// with each glyph quantized by its own [`AtlasGlyph::max_distance`]
+  ///
+  /// The `config` quantizes the background between glyphs.
+  #[must_use]
+  pub fn to_dds(&self, config: RasterConfig) -> Vec<u8> {
+    dds::<N>(self.field.size(), config, self.quantized_le(config))
+  }
+}
+
+/// A DDS texture of the given size holding the texels
+fn dds<const N: usize>(
+  [width, height]: [usize; 2],
+  config: RasterConfig,
+  texels: Vec<u8>,
+) -> Vec<u8> {
+  assert!((1..=4).contains(&N), "DDS textures have 1 to 4 channels");
+  let quantization = config.quantization;
+  let channel_size = quantization.channel_size();
+  let channels = match (N, quantization) {
+    (3, Quantization::F32) => 3,
+    (3, _) => 4,
+    _ => N,
+  };
+  let pitch = width * channels * channel_size;
+
+  let mut bytes = Vec::with_capacity(148 + height * pitch);
+  bytes.extend_from_slice(MAGIC);
+  let header = [
+    124,
+    HEADER_FLAGS,
+    height as u32,
+    width as u32,
+    pitch as u32,
+    // depth & mip levels
+    0,
+    1,
+  ];
+  for value in header {
+    bytes.extend_from_slice(&value.to_le_bytes());
+  }
+  bytes.resize(bytes.len() + 11 * 4, 0);
+  let pixel_format = [32, PIXEL_FORMAT_FLAGS, u32::from_le_bytes(*b"DX10")];
+  for value in pixel_format {
+    bytes.extend_from_slice(&value.to_le_bytes());
+  }
+  // the bit count & masks are unused with a four CC
+  bytes.resize(bytes.len() + 5 * 4, 0);
+  bytes.extend_from_slice(&CAPS.to_le_bytes());
+  // the rest of the caps & the reserved field
+  bytes.resize(bytes.len() + 4 * 4, 0);
+  let header_dx10 = [
+    dxgi_format(quantization, channels),
+    TEXTURE_2D,
+    0,
+    // array size
+    1,
+    0,
+  ];
+  for value in header_dx10 {
+    bytes.extend_from_slice(&value.to_le_bytes());
+  }
+
+  if channels == N {
+    bytes.extend_from_slice(&texels);
+  } else {
+    let mut alpha = vec![0; channel_size];
+    quantization.write_coverage(1., &mut alpha);
+    if cfg!(target_endian = "big") {
+      alpha.reverse();
+    }
+    for texel in texels.chunks_exact(N * channel_size) {
+      bytes.extend_from_slice(texel);
+      bytes.extend_from_slice(&alpha);
+    }
+  }
+  bytes
+}
+
+/// The `DXGI_FORMAT` of a texture with the given number of channels
+fn dxgi_format(quantization: Quantization, channels: usize) -> u32 {
+  match (quantization, channels) {
+    (Quantization::U8, 1) => 61,
+    (Quantization::U8, 2) => 49,
+    (Quantization::U8, _) => 28,
+    (Quantization::U16, 1) => 56,
+    (Quantization::U16, 2) => 35,
+    (Quantization::U16, _) => 11,
+    (Quantization::F16, 1) => 54,
+    (Quantization::F16, 2) => 34,
+    (Quantization::F16, _) => 10,
+    (Quantization::F32, 1) => 41,
+    (Quantization::F32, 2) => 16,
+    (Quantization::F32, 3) => 6,
+    (Quantization::F32, _) => 2,
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+  }
+
+  #[test]
+  fn to_dds() {
+    let samples = (0..6).map(|i| [i as f32 - 3., 1., -1.]).collect();
+    let field = Field::from_samples([3, 2], samples);
+    let config = RasterConfig::default();
+    let dds = field.to_dds(config);
+    // height, width & pitch
+    assert_eq!([12, 16, 20].map(|offset| u32_at(&dds, offset)), [2, 3, 12]);
+    assert_eq!(&dds[84..88], b"DX10");
+    assert_eq!(u32_at(&dds, 128), 28);
+    let texels = field.quantized_le(config);
+    assert_eq!(dds[148..151], texels[..3]);
+    assert_eq!(dds[151], u8::MAX);
+    assert_eq!(dds.len(), 148 + 6 * 4);
+
+    let config = RasterConfig {
+      quantization: Quantization::F32,
+      ..config
+    };
+    let dds = field.to_dds(config);
+    assert_eq!(u32_at(&dds, 128), 6);
+    assert_eq!(dds[148..], field.quantized_le(config));
+
+    let single = Field::from_samples([1, 1], vec![[0.]]);
+    assert_eq!(u32_at(&single.to_dds(RasterConfig::default()), 128), 61);
+  }
+}
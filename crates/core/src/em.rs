@@ -0,0 +1,145 @@
+use crate::*;
+
+/// Maps a shape given in font units onto the pixels of a canvas
+///
+/// Used by [`Shape::sample_em_field`] to produce fields whose distances are
+/// measured in em, so that one field per glyph can be drawn at any size,
+/// with the scale only applied in the shader.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EmScale {
+  /// The size of the em square, in font units
+  pub units_per_em: f32,
+  /// The size of the em square, in pixels of the canvas
+  pub pixels_per_em: f32,
+  /// The point, in font units, sampled by the top-left pixel
+  pub origin: Point,
+}
+
+impl EmScale {
+  /// The largest distance, in em, that fits in the fixed point
+  /// [`Quantization`]s at this scale
+  ///
+  /// This is the [`RasterConfig::max_distance`] the field is sampled with,
+  /// in em; the shader needs it to map quantized channels back to em.
+  pub fn max_distance(&self, config: RasterConfig) -> f32 {
+    config.max_distance / self.pixels_per_em
+  }
+
+  /// Move the [`origin`](Self::origin) so that the font's origin `(0, 0)`
+  /// lands on a whole pixel of the canvas
+  ///
+  /// Returns the snapped scale, and the fractional offset in pixels that was
+  /// removed, which the renderer should add back when placing the glyph.
+  /// Glyphs packed into an atlas then sit on whole texels, so renderers that
+  /// assume texel-aligned rectangles don't blur them.
+  #[must_use]
+  pub fn gridfit(&self) -> (EmScale, Vector) {
+    let scale = self.pixels_per_unit();
+    // where the font's origin lands on the canvas, in pixels
+    let position = self.origin.as_vector() * -scale;
+    let whole = Vector::new(position.x.floor(), position.y.floor());
+    let snapped = EmScale {
+      origin: (whole * (-1. / scale)).as_point(),
+      ..*self
+    };
+    (snapped, position - whole)
+  }
+
+  /// The size of one font unit, in pixels of the canvas
+  fn pixels_per_unit(&self) -> f32 {
+    self.pixels_per_em / self.units_per_em
+  }
+}
+
+impl Shape {
+  /// Sample `N` channels of a shape given in font units for every pixel on a
+  /// canvas of the given `size`, with the distances measured in em
+  ///
+  /// The shape is placed on the canvas according to the [`EmScale`], and
+  /// sampled as in [`Shape::sample_field`].
+  #[must_use]
+  pub fn sample_em_field<const N: usize>(
+    &self,
+    size: [usize; 2],
+    em: EmScale,
+    config: RasterConfig,
+  ) -> Field<N>
+  where
+    Self: SampleChannels<N>,
+  {
+    let scale = em.pixels_per_unit();
+    let mut shape = self.clone();
+    shape.scale_translate(scale, em.origin.as_vector() * -scale);
+
+    let mut field = shape.sample_field(size, config);
+    for sample in field.samples_mut() {
+      *sample = sample.map(|distance| distance / em.pixels_per_em);
+    }
+    field
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+  use float_cmp::assert_approx_eq;
+
+  #[test]
+  fn sample_em_field() {
+    // a square covering the middle half of a 2048 unit em square
+    let shape = Shape::rectangle((512., 512.), (1536., 1536.), &[White]);
+    let em = |pixels_per_em| EmScale {
+      units_per_em: 2048.,
+      pixels_per_em,
+      origin: Point::ZERO,
+    };
+    let config = RasterConfig::default();
+    let small: Field<1> = shape.sample_em_field([16, 16], em(16.), config);
+    let large: Field<1> = shape.sample_em_field([64, 64], em(64.), config);
+
+    // the same points of the em square have the same distances at any size
+    for [x, y] in [[8, 8], [2, 8], [4, 12], [0, 0]] {
+      let [a] = small.get([x, y]);
+      let [b] = large.get([x * 4, y * 4]);
+      assert_approx_eq!(f32, a, b, epsilon = 0.0001);
+    }
+    assert_approx_eq!(f32, small.get([8, 8])[0], 0.25);
+    assert_approx_eq!(f32, small.get([8, 0])[0], -0.25);
+    assert_eq!(em(16.).max_distance(config), config.max_distance / 16.);
+
+    // the origin shifts the em square across the canvas
+    let shifted: Field<1> = shape.sample_em_field(
+      [16, 16],
+      EmScale {
+        origin: Point::new(512., 0.),
+        ..em(16.)
+      },
+      config,
+    );
+    assert_eq!(shifted.get([4, 8]), small.get([8, 8]));
+  }
+
+  #[test]
+  fn gridfit() {
+    let em = EmScale {
+      units_per_em: 1000.,
+      pixels_per_em: 32.,
+      origin: Point::new(-100., -50.),
+    };
+    let (snapped, fraction) = em.gridfit();
+    assert_approx_eq!(Vector, fraction, Vector::new(0.2, 0.6));
+    assert_approx_eq!(Point, snapped.origin, Point::new(-93.75, -31.25));
+    assert_eq!(snapped.pixels_per_em, em.pixels_per_em);
+    // the font's origin now lands on pixel (3, 1)
+    let position = snapped.origin.as_vector() * -(32. / 1000.);
+    assert_approx_eq!(Vector, position, Vector::new(3., 1.));
+
+    // negative positions round down too
+    let (_, fraction) = EmScale {
+      origin: Point::new(100., 0.),
+      ..em
+    }
+    .gridfit();
+    assert_approx_eq!(Vector, fraction, Vector::new(0.8, 0.));
+  }
+}
@@ -0,0 +1,352 @@
+use crate::*;
+
+/// A grid of distance samples with `N` channels per pixel
+///
+/// Samples are stored in row-major order. The number of channels is part of
+/// the type, so a single channel field can't be mistaken for a multi-channel
+/// one, and per-pixel loops over the channels are unrolled at compile time.
+/// The samples are contiguous `[f32; N]` arrays, so
+/// [`samples`](Self::samples) can be uploaded as a float texture as is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field<const N: usize> {
+  width: usize,
+  height: usize,
+  samples: Vec<[f32; N]>,
+}
+
+impl<const N: usize> Field<N> {
+  /// Create a field of the given `size`, with every channel set to `0`
+  #[must_use]
+  pub fn new(size: [usize; 2]) -> Self {
+    let [width, height] = size;
+    Field {
+      width,
+      height,
+      samples: vec![[0.; N]; width * height],
+    }
+  }
+
+  /// Wrap samples given in row-major order
+  ///
+  /// Panics if the number of samples doesn't match the `size`.
+  #[must_use]
+  pub fn from_samples(size: [usize; 2], samples: Vec<[f32; N]>) -> Self {
+    let [width, height] = size;
+    assert_eq!(samples.len(), width * height, "wrong number of samples");
+    Field {
+      width,
+      height,
+      samples,
+    }
+  }
+
+  pub fn width(&self) -> usize {
+    self.width
+  }
+
+  pub fn height(&self) -> usize {
+    self.height
+  }
+
+  pub fn size(&self) -> [usize; 2] {
+    [self.width, self.height]
+  }
+
+  /// The sample for the pixel at `coords`
+  pub fn get(&self, coords: [usize; 2]) -> [f32; N] {
+    self.samples[self.index(coords)]
+  }
+
+  /// Overwrite the sample for the pixel at `coords`
+  pub fn set(&mut self, coords: [usize; 2], sample: [f32; N]) {
+    let index = self.index(coords);
+    self.samples[index] = sample;
+  }
+
+  /// All of the samples, in row-major order
+  pub fn samples(&self) -> &[[f32; N]] {
+    &self.samples
+  }
+
+  pub fn samples_mut(&mut self) -> &mut [[f32; N]] {
+    &mut self.samples
+  }
+
+  pub fn into_samples(self) -> Vec<[f32; N]> {
+    self.samples
+  }
+
+  /// Iterate over the rows of the field, from the top
+  pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[[f32; N]]> {
+    // chunks panics on a zero chunk size, even for an empty slice
+    self.samples.chunks(self.width.max(1))
+  }
+
+  /// Iterate over the coordinates and sample of every pixel, in row-major
+  /// order
+  pub fn pixels(
+    &self,
+  ) -> impl Iterator<Item = (/* x */ usize, /* y */ usize, [f32; N])> + '_ {
+    let width = self.width;
+    self
+      .samples
+      .iter()
+      .enumerate()
+      .map(move |(i, &sample)| (i % width, i / width, sample))
+  }
+
+  /// Iterate over a single channel of every sample, in row-major order
+  ///
+  /// Panics if `channel` isn't less than `N`.
+  pub fn channel(&self, channel: usize) -> impl Iterator<Item = f32> + '_ {
+    assert!(channel < N, "channel out of range");
+    self.samples.iter().map(move |sample| sample[channel])
+  }
+
+  /// Borrow the region of the field covered by `rect`
+  ///
+  /// Panics if `rect` doesn't fit within the field.
+  pub fn view(&self, rect: Rect) -> FieldView<'_, N> {
+    assert!(
+      rect.x + rect.width <= self.width && rect.y + rect.height <= self.height,
+      "rect out of range"
+    );
+    FieldView { field: self, rect }
+  }
+
+  /// Copy the region of the field covered by `rect` into a new field
+  ///
+  /// Panics if `rect` doesn't fit within the field.
+  #[must_use]
+  pub fn crop(&self, rect: Rect) -> Field<N> {
+    self.view(rect).to_field()
+  }
+
+  /// Copy the field with its distances divided by `max_distance` and
+  /// clamped to `[-1, 1]`
+  ///
+  /// For uploading float textures, or encoding the field yourself, without
+  /// the loss of precision of the fixed point [`Quantization`]s. Pass the
+  /// [`RasterConfig::max_distance`] the field was sampled with to match the
+  /// range they cover.
+  ///
+  /// ```
+  /// # use rsdf_core::*;
+  /// let field = Field::from_samples([3, 1], vec![[-8.], [1.], [2.5]]);
+  /// let normalized = field.normalized(5.);
+  /// assert_eq!(normalized.into_samples(), [[-1.], [0.2], [0.5]]);
+  /// ```
+  #[must_use]
+  pub fn normalized(&self, max_distance: f32) -> Field<N> {
+    let samples = self
+      .samples
+      .iter()
+      .map(|sample| sample.map(|d| (d / max_distance).clamp(-1., 1.)))
+      .collect();
+    Field::from_samples(self.size(), samples)
+  }
+
+  fn index(&self, coords: [usize; 2]) -> usize {
+    let [x, y] = coords;
+    assert!(x < self.width && y < self.height, "pixel out of range");
+    y * self.width + x
+  }
+}
+
+/// A rectangle of pixels, with its top-left corner at `(x, y)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rect {
+  pub x: usize,
+  pub y: usize,
+  pub width: usize,
+  pub height: usize,
+}
+
+/// A borrowed region of a [`Field`], see [`Field::view`]
+///
+/// Coordinates are relative to the top-left of the region. Its rows are
+/// slices of the underlying field's rows, so no samples are copied.
+#[derive(Debug, Clone, Copy)]
+pub struct FieldView<'a, const N: usize> {
+  field: &'a Field<N>,
+  rect: Rect,
+}
+
+impl<'a, const N: usize> FieldView<'a, N> {
+  pub fn width(&self) -> usize {
+    self.rect.width
+  }
+
+  pub fn height(&self) -> usize {
+    self.rect.height
+  }
+
+  pub fn size(&self) -> [usize; 2] {
+    [self.rect.width, self.rect.height]
+  }
+
+  /// The region of the underlying field this view covers
+  pub fn rect(&self) -> Rect {
+    self.rect
+  }
+
+  /// The sample for the pixel at `coords`, relative to the region
+  pub fn get(&self, coords: [usize; 2]) -> [f32; N] {
+    let [x, y] = coords;
+    assert!(
+      x < self.rect.width && y < self.rect.height,
+      "pixel out of range"
+    );
+    self.field.get([self.rect.x + x, self.rect.y + y])
+  }
+
+  /// Iterate over the rows of the region, from the top
+  pub fn rows(&self) -> impl DoubleEndedIterator<Item = &'a [[f32; N]]> {
+    let Rect {
+      x,
+      y,
+      width,
+      height,
+    } = self.rect;
+    let field = self.field;
+    (y..y + height).map(move |row| {
+      let start = row * field.width + x;
+      &field.samples[start..start + width]
+    })
+  }
+
+  /// Copy the region into a new field
+  #[must_use]
+  pub fn to_field(&self) -> Field<N> {
+    Field::from_samples(self.size(), self.rows().flatten().copied().collect())
+  }
+}
+
+/// Sampling a [`Shape`] into `N` channels at a time
+///
+/// Implemented for single channel (`1`), multi-channel (`3`) and
+/// multi-channel plus true distance (`4`, see [`Shape::sample_mtsdf`])
+/// fields, which lets [`Shape::sample_field`] share one implementation
+/// between them.
+///
+/// The trait is sealed, so the sampler can change without breaking
+/// downstream code:
+///
+/// ```compile_fail
+/// # use rsdf_core::*;
+/// struct Circle;
+///
+/// impl SampleChannels<1> for Circle {
+///   fn sample_channels(&self, point: Point, _: RasterConfig) -> [f32; 1] {
+///     [point.as_vector().abs() - 1.]
+///   }
+/// }
+/// ```
+pub trait SampleChannels<const N: usize>: sealed::Sealed {
+  /// Sample the signed distance channels at a point
+  fn sample_channels(&self, point: Point, config: RasterConfig) -> [f32; N];
+}
+
+mod sealed {
+  /// Keeps [`SampleChannels`](super::SampleChannels) from being implemented
+  /// outside of this crate
+  pub trait Sealed {}
+
+  impl Sealed for crate::Shape {}
+}
+
+impl SampleChannels<1> for Shape {
+  #[inline]
+  fn sample_channels(&self, point: Point, config: RasterConfig) -> [f32; 1] {
+    if config.pseudo_sdf {
+      [self.sample_pseudo_with(point, config)]
+    } else {
+      [self.sample_single_channel_with(point, config)]
+    }
+  }
+}
+
+impl SampleChannels<3> for Shape {
+  #[inline]
+  fn sample_channels(&self, point: Point, config: RasterConfig) -> [f32; 3] {
+    self.sample_with(point, config)
+  }
+}
+
+impl SampleChannels<4> for Shape {
+  #[inline]
+  fn sample_channels(&self, point: Point, config: RasterConfig) -> [f32; 4] {
+    self.sample_mtsdf_with(point, config)
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn field() {
+    let mut field = Field::<2>::new([3, 2]);
+    field.set([2, 1], [1., -1.]);
+    field.set([0, 1], [2., -2.]);
+    assert_eq!(field.get([2, 1]), [1., -1.]);
+    assert_eq!(field.samples()[5], [1., -1.]);
+    assert_eq!(field.rows().nth(1).unwrap()[0], [2., -2.]);
+    let pixels: Vec<_> = field.pixels().collect();
+    assert_eq!(pixels.len(), 6);
+    assert_eq!(pixels[3], (0, 1, [2., -2.]));
+    assert_eq!(pixels[5], (2, 1, [1., -1.]));
+    assert_eq!(
+      field.channel(1).collect::<Vec<_>>(),
+      [0., 0., 0., -2., 0., -1.]
+    );
+    assert_eq!(
+      Field::from_samples([3, 2], field.clone().into_samples()),
+      field
+    );
+  }
+
+  #[test]
+  fn view() {
+    let field = Field::from_samples(
+      [4, 3],
+      (0..12).map(|i| [i as f32]).collect::<Vec<_>>(),
+    );
+    let rect = Rect {
+      x: 1,
+      y: 1,
+      width: 2,
+      height: 2,
+    };
+    let view = field.view(rect);
+    assert_eq!(view.size(), [2, 2]);
+    assert_eq!(view.get([0, 0]), [5.]);
+    assert_eq!(view.get([1, 1]), [10.]);
+    let rows: Vec<_> = view.rows().rev().collect();
+    assert_eq!(rows, [&[[9.], [10.]], &[[5.], [6.]]]);
+    assert_eq!(
+      field.crop(rect),
+      Field::from_samples([2, 2], vec![[5.], [6.], [9.], [10.]])
+    );
+    // empty regions are fine, even at the far edges
+    let empty = Rect {
+      x: 4,
+      y: 3,
+      width: 0,
+      height: 0,
+    };
+    assert_eq!(field.crop(empty).size(), [0, 0]);
+  }
+
+  #[test]
+  #[should_panic(expected = "rect out of range")]
+  fn view_out_of_range() {
+    let field = Field::<1>::new([4, 3]);
+    let _ = field.view(Rect {
+      x: 3,
+      y: 0,
+      width: 2,
+      height: 1,
+    });
+  }
+}
@@ -0,0 +1,39 @@
+use std::collections::BTreeMap;
+
+/// How a font's characters are laid out in lines of text, in pixels at the
+/// size its glyphs were prepared at
+///
+/// An [`Atlas`](crate::Atlas) only records where each glyph's field is.
+/// Text renderers also need to know which glyph draws each character, and
+/// how far apart to place them, which the font front-ends provide. Written
+/// alongside an atlas in the formats renderers consume, such as BMFont &
+/// msdf-atlas-gen's JSON.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FontLayout {
+  /// The font's size, in pixels per em
+  pub size: f32,
+  /// The distance between the baselines of consecutive lines
+  pub line_height: f32,
+  /// The distance from the top of a line down to its baseline
+  pub ascender: f32,
+  /// The distance from the baseline down to the bottom of a line
+  pub descender: f32,
+  /// The distance from the baseline down to the middle of an underline
+  pub underline_position: f32,
+  /// The thickness of an underline
+  pub underline_thickness: f32,
+  /// The glyph & advance of each character
+  pub chars: BTreeMap<char, CharLayout>,
+  /// The adjustment to the advance between pairs of characters
+  pub kerning: BTreeMap<(char, char), f32>,
+}
+
+/// A character of a [`FontLayout`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CharLayout {
+  /// The id of the glyph drawing the character, as in
+  /// [`GlyphKey::glyph`](crate::GlyphKey::glyph)
+  pub glyph: u16,
+  /// How far the pen moves along the line after the character
+  pub advance: f32,
+}
@@ -0,0 +1,160 @@
+use crate::shape::checksum::Fnv1a;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// Where the data of a font comes from, see [`FontHandle`]
+///
+/// There are no sources for memory-mapped files or system font handles:
+/// map the file, or look the font up, e.g. with `rsdf_builder::system_font`,
+/// and pass its data as [`Bytes`](Self::Bytes). Swapping it in with
+/// [`FontHandle::replace`] still detects whether it changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FontSource {
+  /// Data already in memory, such as a font embedded in the binary
+  Bytes(Arc<[u8]>),
+  /// A font file, read when loaded & reloaded
+  File(PathBuf),
+}
+
+/// The data of a font loaded from a [`FontSource`], which can be swapped
+/// while an application is running
+///
+/// The handle keeps a checksum of the data, stable across runs. Caches and
+/// atlases built from the font store the [`checksum`](Self::checksum) they
+/// were built with, and rebuild once it no longer matches: an atlas records
+/// it with [`Provenance::font_handle`](crate::Provenance::font_handle), and
+/// [`AtlasBuilder::reuse`](crate::AtlasBuilder::reuse) won't reuse the
+/// glyphs of a font whose checksum has changed.
+///
+/// ```
+/// # use rsdf_core::*;
+/// # use std::sync::Arc;
+/// let mut font = FontHandle::load(FontSource::Bytes(Arc::from(&b"v1"[..])))
+///   .unwrap();
+/// let built_with = font.checksum();
+/// // nothing has changed
+/// assert!(!font.reload().unwrap());
+///
+/// font
+///   .replace(FontSource::Bytes(Arc::from(&b"v2"[..])))
+///   .unwrap();
+/// assert_ne!(font.checksum(), built_with);
+/// ```
+#[derive(Debug, Clone)]
+pub struct FontHandle {
+  source: FontSource,
+  data: Arc<[u8]>,
+  checksum: u64,
+  modified: Option<SystemTime>,
+}
+
+impl FontHandle {
+  /// Load the font's data from `source`
+  pub fn load(source: FontSource) -> std::io::Result<Self> {
+    let (data, modified) = read(&source)?;
+    Ok(FontHandle {
+      source,
+      checksum: checksum(&data),
+      data,
+      modified,
+    })
+  }
+
+  pub fn source(&self) -> &FontSource {
+    &self.source
+  }
+
+  /// The font's data, shared with any caches holding on to it
+  pub fn data(&self) -> &Arc<[u8]> {
+    &self.data
+  }
+
+  /// A checksum of the font's data, which changes whenever it does
+  ///
+  /// It's the checksum recorded by
+  /// [`Provenance::font`](crate::Provenance::font).
+  pub fn checksum(&self) -> u64 {
+    self.checksum
+  }
+
+  /// Read the font's file again if it was modified since it was loaded,
+  /// returning whether its data changed
+  ///
+  /// Data in memory never changes. Cheap enough to call every frame, or on
+  /// a file watcher's events.
+  pub fn reload(&mut self) -> std::io::Result<bool> {
+    if let FontSource::File(path) = &self.source {
+      let modified = std::fs::metadata(path)?.modified().ok();
+      if modified.is_some() && modified == self.modified {
+        return Ok(false);
+      }
+    }
+    self.update()
+  }
+
+  /// Swap in the font from another `source`, returning whether its data
+  /// differs from the font being replaced
+  pub fn replace(&mut self, source: FontSource) -> std::io::Result<bool> {
+    self.source = source;
+    self.modified = None;
+    self.update()
+  }
+
+  fn update(&mut self) -> std::io::Result<bool> {
+    let (data, modified) = read(&self.source)?;
+    self.modified = modified;
+    let checksum = checksum(&data);
+    if checksum == self.checksum {
+      return Ok(false);
+    }
+    self.data = data;
+    self.checksum = checksum;
+    Ok(true)
+  }
+}
+
+/// Read the data of a font, along with when its file was last modified
+fn read(
+  source: &FontSource,
+) -> std::io::Result<(Arc<[u8]>, Option<SystemTime>)> {
+  match source {
+    FontSource::Bytes(data) => Ok((data.clone(), None)),
+    FontSource::File(path) => {
+      let modified = std::fs::metadata(path)?.modified().ok();
+      Ok((std::fs::read(path)?.into(), modified))
+    },
+  }
+}
+
+fn checksum(data: &[u8]) -> u64 {
+  let mut hasher = Fnv1a::new();
+  hasher.write(data);
+  hasher.0
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn reload() {
+    let path = std::env::temp_dir()
+      .join(format!("rsdf_font_source_{}.ttf", std::process::id()));
+    std::fs::write(&path, b"first").unwrap();
+    let mut font = FontHandle::load(FontSource::File(path.clone())).unwrap();
+    assert_eq!(&font.data()[..], b"first");
+    assert!(!font.reload().unwrap());
+
+    let checksum = font.checksum();
+    std::fs::write(&path, b"second").unwrap();
+    // coarse modification times may not have ticked over, so force a read
+    font.modified = None;
+    assert!(font.reload().unwrap());
+    assert_eq!(&font.data()[..], b"second");
+    assert_ne!(font.checksum(), checksum);
+
+    std::fs::remove_file(&path).unwrap();
+    assert!(font.reload().is_err());
+  }
+}
@@ -0,0 +1,341 @@
+use crate::shape::checksum::Fnv1a;
+use crate::*;
+
+/// A glyph's shape, scaled to pixels and framed on a canvas with room for
+/// its falloff, ready to be sampled or packed into an atlas
+///
+/// Font front-ends produce these from outlines in font units, so the
+/// bounds & margin math lives in one place, and the atlas only has to place
+/// the canvas.
+///
+/// ```
+/// # use rsdf_builder::ShapeBuilder;
+/// # use rsdf_core::*;
+/// // a 100 unit square glyph sitting on the baseline, y pointing down
+/// let outline = ShapeBuilder::new()
+///   .contour((0., 0.))
+///   .line((0., -100.))
+///   .line((100., -100.))
+///   .line((100., 0.))
+///   .line((0., 0.))
+///   .end_contour()
+///   .build();
+/// let config = RasterConfig::default();
+/// let glyph = PreparedGlyph::new(outline, 0.1, config).unwrap();
+/// assert_eq!(glyph.size, [20, 20]);
+/// assert_eq!(glyph.offset, Vector::new(-5., -15.));
+///
+/// # #[cfg(feature = "atlas")] {
+/// let mut atlas = AtlasBuilder::<3>::new(64);
+/// let font = atlas.add_font("Sans");
+/// atlas.insert_prepared(GlyphKey { font, glyph: 1 }, &glyph, config);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PreparedGlyph {
+  /// The outline, in pixels of the canvas
+  pub shape: Shape,
+  /// The size of the canvas, in pixels
+  pub size: [usize; 2],
+  /// Where the top-left pixel of the canvas lies relative to the glyph's
+  /// origin on the baseline, in pixels with y pointing down
+  pub offset: Vector,
+}
+
+impl PreparedGlyph {
+  /// Scale an outline given in font units, with y pointing down, by `scale`
+  /// pixels per unit, and frame it with a margin of
+  /// [`RasterConfig::max_distance`] pixels
+  ///
+  /// The canvas is aligned to whole pixels of the glyph's origin, so glyphs
+  /// placed at whole pixels line up. Returns `None` for a glyph without an
+  /// outline, such as a space.
+  pub fn new(
+    mut shape: Shape,
+    scale: f32,
+    config: RasterConfig,
+  ) -> Option<Self> {
+    let margin = config.max_distance.ceil();
+    let (min, max) = shape.bounds()?;
+    let min = Vector::new(
+      (min.x * scale).floor() - margin,
+      (min.y * scale).floor() - margin,
+    );
+    let max = Vector::new(
+      (max.x * scale).ceil() + margin,
+      (max.y * scale).ceil() + margin,
+    );
+    shape.scale_translate(scale, -min);
+    let size = max - min;
+    Some(PreparedGlyph {
+      shape,
+      size: [size.x as usize, size.y as usize],
+      offset: min,
+    })
+  }
+
+  /// Sample the glyph's field over its canvas
+  ///
+  /// `N` chooses the channels, see [`SampleChannels`]; `4` gives an MTSDF.
+  #[must_use]
+  pub fn sample<const N: usize>(&self, config: RasterConfig) -> Field<N>
+  where
+    Shape: SampleChannels<N>,
+  {
+    self.shape.sample_field(self.size, config)
+  }
+
+  /// A checksum of the glyph's outline & canvas, along with the fields of
+  /// the `config` that change the samples of its field
+  ///
+  /// Stable across runs, like [`Shape::checksum`], so an atlas can record it
+  /// to tell which glyphs have changed since, see [`AtlasBuilder::reuse`].
+  /// Fields that only change how the samples are written, such as the
+  /// [`quantization`](RasterConfig::quantization), are left out.
+  #[must_use]
+  pub fn checksum(&self, config: RasterConfig) -> u64 {
+    let mut hasher = Fnv1a::new();
+    hasher.write(&self.shape.checksum().to_le_bytes());
+    hasher.write_usize(self.size[0]);
+    hasher.write_usize(self.size[1]);
+    hasher.write_f32(self.offset.x);
+    hasher.write_f32(self.offset.y);
+
+    hasher.write_f32(config.border);
+    hasher.write_f32(config.pseudo_distance_limit);
+    hasher.write_f32(config.tie_epsilon);
+    // fast fill only applies to the fixed point quantizations
+    let fast_fill = config.fast_fill && config.quantization.is_fixed_point();
+    // the rows of the field are flipped, not just those written out
+    hasher.write(&[u8::from(fast_fill), u8::from(config.flip_y)]);
+    hasher.write(&[match config.metric {
+      DistanceMetric::Euclidean => 0,
+      DistanceMetric::Manhattan => 1,
+      DistanceMetric::Chebyshev => 2,
+    }]);
+    hasher.write_f32(config.max_distance);
+    hasher.write(&[u8::from(config.pseudo_sdf)]);
+    hasher.0
+  }
+
+  /// Call `draw` with the coordinates and plain signed distance of each
+  /// pixel of the glyph's canvas, row by row
+  ///
+  /// For a conventional single channel SDF, without taking the median of a
+  /// multi-channel field; the distance is that of
+  /// [`Shape::sample_single_channel`], in pixels, or of
+  /// [`Shape::sample_pseudo`] with [`RasterConfig::pseudo_sdf`].
+  ///
+  /// ```
+  /// # use rsdf_builder::ShapeBuilder;
+  /// # use rsdf_core::*;
+  /// # let outline = ShapeBuilder::new()
+  /// #   .contour((0., 0.))
+  /// #   .line((0., -100.))
+  /// #   .line((100., -100.))
+  /// #   .line((100., 0.))
+  /// #   .end_contour()
+  /// #   .build();
+  /// let config = RasterConfig::default();
+  /// let glyph = PreparedGlyph::new(outline, 0.1, config).unwrap();
+  /// let mut image = vec![0; glyph.size[0] * glyph.size[1]];
+  /// glyph.draw_sdf(config, |x, y, distance| {
+  ///   image[y * glyph.size[0] + x] = distance_color(distance, config.max_distance);
+  /// });
+  /// ```
+  pub fn draw_sdf(
+    &self,
+    config: RasterConfig,
+    mut draw: impl FnMut(usize, usize, f32),
+  ) {
+    for (x, y, [distance]) in self.sample::<1>(config).pixels() {
+      draw(x, y, distance);
+    }
+  }
+}
+
+#[cfg(feature = "atlas")]
+impl<const N: usize> AtlasBuilder<N>
+where
+  Shape: SampleChannels<N>,
+{
+  /// Sample a prepared glyph and add its field along with its offset & the
+  /// range it was sampled with, as in [`AtlasBuilder::insert_with_range`]
+  ///
+  /// The glyph's [checksum](PreparedGlyph::checksum) is recorded in the
+  /// atlas' provenance. If the atlas [reused](AtlasBuilder::reuse) by the
+  /// builder holds the glyph with the same checksum, its field is copied
+  /// from there instead of being sampled again, and kept at the same place.
+  /// Returns whether the glyph was sampled.
+  pub fn insert_prepared(
+    &mut self,
+    key: GlyphKey,
+    glyph: &PreparedGlyph,
+    config: RasterConfig,
+  ) -> bool {
+    let checksum = glyph.checksum(config);
+    let (field, rect) = match self.reusable(key, checksum) {
+      Some((field, rect)) => (field, Some(rect)),
+      None => (glyph.sample(config), None),
+    };
+    self.insert_with_range(key, field, glyph.offset, config.max_distance);
+    self.record_checksum(key, checksum);
+    if let Some(rect) = rect {
+      self.pin(key, rect);
+    }
+    rect.is_none()
+  }
+}
+
+#[cfg(feature = "atlas")]
+impl AtlasBuilder<3> {
+  /// Sample a prepared glyph's multi-channel field, falling back to a single
+  /// channel if it has too many artifacts, as in
+  /// [`Shape::sample_field_checked`], and add it as in
+  /// [`AtlasBuilder::insert_prepared`]
+  ///
+  /// The glyph's quality report is recorded, see [`Atlas::quality`], along
+  /// with whether it fell back, see [`Atlas::is_single_channel`]. The glyph
+  /// is always sampled, and no checksum is recorded for it, so it's never
+  /// [reused](AtlasBuilder::reuse) without its report.
+  pub fn insert_prepared_checked(
+    &mut self,
+    key: GlyphKey,
+    glyph: &PreparedGlyph,
+    config: RasterConfig,
+    policy: FallbackPolicy,
+  ) -> QualityReport {
+    let checked = glyph.shape.sample_field_checked(glyph.size, config, policy);
+    self.insert_with_range(
+      key,
+      checked.field,
+      glyph.offset,
+      config.max_distance,
+    );
+    self.record_quality(key, checked.report);
+    if checked.single_channel {
+      self.record_single_channel(key);
+    }
+    checked.report
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn prepared_glyph() {
+    // a glyph dipping below the baseline, from (10, -20) to (30, 4)
+    let shape = Shape::rectangle((10., -20.), (30., 4.), &[White]);
+    let config = RasterConfig {
+      max_distance: 1.5,
+      ..RasterConfig::default()
+    };
+    let glyph = PreparedGlyph::new(shape, 0.25, config).unwrap();
+    // scaled to (2.5, -5) to (7.5, 1), framed to whole pixels with a margin
+    // of 2
+    assert_eq!(glyph.offset, Vector::new(0., -7.));
+    assert_eq!(glyph.size, [10, 10]);
+    assert_eq!(glyph.shape.points[0], Point::new(2.5, 2.));
+
+    let field = glyph.sample::<1>(config);
+    assert_eq!(field.size(), [10, 10]);
+    assert_eq!(field.get([5, 5]), [2.5]);
+    let mut drawn = vec![];
+    glyph.draw_sdf(config, |x, y, distance| drawn.push((x, y, distance)));
+    assert_eq!(drawn.len(), 100);
+    assert_eq!(drawn[55], (5, 5, 2.5));
+    // the true distance is carried alongside the multi-channel one
+    let [.., true_distance] = glyph.sample::<4>(config).get([0, 0]);
+    assert_eq!(true_distance, -(2.5f32.hypot(2.)));
+
+    let empty = Shape {
+      points: vec![],
+      segments: vec![],
+      splines: vec![],
+      contours: vec![],
+    };
+    assert!(PreparedGlyph::new(empty, 1., config).is_none());
+  }
+
+  #[cfg(feature = "atlas")]
+  #[test]
+  fn insert_prepared_checked() {
+    // a square with an edge of a single channel each, which can't reproduce
+    // its corners, and the same square coloured properly
+    let square = |colours: [Colour; 4]| {
+      let shape = Shape::rectangle((0., 0.), (8., 8.), &colours);
+      PreparedGlyph::new(shape, 1., RasterConfig::default()).unwrap()
+    };
+    let config = RasterConfig::default();
+    let policy = FallbackPolicy::default();
+
+    let mut builder = AtlasBuilder::<3>::new(64);
+    let font = builder.add_font("Sans");
+    let key = |glyph| GlyphKey { font, glyph };
+    let bad = square([Red, Green, Blue, Red]);
+    let report = builder.insert_prepared_checked(key(1), &bad, config, policy);
+    assert!(report.artifacts > 0);
+    assert_eq!(report.corrected, report.artifacts);
+    let good = square([Magenta, Yellow, Magenta, Yellow]);
+    builder.insert_prepared_checked(key(2), &good, config, policy);
+    let atlas = builder.build();
+
+    assert!(atlas.is_single_channel(key(1)));
+    assert_eq!(atlas.quality(key(1)), Some(report));
+    assert!(!atlas.is_single_channel(key(2)));
+    assert_eq!(atlas.quality(key(2)).unwrap().corrected, 0);
+    assert!(atlas.provenance.get("checksum.Sans.1").is_none());
+  }
+
+  #[test]
+  fn checksum() {
+    let shape = Shape {
+      points: vec![(0., 0.).into(), (4., 0.).into(), (0., 0.).into()],
+      segments: (0..2)
+        .map(|points_index| SegmentRef {
+          kind: SegmentKind::Line,
+          points_index,
+        })
+        .collect(),
+      splines: vec![Spline {
+        segments_range: 0..2,
+        colour: White,
+      }],
+      contours: vec![Contour { spline_range: 0..1 }],
+    };
+    let config = RasterConfig::default();
+    let glyph = PreparedGlyph::new(shape, 1., config).unwrap();
+    let checksum = |config| glyph.checksum(config);
+
+    // the same samples, written differently
+    let float = RasterConfig {
+      quantization: Quantization::F32,
+      ..config
+    };
+    assert_eq!(checksum(float), checksum(config));
+    let fast_float = RasterConfig {
+      fast_fill: true,
+      ..float
+    };
+    assert_eq!(checksum(fast_float), checksum(config));
+
+    // different samples
+    let fast = RasterConfig {
+      fast_fill: true,
+      ..config
+    };
+    let wide = RasterConfig {
+      max_distance: 8.,
+      ..config
+    };
+    let chebyshev = RasterConfig {
+      metric: DistanceMetric::Chebyshev,
+      ..config
+    };
+    for changed in [fast, wide, chebyshev] {
+      assert_ne!(checksum(changed), checksum(config));
+    }
+  }
+}
@@ -0,0 +1,138 @@
+use crate::*;
+
+/// What a [`PostProcess`] hook is told about the field it is modifying
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FieldInfo {
+  /// The size of the field, in pixels
+  pub size: [usize; 2],
+  /// The configuration the field was sampled with
+  pub config: RasterConfig,
+}
+
+/// A list of callbacks that modify each field after it has been sampled
+///
+/// Hooks run in the order they were added, see
+/// [`Shape::sample_field_processed`]. They allow custom effects, such as
+/// noise or embossing, without reimplementing the sampling.
+///
+/// ```
+/// # use rsdf_core::*;
+/// let mut hooks = PostProcess::<1>::new();
+/// // grow the shape by half a pixel
+/// hooks.add(|field, _| {
+///   for [distance] in field.samples_mut() {
+///     *distance += 0.5;
+///   }
+/// });
+/// let mut field = Field::from_samples([1, 1], vec![[-0.5]]);
+/// let info = FieldInfo {
+///   size: field.size(),
+///   config: RasterConfig::default(),
+/// };
+/// hooks.run(&mut field, &info);
+/// assert_eq!(field.get([0, 0]), [0.]);
+/// ```
+pub struct PostProcess<'a, const N: usize> {
+  hooks: Vec<Hook<'a, N>>,
+}
+
+type Hook<'a, const N: usize> = Box<dyn FnMut(&mut Field<N>, &FieldInfo) + 'a>;
+
+impl<'a, const N: usize> PostProcess<'a, N> {
+  pub fn new() -> Self {
+    PostProcess { hooks: Vec::new() }
+  }
+
+  /// Add a hook, to run after all the hooks added before it
+  pub fn add(
+    &mut self,
+    hook: impl FnMut(&mut Field<N>, &FieldInfo) + 'a,
+  ) -> &mut Self {
+    self.hooks.push(Box::new(hook));
+    self
+  }
+
+  /// The number of hooks
+  pub fn len(&self) -> usize {
+    self.hooks.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.hooks.is_empty()
+  }
+
+  /// Run every hook on the field, in order
+  pub fn run(&mut self, field: &mut Field<N>, info: &FieldInfo) {
+    for hook in &mut self.hooks {
+      hook(field, info);
+    }
+  }
+}
+
+impl<const N: usize> Default for PostProcess<'_, N> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<const N: usize> std::fmt::Debug for PostProcess<'_, N> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("PostProcess")
+      .field("hooks", &self.hooks.len())
+      .finish()
+  }
+}
+
+impl Shape {
+  /// Sample `N` channels for every pixel on a canvas of the given `size`, as
+  /// in [`Shape::sample_field`], then run the post-processing hooks on the
+  /// field
+  #[must_use]
+  pub fn sample_field_processed<const N: usize>(
+    &self,
+    size: [usize; 2],
+    config: RasterConfig,
+    hooks: &mut PostProcess<'_, N>,
+  ) -> Field<N>
+  where
+    Self: SampleChannels<N>,
+  {
+    let mut field = self.sample_field(size, config);
+    hooks.run(&mut field, &FieldInfo { size, config });
+    field
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn sample_field_processed() {
+    let shape = Shape::rectangle((2., 2.), (6., 6.), &[White]);
+    let config = RasterConfig::default();
+
+    let mut calls = Vec::new();
+    let mut hooks = PostProcess::<3>::new();
+    hooks
+      .add(|field, info| {
+        calls.push(info.size);
+        for sample in field.samples_mut() {
+          *sample = sample.map(|distance| -distance);
+        }
+      })
+      .add(|field, _| field.set([0, 0], [1., 2., 3.]));
+    assert_eq!(hooks.len(), 2);
+
+    let field = shape.sample_field_processed([8, 8], config, &mut hooks);
+    drop(hooks);
+    assert_eq!(calls, [[8, 8]]);
+
+    // hooks run in order, after sampling
+    let plain = shape.sample_field::<3>([8, 8], config);
+    assert_eq!(field.get([0, 0]), [1., 2., 3.]);
+    for coords in [[4, 4], [1, 4], [7, 7]] {
+      assert_eq!(field.get(coords), plain.get(coords).map(|d| -d));
+    }
+  }
+}
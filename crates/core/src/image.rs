@@ -1,52 +1,169 @@
+use crate::*;
 use std::fs::File;
-use std::io::BufWriter;
+use std::io::{self, BufWriter, Write};
+use std::ops::{Deref, DerefMut};
 
 /// Wrapper around a PNG to make setting individual pixels easy
-pub struct Image<'a> {
-  encoder: png::Encoder<'a, BufWriter<File>>,
-  data: Vec<u8>,
-  pub width: usize,
-  pub height: usize,
+///
+/// Requires the `png` feature. The pixels are held in a [`Bitmap`], which
+/// the image dereferences to, and encoded to a writer of type `W`, a file by
+/// default, when the image is [flushed](Image::flush) or
+/// [finished](Image::finish).
+pub struct Image<W: Write = BufWriter<File>> {
+  writer: W,
+  bitmap: Bitmap<u8, 3>,
 }
 
-impl Image<'_> {
+impl Image {
   /// Create a new Image, given a path and dimensions
   pub fn new(path: &str, size: [usize; 2]) -> Self {
     let file = File::create(path).unwrap();
-    let buf_writer = BufWriter::new(file);
-    let mut encoder =
-      png::Encoder::new(buf_writer, size[0] as u32, size[1] as u32);
-
-    encoder.set_color(png::ColorType::Rgb);
-    encoder.set_depth(png::BitDepth::Eight);
+    Self::from_writer(BufWriter::new(file), size)
+  }
+}
 
-    let data_length = size[0] * size[1] * 3;
-    let data = vec![0; data_length];
+impl Image<Vec<u8>> {
+  /// Create a new Image encoded in memory, given its dimensions
+  ///
+  /// [`Image::finish`] returns the encoded PNG.
+  ///
+  /// ```
+  /// # use rsdf_core::*;
+  /// let mut image = Image::in_memory([2, 2]);
+  /// image.set_pixel([1, 0], [255, 0, 0]);
+  /// let png = image.finish().unwrap();
+  /// assert!(png.starts_with(b"\x89PNG"));
+  /// ```
+  pub fn in_memory(size: [usize; 2]) -> Self {
+    Self::from_writer(vec![], size)
+  }
+}
 
+impl<W: Write> Image<W> {
+  /// Create a new Image encoded to the given writer, such as a socket or an
+  /// entry of an archive, given its dimensions
+  pub fn from_writer(writer: W, size: [usize; 2]) -> Self {
     Self {
-      data,
-      encoder,
-      width: size[0],
-      height: size[1],
+      writer,
+      bitmap: Bitmap::new(size),
     }
   }
 
-  /// Set the pixel at the coordinates to the given value
-  #[inline]
-  pub fn set_pixel(&mut self, coords: [usize; 2], val: [u8; 3]) {
-    debug_assert!(
-      coords[0] < self.width && coords[1] < self.height,
-      "coordinates given were outside the dimensions of the image"
-    );
-    let location = (coords[1] * self.width + coords[0]) * 3;
-    self.data[location] = val[0];
-    self.data[location + 1] = val[1];
-    self.data[location + 2] = val[2];
+  /// Encode the image as a PNG to its writer, returning the writer
+  pub fn finish(mut self) -> io::Result<W> {
+    self.bitmap.write_png(&mut self.writer)?;
+    self.writer.flush()?;
+    Ok(self.writer)
   }
 
-  /// Flush the contents of the image to disk
+  /// Flush the contents of the image to disk, or to its writer
+  ///
+  /// Panics if the image can't be written, see [`Image::finish`].
   pub fn flush(self) {
-    let mut writer = self.encoder.write_header().unwrap();
-    writer.write_image_data(&self.data).unwrap();
+    self.finish().unwrap();
+  }
+
+  /// The pixels of the image, without its writer
+  pub fn into_bitmap(self) -> Bitmap<u8, 3> {
+    self.bitmap
+  }
+}
+
+impl<W: Write> Deref for Image<W> {
+  type Target = Bitmap<u8, 3>;
+
+  fn deref(&self) -> &Self::Target {
+    &self.bitmap
+  }
+}
+
+impl<W: Write> DerefMut for Image<W> {
+  fn deref_mut(&mut self) -> &mut Self::Target {
+    &mut self.bitmap
+  }
+}
+
+impl<const N: usize> Bitmap<u8, N> {
+  /// Encode the bitmap as an 8-bit PNG to the writer
+  ///
+  /// Requires the `png` feature. Bitmaps of 1 to 4 channels are encoded as
+  /// greyscale, greyscale & alpha, RGB & RGBA images respectively.
+  pub fn write_png(&self, writer: impl Write) -> io::Result<()> {
+    let bytes = self.pixels().as_flattened();
+    encode_png::<N>(writer, self.size(), png::BitDepth::Eight, bytes)
+  }
+}
+
+impl<const N: usize> Bitmap<u16, N> {
+  /// Encode the bitmap as a 16-bit PNG to the writer, as
+  /// [`Bitmap::write_png`] does an 8-bit one
+  pub fn write_png(&self, writer: impl Write) -> io::Result<()> {
+    // PNG stores samples big-endian
+    let bytes: Vec<u8> = self
+      .pixels()
+      .as_flattened()
+      .iter()
+      .flat_map(|channel| channel.to_be_bytes())
+      .collect();
+    encode_png::<N>(writer, self.size(), png::BitDepth::Sixteen, &bytes)
+  }
+}
+
+fn encode_png<const N: usize>(
+  writer: impl Write,
+  size: [usize; 2],
+  depth: png::BitDepth,
+  bytes: &[u8],
+) -> io::Result<()> {
+  let color = match N {
+    1 => png::ColorType::Grayscale,
+    2 => png::ColorType::GrayscaleAlpha,
+    3 => png::ColorType::Rgb,
+    4 => png::ColorType::Rgba,
+    _ => panic!("PNGs have 1 to 4 channels"),
+  };
+  let mut encoder = png::Encoder::new(writer, size[0] as u32, size[1] as u32);
+  encoder.set_color(color);
+  encoder.set_depth(depth);
+  let mut writer = encoder.write_header()?;
+  writer.write_image_data(bytes)?;
+  writer.finish()?;
+  Ok(())
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  fn decode(png: &[u8]) -> (png::OutputInfo, Vec<u8>) {
+    let decoder = png::Decoder::new(png);
+    let mut reader = decoder.read_info().unwrap();
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).unwrap();
+    (info, pixels)
+  }
+
+  #[test]
+  fn from_writer() {
+    let mut image = Image::from_writer(io::Cursor::new(vec![]), [3, 2]);
+    image.set_pixel([2, 1], [1, 2, 3]);
+    let png = image.finish().unwrap().into_inner();
+
+    let (info, pixels) = decode(&png);
+    assert_eq!([info.width, info.height], [3, 2]);
+    assert_eq!(pixels[15..], [1, 2, 3]);
+    assert!(pixels[..15].iter().all(|&channel| channel == 0));
+  }
+
+  #[test]
+  fn write_png() {
+    let mut bitmap = Bitmap::<u16, 1>::new([2, 1]);
+    bitmap.set_pixel([1, 0], [0x1234]);
+    let mut png = vec![];
+    bitmap.write_png(&mut png).unwrap();
+    let (info, pixels) = decode(&png);
+    assert_eq!(info.color_type, png::ColorType::Grayscale);
+    assert_eq!(info.bit_depth, png::BitDepth::Sixteen);
+    assert_eq!(pixels, [0, 0, 0x12, 0x34]);
   }
 }
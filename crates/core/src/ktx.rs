@@ -0,0 +1,300 @@
+use crate::*;
+#[cfg(feature = "zlib")]
+use std::io::Write;
+
+/// Compression applied on top of the texture data in a KTX2 container, see
+/// [`Field::to_ktx2`]
+///
+/// Which variants exist depends on the enabled features, so matches must have
+/// a wildcard arm.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Supercompression {
+  /// Store the texels as they are, ready to be uploaded
+  #[default]
+  None,
+  /// Deflate the texels with zlib, which loaders inflate before uploading
+  ///
+  /// Requires the `zlib` feature. Fields compress well, since they're
+  /// smooth away from the edges.
+  #[cfg(feature = "zlib")]
+  Zlib,
+}
+
+/// The identifier every KTX2 container starts with
+const IDENTIFIER: [u8; 12] = [
+  0xab, b'K', b'T', b'X', b' ', b'2', b'0', 0xbb, b'\r', b'\n', 0x1a, b'\n',
+];
+/// The length of the header & the index of its only level
+const HEADER_LEN: usize = 80 + 24;
+const KEY_VALUE: &[u8] = b"KTXwriter\0rsdf\0";
+
+impl<const N: usize> Field<N> {
+  /// Encode the field as a KTX2 texture, quantized as
+  /// [`Shape::rasterize_into`] would by the [`RasterConfig::quantization`]
+  /// & [`RasterConfig::max_distance`]
+  ///
+  /// Each channel of the field is a channel of the texture, so fields of 1
+  /// to 4 channels are supported, as `R`, `RG`, `RGB` & `RGBA` formats of
+  /// the quantization, e.g. `VK_FORMAT_R8G8B8_UNORM` for a multi-channel
+  /// field in [`Quantization::U8`]. The fixed point formats are linear, not
+  /// sRGB, since they store distances. GPU engines can load the texture of a
+  /// glyph, or an [`Atlas`]'s field, without decoding an image first.
+  ///
+  /// ```
+  /// # use rsdf_core::*;
+  /// let field = Field::from_samples([2, 1], vec![[-5., 0., 5.]; 2]);
+  /// let ktx2 = field.to_ktx2(RasterConfig::default(), Supercompression::None);
+  /// assert!(ktx2.starts_with(b"\xabKTX 20\xbb"));
+  /// ```
+  #[must_use]
+  pub fn to_ktx2(
+    &self,
+    config: RasterConfig,
+    supercompression: Supercompression,
+  ) -> Vec<u8> {
+    ktx2::<N>(
+      self.size(),
+      config,
+      supercompression,
+      self.quantized_le(config),
+    )
+  }
+}
+
+#[cfg(feature = "atlas")]
+impl<const N: usize> Atlas<N> {
+  /// Encode the atlas' field as a KTX2 texture, as [`Field::to_ktx2`] does,
+  /// with each glyph quantized by its own [`AtlasGlyph::max_distance`]
+  ///
+  /// The `config` quantizes the background between glyphs.
+  #[must_use]
+  pub fn to_ktx2(
+    &self,
+    config: RasterConfig,
+    supercompression: Supercompression,
+  ) -> Vec<u8> {
+    let texels = self.quantized_le(config);
+    ktx2::<N>(self.field.size(), config, supercompression, texels)
+  }
+}
+
+/// A KTX2 texture of the given size holding the texels
+fn ktx2<const N: usize>(
+  [width, height]: [usize; 2],
+  config: RasterConfig,
+  supercompression: Supercompression,
+  texels: Vec<u8>,
+) -> Vec<u8> {
+  assert!((1..=4).contains(&N), "KTX2 textures have 1 to 4 channels");
+  let channel_size = config.quantization.channel_size();
+  let texel_size = N * channel_size;
+  let (scheme, level) = match supercompression {
+    Supercompression::None => (0, texels.clone()),
+    #[cfg(feature = "zlib")]
+    Supercompression::Zlib => {
+      let mut encoder =
+        flate2::write::ZlibEncoder::new(vec![], flate2::Compression::best());
+      // writing to a Vec can't fail
+      let _ = encoder.write_all(&texels);
+      (3, encoder.finish().unwrap_or_default())
+    },
+  };
+
+  let dfd = data_format_descriptor::<N>(config.quantization);
+  let dfd_offset = HEADER_LEN;
+  let kvd_offset = dfd_offset + dfd.len();
+  let kvd_len = 4 + KEY_VALUE.len().next_multiple_of(4);
+  // uncompressed levels are aligned to whole texels, & to 4 bytes
+  let alignment = match supercompression {
+    Supercompression::None => lcm(texel_size, 4),
+    #[cfg(feature = "zlib")]
+    Supercompression::Zlib => 1,
+  };
+  let level_offset = (kvd_offset + kvd_len).next_multiple_of(alignment);
+
+  let mut bytes = Vec::with_capacity(level_offset + level.len());
+  bytes.extend_from_slice(&IDENTIFIER);
+  let u32s = [
+    vk_format::<N>(config.quantization),
+    channel_size as u32,
+    width as u32,
+    height as u32,
+    // depth, array layers, faces & mip levels
+    0,
+    0,
+    1,
+    1,
+    scheme,
+    dfd_offset as u32,
+    dfd.len() as u32,
+    kvd_offset as u32,
+    kvd_len as u32,
+  ];
+  for value in u32s {
+    bytes.extend_from_slice(&value.to_le_bytes());
+  }
+  // there's no supercompression global data
+  let u64s = [0, 0, level_offset, level.len(), texels.len()];
+  for value in u64s {
+    bytes.extend_from_slice(&(value as u64).to_le_bytes());
+  }
+  bytes.extend_from_slice(&dfd);
+  bytes.extend_from_slice(&(KEY_VALUE.len() as u32).to_le_bytes());
+  bytes.extend_from_slice(KEY_VALUE);
+  bytes.resize(level_offset, 0);
+  bytes.extend_from_slice(&level);
+  bytes
+}
+
+/// The `VkFormat` of a texture of `N` channels in the given quantization
+fn vk_format<const N: usize>(quantization: Quantization) -> u32 {
+  match (quantization, N) {
+    (Quantization::U8, 1) => 9,
+    (Quantization::U8, 2) => 16,
+    (Quantization::U8, 3) => 23,
+    (Quantization::U8, _) => 37,
+    (Quantization::U16, 1) => 70,
+    (Quantization::U16, 2) => 77,
+    (Quantization::U16, 3) => 84,
+    (Quantization::U16, _) => 91,
+    (Quantization::F16, 1) => 76,
+    (Quantization::F16, 2) => 83,
+    (Quantization::F16, 3) => 90,
+    (Quantization::F16, _) => 97,
+    (Quantization::F32, 1) => 100,
+    (Quantization::F32, 2) => 103,
+    (Quantization::F32, 3) => 106,
+    (Quantization::F32, _) => 109,
+  }
+}
+
+/// A data format descriptor of a single basic block, with a sample per
+/// channel, prefixed by its total length
+fn data_format_descriptor<const N: usize>(
+  quantization: Quantization,
+) -> Vec<u8> {
+  let channel_size = quantization.channel_size();
+  let block_len = 24 + 16 * N;
+  let mut dfd = Vec::with_capacity(4 + block_len);
+  dfd.extend_from_slice(&(4 + block_len as u32).to_le_bytes());
+  // Khronos' basic descriptor block, version 2
+  dfd.extend_from_slice(&0u32.to_le_bytes());
+  dfd.extend_from_slice(&2u16.to_le_bytes());
+  dfd.extend_from_slice(&(block_len as u16).to_le_bytes());
+  // RGBSDA colour model, BT.709 primaries, linear transfer, straight alpha
+  dfd.extend_from_slice(&[1, 1, 1, 0]);
+  // a texel block of a single texel
+  dfd.extend_from_slice(&[0; 4]);
+  dfd.extend_from_slice(&[(N * channel_size) as u8, 0, 0, 0, 0, 0, 0, 0]);
+
+  let (qualifiers, lower, upper) = match quantization {
+    Quantization::U8 => (0, 0, u8::MAX as u32),
+    Quantization::U16 => (0, 0, u16::MAX as u32),
+    // float & signed
+    Quantization::F16 | Quantization::F32 => {
+      (0xc0, (-1f32).to_bits(), 1f32.to_bits())
+    },
+  };
+  let bits = channel_size * 8;
+  for (channel, id) in [0u8, 1, 2, 15].into_iter().take(N).enumerate() {
+    dfd.extend_from_slice(&((channel * bits) as u16).to_le_bytes());
+    dfd.push(bits as u8 - 1);
+    dfd.push(id | qualifiers);
+    dfd.extend_from_slice(&[0; 4]);
+    dfd.extend_from_slice(&lower.to_le_bytes());
+    dfd.extend_from_slice(&upper.to_le_bytes());
+  }
+  dfd
+}
+
+fn lcm(a: usize, b: usize) -> usize {
+  (1..=b).map(|n| n * a).find(|m| m % b == 0).unwrap_or(a * b)
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  fn format<const N: usize>(config: RasterConfig) -> Option<ktx2::Format> {
+    let field = Field::<N>::new([2, 2]);
+    let ktx2 = field.to_ktx2(config, Supercompression::None);
+    ktx2::Reader::new(&ktx2).unwrap().header().format
+  }
+
+  #[test]
+  fn to_ktx2() {
+    let samples = (0..12).map(|i| [i as f32 - 6., 1., -1.]).collect();
+    let field = Field::from_samples([4, 3], samples);
+    let config = RasterConfig::default();
+    let ktx2 = field.to_ktx2(config, Supercompression::None);
+    let reader = ktx2::Reader::new(&ktx2).unwrap();
+    let header = reader.header();
+    assert_eq!(header.format, Some(ktx2::Format::R8G8B8_UNORM));
+    assert_eq!([header.pixel_width, header.pixel_height], [4, 3]);
+    assert_eq!(header.supercompression_scheme, None);
+    let level = reader.levels().next().unwrap();
+    assert_eq!(level.data, field.quantized_le(config));
+    let offset = ktx2.len() - level.data.len();
+    assert_eq!(offset % 12, 0);
+
+    let dfd = reader.dfd_blocks().next().unwrap();
+    let block = ktx2::DfdBlockBasic::parse(dfd.data).unwrap();
+    let samples: Vec<_> = block.sample_information().collect();
+    assert_eq!(samples.len(), 3);
+    assert_eq!(samples[2].bit_offset, 16);
+    assert_eq!(samples[2].upper, 255);
+    let (key, value) = reader.key_value_data().next().unwrap();
+    assert_eq!((key, value), ("KTXwriter", &b"rsdf\0"[..]));
+  }
+
+  #[test]
+  fn vk_formats() {
+    use ktx2::Format;
+    let config = |quantization| RasterConfig {
+      quantization,
+      ..RasterConfig::default()
+    };
+    let u8 = config(Quantization::U8);
+    assert_eq!(format::<1>(u8), Some(Format::R8_UNORM));
+    assert_eq!(format::<2>(u8), Some(Format::R8G8_UNORM));
+    assert_eq!(format::<4>(u8), Some(Format::R8G8B8A8_UNORM));
+    let u16 = config(Quantization::U16);
+    assert_eq!(format::<1>(u16), Some(Format::R16_UNORM));
+    assert_eq!(format::<4>(u16), Some(Format::R16G16B16A16_UNORM));
+    let f16 = config(Quantization::F16);
+    assert_eq!(format::<2>(f16), Some(Format::R16G16_SFLOAT));
+    assert_eq!(format::<4>(f16), Some(Format::R16G16B16A16_SFLOAT));
+    let f32 = config(Quantization::F32);
+    assert_eq!(format::<1>(f32), Some(Format::R32_SFLOAT));
+    assert_eq!(format::<2>(f32), Some(Format::R32G32_SFLOAT));
+    assert_eq!(format::<4>(f32), Some(Format::R32G32B32A32_SFLOAT));
+  }
+
+  #[cfg(feature = "zlib")]
+  #[test]
+  fn to_ktx2_zlib() {
+    use std::io::Read;
+    let samples = (0..12).map(|i| [i as f32 - 6., 1., -1.]).collect();
+    let field = Field::from_samples([4, 3], samples);
+    let config = RasterConfig {
+      quantization: Quantization::F32,
+      ..RasterConfig::default()
+    };
+    let ktx2 = field.to_ktx2(config, Supercompression::Zlib);
+    let reader = ktx2::Reader::new(&ktx2).unwrap();
+    let header = reader.header();
+    assert_eq!(header.format, Some(ktx2::Format::R32G32B32_SFLOAT));
+    assert_eq!(
+      header.supercompression_scheme,
+      Some(ktx2::SupercompressionScheme::ZLIB)
+    );
+    let level = reader.levels().next().unwrap();
+    let mut texels = vec![];
+    flate2::read::ZlibDecoder::new(level.data)
+      .read_to_end(&mut texels)
+      .unwrap();
+    assert_eq!(texels.len() as u64, level.uncompressed_byte_length);
+    assert_eq!(texels[..4], (-6f32).to_le_bytes());
+  }
+}
@@ -1,29 +1,106 @@
 #![doc = include_str!("../../../README.md")]
 
+#[cfg(any(feature = "unstable", test))]
+mod adaptive;
+#[cfg(feature = "atlas")]
+mod atlas;
+#[cfg(feature = "atlas")]
+mod atlas_json;
+#[cfg(any(feature = "unstable", test))]
+mod axis;
+mod binary;
+mod bitmap;
+#[cfg(feature = "atlas")]
+mod bmfont;
+mod dds;
+mod em;
+mod field;
+#[cfg(feature = "atlas")]
+mod font_layout;
+mod font_source;
+mod glyph;
+mod hooks;
+#[cfg(feature = "png")]
 mod image;
+mod ktx;
+mod limits;
 mod math;
+mod npy;
+#[cfg(feature = "exr")]
+mod openexr;
+mod packed;
+#[cfg(feature = "atlas")]
+mod provenance;
+mod quality;
+mod raster;
+mod render;
+mod rgba;
+mod sequence;
 mod shape;
+#[cfg(any(feature = "unstable", test))]
+mod skeleton;
+mod soft_mask;
 
 use math::*;
-use shape::*;
 
+#[cfg(feature = "atlas")]
+pub use atlas::{Atlas, AtlasBuilder, AtlasGlyph, GlyphKey, GridAtlas};
+#[cfg(feature = "atlas")]
+pub use atlas_json::AtlasJsonError;
+pub use binary::DecodeError;
+pub use bitmap::Bitmap;
+pub use em::EmScale;
+pub use field::{Field, FieldView, Rect, SampleChannels};
+#[cfg(feature = "atlas")]
+pub use font_layout::{CharLayout, FontLayout};
+pub use font_source::{FontHandle, FontSource};
+pub use glyph::PreparedGlyph;
+pub use hooks::{FieldInfo, PostProcess};
+#[cfg(feature = "png")]
 pub use image::Image;
+pub use ktx::Supercompression;
+pub use limits::{LimitError, ShapeLimits};
 pub use math::{Point, Vector};
+pub use packed::ChannelPacking;
+#[cfg(feature = "atlas")]
+pub use provenance::Provenance;
+pub use quality::{CheckedField, FallbackPolicy, QualityReport};
+pub use raster::{
+  scrub_non_finite, DistanceMetric, Quantization, RasterConfig, RegionDesc,
+  SdfRange,
+};
+pub use render::{CpuRenderer, FieldRenderer};
+pub use rgba::AlphaMode;
+pub use sequence::{FrameSampler, Volume};
 pub use shape::{
   primitives::elliptical_arc, Colour, Colour::*, Contour, SegmentKind,
-  SegmentRef, Shape, Spline,
+  SegmentRef, Shape, Spline, Winner,
 };
 
-pub const MAX_DISTANCE: f32 = 5.;
-pub const MAX_COLOUR: f32 = 256.0;
+/// The default [`RasterConfig::max_distance`], in pixels
+pub(crate) const DEFAULT_MAX_DISTANCE: f32 = 5.;
+
+/// Function to convert a distance in the range
+/// `[-max_distance, max_distance]` to an 8-bit integer value centered in the
+/// middle of the 8bit range, to be stored in a colour channel in an image.
+#[inline]
+pub fn distance_color(distance: f32, max_distance: f32) -> u8 {
+  let normalised = (distance / max_distance).clamp(-1., 1.);
+  // the 256 levels of a byte, with the edge between the middle two
+  ((normalised + 1.) / 2. * 256. - 1.) as u8
+}
+
+/// The median of the channels of a multi-channel sample, which recovers the
+/// signed distance
+#[inline]
+pub fn median([a, b, c]: [f32; 3]) -> f32 {
+  a.min(b).max(a.max(b).min(c))
+}
 
-/// Function to convert a distance in the range [0, 1] to an 8-bit integer
-/// value centered in the middle of the 8bit range, to be stored in a colour
-/// channel in an image.
+/// The inverse of [`distance_color`], to read distances back out of an image
 #[inline]
-pub fn distance_color(distance: f32) -> u8 {
-  let distance = distance.clamp(-MAX_DISTANCE, MAX_DISTANCE);
-  (((distance + MAX_DISTANCE) / (2.0 * MAX_DISTANCE) * MAX_COLOUR) - 1.0) as u8
+pub fn color_distance(color: u8, max_distance: f32) -> f32 {
+  ((color as f32 + 1.) / 256. * 2. - 1.) * max_distance
 }
 
 /// A marker to store which end of a segment a point's distance references
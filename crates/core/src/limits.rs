@@ -0,0 +1,122 @@
+use crate::*;
+
+/// Limits on the complexity of a [`Shape`], see [`Shape::check_limits`]
+///
+/// Sampling a field takes time proportional to the number of pixels times
+/// the number of splines, so a server accepting user supplied SVGs or fonts
+/// should bound the shapes it will sample. Every limit is unbounded by
+/// default.
+///
+/// ```
+/// # use rsdf_core::*;
+/// let limits = ShapeLimits {
+///   max_segments: 10_000,
+///   ..ShapeLimits::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShapeLimits {
+  pub max_contours: usize,
+  pub max_segments: usize,
+  pub max_points: usize,
+}
+
+impl Default for ShapeLimits {
+  fn default() -> Self {
+    Self {
+      max_contours: usize::MAX,
+      max_segments: usize::MAX,
+      max_points: usize::MAX,
+    }
+  }
+}
+
+/// A [`ShapeLimits`] limit that a shape exceeded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LimitError {
+  Contours { limit: usize, found: usize },
+  Segments { limit: usize, found: usize },
+  Points { limit: usize, found: usize },
+}
+
+impl std::fmt::Display for LimitError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let (what, limit, found) = match *self {
+      LimitError::Contours { limit, found } => ("contours", limit, found),
+      LimitError::Segments { limit, found } => ("segments", limit, found),
+      LimitError::Points { limit, found } => ("points", limit, found),
+    };
+    write!(f, "the shape has {found} {what}, over the limit of {limit}")
+  }
+}
+
+impl std::error::Error for LimitError {}
+
+impl ShapeLimits {
+  /// Check counts of contours, segments & points against the limits, in
+  /// that order
+  pub fn check(
+    &self,
+    contours: usize,
+    segments: usize,
+    points: usize,
+  ) -> Result<(), LimitError> {
+    if contours > self.max_contours {
+      return Err(LimitError::Contours {
+        limit: self.max_contours,
+        found: contours,
+      });
+    }
+    if segments > self.max_segments {
+      return Err(LimitError::Segments {
+        limit: self.max_segments,
+        found: segments,
+      });
+    }
+    if points > self.max_points {
+      return Err(LimitError::Points {
+        limit: self.max_points,
+        found: points,
+      });
+    }
+    Ok(())
+  }
+}
+
+impl Shape {
+  /// Check that the shape is within the [`ShapeLimits`]
+  pub fn check_limits(&self, limits: ShapeLimits) -> Result<(), LimitError> {
+    limits.check(self.contours.len(), self.segments.len(), self.points.len())
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn check() {
+    let limits = ShapeLimits {
+      max_contours: 2,
+      max_segments: 10,
+      ..ShapeLimits::default()
+    };
+    assert_eq!(limits.check(2, 10, 1_000_000), Ok(()));
+    assert_eq!(
+      limits.check(3, 20, 0),
+      Err(LimitError::Contours { limit: 2, found: 3 })
+    );
+    assert_eq!(
+      limits.check(1, 11, 0),
+      Err(LimitError::Segments {
+        limit: 10,
+        found: 11
+      })
+    );
+    assert_eq!(
+      LimitError::Points { limit: 4, found: 5 }.to_string(),
+      "the shape has 5 points, over the limit of 4"
+    );
+  }
+}
@@ -0,0 +1,34 @@
+/// Convert an `f32` to the bits of an IEEE 754 half precision float
+///
+/// Rounds to the nearest representable value, with ties to even. Values too
+/// large for a half become infinite, and NaNs stay NaN.
+pub fn f32_to_f16_bits(value: f32) -> u16 {
+  ::half::f16::from_f32(value).to_bits()
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn f32_to_f16() {
+    assert_eq!(f32_to_f16_bits(0.), 0x0000);
+    assert_eq!(f32_to_f16_bits(-0.), 0x8000);
+    assert_eq!(f32_to_f16_bits(1.), 0x3c00);
+    assert_eq!(f32_to_f16_bits(-2.), 0xc000);
+    assert_eq!(f32_to_f16_bits(0.5), 0x3800);
+    assert_eq!(f32_to_f16_bits(65504.), 0x7bff);
+    assert_eq!(f32_to_f16_bits(65520.), 0x7c00);
+    assert_eq!(f32_to_f16_bits(f32::INFINITY), 0x7c00);
+    assert_eq!(f32_to_f16_bits(f32::NEG_INFINITY), 0xfc00);
+    assert!(f32_to_f16_bits(f32::NAN) & 0x7fff > 0x7c00);
+    // smallest subnormal
+    assert_eq!(f32_to_f16_bits(2f32.powi(-24)), 0x0001);
+    // largest subnormal
+    assert_eq!(f32_to_f16_bits(1023. / 1024. * 2f32.powi(-14)), 0x03ff);
+    // rounding
+    assert_eq!(f32_to_f16_bits(1. + 1. / 2048.), 0x3c00);
+    assert_eq!(f32_to_f16_bits(1. + 3. / 2048.), 0x3c02);
+    assert_eq!(f32_to_f16_bits(0.1), 0x2e66);
+  }
+}
@@ -1,9 +1,12 @@
+pub mod half;
 pub mod point;
 pub mod roots;
 pub mod vector;
 
+pub use half::*;
 pub use point::*;
 pub use roots::*;
 pub use vector::*;
 
+#[allow(unused_imports)]
 pub use std::f32::consts::*;
@@ -3,12 +3,20 @@ use std::ops::Sub;
 use super::*;
 
 /// A point in 2D space
+///
+/// Laid out as a pair of `f32`s, `x` then `y`, so slices of them can be
+/// handed to GPU buffers & foreign code as is.
 #[derive(Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
 pub struct Point {
   pub x: f32,
   pub y: f32,
 }
 
+const _: () = assert!(std::mem::size_of::<Point>() == 8);
+const _: () = assert!(std::mem::align_of::<Point>() == 4);
+
 impl std::fmt::Debug for Point {
   fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
     formatter
@@ -12,7 +12,23 @@ pub fn roots_in_range<const TERMS: usize, R: RangeBounds<f32>>(
   polynomial: &[f32; TERMS],
   range: R,
 ) -> ArrayVec<f32, TERMS> {
-  aberth::aberth(polynomial, 15, EPSILON)
+  roots_in_range_with_iterations(polynomial, range, 15)
+}
+
+/// Find real roots in the given range, allowing up to `max_iterations` rounds
+/// of the Aberth method
+///
+/// Higher degree polynomials can need more rounds than [`roots_in_range`]
+/// allows before their roots converge.
+pub fn roots_in_range_with_iterations<
+  const TERMS: usize,
+  R: RangeBounds<f32>,
+>(
+  polynomial: &[f32; TERMS],
+  range: R,
+  max_iterations: u32,
+) -> ArrayVec<f32, TERMS> {
+  aberth::aberth(polynomial, max_iterations, EPSILON)
     .iter()
     .filter(|root| root.im.abs() <= EPSILON && range.contains(&root.re))
     .map(|root| root.re)
@@ -23,29 +39,44 @@ pub fn roots_in_range<const TERMS: usize, R: RangeBounds<f32>>(
 ///
 /// `x` is the initial guess, `f` is the function and `df` & `ddf` are the
 /// first and second derivatives.
+///
+/// Works in double precision, since the functions it's used for are sums of
+/// trigonometric terms that cancel catastrophically in single precision.
 pub fn halleys_method(
-  mut x: f32,
-  f: impl Fn(f32) -> f32,
-  df: impl Fn(f32) -> f32,
-  ddf: impl Fn(f32) -> f32,
-) -> f32 {
-  for _ in 0..16 {
+  mut x: f64,
+  f: impl Fn(f64) -> f64,
+  df: impl Fn(f64) -> f64,
+  ddf: impl Fn(f64) -> f64,
+) -> f64 {
+  for _ in 0..32 {
     let fx = f(x);
-    if fx.abs() < 0.001 {
+    if fx == 0. {
       return x;
     }
     let dfx = df(x);
     let ddfx = ddf(x);
-    x -= (2. * fx * dfx) / (2. * dfx * dfx - fx * ddfx);
+    let step = (2. * fx * dfx) / (2. * dfx * dfx - fx * ddfx);
+    if !step.is_finite() {
+      return x;
+    }
+    x -= step;
+    if step.abs() <= HALLEY_TOLERANCE * x.abs().max(1.) {
+      return x;
+    }
   }
   x
 }
 
+/// Relative step size below which [`halleys_method`] is considered to have
+/// converged
+const HALLEY_TOLERANCE: f64 = 1e-12;
+
 #[cfg(any(test, doctest))]
 mod tests {
   use super::*;
 
   #[test]
+  #[allow(clippy::useless_vec)]
   fn range() {
     {
       // x^3 -12x^2 + 39x - 28 = 0
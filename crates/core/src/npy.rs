@@ -0,0 +1,74 @@
+use crate::*;
+use std::io::Write;
+
+impl<const N: usize> Field<N> {
+  /// Encode the field as a NumPy `.npy` array of little-endian 32-bit
+  /// floats, with the shape `(height, width, N)`
+  ///
+  /// Scientific & machine learning tools can load the raw distances with
+  /// `numpy.load`, without a round trip through a quantized image. Each
+  /// plane of distances is a slice along the last axis, e.g.
+  /// `array[..., 0]`.
+  ///
+  /// ```
+  /// # use rsdf_core::*;
+  /// let field = Field::from_samples([2, 1], vec![[-1.5], [2.]]);
+  /// let npy = field.to_npy();
+  /// assert!(npy.starts_with(b"\x93NUMPY"));
+  /// assert_eq!(npy.len(), 128 + 2 * 4);
+  /// ```
+  #[must_use]
+  pub fn to_npy(&self) -> Vec<u8> {
+    let mut bytes = vec![];
+    let _ = self.write_npy(&mut bytes);
+    bytes
+  }
+
+  /// Write the field to a `.npy` file or stream, as encoded by
+  /// [`Field::to_npy`]
+  pub fn write_npy(&self, mut write: impl Write) -> std::io::Result<()> {
+    let mut header = format!(
+      "{{'descr': '<f4', 'fortran_order': False, 'shape': ({}, {}, {}), }}",
+      self.height(),
+      self.width(),
+      N
+    );
+    // the magic, version & header length take 10 bytes, & the data must
+    // start on a 64 byte boundary, after a newline
+    let len = (10 + header.len() + 1).next_multiple_of(64) - 10;
+    while header.len() < len - 1 {
+      header.push(' ');
+    }
+    header.push('\n');
+
+    write.write_all(b"\x93NUMPY\x01\x00")?;
+    write.write_all(&(len as u16).to_le_bytes())?;
+    write.write_all(header.as_bytes())?;
+    let mut data = Vec::with_capacity(self.samples().len() * N * 4);
+    for sample in self.samples() {
+      for &channel in sample {
+        data.extend_from_slice(&channel.to_le_bytes());
+      }
+    }
+    write.write_all(&data)
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn to_npy() {
+    let field = Field::from_samples([1, 2], vec![[1., 2., 3.], [4., 5., 6.]]);
+    let npy = field.to_npy();
+    let len = u16::from_le_bytes([npy[8], npy[9]]) as usize;
+    assert_eq!((10 + len) % 64, 0);
+    let header = std::str::from_utf8(&npy[10..10 + len]).unwrap();
+    assert!(header.contains("'shape': (2, 1, 3)"));
+    assert!(header.ends_with('\n'));
+    let data = &npy[10 + len..];
+    assert_eq!(data.len(), 6 * 4);
+    assert_eq!(data[12..16], 4f32.to_le_bytes());
+  }
+}
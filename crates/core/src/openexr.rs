@@ -0,0 +1,76 @@
+use crate::*;
+use exr::prelude::{
+  AnyChannel, AnyChannels, FlatSamples, SmallVec, WritableImage,
+};
+use std::io::{Seek, Write};
+use std::path::Path;
+
+impl<const N: usize> Field<N> {
+  /// Write the field's raw distances to an OpenEXR image at `path`, one
+  /// 32-bit float channel per channel of the field
+  ///
+  /// Requires the `exr` feature. Unlike the fixed point
+  /// [`Quantization`]s nothing is clamped, so image tools can inspect the
+  /// distances far from the edges too. Channels are named `R`, `G`, `B` &
+  /// `A` in order, or by their index beyond the fourth.
+  pub fn write_exr(&self, path: impl AsRef<Path>) -> exr::error::UnitResult {
+    exr_image(self).write().to_file(path)
+  }
+
+  /// Write the field to an OpenEXR image in a buffer or stream, as
+  /// [`Field::write_exr`] does to a file
+  pub fn write_exr_to(
+    &self,
+    write: impl Write + Seek,
+  ) -> exr::error::UnitResult {
+    exr_image(self).write().to_buffered(write)
+  }
+}
+
+type ExrImage = exr::image::Image<exr::image::Layer<AnyChannels<FlatSamples>>>;
+
+fn exr_image<const N: usize>(field: &Field<N>) -> ExrImage {
+  let channels: SmallVec<_> = (0..N)
+    .map(|channel| {
+      let name = match ["R", "G", "B", "A"].get(channel) {
+        Some(name) => name.to_string(),
+        None => channel.to_string(),
+      };
+      let samples = FlatSamples::F32(field.channel(channel).collect());
+      AnyChannel::new(name.as_str(), samples)
+    })
+    .collect();
+  let [width, height] = field.size();
+  exr::image::Image::from_channels(
+    (width, height),
+    AnyChannels::sort(channels),
+  )
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+  use exr::prelude::read_first_flat_layer_from_file;
+
+  #[test]
+  fn write_exr() {
+    let field =
+      Field::from_samples([2, 1], vec![[-7.5, 1., 2.], [3., 4., 5.]]);
+    let mut buffer = std::io::Cursor::new(vec![]);
+    field.write_exr_to(&mut buffer).unwrap();
+    assert_eq!(buffer.get_ref()[..4], [0x76, 0x2f, 0x31, 0x01]);
+
+    let path = std::env::temp_dir()
+      .join(format!("rsdf_write_exr_{}.exr", std::process::id()));
+    field.write_exr(&path).unwrap();
+    let image = read_first_flat_layer_from_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let channels = &image.layer_data.channel_data.list;
+    assert_eq!(channels[0].name.to_string(), "B");
+    assert_eq!(channels[2].name.to_string(), "R");
+    assert_eq!(
+      channels[2].sample_data.value_by_flat_index(0).to_f32(),
+      -7.5
+    );
+  }
+}
@@ -0,0 +1,99 @@
+use crate::*;
+
+/// Describes how the single channel fields of two shapes are packed into the
+/// red & green channels of a [`Field<2>`], see [`Shape::sample_packed_field`]
+///
+/// Store it alongside the texture, so that a shader knows which channel
+/// holds which shape, and how to turn them back into pixels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelPacking {
+  /// The name of the shape in each channel, red first
+  pub names: [String; 2],
+  /// The [`RasterConfig::max_distance`] both channels were sampled with
+  pub max_distance: f32,
+}
+
+impl ChannelPacking {
+  /// The channel holding the shape with the given `name`
+  pub fn channel(&self, name: &str) -> Option<usize> {
+    self.names.iter().position(|n| n == name)
+  }
+
+  /// Split one channel of a packed field back out into a field of its own
+  pub fn unpack(&self, field: &Field<2>, channel: usize) -> Field<1> {
+    let samples = field.samples().iter().map(|s| [s[channel]]).collect();
+    Field::from_samples(field.size(), samples)
+  }
+}
+
+impl Shape {
+  /// Sample the single channel fields of this shape and `other` into the
+  /// red & green channels of one field, in a single pass over the pixels
+  ///
+  /// Useful for effects that need two independent shapes per glyph, such as
+  /// a glyph's body along with its outline or halo. Both shapes are sampled
+  /// with the same `config`, though [`RasterConfig::fast_fill`] is ignored.
+  /// The `names` of the shapes are recorded in the returned
+  /// [`ChannelPacking`].
+  ///
+  /// ```
+  /// # use rsdf_builder::ShapeBuilder;
+  /// # use rsdf_core::*;
+  /// let square = |min: f32, max: f32| {
+  ///   ShapeBuilder::new()
+  ///     .contour((min, min))
+  ///     .line((max, min))
+  ///     .line((max, max))
+  ///     .line((min, max))
+  ///     .end_contour()
+  ///     .build()
+  /// };
+  /// let (body, halo) = (square(10., 20.), square(8., 22.));
+  /// let config = RasterConfig::default();
+  /// let (field, packing) =
+  ///   body.sample_packed_field(&halo, ["body", "halo"], [32, 32], config);
+  /// assert_eq!(packing.channel("halo"), Some(1));
+  /// assert_eq!(field.get([15, 9]), [-1., 1.]);
+  /// ```
+  #[must_use]
+  pub fn sample_packed_field(
+    &self,
+    other: &Shape,
+    names: [&str; 2],
+    size: [usize; 2],
+    config: RasterConfig,
+  ) -> (Field<2>, ChannelPacking) {
+    let [width, height] = size;
+    let row = |y: usize| if config.flip_y { height - 1 - y } else { y };
+    let mut field = Field::new(size);
+    for y in 0..height {
+      for x in 0..width {
+        let [first] = self.sample_pixel_channels([x, y], size, config);
+        let [second] = other.sample_pixel_channels([x, y], size, config);
+        field.set([x, row(y)], [first, second]);
+      }
+    }
+    let packing = ChannelPacking {
+      names: names.map(String::from),
+      max_distance: config.max_distance,
+    };
+    (field, packing)
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn unpack() {
+    let packing = ChannelPacking {
+      names: ["body".into(), "halo".into()],
+      max_distance: 5.,
+    };
+    let field = Field::from_samples([2, 1], vec![[1., 2.], [3., 4.]]);
+    assert_eq!(packing.channel("body"), Some(0));
+    assert_eq!(packing.channel("shadow"), None);
+    assert_eq!(packing.unpack(&field, 1).into_samples(), [[2.], [4.]]);
+  }
+}
@@ -0,0 +1,138 @@
+use crate::shape::checksum::Fnv1a;
+use crate::*;
+use std::collections::BTreeMap;
+
+/// A record of how an [`Atlas`] was generated, so that a rendered atlas can
+/// be traced back to the exact parameters that produced it
+///
+/// Records are plain key-value pairs, kept in order of key. The common ones
+/// have their own methods, and tools can log anything else they need with
+/// [`record`](Self::record).
+///
+/// ```
+/// # use rsdf_core::*;
+/// let config = RasterConfig::default();
+/// let provenance = Provenance::new(config)
+///   .colouring("alternating")
+///   .font("Sans", b"font data")
+///   .record("pipeline", "ui-atlas v2");
+/// let atlas = AtlasBuilder::<3>::new(64).provenance(provenance).build();
+/// assert_eq!(atlas.provenance.get("colouring"), Some("alternating"));
+/// assert!(atlas.provenance.get("font.Sans").is_some());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Provenance {
+  pub records: BTreeMap<String, String>,
+}
+
+impl Provenance {
+  /// Start a record of a generation using `config`, along with the version
+  /// of rsdf_core doing the generating
+  pub fn new(config: RasterConfig) -> Self {
+    Provenance::default()
+      .record("version", env!("CARGO_PKG_VERSION"))
+      .record("config", format!("{config:?}"))
+  }
+
+  /// Record a value under `key`, replacing any value already recorded there
+  pub fn record(
+    mut self,
+    key: impl Into<String>,
+    value: impl Into<String>,
+  ) -> Self {
+    self.records.insert(key.into(), value.into());
+    self
+  }
+
+  /// Record the strategy used to colour the shapes' splines, including its
+  /// seed if it has one
+  pub fn colouring(self, strategy: impl Into<String>) -> Self {
+    self.record("colouring", strategy)
+  }
+
+  /// Record a checksum of the data of the font registered as `name`, under
+  /// `font.<name>`
+  ///
+  /// The checksum is stable across runs and platforms, so it can be compared
+  /// against the font files at hand to check the atlas is up to date.
+  pub fn font(self, name: &str, data: &[u8]) -> Self {
+    let mut hasher = Fnv1a::new();
+    hasher.write(data);
+    self.record(font_record(name), format!("{:016x}", hasher.0))
+  }
+
+  /// Record the checksum of a loaded font, as [`Provenance::font`] does for
+  /// its data
+  pub fn font_handle(self, name: &str, font: &FontHandle) -> Self {
+    self.record(font_record(name), format!("{:016x}", font.checksum()))
+  }
+
+  /// The checksum recorded for the font registered as `name`, see
+  /// [`Provenance::font`]
+  ///
+  /// ```
+  /// # use rsdf_core::*;
+  /// # use std::sync::Arc;
+  /// let mut font =
+  ///   FontHandle::load(FontSource::Bytes(Arc::from(&b"v1"[..]))).unwrap();
+  /// let provenance = Provenance::default().font_handle("Sans", &font);
+  /// assert_eq!(provenance.font_checksum("Sans"), Some(font.checksum()));
+  ///
+  /// font.replace(FontSource::Bytes(Arc::from(&b"v2"[..]))).unwrap();
+  /// assert_ne!(provenance.font_checksum("Sans"), Some(font.checksum()));
+  /// ```
+  pub fn font_checksum(&self, name: &str) -> Option<u64> {
+    let record = self.get(&font_record(name))?;
+    u64::from_str_radix(record, 16).ok()
+  }
+
+  /// The value recorded under `key`
+  pub fn get(&self, key: &str) -> Option<&str> {
+    self.records.get(key).map(String::as_str)
+  }
+}
+
+fn font_record(name: &str) -> String {
+  format!("font.{name}")
+}
+
+impl std::fmt::Display for Provenance {
+  /// One `key = value` line per record, to be logged or written alongside
+  /// the atlas
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    for (key, value) in &self.records {
+      writeln!(f, "{key} = {value}")?;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn provenance() {
+    let config = RasterConfig {
+      max_distance: 3.,
+      ..RasterConfig::default()
+    };
+    let provenance = Provenance::new(config)
+      .font("Sans", b"abc")
+      .font("Icons", b"abd");
+    assert_eq!(provenance.get("version"), Some(env!("CARGO_PKG_VERSION")));
+    assert!(provenance
+      .get("config")
+      .unwrap()
+      .contains("max_distance: 3.0"));
+    assert_ne!(provenance.get("font.Sans"), provenance.get("font.Icons"));
+    // the FNV-1a hash of "abc"
+    assert_eq!(provenance.get("font.Sans"), Some("e71fa2190541574b"));
+    assert_eq!(provenance.font_checksum("Sans"), Some(0xe71fa2190541574b));
+    assert_eq!(provenance.font_checksum("Serif"), None);
+
+    let text = provenance.to_string();
+    assert_eq!(text.lines().count(), 4);
+    assert!(text.starts_with("config = RasterConfig {"));
+  }
+}
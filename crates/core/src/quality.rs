@@ -0,0 +1,287 @@
+use crate::*;
+
+/// How faithfully a multi-channel field reproduces the true signed distance
+/// of its shape, see [`Shape::quality_report`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct QualityReport {
+  /// The number of pixels compared
+  pub pixels: usize,
+  /// Pixels where the median of the channels lands on the wrong side of the
+  /// edge, by more than the threshold
+  pub artifacts: usize,
+  /// Pixels holding a NaN or infinite channel, which need scrubbing before
+  /// the field can be used, see [`scrub_non_finite`]
+  pub non_finite: usize,
+  /// Pixels where the median of the channels differs from the true signed
+  /// distance by more than the threshold, both clamped to the
+  /// [`max_distance`](RasterConfig::max_distance)
+  ///
+  /// These include the pixels outside of corners, where pseudo-distances
+  /// legitimately differ, so this measures how closely the field follows
+  /// the true distance rather than how many pixels render wrongly.
+  pub deviating: usize,
+  /// Pixels replaced by a correction of the field, such as the single
+  /// channel fallback of [`Shape::sample_field_checked`]
+  pub corrected: usize,
+}
+
+impl QualityReport {
+  /// The fraction of pixels whose median deviates from the true distance,
+  /// from `0` (exact) to `1`
+  pub fn deviation_fraction(&self) -> f32 {
+    if self.pixels == 0 {
+      return 0.;
+    }
+    self.deviating as f32 / self.pixels as f32
+  }
+
+  /// The fraction of pixels that are artifacts or non-finite, from `0`
+  /// (perfect) to `1`
+  pub fn artifact_fraction(&self) -> f32 {
+    if self.pixels == 0 {
+      return 0.;
+    }
+    (self.artifacts + self.non_finite) as f32 / self.pixels as f32
+  }
+}
+
+impl std::fmt::Display for QualityReport {
+  /// The counts as `name=count` pairs, the form an atlas records them in
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(
+      f,
+      "pixels={} artifacts={} non_finite={} deviating={} corrected={}",
+      self.pixels,
+      self.artifacts,
+      self.non_finite,
+      self.deviating,
+      self.corrected
+    )
+  }
+}
+
+impl std::str::FromStr for QualityReport {
+  type Err = std::num::ParseIntError;
+
+  /// Parse a report written by its [`Display`](std::fmt::Display)
+  /// implementation, with counts that are missing left at zero
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let mut report = QualityReport::default();
+    for (name, count) in s.split_whitespace().filter_map(|p| p.split_once('='))
+    {
+      let count = count.parse()?;
+      match name {
+        "pixels" => report.pixels = count,
+        "artifacts" => report.artifacts = count,
+        "non_finite" => report.non_finite = count,
+        "deviating" => report.deviating = count,
+        "corrected" => report.corrected = count,
+        _ => {},
+      }
+    }
+    Ok(report)
+  }
+}
+
+impl Shape {
+  /// Compare a multi-channel field against the true signed distance of the
+  /// shape
+  ///
+  /// The field should have been sampled from this shape with the same
+  /// [`RasterConfig`], e.g. by [`Shape::sample_field`]. Away from the edges
+  /// pseudo-distances legitimately differ from the true distance (outside
+  /// of corners, for example), so only pixels whose median has the wrong
+  /// sign count as artifacts; these are the ones that show up as speckles
+  /// and notches when the field is rendered. Pixels whose median differs
+  /// from the true distance by more than the `threshold` are counted
+  /// separately, see [`QualityReport::deviating`].
+  #[must_use]
+  pub fn quality_report(
+    &self,
+    field: &Field<3>,
+    threshold: f32,
+    config: RasterConfig,
+  ) -> QualityReport {
+    let size = field.size();
+    let height = field.height();
+
+    let mut report = QualityReport {
+      pixels: field.samples().len(),
+      ..QualityReport::default()
+    };
+    for (x, y, sample) in field.pixels() {
+      if !sample.iter().all(|channel| channel.is_finite()) {
+        report.non_finite += 1;
+        continue;
+      }
+      let y = if config.flip_y { height - 1 - y } else { y };
+      let [expected] = self.sample_pixel_channels::<1>([x, y], size, config);
+      let median = median(sample);
+      if median * expected.signum() < -threshold {
+        report.artifacts += 1;
+      }
+      let clamp = |d: f32| d.clamp(-config.max_distance, config.max_distance);
+      if (clamp(median) - clamp(expected)).abs() > threshold {
+        report.deviating += 1;
+      }
+    }
+    report
+  }
+}
+
+/// When [`Shape::sample_field_checked`] should give up on a multi-channel
+/// field and fall back to a single channel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FallbackPolicy {
+  /// How far onto the wrong side of the edge a pixel has to be to count as
+  /// an artifact, see [`Shape::quality_report`]
+  pub threshold: f32,
+  /// The largest [`QualityReport::artifact_fraction`] that is accepted
+  pub max_artifact_fraction: f32,
+}
+
+impl Default for FallbackPolicy {
+  fn default() -> Self {
+    FallbackPolicy {
+      threshold: 0.25,
+      max_artifact_fraction: 0.,
+    }
+  }
+}
+
+/// A field sampled by [`Shape::sample_field_checked`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckedField {
+  pub field: Field<3>,
+  /// The quality of the multi-channel field, even if it was replaced, in
+  /// which case its artifacts are counted as
+  /// [`corrected`](QualityReport::corrected)
+  pub report: QualityReport,
+  /// Whether the multi-channel field was replaced by the single channel
+  /// distance, copied into all three channels
+  pub single_channel: bool,
+}
+
+impl Shape {
+  /// Sample the multi-channel field of the shape, falling back to a single
+  /// channel if it has too many artifacts
+  ///
+  /// Shapes that defeat the edge colouring, such as heavily self-overlapping
+  /// decorative glyphs, are regenerated as a plain signed distance field,
+  /// trading sharp corners for correctness. The single channel is copied into
+  /// all three channels, so it can be stored and rendered like any other
+  /// multi-channel field.
+  #[must_use]
+  pub fn sample_field_checked(
+    &self,
+    size: [usize; 2],
+    config: RasterConfig,
+    policy: FallbackPolicy,
+  ) -> CheckedField {
+    let field = self.sample_field(size, config);
+    let report = self.quality_report(&field, policy.threshold, config);
+    if report.artifact_fraction() <= policy.max_artifact_fraction {
+      return CheckedField {
+        field,
+        report,
+        single_channel: false,
+      };
+    }
+
+    let single: Field<1> = self.sample_field(size, config);
+    let samples = single.into_samples().into_iter().map(|[d]| [d; 3]);
+    CheckedField {
+      field: Field::from_samples(size, samples.collect()),
+      report: QualityReport {
+        corrected: report.artifacts + report.non_finite,
+        ..report
+      },
+      single_channel: true,
+    }
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  /// A square spanning 4..12 of a 16x16 canvas, with each edge a spline of
+  /// its own colour
+  fn square(colours: [Colour; 4]) -> Shape {
+    Shape::rectangle((4., 4.), (12., 12.), &colours)
+  }
+
+  #[test]
+  fn quality_report() {
+    let shape = square([Magenta, Yellow, Magenta, Yellow]);
+    for flip_y in [false, true] {
+      let config = RasterConfig {
+        flip_y,
+        ..RasterConfig::default()
+      };
+      let mut field = shape.sample_field([16, 16], config);
+      let report = shape.quality_report(&field, 0.01, config);
+      assert_eq!(
+        report,
+        QualityReport {
+          pixels: 256,
+          artifacts: 0,
+          non_finite: 0,
+          // the pseudo-distances outside of the corners
+          deviating: 49,
+          corrected: 0,
+        }
+      );
+
+      field.set([8, 8], [-1.; 3]);
+      field.set([9, 8], [4., f32::NAN, 4.]);
+      field.set([0, 0], [-1., 3., 2.]);
+      // inaccurate, but on the right side of the edge
+      field.set([15, 15], [-1.; 3]);
+      field.set([3, 8], [-0.005; 3]);
+      let report = shape.quality_report(&field, 0.01, config);
+      assert_eq!(report.artifacts, 2);
+      assert_eq!(report.non_finite, 1);
+      assert_eq!(report.artifact_fraction(), 3. / 256.);
+      // the corners were already off the true distance
+      assert_eq!(report.deviating, 49 + 2);
+      assert_eq!(report.deviation_fraction(), 51. / 256.);
+      assert_eq!(report.to_string().parse(), Ok(report));
+    }
+  }
+
+  #[test]
+  fn sample_field_checked() {
+    let config = RasterConfig::default();
+    let policy = FallbackPolicy::default();
+
+    let good = square([Magenta, Yellow, Magenta, Yellow]);
+    let checked = good.sample_field_checked([16, 16], config, policy);
+    assert!(!checked.single_channel);
+    assert_eq!(checked.report.corrected, 0);
+    assert_eq!(checked.field, good.sample_field([16, 16], config));
+
+    // edges of a single channel each can't reproduce the corners
+    let bad = square([Red, Green, Blue, Red]);
+    let checked = bad.sample_field_checked([16, 16], config, policy);
+    assert!(checked.single_channel);
+    assert!(checked.report.artifacts > 0);
+    assert_eq!(checked.report.corrected, checked.report.artifacts);
+    let single: Field<1> = bad.sample_field([16, 16], config);
+    for ((_, _, [r, g, b]), [d]) in
+      checked.field.pixels().zip(single.samples())
+    {
+      assert_eq!([r, g, b], [*d; 3]);
+    }
+    let report = bad.quality_report(&checked.field, policy.threshold, config);
+    assert_eq!(report.artifacts, 0);
+
+    // unless the policy accepts them
+    let lenient = FallbackPolicy {
+      max_artifact_fraction: 1.,
+      ..policy
+    };
+    let checked = bad.sample_field_checked([16, 16], config, lenient);
+    assert!(!checked.single_channel);
+  }
+}
@@ -0,0 +1,925 @@
+use crate::*;
+use std::ops::Range;
+
+/// Settings used when rasterizing a [`Shape`] into a [`Bitmap`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RasterConfig {
+  /// Width of the soft border, in pixels
+  ///
+  /// Within this distance of the edge of the canvas the field is pulled
+  /// towards "fully outside", so that a sprite rendered with padding doesn't
+  /// show a hard cut where the shape meets the edge of the canvas. A border
+  /// of `0` leaves the field untouched.
+  pub border: f32,
+  /// Distance from a spline, as a multiple of the
+  /// [`max_distance`](Self::max_distance), beyond which
+  /// its pseudo-distance is replaced by the true distance
+  ///
+  /// Pseudo-distances measured against the extensions of a spline can be
+  /// wildly wrong far away from it, and occasionally win a channel, creating
+  /// streaks in the field.
+  pub pseudo_distance_limit: f32,
+  /// Threshold below which a pair of distances (or orthogonalities) are
+  /// considered equal when choosing the nearest spline
+  ///
+  /// Points that are equidistant to a pair of splines, such as those on the
+  /// bisector of a corner, are resolved by orthogonality.
+  pub tie_epsilon: f32,
+  /// Skip sampling pixels that are far inside or outside of the shape
+  ///
+  /// The canvas is first classified on a coarse grid, using a conservative
+  /// distance to the segments. Cells with no segment within the
+  /// [`max_distance`](Self::max_distance) are filled with the saturated
+  /// distance, and only the band of pixels near the edges is sampled
+  /// exactly. Pseudo-distances in the saturated cells
+  /// are ignored, so the result is only exact when the
+  /// [`pseudo_distance_limit`](Self::pseudo_distance_limit) is at most `1`.
+  ///
+  /// Only the fixed point [`quantization`](Self::quantization)s clamp
+  /// distances to the `max_distance`, so fast fill is ignored for
+  /// [`Quantization::F16`] and [`Quantization::F32`], which keep the exact
+  /// distance of every pixel.
+  pub fast_fill: bool,
+  /// Return the rows of [`Shape::sample_grid`] bottom-up, for consumers that
+  /// expect a bottom-left origin
+  ///
+  /// Sampling is unaffected; only the order the rows are written in changes.
+  pub flip_y: bool,
+  /// The format channels are written in by [`Shape::rasterize_into`]
+  pub quantization: Quantization,
+  /// The norm distances are measured in
+  ///
+  /// Anything other than [`DistanceMetric::Euclidean`] gives a stylised
+  /// field, for effects like diamond or boxy glows.
+  pub metric: DistanceMetric,
+  /// The distance, in pixels, mapped to the ends of the fixed point
+  /// [`Quantization`]s
+  ///
+  /// Distances beyond it are clamped. A larger range lets effects like
+  /// outlines and glows reach further from the edge, at the cost of
+  /// precision near it; glyphs sharing an atlas can each use their own
+  /// range, e.g. a small one for tiny punctuation and a larger one for
+  /// complex glyphs. An [`Atlas`] stores the range with each glyph, see
+  /// [`AtlasGlyph::max_distance`], for the shader to turn the normalised
+  /// channels back into pixels:
+  ///
+  /// ```glsl
+  /// float distance = (median(texel.r, texel.g, texel.b) - 0.5)
+  ///   * 2.0 * glyph_max_distance;
+  /// ```
+  pub max_distance: f32,
+  /// Sample single channel fields as pseudo-distances, as in msdfgen's PSDF,
+  /// see [`Shape::sample_pseudo`]
+  ///
+  /// Applies wherever a single channel is sampled through
+  /// [`SampleChannels`], such as [`Shape::sample_field`] and
+  /// [`PreparedGlyph::draw_sdf`]; multi-channel fields are unaffected.
+  pub pseudo_sdf: bool,
+}
+
+/// The width of the band of distances a field covers, from fully outside to
+/// fully inside, as given to msdfgen's `-pxrange` & `-range`
+///
+/// Converts into a [`RasterConfig::max_distance`], which is half the width.
+///
+/// ```
+/// # use rsdf_core::*;
+/// // a glyph drawn at 0.02 pixels per font unit
+/// let config = RasterConfig::default().with_range(SdfRange::Units(200.), 0.02);
+/// assert_eq!(config.max_distance, 2.);
+/// assert_eq!(SdfRange::Pixels(4.).max_distance(0.02), 2.);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum SdfRange {
+  /// A width in pixels of the canvas, the same for every glyph
+  Pixels(f32),
+  /// A width in the units of the shape's coordinates, such as font units,
+  /// which scales along with the glyph
+  Units(f32),
+}
+
+impl SdfRange {
+  /// The distance, in pixels, from the edge to either end of the range, for
+  /// a shape drawn at `pixels_per_unit`
+  pub fn max_distance(self, pixels_per_unit: f32) -> f32 {
+    match self {
+      SdfRange::Pixels(width) => width / 2.,
+      SdfRange::Units(width) => width * pixels_per_unit / 2.,
+    }
+  }
+}
+
+impl RasterConfig {
+  /// Set the [`max_distance`](Self::max_distance) from an [`SdfRange`], for
+  /// a shape drawn at `pixels_per_unit`
+  #[must_use]
+  pub fn with_range(self, range: SdfRange, pixels_per_unit: f32) -> Self {
+    RasterConfig {
+      max_distance: range.max_distance(pixels_per_unit),
+      ..self
+    }
+  }
+}
+
+/// The norm used to measure the distance to the nearest point of a shape
+///
+/// The nearest point is always found using the euclidean distance, so this
+/// only changes how far away it is considered to be.
+///
+/// More metrics may be added, so downstream matches need a wildcard arm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum DistanceMetric {
+  /// The straight-line distance
+  #[default]
+  Euclidean,
+  /// The sum of the horizontal and vertical distances, giving diamond
+  /// shaped falloff around corners
+  Manhattan,
+  /// The larger of the horizontal and vertical distances, giving square
+  /// falloff around corners
+  Chebyshev,
+}
+
+impl DistanceMetric {
+  /// The length of a vector under this metric
+  pub fn length(self, vector: Vector) -> f32 {
+    match self {
+      DistanceMetric::Euclidean => vector.abs(),
+      DistanceMetric::Manhattan => vector.x.abs() + vector.y.abs(),
+      DistanceMetric::Chebyshev => vector.x.abs().max(vector.y.abs()),
+    }
+  }
+}
+
+/// The format each channel of a pixel is written in
+///
+/// More formats may be added, so downstream matches need a wildcard arm:
+///
+/// ```compile_fail
+/// # use rsdf_core::Quantization;
+/// fn is_float(quantization: Quantization) -> bool {
+///   match quantization {
+///     Quantization::U8 | Quantization::U16 => false,
+///     Quantization::F16 | Quantization::F32 => true,
+///   }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum Quantization {
+  /// 8-bit unsigned normalised, see [`distance_color`]
+  #[default]
+  U8,
+  /// 16-bit unsigned normalised, mapping
+  /// `[-max_distance, max_distance]` to the full range, see
+  /// [`RasterConfig::max_distance`]
+  U16,
+  /// Half precision float holding the signed distance in pixels
+  F16,
+  /// Single precision float holding the signed distance in pixels
+  F32,
+}
+
+impl Quantization {
+  /// The number of bytes taken by each channel
+  pub fn channel_size(self) -> usize {
+    match self {
+      Quantization::U8 => 1,
+      Quantization::U16 | Quantization::F16 => 2,
+      Quantization::F32 => 4,
+    }
+  }
+
+  /// Whether distances are clamped to `[-max_distance, max_distance]`
+  pub(crate) fn is_fixed_point(self) -> bool {
+    matches!(self, Quantization::U8 | Quantization::U16)
+  }
+
+  /// Write a distance into `out`, which must be [`channel_size`] bytes long,
+  /// with the fixed point formats covering `[-max_distance, max_distance]`
+  ///
+  /// Multi-byte formats are written in native byte order, ready to be
+  /// uploaded to the GPU.
+  ///
+  /// [`channel_size`]: Self::channel_size
+  pub fn write_with_range(
+    self,
+    distance: f32,
+    max_distance: f32,
+    out: &mut [u8],
+  ) {
+    match self {
+      Quantization::U8 => out[0] = distance_color(distance, max_distance),
+      Quantization::U16 => {
+        let normalised = ((distance / max_distance).clamp(-1., 1.) + 1.) / 2.;
+        let value = (normalised * u16::MAX as f32).round() as u16;
+        out.copy_from_slice(&value.to_ne_bytes());
+      },
+      Quantization::F16 => {
+        out.copy_from_slice(&f32_to_f16_bits(distance).to_ne_bytes())
+      },
+      Quantization::F32 => out.copy_from_slice(&distance.to_ne_bytes()),
+    }
+  }
+}
+
+/// Describes a sub-rectangle of a caller-provided pixel buffer
+///
+/// Used by [`Shape::rasterize_into`] to write straight into a larger buffer,
+/// such as a mapped staging texture or the CPU copy of an atlas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegionDesc {
+  /// Byte offset of the region's top-left pixel within the buffer
+  pub offset: usize,
+  /// Width of the region in pixels
+  pub width: usize,
+  /// Height of the region in pixels
+  pub height: usize,
+  /// Number of bytes from the start of one row to the start of the next
+  pub row_pitch: usize,
+  /// Number of bytes from one pixel to the next within a row
+  ///
+  /// Must be at least 3 (or `N`, for [`Shape::rasterize_channels_into`])
+  /// channels of the [`RasterConfig::quantization`]; any
+  /// extra bytes, e.g. an alpha channel, are left untouched.
+  pub pixel_stride: usize,
+}
+
+/// Size in pixels of the cells used to classify the canvas when
+/// [`RasterConfig::fast_fill`] is enabled
+const FAST_FILL_CELL: usize = 8;
+
+impl Default for RasterConfig {
+  fn default() -> Self {
+    RasterConfig {
+      border: 0.,
+      pseudo_distance_limit: f32::INFINITY,
+      tie_epsilon: 0.0001,
+      fast_fill: false,
+      flip_y: false,
+      quantization: Quantization::U8,
+      metric: DistanceMetric::Euclidean,
+      max_distance: DEFAULT_MAX_DISTANCE,
+      pseudo_sdf: false,
+    }
+  }
+}
+
+impl Shape {
+  /// Sample the multi-channel signed pseudo distance of the shape for the
+  /// pixel at `coords` on a canvas of the given `size`, applying the
+  /// [`RasterConfig`]
+  #[must_use]
+  pub fn sample_pixel(
+    &self,
+    coords: [usize; 2],
+    size: [usize; 2],
+    config: RasterConfig,
+  ) -> [f32; 3] {
+    self.sample_pixel_channels(coords, size, config)
+  }
+
+  /// Sample `N` channels of the shape for the pixel at `coords` on a canvas
+  /// of the given `size`, applying the [`RasterConfig`]
+  #[must_use]
+  pub fn sample_pixel_channels<const N: usize>(
+    &self,
+    coords: [usize; 2],
+    size: [usize; 2],
+    config: RasterConfig,
+  ) -> [f32; N]
+  where
+    Self: SampleChannels<N>,
+  {
+    let point = Point::from((coords[0] as f32, coords[1] as f32));
+    apply_border(self.sample_channels(point, config), coords, size, config)
+  }
+
+  /// Sample the multi-channel signed pseudo distance of the shape for every
+  /// pixel on a canvas of the given `size`, applying the [`RasterConfig`]
+  ///
+  /// The samples are returned in row-major order.
+  #[must_use]
+  pub fn sample_grid(
+    &self,
+    size: [usize; 2],
+    config: RasterConfig,
+  ) -> Vec<[f32; 3]> {
+    self.sample_field(size, config).into_samples()
+  }
+
+  /// Sample `N` channels of the shape for every pixel on a canvas of the
+  /// given `size`, applying the [`RasterConfig`]
+  ///
+  /// Pixel `(x, y)` samples the shape at the point `(x, y)`, so the shape's
+  /// coordinates are pixels, with the origin at the top-left.
+  ///
+  /// ```
+  /// # use rsdf_builder::ShapeBuilder;
+  /// # use rsdf_core::*;
+  /// // a 16 pixel wide bar across the middle of the canvas
+  /// let bar = ShapeBuilder::from_svg_path("M0,8 H32 V24 H0 Z")
+  ///   .unwrap()
+  ///   .build();
+  /// let config = RasterConfig::default();
+  /// let field: Field<1> = bar.sample_field([32, 32], config);
+  /// assert_eq!(field.get([15, 12]), [4.]);
+  /// assert_eq!(field.get([15, 5]), [-3.]);
+  /// // distances are only clamped to the max_distance when quantized
+  /// assert_eq!(field.get([15, 16]), [8.]);
+  /// ```
+  ///
+  /// The channel count is checked at compile time, so only counts with a
+  /// [`SampleChannels`] implementation can be sampled:
+  ///
+  /// ```compile_fail
+  /// # use rsdf_builder::ShapeBuilder;
+  /// # use rsdf_core::*;
+  /// let dot = ShapeBuilder::new().dot((8., 8.), 4.).build();
+  /// let config = RasterConfig::default();
+  /// let field: Field<2> = dot.sample_field([16, 16], config);
+  /// ```
+  #[must_use]
+  pub fn sample_field<const N: usize>(
+    &self,
+    size: [usize; 2],
+    config: RasterConfig,
+  ) -> Field<N>
+  where
+    Self: SampleChannels<N>,
+  {
+    let mut field = Field::new(size);
+    self.sample_field_into(&mut field, config);
+    field
+  }
+
+  /// Sample the shape into every pixel of an existing field, as in
+  /// [`Shape::sample_field`]
+  ///
+  /// Reusing a field avoids an allocation per call, e.g. when sampling the
+  /// frames of an animation, see [`FrameSampler`].
+  pub fn sample_field_into<const N: usize>(
+    &self,
+    field: &mut Field<N>,
+    config: RasterConfig,
+  ) where
+    Self: SampleChannels<N>,
+  {
+    let size = field.size();
+    let [width, height] = size;
+    let row = |y: usize| if config.flip_y { height - 1 - y } else { y };
+
+    if !config.fast_fill || !config.quantization.is_fixed_point() {
+      for y in 0..height {
+        for x in 0..width {
+          let sample = self.sample_pixel_channels([x, y], size, config);
+          field.set([x, row(y)], sample);
+        }
+      }
+      return;
+    }
+
+    for cell_y in (0..height).step_by(FAST_FILL_CELL) {
+      for cell_x in (0..width).step_by(FAST_FILL_CELL) {
+        let xs = cell_x..(cell_x + FAST_FILL_CELL).min(width);
+        let ys = cell_y..(cell_y + FAST_FILL_CELL).min(height);
+        let saturated =
+          self.saturated_distance(xs.clone(), ys.clone(), config);
+        for y in ys {
+          for x in xs.clone() {
+            let sample = match saturated {
+              Some(distance) => {
+                apply_border([distance; N], [x, y], size, config)
+              },
+              None => self.sample_pixel_channels([x, y], size, config),
+            };
+            field.set([x, row(y)], sample);
+          }
+        }
+      }
+    }
+  }
+
+  /// If no segment of the shape comes within the
+  /// [`RasterConfig::max_distance`] of the given block of pixels, returns the
+  /// saturated signed distance shared by all of them
+  fn saturated_distance(
+    &self,
+    xs: Range<usize>,
+    ys: Range<usize>,
+    config: RasterConfig,
+  ) -> Option<f32> {
+    let min = Point::new(xs.start as f32, ys.start as f32);
+    let max = Point::new((xs.end - 1) as f32, (ys.end - 1) as f32);
+    let centre = Point::new((min.x + max.x) / 2., (min.y + max.y) / 2.);
+    let half_diagonal = (max - centre).abs();
+
+    let lower_bound = self
+      .segments
+      .iter()
+      .map(|&segment_ref| {
+        self.get_segment(segment_ref).distance_lower_bound(centre)
+      })
+      .fold(f32::INFINITY, f32::min)
+      - half_diagonal;
+    // the chebyshev distance can be shorter than the euclidean one
+    let lower_bound = match config.metric {
+      DistanceMetric::Chebyshev => lower_bound / std::f32::consts::SQRT_2,
+      _ => lower_bound,
+    };
+
+    let max_distance = config.max_distance;
+    (lower_bound > max_distance)
+      .then(|| max_distance.copysign(self.sample_single_channel(centre)))
+  }
+
+  /// Rasterize the multi-channel signed distance field of the shape into the
+  /// given bitmap, such as that of an `Image`
+  ///
+  /// The rows are written bottom-up with [`RasterConfig::flip_y`].
+  ///
+  /// Returns the number of channels that had to be scrubbed, see
+  /// [`scrub_non_finite`].
+  pub fn rasterize(
+    &self,
+    image: &mut Bitmap<u8, 3>,
+    config: RasterConfig,
+  ) -> usize {
+    let size = [image.width, image.height];
+    let mut samples = self.sample_grid(size, config);
+    let scrubbed = scrub_non_finite(&mut samples, config.max_distance);
+    for y in 0..image.height {
+      for x in 0..image.width {
+        let sample = samples[y * image.width + x];
+        image.set_pixel(
+          [x, y],
+          sample.map(|distance| distance_color(distance, config.max_distance)),
+        );
+      }
+    }
+    scrubbed
+  }
+
+  /// Rasterize the multi-channel signed distance field of the shape into a
+  /// region of a caller-provided buffer
+  ///
+  /// Each pixel is written as 3 channels (red, green, blue) in the format
+  /// given by [`RasterConfig::quantization`], at the position described by
+  /// the [`RegionDesc`]; the rest of the buffer is left as is.
+  ///
+  /// Returns the number of channels that had to be scrubbed, see
+  /// [`scrub_non_finite`]. Panics if the region doesn't fit within the
+  /// buffer.
+  pub fn rasterize_into(
+    &self,
+    buffer: &mut [u8],
+    region: RegionDesc,
+    config: RasterConfig,
+  ) -> usize {
+    self.rasterize_channels_into::<3>(buffer, region, config)
+  }
+
+  /// Rasterize `N` channels of the shape into a region of a caller-provided
+  /// buffer, as [`Shape::rasterize_into`] does for 3
+  pub fn rasterize_channels_into<const N: usize>(
+    &self,
+    buffer: &mut [u8],
+    region: RegionDesc,
+    config: RasterConfig,
+  ) -> usize
+  where
+    Self: SampleChannels<N>,
+  {
+    let channel_size = config.quantization.channel_size();
+    let pixel_size = N * channel_size;
+    assert!(
+      region.pixel_stride >= pixel_size,
+      "pixel stride is too small for the quantization"
+    );
+    if region.width == 0 || region.height == 0 {
+      return 0;
+    }
+    let end = region.offset
+      + (region.height - 1) * region.row_pitch
+      + (region.width - 1) * region.pixel_stride
+      + pixel_size;
+    assert!(end <= buffer.len(), "region is outside of the buffer");
+
+    let mut field =
+      self.sample_field::<N>([region.width, region.height], config);
+    let scrubbed = scrub_non_finite(field.samples_mut(), config.max_distance);
+    for (y, row) in field.rows().enumerate() {
+      for (x, sample) in row.iter().enumerate() {
+        let location =
+          region.offset + y * region.row_pitch + x * region.pixel_stride;
+        let pixel = &mut buffer[location..location + pixel_size];
+        for (channel, &distance) in
+          pixel.chunks_exact_mut(channel_size).zip(sample)
+        {
+          config.quantization.write_with_range(
+            distance,
+            config.max_distance,
+            channel,
+          );
+        }
+      }
+    }
+    scrubbed
+  }
+}
+
+impl<const N: usize> Field<N> {
+  /// The field's channels quantized as [`Shape::rasterize_into`] writes
+  /// them, tightly packed & in little-endian order, as texture containers
+  /// store them
+  pub(crate) fn quantized_le(&self, config: RasterConfig) -> Vec<u8> {
+    let mut samples = self.samples().to_vec();
+    scrub_non_finite(&mut samples, config.max_distance);
+    let channel_size = config.quantization.channel_size();
+    let mut bytes = vec![0; samples.len() * N * channel_size];
+    for (channel, distance) in bytes
+      .chunks_exact_mut(channel_size)
+      .zip(samples.iter().flatten())
+    {
+      config.quantization.write_with_range(
+        *distance,
+        config.max_distance,
+        channel,
+      );
+      if cfg!(target_endian = "big") {
+        channel.reverse();
+      }
+    }
+    bytes
+  }
+}
+
+/// Replace NaN and infinite distances with finite ones, returning the number
+/// of channels that were replaced
+///
+/// Infinities are clamped to `max_distance`, such as the
+/// [`RasterConfig::max_distance`], and NaNs become fully outside,
+/// so that a single bad sample can't spread across a texture once it's
+/// filtered.
+///
+/// Both point at a bug, or at a shape that can't be sampled: a NaN comes
+/// from the solvers, and an infinity from a channel no spline is coloured
+/// with, or a shape without any contours, which is infinitely far from
+/// every sample. With the `assert-finite` feature they panic instead, to
+/// help track the offending sample down.
+pub fn scrub_non_finite<const N: usize>(
+  samples: &mut [[f32; N]],
+  max_distance: f32,
+) -> usize {
+  let mut scrubbed = 0;
+  for (i, sample) in samples.iter_mut().enumerate() {
+    for distance in sample.iter_mut().filter(|d| !d.is_finite()) {
+      if cfg!(feature = "assert-finite") {
+        panic!("non-finite distance {distance} in sample {i}");
+      }
+      *distance = if distance.is_nan() {
+        -max_distance
+      } else {
+        distance.clamp(-max_distance, max_distance)
+      };
+      scrubbed += 1;
+    }
+  }
+  scrubbed
+}
+
+/// Intersect the sample with the canvas inset by the
+/// [`RasterConfig::border`]
+fn apply_border<const N: usize>(
+  sample: [f32; N],
+  coords: [usize; 2],
+  size: [usize; 2],
+  config: RasterConfig,
+) -> [f32; N] {
+  if config.border > 0. {
+    let edge_distance = [
+      coords[0],
+      coords[1],
+      size[0] - 1 - coords[0],
+      size[1] - 1 - coords[1],
+    ]
+    .into_iter()
+    .min()
+    .unwrap() as f32;
+    let border_distance = edge_distance - config.border;
+    sample.map(|distance| distance.min(border_distance))
+  } else {
+    sample
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  fn square() -> Shape {
+    // a square covering the top-left corner of a 16x16 canvas
+    Shape::rectangle((-1., -1.), (9., 9.), &[White])
+  }
+
+  #[test]
+  fn border() {
+    let shape = square();
+    let size = [16, 16];
+
+    let config = RasterConfig::default();
+    let [r, _, _] = shape.sample_pixel([0, 4], size, config);
+    assert!(r > 0.);
+
+    let config = RasterConfig {
+      border: 2.,
+      ..Default::default()
+    };
+    let [r, g, b] = shape.sample_pixel([0, 4], size, config);
+    assert_eq!([r, g, b], [-2.; 3]);
+    let [r, _, _] = shape.sample_pixel([4, 0], size, config);
+    assert_eq!(r, -2.);
+    let [r, _, _] = shape.sample_pixel([1, 4], size, config);
+    assert_eq!(r, -1.);
+    // away from the border the field is left untouched
+    let [r, _, _] = shape.sample_pixel([8, 4], size, config);
+    assert_eq!(r, shape.sample(Point::new(8., 4.))[0]);
+  }
+
+  #[test]
+  fn fast_fill() {
+    // offset by half a pixel, as pixel centres lying exactly on the extension
+    // of an edge tie between the edge and its neighbour, which exact sampling
+    // resolves to a pseudo-distance that fast fill saturates instead
+    let mut shape = square();
+    let widen = |c: f32| c + 0.5 * c.signum();
+    for point in &mut shape.points {
+      *point = (widen(point.x), widen(point.y)).into();
+    }
+    let size = [40, 32];
+    let config = RasterConfig {
+      pseudo_distance_limit: 1.,
+      ..Default::default()
+    };
+    let fast_config = RasterConfig {
+      fast_fill: true,
+      ..config
+    };
+
+    let exact = shape.sample_grid(size, config);
+    let fast = shape.sample_grid(size, fast_config);
+    let clamp =
+      |s: [f32; 3]| s.map(|d| distance_color(d, config.max_distance));
+    assert_eq!(
+      exact.into_iter().map(clamp).collect::<Vec<_>>(),
+      fast.into_iter().map(clamp).collect::<Vec<_>>(),
+    );
+
+    // cells are only saturated beyond a wider range
+    let wide = |config| RasterConfig {
+      max_distance: 12.,
+      ..config
+    };
+    let exact = shape.sample_grid(size, wide(config));
+    let fast = shape.sample_grid(size, wide(fast_config));
+    let clamp = |s: [f32; 3]| s.map(|d| d.clamp(-12., 12.));
+    assert_eq!(
+      exact.into_iter().map(clamp).collect::<Vec<_>>(),
+      fast.into_iter().map(clamp).collect::<Vec<_>>(),
+    );
+
+    // float quantizations keep the unclamped distances, so are never filled
+    for quantization in [Quantization::F16, Quantization::F32] {
+      let float = |config| RasterConfig {
+        quantization,
+        ..config
+      };
+      assert_eq!(
+        shape.sample_grid(size, float(config)),
+        shape.sample_grid(size, float(fast_config)),
+      );
+    }
+  }
+
+  #[test]
+  fn sample_field() {
+    let shape = square();
+    let size = [12, 10];
+    let config = RasterConfig {
+      border: 1.,
+      ..Default::default()
+    };
+    let single = shape.sample_field::<1>(size, config);
+    let multi = shape.sample_field::<3>(size, config);
+    assert_eq!(multi.samples(), shape.sample_grid(size, config));
+    for y in 0..size[1] {
+      for x in 0..size[0] {
+        let point = (x as f32, y as f32);
+        let expected = apply_border(
+          [shape.sample_single_channel(point)],
+          [x, y],
+          size,
+          config,
+        );
+        assert_eq!(single.get([x, y]), expected);
+      }
+    }
+  }
+
+  #[test]
+  fn flip_y() {
+    let shape = square();
+    let size = [16, 12];
+    let config = RasterConfig::default();
+    let flipped_config = RasterConfig {
+      flip_y: true,
+      ..config
+    };
+
+    let samples = shape.sample_grid(size, config);
+    let flipped = shape.sample_grid(size, flipped_config);
+    for (row, flipped_row) in
+      samples.chunks(size[0]).zip(flipped.chunks(size[0]).rev())
+    {
+      assert_eq!(row, flipped_row);
+    }
+  }
+
+  #[test]
+  #[cfg_attr(feature = "assert-finite", ignore)]
+  fn scrub_non_finite() {
+    let mut samples = [
+      [0., 1., -1.],
+      [f32::NAN, f32::INFINITY, f32::NEG_INFINITY],
+      [100., -f32::NAN, 2.],
+    ];
+    assert_eq!(super::scrub_non_finite(&mut samples, 5.), 4);
+    assert_eq!(samples, [[0., 1., -1.], [-5., 5., -5.], [100., -5., 2.],]);
+
+    let region = RegionDesc {
+      offset: 0,
+      width: 12,
+      height: 12,
+      row_pitch: 12 * 3,
+      pixel_stride: 3,
+    };
+    let mut buffer = vec![0; 12 * 12 * 3];
+    let config = RasterConfig::default();
+    assert_eq!(square().rasterize_into(&mut buffer, region, config), 0);
+    // an empty shape is infinitely far away
+    let empty = Shape {
+      points: vec![],
+      segments: vec![],
+      splines: vec![],
+      contours: vec![],
+    };
+    assert_eq!(empty.rasterize_into(&mut buffer, region, config), 12 * 36);
+    assert!(buffer.iter().all(|&channel| channel == 0));
+  }
+
+  #[test]
+  fn rasterize_into() {
+    let shape = square();
+    let config = RasterConfig::default();
+    // a 6x5 region at (2, 3) of a 16x16 RGBA buffer
+    let (pitch, stride) = (16 * 4, 4);
+    let region = RegionDesc {
+      offset: 3 * pitch + 2 * stride,
+      width: 6,
+      height: 5,
+      row_pitch: pitch,
+      pixel_stride: stride,
+    };
+    let mut buffer = vec![7u8; 16 * pitch];
+    shape.rasterize_into(&mut buffer, region, config);
+
+    let samples = shape.sample_grid([6, 5], config);
+    for y in 0..16 {
+      for x in 0..16 {
+        let location = y * pitch + x * stride;
+        let pixel = &buffer[location..location + 4];
+        if (2..8).contains(&x) && (3..8).contains(&y) {
+          let expected = samples[(y - 3) * 6 + x - 2]
+            .map(|d| distance_color(d, config.max_distance));
+          assert_eq!(pixel[..3], expected);
+          assert_eq!(pixel[3], 7);
+        } else {
+          assert_eq!(pixel, [7; 4]);
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn quantization() {
+    let shape = square();
+    let size = [12, 12];
+    let samples = shape.sample_grid(size, RasterConfig::default());
+    for quantization in [
+      Quantization::U8,
+      Quantization::U16,
+      Quantization::F16,
+      Quantization::F32,
+    ] {
+      let config = RasterConfig {
+        quantization,
+        ..Default::default()
+      };
+      let pixel_size = 3 * quantization.channel_size();
+      let region = RegionDesc {
+        offset: 0,
+        width: size[0],
+        height: size[1],
+        row_pitch: size[0] * pixel_size,
+        pixel_stride: pixel_size,
+      };
+      let mut buffer = vec![0; size[0] * size[1] * pixel_size];
+      shape.rasterize_into(&mut buffer, region, config);
+
+      for (pixel, sample) in buffer.chunks(pixel_size).zip(&samples) {
+        // each channel is written as a lone distance would be, which
+        // `write_with_range` below checks against known bit patterns
+        let size = quantization.channel_size();
+        let mut expected = vec![0; 3 * size];
+        for (out, &distance) in expected.chunks_mut(size).zip(sample) {
+          quantization.write_with_range(distance, 5., out);
+        }
+        assert_eq!(pixel, expected);
+      }
+    }
+  }
+
+  #[test]
+  fn write_with_range() {
+    let distances = [-10., -5., -1., 0., 2.5, 5.];
+    let write = |quantization: Quantization| -> Vec<u8> {
+      let size = quantization.channel_size();
+      let mut out = vec![0; distances.len() * size];
+      for (out, &distance) in out.chunks_mut(size).zip(&distances) {
+        quantization.write_with_range(distance, 5., out);
+      }
+      out
+    };
+
+    assert_eq!(write(Quantization::U8), [0, 0, 101, 127, 191, 255]);
+    let u16s = [0u16, 0, 26214, 32768, 49151, 65535];
+    assert_eq!(
+      write(Quantization::U16),
+      u16s.map(u16::to_ne_bytes).concat()
+    );
+    let f16s = [0xc900u16, 0xc500, 0xbc00, 0x0000, 0x4100, 0x4500];
+    assert_eq!(
+      write(Quantization::F16),
+      f16s.map(u16::to_ne_bytes).concat()
+    );
+    let f32s = [
+      0xc120_0000u32,
+      0xc0a0_0000,
+      0xbf80_0000,
+      0x0000_0000,
+      0x4020_0000,
+      0x40a0_0000,
+    ];
+    assert_eq!(
+      write(Quantization::F32),
+      f32s.map(u32::to_ne_bytes).concat()
+    );
+  }
+
+  #[test]
+  fn max_distance() {
+    let shape = square();
+    let size = [16, 16];
+    let region = RegionDesc {
+      offset: 0,
+      width: size[0],
+      height: size[1],
+      row_pitch: size[0] * 6,
+      pixel_stride: 6,
+    };
+    let config = RasterConfig {
+      quantization: Quantization::U16,
+      max_distance: 10.,
+      ..Default::default()
+    };
+    let mut buffer = vec![0; size[0] * size[1] * 6];
+    shape.rasterize_into(&mut buffer, region, config);
+
+    let samples = shape.sample_grid(size, config);
+    for (pixel, sample) in buffer.chunks(6).zip(&samples) {
+      for (channel, &distance) in pixel.chunks(2).zip(sample) {
+        let value = u16::from_ne_bytes([channel[0], channel[1]]) as f32;
+        // decoded as in a shader
+        let decoded = (value / 65535. - 0.5) * 2. * config.max_distance;
+        let expected = distance.clamp(-10., 10.);
+        assert!((decoded - expected).abs() < 0.001, "{decoded} {expected}");
+      }
+    }
+    // the far corner is beyond the default range, but not this one
+    let [far] = shape.sample_field::<1>(size, config).get([15, 15]);
+    let default_range = RasterConfig::default().max_distance;
+    assert!(far < -default_range && far > -10.);
+  }
+}
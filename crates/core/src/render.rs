@@ -0,0 +1,170 @@
+use crate::*;
+
+/// Reconstructs the shape encoded in a distance [`Field`], for previews and
+/// tests
+///
+/// A renderer produces the coverage of each pixel of an output raster of the
+/// given `size`, from `0` (outside) to `1` (inside), with the field stretched
+/// to cover the whole output.
+///
+/// The trait is generic over the field's channels, rather than its method,
+/// so backends can be chosen at runtime behind a `dyn FieldRenderer<N>`:
+///
+/// ```
+/// # use rsdf_core::*;
+/// /// Nearest-neighbour & aliased, for pixel art previews
+/// struct Nearest;
+///
+/// impl FieldRenderer<1> for Nearest {
+///   fn render(&self, field: &Field<1>, size: [usize; 2]) -> Field<1> {
+///     let samples = (0..size[0] * size[1]).map(|i| {
+///       let x = i % size[0] * field.width() / size[0];
+///       let y = i / size[0] * field.height() / size[1];
+///       [f32::from(field.get([x, y])[0] > 0.)]
+///     });
+///     Field::from_samples(size, samples.collect())
+///   }
+/// }
+///
+/// let field = Field::from_samples([2, 1], vec![[-1.], [1.]]);
+/// let backends: [&dyn FieldRenderer<1>; 2] = [&CpuRenderer, &Nearest];
+/// for backend in backends {
+///   let coverage = backend.render(&field, [4, 1]);
+///   assert_eq!(coverage.get([0, 0]), [0.]);
+///   assert_eq!(coverage.get([3, 0]), [1.]);
+/// }
+/// ```
+pub trait FieldRenderer<const N: usize> {
+  fn render(&self, field: &Field<N>, size: [usize; 2]) -> Field<1>;
+}
+
+/// Renders fields on the CPU
+///
+/// The field is bilinearly interpolated, and the median of the first 3
+/// channels gives the distance (or the only channel, for a single channel
+/// field). Edges are antialiased over the width of one output pixel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CpuRenderer;
+
+impl<const N: usize> FieldRenderer<N> for CpuRenderer {
+  fn render(&self, field: &Field<N>, size: [usize; 2]) -> Field<1> {
+    let [width, height] = size;
+    let mut output = Field::new(size);
+    if field.width() == 0 || field.height() == 0 {
+      return output;
+    }
+    let scale = [
+      width as f32 / field.width() as f32,
+      height as f32 / field.height() as f32,
+    ];
+    // distances are in field pixels, so scale them into output pixels
+    let distance_scale = (scale[0] + scale[1]) / 2.;
+
+    for y in 0..height {
+      for x in 0..width {
+        // the centre of the output pixel, in field coordinates
+        let fx = (x as f32 + 0.5) / scale[0] - 0.5;
+        let fy = (y as f32 + 0.5) / scale[1] - 0.5;
+        let sample = bilinear(field, fx, fy);
+        let distance = if N >= 3 {
+          median([sample[0], sample[1], sample[2]])
+        } else {
+          sample[0]
+        };
+        let coverage = (distance * distance_scale + 0.5).clamp(0., 1.);
+        output.set([x, y], [coverage]);
+      }
+    }
+    output
+  }
+}
+
+impl<const N: usize> Field<N> {
+  /// Preview the field as text, `width` characters wide, for terminals & CI
+  /// logs
+  ///
+  /// The field is rendered with the [`CpuRenderer`], and each character
+  /// shaded by its coverage, from a space (outside) to a full block
+  /// (inside). Characters are taken to be twice as tall as they are wide, so
+  /// each covers two rows of the rendered preview.
+  ///
+  /// ```
+  /// # use rsdf_core::*;
+  /// // a field of a vertical edge, inside on the right
+  /// let samples = (0..16).map(|i| [(i % 4) as f32 - 1.5]).collect();
+  /// let field = Field::from_samples([4, 4], samples);
+  /// assert_eq!(field.to_ascii(4), " ░██\n ░██\n");
+  /// ```
+  #[must_use]
+  pub fn to_ascii(&self, width: usize) -> String {
+    const SHADES: [char; 5] = [' ', '░', '▒', '▓', '█'];
+    if self.width() == 0 {
+      return String::new();
+    }
+    let height = (self.height() * width).div_ceil(self.width() * 2);
+    let coverage = CpuRenderer.render(self, [width, height]);
+    let mut text = String::with_capacity((width * 3 + 1) * height);
+    for row in coverage.rows() {
+      for &[coverage] in row {
+        let shade = (coverage * (SHADES.len() - 1) as f32).round() as usize;
+        text.push(SHADES[shade]);
+      }
+      text.push('\n');
+    }
+    text
+  }
+}
+
+/// Sample the field between pixels, clamping to its edges
+fn bilinear<const N: usize>(field: &Field<N>, x: f32, y: f32) -> [f32; N] {
+  let max = [field.width() - 1, field.height() - 1];
+  let x = x.clamp(0., max[0] as f32);
+  let y = y.clamp(0., max[1] as f32);
+  let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+  let (x1, y1) = ((x0 + 1).min(max[0]), (y0 + 1).min(max[1]));
+  let (wx, wy) = (x - x0 as f32, y - y0 as f32);
+
+  let [a, b, c, d] =
+    [[x0, y0], [x1, y0], [x0, y1], [x1, y1]].map(|coords| field.get(coords));
+  std::array::from_fn(|i| {
+    let top = a[i] + (b[i] - a[i]) * wx;
+    let bottom = c[i] + (d[i] - c[i]) * wx;
+    top + (bottom - top) * wy
+  })
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn cpu_renderer() {
+    // a square spanning 4..12 of a 16x16 canvas
+    let shape = Shape::rectangle(
+      (4., 4.),
+      (12., 12.),
+      &[Magenta, Yellow, Magenta, Yellow],
+    );
+    let config = RasterConfig::default();
+    let multi = shape.sample_field::<3>([16, 16], config);
+    let single = shape.sample_field::<1>([16, 16], config);
+
+    for field in [CpuRenderer.render(&multi, [64, 64])]
+      .into_iter()
+      .chain([CpuRenderer.render(&single, [64, 64])])
+    {
+      assert_eq!(field.size(), [64, 64]);
+      // well inside & outside, including next to the corners
+      for coords in [[32, 32], [20, 20], [44, 44]] {
+        assert_eq!(field.get(coords), [1.], "{coords:?}");
+      }
+      for coords in [[2, 2], [32, 8], [12, 12], [52, 52]] {
+        assert_eq!(field.get(coords), [0.], "{coords:?}");
+      }
+      // the edge of the square falls between output pixels 17 and 18
+      let [left] = field.get([17, 32]);
+      let [right] = field.get([18, 32]);
+      assert!(left < 0.5 && right > 0.5, "{left} {right}");
+    }
+  }
+}
@@ -0,0 +1,148 @@
+use crate::*;
+
+/// How the alpha channel of RGBA output is derived, see [`Field::to_rgba`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum AlphaMode {
+  /// The same alpha for every pixel, from `0` (transparent) to `1` (opaque)
+  Fixed(f32),
+  /// The coverage of the median distance: opaque inside the shape, fading
+  /// over a pixel at its edge to the `background` alpha outside it
+  ///
+  /// A `background` of `0` gives a glyph on a transparent background, as UI
+  /// frameworks expect when compositing glyph textures.
+  Median { background: f32 },
+}
+
+impl Default for AlphaMode {
+  fn default() -> Self {
+    AlphaMode::Fixed(1.)
+  }
+}
+
+impl AlphaMode {
+  /// The alpha of a pixel with the given median distance, in pixels
+  fn alpha(self, median: f32) -> u8 {
+    let alpha = match self {
+      AlphaMode::Fixed(alpha) => alpha,
+      AlphaMode::Median { background } => {
+        let coverage = (median + 0.5).clamp(0., 1.);
+        background + (1. - background) * coverage
+      },
+    };
+    (alpha.clamp(0., 1.) * u8::MAX as f32).round() as u8
+  }
+}
+
+impl<const N: usize> Field<N> {
+  /// Encode the field as 8-bit RGBA pixels, with the alpha channel derived
+  /// by the [`AlphaMode`]
+  ///
+  /// The colour channels are encoded as [`Shape::rasterize`] would, by the
+  /// [`RasterConfig::max_distance`]. Single channel fields are repeated in
+  /// each colour channel, and the fourth channel of an MTSDF is replaced by
+  /// the alpha. The median is that of the first 3 channels, or the first
+  /// channel of fields with fewer.
+  ///
+  /// ```
+  /// # use rsdf_core::*;
+  /// let field = Field::from_samples([2, 1], vec![[3., 2., 4.], [-3.; 3]]);
+  /// let alpha = AlphaMode::Median { background: 0. };
+  /// let rgba = field.to_rgba(RasterConfig::default(), alpha);
+  /// assert_eq!(rgba.get_pixel([0, 0])[3], 255);
+  /// assert_eq!(rgba.get_pixel([1, 0])[3], 0);
+  /// ```
+  #[must_use]
+  pub fn to_rgba(
+    &self,
+    config: RasterConfig,
+    alpha: AlphaMode,
+  ) -> Bitmap<u8, 4> {
+    let mut bitmap = Bitmap::new(self.size());
+    for (x, y, sample) in self.pixels() {
+      let [r, g, b] = std::array::from_fn(|i| sample[i.min(N - 1)]);
+      let median = if N >= 3 { median([r, g, b]) } else { r };
+      let [r, g, b] = [r, g, b]
+        .map(|distance| distance_color(distance, config.max_distance));
+      bitmap.set_pixel([x, y], [r, g, b, alpha.alpha(median)]);
+    }
+    bitmap
+  }
+}
+
+impl PreparedGlyph {
+  /// Sample the glyph's multi-channel field, and encode it as 8-bit RGBA
+  /// pixels, as [`Field::to_rgba`] does
+  #[must_use]
+  pub fn draw_rgba(
+    &self,
+    config: RasterConfig,
+    alpha: AlphaMode,
+  ) -> Bitmap<u8, 4> {
+    self.sample::<3>(config).to_rgba(config, alpha)
+  }
+}
+
+#[cfg(feature = "atlas")]
+impl<const N: usize> Atlas<N> {
+  /// Encode the atlas' field as 8-bit RGBA pixels, as [`Field::to_rgba`]
+  /// does, with each glyph encoded by its own [`AtlasGlyph::max_distance`]
+  ///
+  /// The `config` encodes the background of the atlas, between glyphs. It's
+  /// outside of every glyph, so it takes the `background` alpha of
+  /// [`AlphaMode::Median`].
+  #[must_use]
+  pub fn to_rgba(
+    &self,
+    config: RasterConfig,
+    alpha: AlphaMode,
+  ) -> Bitmap<u8, 4> {
+    let mut bitmap = self.field.to_rgba(config, alpha);
+    for glyph in self.glyphs.values() {
+      let glyph_config = RasterConfig {
+        max_distance: glyph.max_distance,
+        ..config
+      };
+      let field = self.field.view(glyph.rect).to_field();
+      let pixels = field.to_rgba(glyph_config, alpha).into_pixels();
+      for (x, y, _) in field.pixels() {
+        let pixel = pixels[y * field.width() + x];
+        bitmap.set_pixel([glyph.rect.x + x, glyph.rect.y + y], pixel);
+      }
+    }
+    bitmap
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn to_rgba() {
+    let config = RasterConfig {
+      max_distance: 2.5,
+      ..Default::default()
+    };
+    // inside, on the edge, & outside
+    let samples = vec![[1., 4., 2.], [0., 0.2, -0.2], [-1.; 3]];
+    let field = Field::from_samples([3, 1], samples);
+
+    let half = AlphaMode::Median { background: 0.5 };
+    let rgba = field.to_rgba(config, half);
+    let alphas = rgba.pixels().iter().map(|pixel| pixel[3]);
+    assert_eq!(alphas.collect::<Vec<_>>(), [255, 191, 128]);
+    let [r, g, b, _] = rgba.get_pixel([0, 0]);
+    assert_eq!([r, g, b], [1., 4., 2.].map(|d| distance_color(d, 2.5)));
+
+    let opaque = field.to_rgba(config, AlphaMode::default());
+    assert!(opaque.pixels().iter().all(|pixel| pixel[3] == 255));
+
+    let single = Field::from_samples([1, 1], vec![[0.5]]);
+    let [r, g, b, a] = single
+      .to_rgba(config, AlphaMode::Median { background: 0. })
+      .get_pixel([0, 0]);
+    assert_eq!([r, g, b], [distance_color(0.5, 2.5); 3]);
+    assert_eq!(a, 255);
+  }
+}
@@ -0,0 +1,175 @@
+use crate::*;
+
+/// Samples the frames of an animated shape, such as a glyph interpolated
+/// between two instances of a variable font, into a single reused field
+///
+/// A frame whose shape is unchanged from the previous frame, as decided by
+/// [`Shape::checksum`], isn't sampled again, so held frames are cheap.
+///
+/// ```
+/// # use rsdf_builder::ShapeBuilder;
+/// # use rsdf_core::*;
+/// let square = |size: f32| {
+///   ShapeBuilder::new()
+///     .contour((0., 0.))
+///     .line((size, 0.))
+///     .line((size, size))
+///     .line((0., size))
+///     .line((0., 0.))
+///     .end_contour()
+///     .build()
+/// };
+/// let config = RasterConfig::default();
+/// let mut sampler = FrameSampler::<3>::new([16, 16], config);
+/// for frame in 0..8 {
+///   // a square growing from 8 to 15 pixels
+///   let field = sampler.sample(&square(8. + frame as f32));
+///   // ... upload or encode the field ...
+/// #   assert_eq!(field.get([4, 4]), [4.; 3]);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct FrameSampler<const N: usize> {
+  field: Field<N>,
+  config: RasterConfig,
+  /// The checksum of the shape last sampled into the field
+  last: Option<u64>,
+}
+
+impl<const N: usize> FrameSampler<N>
+where
+  Shape: SampleChannels<N>,
+{
+  /// Sample frames of the given `size` with the given `config`
+  pub fn new(size: [usize; 2], config: RasterConfig) -> Self {
+    FrameSampler {
+      field: Field::new(size),
+      config,
+      last: None,
+    }
+  }
+
+  /// Sample the next frame, overwriting the previous one
+  pub fn sample(&mut self, shape: &Shape) -> &Field<N> {
+    let checksum = shape.checksum();
+    if self.last != Some(checksum) {
+      shape.sample_field_into(&mut self.field, self.config);
+      self.last = Some(checksum);
+    }
+    &self.field
+  }
+
+  /// The most recently sampled frame
+  pub fn field(&self) -> &Field<N> {
+    &self.field
+  }
+}
+
+/// A stack of equally sized fields, such as the frames of an animation
+/// stored as the layers of a 3D texture
+#[derive(Debug, Clone, PartialEq)]
+pub struct Volume<const N: usize> {
+  /// The width & height of each layer, and the number of layers
+  size: [usize; 3],
+  samples: Vec<[f32; N]>,
+}
+
+impl<const N: usize> Volume<N> {
+  pub fn width(&self) -> usize {
+    self.size[0]
+  }
+
+  pub fn height(&self) -> usize {
+    self.size[1]
+  }
+
+  /// The number of layers
+  pub fn depth(&self) -> usize {
+    self.size[2]
+  }
+
+  pub fn size(&self) -> [usize; 3] {
+    self.size
+  }
+
+  /// The channels of the sample at `(x, y)` in layer `z`
+  pub fn get(&self, [x, y, z]: [usize; 3]) -> [f32; N] {
+    let [width, height, _] = self.size;
+    self.samples[(z * height + y) * width + x]
+  }
+
+  /// The samples of a layer, in row-major order
+  pub fn layer(&self, z: usize) -> &[[f32; N]] {
+    let layer_len = self.size[0] * self.size[1];
+    &self.samples[z * layer_len..(z + 1) * layer_len]
+  }
+
+  /// Every sample, layer by layer, in the order a 3D texture is uploaded
+  pub fn samples(&self) -> &[[f32; N]] {
+    &self.samples
+  }
+}
+
+impl Shape {
+  /// Sample a sequence of shapes into the layers of a [`Volume`], one layer
+  /// per shape, as in [`Shape::sample_field`]
+  ///
+  /// A layer whose shape is unchanged from the layer before it is copied
+  /// instead of sampled again.
+  #[must_use]
+  pub fn sample_volume<'a, const N: usize>(
+    shapes: impl IntoIterator<Item = &'a Shape>,
+    size: [usize; 2],
+    config: RasterConfig,
+  ) -> Volume<N>
+  where
+    Self: SampleChannels<N>,
+  {
+    let mut sampler = FrameSampler::new(size, config);
+    let mut samples = vec![];
+    let mut depth = 0;
+    for shape in shapes {
+      samples.extend_from_slice(sampler.sample(shape).samples());
+      depth += 1;
+    }
+    Volume {
+      size: [size[0], size[1], depth],
+      samples,
+    }
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  fn square(size: f32) -> Shape {
+    Shape::rectangle((0., 0.), (size, size), &[White])
+  }
+
+  #[test]
+  fn frame_sampler() {
+    let config = RasterConfig::default();
+    let mut sampler = FrameSampler::<3>::new([12, 12], config);
+    for size in [4., 8., 8., 10.] {
+      let expected = square(size).sample_field::<3>([12, 12], config);
+      assert_eq!(*sampler.sample(&square(size)), expected);
+    }
+    assert_eq!(sampler.field().size(), [12, 12]);
+  }
+
+  #[test]
+  fn sample_volume() {
+    let config = RasterConfig::default();
+    let shapes = [square(4.), square(4.), square(8.)];
+    let volume = Shape::sample_volume::<1>(&shapes, [10, 6], config);
+    assert_eq!(volume.size(), [10, 6, 3]);
+    assert_eq!(volume.samples().len(), 180);
+    for (z, shape) in shapes.iter().enumerate() {
+      let field = shape.sample_field::<1>([10, 6], config);
+      assert_eq!(volume.layer(z), field.samples());
+    }
+    assert_eq!(volume.get([6, 2, 0]), [-2.]);
+    assert_eq!(volume.get([6, 2, 2]), [2.]);
+  }
+}
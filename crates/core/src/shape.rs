@@ -1,17 +1,33 @@
+pub mod checksum;
+pub mod clip;
 pub mod colour;
+pub mod contour;
+pub mod curvature;
+pub mod description;
+#[cfg(feature = "serde")]
+mod deserialize;
 pub mod distance;
+pub mod lerp;
 pub mod primitives;
 pub mod sample;
+pub mod transform;
+pub mod winners;
 
 use crate::*;
-pub use colour::{Colour, Colour::*};
-pub use primitives::{Primitive, Segment, SegmentKind};
+pub use colour::Colour;
+pub use primitives::SegmentKind;
 use std::ops::Range;
+pub use winners::Winner;
 
 /// Reference to a segment
 ///
 /// SegmentKind implicitly gives the length.
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(try_from = "deserialize::UncheckedSegmentRef")
+)]
 pub struct SegmentRef {
   pub kind: SegmentKind,
   pub points_index: usize,
@@ -19,6 +35,11 @@ pub struct SegmentRef {
 
 /// Reference to a spline in the [`Contour`]
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(try_from = "deserialize::UncheckedSpline")
+)]
 pub struct Spline {
   pub segments_range: Range<usize>,
   pub colour: Colour,
@@ -29,12 +50,19 @@ pub struct Spline {
 /// Sharp corners are assumed to be located at the boundary points of adjacent
 /// splines.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(try_from = "deserialize::UncheckedContour")
+)]
 pub struct Contour {
   pub spline_range: Range<usize>,
 }
 
 /// Representation of a shape ready to be decomposed into a raster SDF
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "deserialize::UncheckedShape"))]
 pub struct Shape {
   /// Buffer containing the points
   pub points: Vec<Point>,
@@ -45,3 +73,47 @@ pub struct Shape {
   /// Buffer containing the contours
   pub contours: Vec<Contour>,
 }
+
+#[cfg(test)]
+impl Shape {
+  /// A rectangle from `min` to `max` wound clockwise, drawn as a single
+  /// spline given one colour, or as a spline per edge given four
+  pub(crate) fn rectangle(
+    min: impl Into<Point>,
+    max: impl Into<Point>,
+    colours: &[Colour],
+  ) -> Shape {
+    let (min, max) = (min.into(), max.into());
+    let splines: Vec<Spline> = match colours {
+      &[colour] => vec![Spline {
+        segments_range: 0..4,
+        colour,
+      }],
+      _ => (0..4)
+        .map(|i| Spline {
+          segments_range: i..i + 1,
+          colour: colours[i],
+        })
+        .collect(),
+    };
+    Shape {
+      points: vec![
+        min,
+        Point::new(max.x, min.y),
+        max,
+        Point::new(min.x, max.y),
+        min,
+      ],
+      segments: (0..4)
+        .map(|points_index| SegmentRef {
+          kind: SegmentKind::Line,
+          points_index,
+        })
+        .collect(),
+      contours: vec![Contour {
+        spline_range: 0..splines.len(),
+      }],
+      splines,
+    }
+  }
+}
@@ -0,0 +1,125 @@
+use crate::*;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// Incremental FNV-1a hasher
+///
+/// Unlike `std::hash::DefaultHasher` the output is stable between runs,
+/// platforms, and compiler versions, so it can be stored alongside generated
+/// outputs and compared against later.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Fnv1a(pub(crate) u64);
+
+impl Fnv1a {
+  pub(crate) fn new() -> Self {
+    Fnv1a(FNV_OFFSET_BASIS)
+  }
+
+  pub(crate) fn write(&mut self, bytes: &[u8]) {
+    for &byte in bytes {
+      self.0 ^= byte as u64;
+      self.0 = self.0.wrapping_mul(FNV_PRIME);
+    }
+  }
+
+  pub(crate) fn write_usize(&mut self, value: usize) {
+    self.write(&(value as u64).to_le_bytes());
+  }
+
+  pub(crate) fn write_f32(&mut self, value: f32) {
+    // normalise -0.0 to 0.0 so that equal shapes produce equal checksums
+    let value = if value == 0f32 { 0f32 } else { value };
+    self.write(&value.to_bits().to_le_bytes());
+  }
+}
+
+impl Shape {
+  /// Compute a checksum of the shape's content
+  ///
+  /// Two shapes with identical points, segments, splines (including their
+  /// colours) and contours produce the same checksum. The value is stable
+  /// across runs, so it can be stored with a generated field and used to
+  /// decide whether the field needs to be regenerated.
+  #[must_use]
+  pub fn checksum(&self) -> u64 {
+    let mut hasher = Fnv1a::new();
+
+    hasher.write_usize(self.points.len());
+    for point in &self.points {
+      hasher.write_f32(point.x);
+      hasher.write_f32(point.y);
+    }
+    hasher.write_usize(self.segments.len());
+    for SegmentRef { kind, points_index } in &self.segments {
+      hasher.write(&[*kind as u8]);
+      hasher.write_usize(*points_index);
+    }
+    hasher.write_usize(self.splines.len());
+    for Spline {
+      segments_range,
+      colour,
+    } in &self.splines
+    {
+      hasher.write_usize(segments_range.start);
+      hasher.write_usize(segments_range.end);
+      hasher.write(&[*colour as u8]);
+    }
+    hasher.write_usize(self.contours.len());
+    for Contour { spline_range } in &self.contours {
+      hasher.write_usize(spline_range.start);
+      hasher.write_usize(spline_range.end);
+    }
+
+    hasher.0
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  fn triangle(colour: Colour) -> Shape {
+    Shape {
+      points: vec![
+        (0., 0.).into(),
+        (1., 0.).into(),
+        (0., 1.).into(),
+        (0., 0.).into(),
+      ],
+      segments: vec![
+        SegmentRef {
+          kind: SegmentKind::Line,
+          points_index: 0,
+        },
+        SegmentRef {
+          kind: SegmentKind::Line,
+          points_index: 1,
+        },
+        SegmentRef {
+          kind: SegmentKind::Line,
+          points_index: 2,
+        },
+      ],
+      splines: vec![Spline {
+        segments_range: 0..3,
+        colour,
+      }],
+      contours: vec![Contour { spline_range: 0..1 }],
+    }
+  }
+
+  #[test]
+  fn checksum() {
+    assert_eq!(triangle(White).checksum(), triangle(White).checksum());
+    assert_ne!(triangle(White).checksum(), triangle(Magenta).checksum());
+
+    let mut moved = triangle(White);
+    moved.points[1].x = 2.;
+    assert_ne!(triangle(White).checksum(), moved.checksum());
+
+    let mut negative_zero = triangle(White);
+    negative_zero.points[0].x = -0.;
+    assert_eq!(triangle(White).checksum(), negative_zero.checksum());
+  }
+}
@@ -0,0 +1,540 @@
+use crate::*;
+use arrayvec::ArrayVec;
+use shape::primitives::Segment;
+use std::f32::consts::TAU;
+
+impl Shape {
+  /// Clip the shape to the half-plane on the side of the line through
+  /// `point` that `normal` points to
+  ///
+  /// Segments are split where they cross the line, and the parts outside
+  /// dropped. Closed contours that were cut are closed again with new line
+  /// segments along the line, so a glyph cut in half stays filled, holes
+  /// and all. Cut contours are recoloured, with each new line a spline of
+  /// its own, since its ends are sharp corners. Open contours are only cut.
+  ///
+  /// ```
+  /// # use rsdf_builder::ShapeBuilder;
+  /// # use rsdf_core::*;
+  /// // a 10x10 square
+  /// let square = ShapeBuilder::new()
+  ///   .contour((0., 0.))
+  ///   .line((10., 0.))
+  ///   .line((10., 10.))
+  ///   .line((0., 10.))
+  ///   .end_contour()
+  ///   .build();
+  /// // keep the left half, where x <= 4
+  /// let left = square.clip_halfplane((4., 0.).into(), Vector::new(-1., 0.));
+  /// assert_eq!(left.bounds(), Some(((0., 0.).into(), (4., 10.).into())));
+  /// assert_eq!(left.sample_single_channel((3., 5.)), 1.);
+  /// ```
+  #[must_use]
+  pub fn clip_halfplane(&self, point: Point, normal: Vector) -> Shape {
+    let offset = normal.dot(point.as_vector());
+    let side = |p: Point| normal.dot(p.as_vector()) - offset;
+
+    // contours kept whole, & cut contours to be recoloured
+    let mut kept = Shape {
+      points: vec![],
+      segments: vec![],
+      splines: vec![],
+      contours: vec![],
+    };
+    let mut cut: Vec<Vec<Piece>> = vec![];
+    // parts of closed contours inside the half-plane, which start & end on
+    // the line
+    let mut runs: Vec<Vec<Piece>> = vec![];
+
+    for contour in &self.contours {
+      let mut pieces: Vec<(Piece, bool)> = vec![];
+      for spline in contour.spline_range.clone() {
+        let segments_range = self.splines[spline].segments_range.clone();
+        for &segment_ref in &self.segments[segments_range] {
+          let segment = self.get_segment(segment_ref);
+          let mut times = crossing_times(segment, normal, offset);
+          times.sort_by(f32::total_cmp);
+          let bounds: Vec<f32> =
+            [0.].into_iter().chain(times).chain([1.]).collect();
+          for span in bounds.windows(2) {
+            let (a, b) = (span[0], span[1]);
+            if b - a <= 1e-6 {
+              continue;
+            }
+            let inside = side(segment.sample((a + b) / 2.)) >= 0.;
+            let piece = Piece::from_segment(self, segment_ref, a, b, spline);
+            pieces.push((piece, inside));
+          }
+        }
+      }
+
+      if pieces.iter().all(|&(_, inside)| inside) {
+        kept.append(&self.contour_shape(contour));
+        continue;
+      }
+      // compare the stored end points, as sampling arcs loses precision
+      let (first, last) = (&pieces[0].0, &pieces[pieces.len() - 1].0);
+      if !float_cmp::approx_eq!(Point, first.start(), last.end()) {
+        cut.extend(inside_runs(pieces));
+        continue;
+      }
+      if let Some(entry) = (0..pieces.len()).find(|&i| {
+        pieces[i].1 && !pieces[(i + pieces.len() - 1) % pieces.len()].1
+      }) {
+        pieces.rotate_left(entry);
+        runs.extend(inside_runs(pieces));
+      }
+    }
+
+    // pair up the ends of the runs along the line, so that each pair bounds
+    // a stretch of the line within the shape, from an exit to an entry
+    let tangent = Vector::new(-normal.y, normal.x);
+    let along = |p: Point| tangent.dot(p.as_vector());
+    let mut ends: Vec<(f32, usize, bool)> = runs
+      .iter()
+      .enumerate()
+      .flat_map(|(i, run)| {
+        let exit = run[run.len() - 1].end();
+        [(along(run[0].start()), i, true), (along(exit), i, false)]
+      })
+      .collect();
+    ends.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut next = vec![None; runs.len()];
+    for pair in ends.chunks_exact(2) {
+      match (pair[0], pair[1]) {
+        ((_, exit, false), (_, entry, true))
+        | ((_, entry, true), (_, exit, false)) => next[exit] = Some(entry),
+        _ => {},
+      }
+    }
+
+    // chain the runs into closed contours along the line
+    let mut visited = vec![false; runs.len()];
+    for start in 0..runs.len() {
+      if visited[start] {
+        continue;
+      }
+      let mut chain = vec![];
+      let mut i = start;
+      loop {
+        visited[i] = true;
+        chain.extend(runs[i].iter().cloned());
+        let j = next[i].filter(|&j| !visited[j]).unwrap_or(start);
+        let from = chain[chain.len() - 1].end();
+        let to = runs[j][0].start();
+        if !float_cmp::approx_eq!(Point, from, to) {
+          chain.push(Piece::line(from, to));
+        }
+        if j == start {
+          break;
+        }
+        i = j;
+      }
+      cut.push(chain);
+    }
+
+    for pieces in cut {
+      kept.push_pieces(pieces);
+    }
+    kept
+  }
+
+  /// Clip the shape to the rectangle from `min` to `max`, as
+  /// [`Shape::clip_halfplane`] does to each of its sides
+  ///
+  /// Shapes clipped to a tile before sampling stay closed along its edges,
+  /// so the tile's field is the field of what's visible in it.
+  ///
+  /// ```
+  /// # use rsdf_builder::ShapeBuilder;
+  /// # use rsdf_core::*;
+  /// // a circle of radius 10
+  /// let circle = ShapeBuilder::new().dot((0., 0.), 10.).build();
+  /// let quarter = circle.clip_to_rect((0., 0.).into(), (20., 20.).into());
+  /// // the arc, & the two sides of the rectangle it was closed along
+  /// assert_eq!(quarter.segments.len(), 3);
+  /// assert!(quarter.sample_single_channel((2., 2.)) > 0.);
+  /// assert!(quarter.sample_single_channel((-2., 2.)) < 0.);
+  /// ```
+  #[must_use]
+  pub fn clip_to_rect(&self, min: Point, max: Point) -> Shape {
+    [
+      (min, Vector::new(1., 0.)),
+      (min, Vector::new(0., 1.)),
+      (max, Vector::new(-1., 0.)),
+      (max, Vector::new(0., -1.)),
+    ]
+    .into_iter()
+    .fold(self.clone(), |shape, (point, normal)| {
+      shape.clip_halfplane(point, normal)
+    })
+  }
+
+  /// A shape of a single contour of the shape, keeping its colours
+  fn contour_shape(&self, contour: &Contour) -> Shape {
+    let splines = &self.splines[contour.spline_range.clone()];
+    let segments = splines[0].segments_range.start
+      ..splines[splines.len() - 1].segments_range.end;
+    let mut shape = Shape {
+      points: vec![],
+      segments: vec![],
+      splines: vec![],
+      contours: vec![],
+    };
+    for &segment_ref in &self.segments[segments.clone()] {
+      let piece = Piece::from_segment(self, segment_ref, 0., 1., 0);
+      shape.push_segment(&piece);
+    }
+    shape.splines.extend(splines.iter().map(|spline| Spline {
+      segments_range: spline.segments_range.start - segments.start
+        ..spline.segments_range.end - segments.start,
+      colour: spline.colour,
+    }));
+    shape.contours.push(Contour {
+      spline_range: 0..splines.len(),
+    });
+    shape
+  }
+
+  /// Add a segment to the end of the shape's segments, sharing its start
+  /// with the last point where they meet
+  fn push_segment(&mut self, piece: &Piece) {
+    let joined = self.points.last() == Some(&piece.points[0]);
+    let points = if joined {
+      &piece.points[1..]
+    } else {
+      &piece.points[..]
+    };
+    let start = self.points.len() - joined as usize;
+    self.points.extend_from_slice(points);
+    let points_index = match piece.kind {
+      // the arc's parameters come after its starting point
+      SegmentKind::EllipticalArc => start + 1,
+      _ => start,
+    };
+    self.segments.push(SegmentRef {
+      kind: piece.kind,
+      points_index,
+    });
+  }
+
+  /// Add a contour of pieces, with a spline for each run of pieces from the
+  /// same original spline, coloured as the builder would
+  fn push_pieces(&mut self, pieces: Vec<Piece>) {
+    let first_spline = self.splines.len();
+    let mut previous = None;
+    for piece in &pieces {
+      let segment = self.segments.len();
+      self.push_segment(piece);
+      if piece.spline.is_some() && piece.spline == previous {
+        let last = self.splines.len() - 1;
+        self.splines[last].segments_range.end = segment + 1;
+      } else {
+        self.splines.push(Spline {
+          segments_range: segment..segment + 1,
+          colour: Colour::Magenta,
+        });
+      }
+      previous = piece.spline;
+    }
+    let splines = &mut self.splines[first_spline..];
+    if let [spline] = splines {
+      spline.colour = Colour::White;
+    } else {
+      let mut colour = Colour::Magenta;
+      for spline in splines {
+        spline.colour = colour;
+        colour = colour.next_spline_colour();
+      }
+    }
+    self.contours.push(Contour {
+      spline_range: first_spline..self.splines.len(),
+    });
+  }
+}
+
+/// Part of a segment of a shape being clipped, with its own points
+#[derive(Debug, Clone)]
+struct Piece {
+  kind: SegmentKind,
+  /// The segment's points, including the start & end points of an arc
+  points: ArrayVec<Point, 6>,
+  /// The spline of the original shape it's part of, or `None` for new lines
+  /// along the clipping line
+  spline: Option<usize>,
+}
+
+impl Piece {
+  fn line(start: Point, end: Point) -> Self {
+    Piece {
+      kind: SegmentKind::Line,
+      points: [start, end].into_iter().collect(),
+      spline: None,
+    }
+  }
+
+  /// The part of the segment between the times `a` & `b`
+  fn from_segment(
+    shape: &Shape,
+    SegmentRef {
+      kind,
+      points_index: i,
+    }: SegmentRef,
+    a: f32,
+    b: f32,
+    spline: usize,
+  ) -> Self {
+    let points = match kind {
+      SegmentKind::Line => bezier_slice(&shape.points[i..i + 2], a, b),
+      SegmentKind::QuadBezier => bezier_slice(&shape.points[i..i + 3], a, b),
+      SegmentKind::CubicBezier => bezier_slice(&shape.points[i..i + 4], a, b),
+      SegmentKind::EllipticalArc => {
+        let segment = Segment::EllipticalArc(&shape.points[i..i + 4]);
+        let arc = elliptical_arc::CentreParam::from_ps(&shape.points[i..]);
+        let part = elliptical_arc::CentreParam {
+          theta: arc.theta + a * arc.delta,
+          delta: (b - a) * arc.delta,
+          ..arc
+        };
+        let start = if a > 0. {
+          segment.sample(a)
+        } else {
+          shape.points[i - 1]
+        };
+        let end = if b < 1. {
+          segment.sample(b)
+        } else {
+          shape.points[i + 4]
+        };
+        [start]
+          .into_iter()
+          .chain(part.to_ps())
+          .chain([end])
+          .collect()
+      },
+    };
+    Piece {
+      kind,
+      points,
+      spline: Some(spline),
+    }
+  }
+
+  fn start(&self) -> Point {
+    self.points[0]
+  }
+
+  fn end(&self) -> Point {
+    self.points[self.points.len() - 1]
+  }
+}
+
+/// Group the consecutive pieces inside the half-plane
+fn inside_runs(pieces: Vec<(Piece, bool)>) -> Vec<Vec<Piece>> {
+  let mut runs: Vec<Vec<Piece>> = vec![];
+  let mut previous = false;
+  for (piece, inside) in pieces {
+    match (inside, previous) {
+      (true, true) => runs.last_mut().unwrap().push(piece),
+      (true, false) => runs.push(vec![piece]),
+      _ => {},
+    }
+    previous = inside;
+  }
+  runs
+}
+
+/// The control points of the part of a bezier curve between the times `a`
+/// & `b`, by de Casteljau's algorithm
+fn bezier_slice(ps: &[Point], a: f32, b: f32) -> ArrayVec<Point, 6> {
+  let mut points: ArrayVec<Point, 6> = ps.iter().copied().collect();
+  if b < 1. {
+    points = split(&points, b).0;
+  }
+  if a > 0. {
+    points = split(&points, a / b).1;
+  }
+  points
+}
+
+/// Split a bezier curve at time `t` into the control points of its two
+/// halves
+fn split(ps: &[Point], t: f32) -> (ArrayVec<Point, 6>, ArrayVec<Point, 6>) {
+  let mut layer: ArrayVec<Point, 6> = ps.iter().copied().collect();
+  let mut left = ArrayVec::new();
+  let mut right = ArrayVec::new();
+  left.push(layer[0]);
+  right.push(layer[layer.len() - 1]);
+  while layer.len() > 1 {
+    layer = layer
+      .windows(2)
+      .map(|pair| pair[0] + (pair[1] - pair[0]) * t)
+      .collect();
+    left.push(layer[0]);
+    right.push(layer[layer.len() - 1]);
+  }
+  right.reverse();
+  (left, right)
+}
+
+/// The times at which a segment crosses the line where `normal · p` is
+/// `offset`, within `[0, 1]`
+pub(crate) fn crossing_times(
+  segment: Segment,
+  normal: Vector,
+  offset: f32,
+) -> ArrayVec<f32, 3> {
+  let on = |p: Point| normal.dot(p.as_vector()) - offset;
+  let times: ArrayVec<f32, 3> = match segment {
+    Segment::Line(ps) => {
+      let [a, b] = [on(ps[0]), on(ps[1])];
+      if a == b {
+        ArrayVec::new()
+      } else {
+        [a / (a - b)].into_iter().collect()
+      }
+    },
+    Segment::QuadBezier(ps) => {
+      let [a, b, c] = [on(ps[0]), on(ps[1]), on(ps[2])];
+      polynomial_roots([a, 2. * (b - a), a - 2. * b + c, 0.])
+    },
+    Segment::CubicBezier(ps) => {
+      let [a, b, c, d] = [on(ps[0]), on(ps[1]), on(ps[2]), on(ps[3])];
+      polynomial_roots([
+        a,
+        3. * (b - a),
+        3. * (a - 2. * b + c),
+        3. * (b - c) + d - a,
+      ])
+    },
+    Segment::EllipticalArc(ps) => {
+      return arc_crossing_times(ps, normal, offset);
+    },
+  };
+  times
+    .into_iter()
+    .filter(|t| (0. ..=1.).contains(t))
+    .collect()
+}
+
+/// The real roots of a polynomial of up to degree 3, with its coefficients
+/// in ascending order by degree
+pub(crate) fn polynomial_roots(polynomial: [f32; 4]) -> ArrayVec<f32, 3> {
+  let scale = polynomial.iter().fold(0f32, |m, c| m.max(c.abs()));
+  let negligible = |c: f32| c.abs() <= scale * 1e-6;
+  let [a, b, c, d] = polynomial;
+  if !negligible(d) {
+    return math::roots_in_range(&polynomial, ..).into_iter().collect();
+  }
+  let mut roots = ArrayVec::new();
+  if !negligible(c) {
+    let discriminant = b * b - 4. * c * a;
+    if discriminant >= 0. {
+      let root = discriminant.sqrt();
+      roots.push((-b - root) / (2. * c));
+      roots.push((-b + root) / (2. * c));
+    }
+  } else if !negligible(b) {
+    roots.push(-a / b);
+  }
+  roots
+}
+
+/// As for [`crossing_times`], for an elliptical arc
+fn arc_crossing_times(
+  ps: &[Point],
+  normal: Vector,
+  offset: f32,
+) -> ArrayVec<f32, 3> {
+  let arc = elliptical_arc::CentreParam::from_ps(ps);
+  let (sin, cos) = arc.phi.sin_cos();
+  let [rx, ry] = [arc.r, arc.k * arc.r];
+  // across the line, the ellipse is at centre + p cos(angle) + q sin(angle)
+  let p = rx * normal.dot(Vector::new(cos, sin));
+  let q = ry * normal.dot(Vector::new(-sin, cos));
+  let radius = p.hypot(q);
+  let ratio = (offset - normal.dot(arc.centre.as_vector())) / radius;
+  if !(-1. ..=1.).contains(&ratio) {
+    return ArrayVec::new();
+  }
+  let alpha = q.atan2(p);
+  let spread = ratio.acos();
+  [alpha - spread, alpha + spread]
+    .into_iter()
+    .filter_map(|angle| {
+      // how far along the arc the angle is, in the arc's direction
+      let along = ((angle - arc.theta) * arc.delta.signum()).rem_euclid(TAU);
+      (along <= arc.delta.abs()).then(|| along / arc.delta.abs())
+    })
+    .collect()
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn polynomial_roots() {
+    let mut roots = super::polynomial_roots([-2., 0., 1., 0.]).to_vec();
+    roots.sort_by(f32::total_cmp);
+    float_cmp::assert_approx_eq!(f32, roots[0], -(2f32.sqrt()));
+    float_cmp::assert_approx_eq!(f32, roots[1], 2f32.sqrt());
+    assert_eq!(super::polynomial_roots([1., 0., 0., 0.]).len(), 0);
+  }
+
+  #[test]
+  fn split() {
+    let quad = [(0., 0.), (5., 10.), (10., 0.)].map(Point::from);
+    let (left, right) = super::split(&quad, 0.5);
+    assert_eq!(left[..], [(0., 0.), (2.5, 5.), (5., 5.)].map(Point::from));
+    assert_eq!(right[..], [(5., 5.), (7.5, 5.), (10., 0.)].map(Point::from));
+    let middle = bezier_slice(&quad, 0.25, 0.75);
+    let segment = Segment::QuadBezier(&quad);
+    float_cmp::assert_approx_eq!(Point, middle[0], segment.sample(0.25));
+    float_cmp::assert_approx_eq!(Point, middle[2], segment.sample(0.75));
+  }
+
+  #[test]
+  fn clip_halfplane() {
+    // a square 20 wide, with a 10 wide square hole in the middle
+    let mut shape = Shape {
+      points: vec![],
+      segments: vec![],
+      splines: vec![],
+      contours: vec![],
+    };
+    for corners in [
+      [(0., 0.), (20., 0.), (20., 20.), (0., 20.)],
+      [(5., 5.), (5., 15.), (15., 15.), (15., 5.)],
+    ] {
+      let corners = corners.map(Point::from);
+      let pieces = (0..4)
+        .map(|i| Piece {
+          spline: Some(i),
+          ..Piece::line(corners[i], corners[(i + 1) % 4])
+        })
+        .collect();
+      shape.push_pieces(pieces);
+    }
+    let before = shape.sample_single_channel((2., 10.));
+
+    // cut through the hole, keeping the left, where x <= 10
+    let left = shape.clip_halfplane((10., 0.).into(), Vector::new(-1., 0.));
+    assert_eq!(left.contours.len(), 1);
+    assert!(left.is_contour_closed(&left.contours[0]));
+    assert_eq!(left.bounds(), Some(((0., 0.).into(), (10., 20.).into())));
+    assert_eq!(left.sample_single_channel((2., 10.)), before);
+    assert_eq!(left.sample_single_channel((8., 10.)), -3.);
+    assert_eq!(left.sample_single_channel((9., 2.)), 1.);
+    assert_eq!(left.sample_single_channel((12., 2.)), -2.);
+
+    // the hole is kept whole beside the cut
+    let wide = shape.clip_halfplane((17., 0.).into(), Vector::new(-1., 0.));
+    assert_eq!(wide.contours.len(), 2);
+    assert_eq!(wide.sample_single_channel((10., 10.)), -5.);
+    assert!(shape
+      .clip_halfplane((30., 0.).into(), Vector::new(1., 0.))
+      .contours
+      .is_empty());
+  }
+}
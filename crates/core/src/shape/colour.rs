@@ -1,7 +1,6 @@
-use num_derive::FromPrimitive;
-
 /// Basic type supporting bitwise binary operations on colour channels
-#[derive(Debug, Clone, Copy, FromPrimitive, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[rustfmt::skip]
 pub enum Colour {
   Black   = 0b000,
@@ -14,15 +13,54 @@ pub enum Colour {
   White   = 0b111,
 }
 
+impl Colour {
+  /// The colour with the given channel bits, red in the lowest bit, or
+  /// `None` if any bits above the three channels are set
+  #[must_use]
+  pub const fn from_bits(bits: u8) -> Option<Colour> {
+    if bits > 0b111 {
+      return None;
+    }
+    Some(Colour::from_bits_truncate(bits))
+  }
+
+  /// The colour with the given channel bits, ignoring any bits above the
+  /// three channels
+  #[must_use]
+  pub const fn from_bits_truncate(bits: u8) -> Colour {
+    match bits & 0b111 {
+      0b000 => Colour::Black,
+      0b001 => Colour::Red,
+      0b010 => Colour::Green,
+      0b100 => Colour::Blue,
+      0b011 => Colour::Yellow,
+      0b110 => Colour::Cyan,
+      0b101 => Colour::Magenta,
+      _ => Colour::White,
+    }
+  }
+
+  /// The colour of the spline following one of this colour in a contour
+  ///
+  /// Contours start with [`Magenta`](Colour::Magenta), then alternate
+  /// between [`Yellow`](Colour::Yellow) and [`Cyan`](Colour::Cyan), so
+  /// neighbouring splines always share exactly one channel.
+  #[must_use]
+  pub fn next_spline_colour(self) -> Colour {
+    if self == Colour::Magenta {
+      Colour::Yellow
+    } else {
+      self ^ Colour::Magenta
+    }
+  }
+}
+
 impl std::ops::BitAnd for Colour {
   type Output = Self;
 
   #[inline]
   fn bitand(self, rhs: Colour) -> Colour {
-    unsafe {
-      num_traits::FromPrimitive::from_u8(self as u8 & rhs as u8)
-        .unwrap_unchecked()
-    }
+    Colour::from_bits_truncate(self as u8 & rhs as u8)
   }
 }
 
@@ -31,10 +69,7 @@ impl std::ops::BitOr for Colour {
 
   #[inline]
   fn bitor(self, rhs: Colour) -> Colour {
-    unsafe {
-      num_traits::FromPrimitive::from_u8(self as u8 | rhs as u8)
-        .unwrap_unchecked()
-    }
+    Colour::from_bits_truncate(self as u8 | rhs as u8)
   }
 }
 
@@ -43,10 +78,7 @@ impl std::ops::BitXor for Colour {
 
   #[inline]
   fn bitxor(self, rhs: Colour) -> Colour {
-    unsafe {
-      num_traits::FromPrimitive::from_u8(self as u8 ^ rhs as u8)
-        .unwrap_unchecked()
-    }
+    Colour::from_bits_truncate(self as u8 ^ rhs as u8)
   }
 }
 
@@ -55,16 +87,40 @@ impl std::ops::Not for Colour {
 
   #[inline]
   fn not(self) -> Colour {
-    unsafe {
-      num_traits::FromPrimitive::from_u8(!(self as u8) & 0b111)
-        .unwrap_unchecked()
-    }
+    Colour::from_bits_truncate(!(self as u8))
   }
 }
 
 #[cfg(any(test, doctest))]
 mod tests {
-  use super::Colour::*;
+  use super::Colour::{self, *};
+
+  const ALL: [Colour; 8] =
+    [Black, Red, Green, Blue, Yellow, Cyan, Magenta, White];
+
+  #[test]
+  fn from_bits() {
+    for colour in ALL {
+      assert_eq!(Colour::from_bits(colour as u8), Some(colour));
+      assert_eq!(Colour::from_bits_truncate(colour as u8 | 0b1000), colour);
+    }
+    for bits in 0b1000..=u8::MAX {
+      assert_eq!(Colour::from_bits(bits), None);
+    }
+  }
+
+  #[test]
+  fn exhaustive() {
+    // every pair of colours, against the operations on their bits
+    for a in ALL {
+      assert_eq!((!a) as u8, !(a as u8) & 0b111);
+      for b in ALL {
+        assert_eq!((a & b) as u8, a as u8 & b as u8);
+        assert_eq!((a | b) as u8, a as u8 | b as u8);
+        assert_eq!((a ^ b) as u8, a as u8 ^ b as u8);
+      }
+    }
+  }
 
   #[test]
   fn bitand() {
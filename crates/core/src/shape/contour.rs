@@ -0,0 +1,534 @@
+use crate::*;
+
+/// Number of straight line pieces each curved segment is divided into when
+/// approximating the area of a contour
+const AREA_SUBDIVISIONS: usize = 16;
+
+/// Contours whose area is below this threshold are considered degenerate
+const DEGENERATE_AREA: f32 = 0.0001;
+
+impl Shape {
+  /// Approximate the signed area enclosed by a [`Contour`]
+  ///
+  /// The sign of the area depends on the winding direction of the contour.
+  #[must_use]
+  pub fn contour_area(&self, contour: &Contour) -> f32 {
+    let mut area = 0.;
+    for spline in &self.splines[contour.spline_range.clone()] {
+      for &segment_ref in &self.segments[spline.segments_range.clone()] {
+        let segment = self.get_segment(segment_ref);
+        let subdivisions = match segment_ref.kind {
+          SegmentKind::Line => 1,
+          _ => AREA_SUBDIVISIONS,
+        };
+        let mut previous = segment.sample(0.);
+        for i in 1..=subdivisions {
+          let next = segment.sample(i as f32 / subdivisions as f32);
+          // shoelace formula
+          area += previous.as_vector().signed_area(next.as_vector());
+          previous = next;
+        }
+      }
+    }
+    area / 2.
+  }
+
+  /// Check whether a [`Contour`] ends where it starts
+  pub fn is_contour_closed(&self, contour: &Contour) -> bool {
+    let splines = &self.splines[contour.spline_range.clone()];
+    let (Some(first), Some(last)) = (splines.first(), splines.last()) else {
+      return false;
+    };
+    let (Some(&first), Some(&last)) = (
+      self.segments[first.segments_range.clone()].first(),
+      self.segments[last.segments_range.clone()].last(),
+    ) else {
+      return false;
+    };
+    let start = self.get_segment(first).sample(0.);
+    let end = self.get_segment(last).sample(1.);
+    float_cmp::approx_eq!(Point, start, end)
+  }
+
+  /// The sign of the total area enclosed by the closed contours of the shape
+  ///
+  /// Outline sources disagree on which winding direction encloses the inside
+  /// of a shape. Outer contours enclose more area than the holes within
+  /// them, so the sign of the total tells which direction a shape uses. A
+  /// positive sign means the inside has positive distances, as the samplers
+  /// expect. With a negative sign the samples need to be negated. A shape
+  /// enclosing no area counts as positive.
+  #[must_use]
+  pub fn winding_sign(&self) -> f32 {
+    let area: f32 = self
+      .contours
+      .iter()
+      .filter(|contour| self.is_contour_closed(contour))
+      .map(|contour| self.contour_area(contour))
+      .sum();
+    if area < 0. {
+      -1.
+    } else {
+      1.
+    }
+  }
+
+  /// Rotate each closed contour so that it starts at a corner
+  ///
+  /// Outline sources start contours wherever they please, which may be
+  /// partway along a smooth curve. The spline running through the start
+  /// point is then split in two, and each half coloured separately. The
+  /// halves are merged and the contour's splines recoloured from the corner
+  /// it now starts at (see [`Colour::next_spline_colour`]), so that the
+  /// colouring doesn't depend on where the outline began. Contours that
+  /// already start at a corner, have no corners, or are open are left alone.
+  pub fn rearrange_contours(&mut self) {
+    let mut contours = std::mem::take(&mut self.contours);
+    let mut splines = Vec::with_capacity(self.splines.len());
+    for contour in &mut contours {
+      let range = contour.spline_range.clone();
+      let start = splines.len();
+      if !self.starts_mid_spline(contour) {
+        splines.extend_from_slice(&self.splines[range]);
+        contour.spline_range = start..splines.len();
+        continue;
+      }
+
+      let old = &self.splines[range];
+      let shift = old[0].segments_range.len();
+      let segments_range =
+        old[0].segments_range.start..old[old.len() - 1].segments_range.end;
+      self.segments[segments_range.clone()].rotate_left(shift);
+      let mut colour = Colour::Magenta;
+      for spline in &old[1..] {
+        let range = &spline.segments_range;
+        splines.push(Spline {
+          segments_range: range.start - shift..range.end - shift,
+          colour,
+        });
+        colour = colour.next_spline_colour();
+      }
+      // the first spline now continues on from the end of the last one
+      splines.last_mut().unwrap().segments_range.end = segments_range.end;
+      if let [spline] = &mut splines[start..] {
+        spline.colour = White;
+      }
+      contour.spline_range = start..splines.len();
+    }
+    self.contours = contours;
+    self.splines = splines;
+  }
+
+  /// Check whether a closed contour with several splines joins smoothly at
+  /// its start point
+  fn starts_mid_spline(&self, contour: &Contour) -> bool {
+    let splines = &self.splines[contour.spline_range.clone()];
+    let contiguous = splines
+      .windows(2)
+      .all(|pair| pair[0].segments_range.end == pair[1].segments_range.start);
+    if splines.len() < 2
+      || !contiguous
+      || splines
+        .iter()
+        .any(|spline| spline.segments_range.is_empty())
+      || !self.is_contour_closed(contour)
+    {
+      return false;
+    }
+    let first = self.segments[splines[0].segments_range.start];
+    let last =
+      self.segments[splines[splines.len() - 1].segments_range.end - 1];
+    let incoming = self.get_segment(last).sample_derivative(1.).norm();
+    let outgoing = self.get_segment(first).sample_derivative(0.).norm();
+    float_cmp::approx_eq!(Vector, incoming, outgoing)
+  }
+
+  /// A box containing every contour of the shape, as its `(min, max)`
+  /// corners
+  ///
+  /// The box is conservative: curves are bounded by their control points and
+  /// elliptical arcs by the circle of their major radius, so it may be
+  /// larger than the shape. Returns `None` for a shape with no segments.
+  #[must_use]
+  pub fn bounds(&self) -> Option<(Point, Point)> {
+    self
+      .contours
+      .iter()
+      .flat_map(|contour| &self.splines[contour.spline_range.clone()])
+      .flat_map(|spline| &self.segments[spline.segments_range.clone()])
+      .map(|&segment_ref| self.get_segment(segment_ref).bounds())
+      .reduce(|(min_a, max_a), (min_b, max_b)| {
+        (
+          Point::new(min_a.x.min(min_b.x), min_a.y.min(min_b.y)),
+          Point::new(max_a.x.max(max_b.x), max_a.y.max(max_b.y)),
+        )
+      })
+  }
+
+  /// Add the contours of `other` to the shape, after its own
+  ///
+  /// The contours keep their splines and colours, so two layers of a glyph
+  /// can be framed or sampled as one.
+  pub fn append(&mut self, other: &Shape) {
+    let [points, segments, splines] =
+      [self.points.len(), self.segments.len(), self.splines.len()];
+    self.points.extend_from_slice(&other.points);
+    self
+      .segments
+      .extend(other.segments.iter().map(|segment| SegmentRef {
+        kind: segment.kind,
+        points_index: segment.points_index + points,
+      }));
+    self
+      .splines
+      .extend(other.splines.iter().map(|spline| Spline {
+        segments_range: spline.segments_range.start + segments
+          ..spline.segments_range.end + segments,
+        colour: spline.colour,
+      }));
+    self
+      .contours
+      .extend(other.contours.iter().map(|contour| Contour {
+        spline_range: contour.spline_range.start + splines
+          ..contour.spline_range.end + splines,
+      }));
+  }
+
+  /// Remove closed contours that enclose no area
+  ///
+  /// Degenerate contours, such as those that fold back on themselves, poison
+  /// the search for the nearest spline. Open contours are left alone, since
+  /// they are only meaningful to [`Shape::sample_unsigned`]. Returns the
+  /// indices the removed contours had before culling.
+  ///
+  /// The splines, segments & points of the removed contours are dropped
+  /// too, so that every one left belongs to a contour, keeping the rest in
+  /// order.
+  pub fn cull_degenerate_contours(&mut self) -> Vec<usize> {
+    let degenerate: Vec<usize> = self
+      .contours
+      .iter()
+      .enumerate()
+      .filter(|(_, contour)| {
+        contour.spline_range.is_empty()
+          || (self.is_contour_closed(contour)
+            && self.contour_area(contour).abs() < DEGENERATE_AREA)
+      })
+      .map(|(i, _)| i)
+      .collect();
+    if degenerate.is_empty() {
+      return degenerate;
+    }
+    for &i in degenerate.iter().rev() {
+      self.contours.remove(i);
+    }
+    self.drop_unreferenced();
+    degenerate
+  }
+
+  /// Drop the splines no contour refers to, then the segments no spline
+  /// refers to, then the points no segment reads, updating the ranges &
+  /// indices into them
+  fn drop_unreferenced(&mut self) {
+    let mut splines = vec![false; self.splines.len()];
+    for contour in &self.contours {
+      splines[contour.spline_range.clone()].fill(true);
+    }
+    let mut segments = vec![false; self.segments.len()];
+    for (spline, _) in self.splines.iter().zip(&splines).filter(|(_, &s)| s) {
+      segments[spline.segments_range.clone()].fill(true);
+    }
+    let mut points = vec![false; self.points.len()];
+    for (&segment, _) in
+      self.segments.iter().zip(&segments).filter(|(_, &s)| s)
+    {
+      let SegmentRef { kind, points_index } = segment;
+      // an arc starts at the point before its parameters, & ends after them
+      let read = match kind {
+        SegmentKind::Line => points_index..points_index + 2,
+        SegmentKind::QuadBezier => points_index..points_index + 3,
+        SegmentKind::CubicBezier => points_index..points_index + 4,
+        SegmentKind::EllipticalArc => {
+          points_index.saturating_sub(1)..points_index + 5
+        },
+      };
+      let end = read.end.min(points.len());
+      points[read.start.min(end)..end].fill(true);
+    }
+
+    let [spline_index, segment_index, point_index] =
+      [&splines, &segments, &points].map(|kept| compacted_indices(kept));
+    for contour in &mut self.contours {
+      let range = &contour.spline_range;
+      contour.spline_range =
+        spline_index[range.start]..spline_index[range.end];
+    }
+    for spline in &mut self.splines {
+      let range = &spline.segments_range;
+      spline.segments_range =
+        segment_index[range.start]..segment_index[range.end];
+    }
+    for segment in &mut self.segments {
+      segment.points_index = point_index[segment.points_index];
+    }
+    retain_kept(&mut self.splines, &splines);
+    retain_kept(&mut self.segments, &segments);
+    retain_kept(&mut self.points, &points);
+  }
+}
+
+/// The index each item will have once the items that aren't `kept` are
+/// dropped, followed by the number of items kept, so the ends of ranges map
+/// too
+fn compacted_indices(kept: &[bool]) -> Vec<usize> {
+  let mut count = 0;
+  let mut indices = Vec::with_capacity(kept.len() + 1);
+  for &kept in kept {
+    indices.push(count);
+    count += kept as usize;
+  }
+  indices.push(count);
+  indices
+}
+
+fn retain_kept<T>(items: &mut Vec<T>, kept: &[bool]) {
+  let mut kept = kept.iter();
+  items.retain(|_| *kept.next().unwrap());
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+  use float_cmp::assert_approx_eq;
+
+  fn shape(points: Vec<Point>) -> Shape {
+    let segments = (0..points.len() - 1)
+      .map(|points_index| SegmentRef {
+        kind: SegmentKind::Line,
+        points_index,
+      })
+      .collect();
+    Shape {
+      splines: vec![Spline {
+        segments_range: 0..points.len() - 1,
+        colour: White,
+      }],
+      points,
+      segments,
+      contours: vec![Contour { spline_range: 0..1 }],
+    }
+  }
+
+  #[test]
+  fn contour_area() {
+    let square = shape(vec![
+      (0., 0.).into(),
+      (2., 0.).into(),
+      (2., 2.).into(),
+      (0., 2.).into(),
+      (0., 0.).into(),
+    ]);
+    let area = square.contour_area(&square.contours[0]);
+    assert_approx_eq!(f32, area, 4.);
+
+    let reversed = shape(vec![
+      (0., 0.).into(),
+      (0., 2.).into(),
+      (2., 2.).into(),
+      (2., 0.).into(),
+      (0., 0.).into(),
+    ]);
+    let area = reversed.contour_area(&reversed.contours[0]);
+    assert_approx_eq!(f32, area, -4.);
+  }
+
+  #[test]
+  fn winding_sign() {
+    let square = [(0., 0.), (4., 0.), (4., 4.), (0., 4.), (0., 0.)];
+    let hole = [(1., 1.), (1., 3.), (3., 3.), (3., 1.), (1., 1.)];
+    let with_hole = |outer: &[(f32, f32)], inner: &[(f32, f32)]| {
+      let mut shape =
+        shape(outer.iter().chain(inner).map(|&p| p.into()).collect());
+      shape.segments = (0..4)
+        .chain(5..9)
+        .map(|points_index| SegmentRef {
+          kind: SegmentKind::Line,
+          points_index,
+        })
+        .collect();
+      shape.splines = vec![
+        Spline {
+          segments_range: 0..4,
+          colour: White,
+        },
+        Spline {
+          segments_range: 4..8,
+          colour: White,
+        },
+      ];
+      shape.contours = vec![
+        Contour { spline_range: 0..1 },
+        Contour { spline_range: 1..2 },
+      ];
+      shape
+    };
+    let reversed = |ps: &[(f32, f32)]| ps.iter().rev().copied().collect();
+
+    let framed = with_hole(&square, &hole);
+    assert_eq!(framed.winding_sign(), 1.);
+    // between the square and the hole is inside
+    assert!(framed.sample_single_channel(Point::new(0.5, 2.)) > 0.);
+    let (square, hole): (Vec<_>, Vec<_>) =
+      (reversed(&square), reversed(&hole));
+    assert_eq!(with_hole(&square, &hole).winding_sign(), -1.);
+
+    let open = shape(vec![(0., 0.).into(), (2., 2.).into()]);
+    assert_eq!(open.winding_sign(), 1.);
+  }
+
+  #[test]
+  fn rearrange_contours() {
+    // a square, started in the middle of its top edge, with a spline per
+    // segment, as the builder would split it
+    let mut square = shape(vec![
+      (5., 0.).into(),
+      (10., 0.).into(),
+      (10., 10.).into(),
+      (0., 10.).into(),
+      (0., 0.).into(),
+      (5., 0.).into(),
+    ]);
+    let mut colour = Magenta;
+    square.splines = (0..5)
+      .map(|i| {
+        let spline = Spline {
+          segments_range: i..i + 1,
+          colour,
+        };
+        colour = colour.next_spline_colour();
+        spline
+      })
+      .collect();
+    square.contours = vec![Contour { spline_range: 0..5 }];
+    let unchanged = square.clone();
+
+    square.rearrange_contours();
+    assert_eq!(square.splines.len(), 4);
+    assert_eq!(square.contours[0].spline_range, 0..4);
+    let start = square.get_segment(square.segments[0]).sample(0.);
+    assert_eq!(start, Point::new(10., 0.));
+    let ranges: Vec<_> = square
+      .splines
+      .iter()
+      .map(|spline| (spline.segments_range.clone(), spline.colour))
+      .collect();
+    assert_eq!(
+      ranges,
+      [
+        (0..1, Magenta),
+        (1..2, Yellow),
+        (2..3, Cyan),
+        (3..5, Yellow)
+      ]
+    );
+    for point in [(5., 1.), (9., 5.), (-1., 4.), (5., 12.)] {
+      assert_eq!(
+        square.sample_single_channel(point),
+        unchanged.sample_single_channel(point)
+      );
+    }
+
+    // starting at a corner is left alone
+    let checksum = square.checksum();
+    square.rearrange_contours();
+    assert_eq!(square.checksum(), checksum);
+  }
+
+  #[test]
+  fn bounds() {
+    let square = shape(vec![
+      (1., 2.).into(),
+      (5., 2.).into(),
+      (5., 3.).into(),
+      (1., 3.).into(),
+      (1., 2.).into(),
+    ]);
+    assert_eq!(square.bounds(), Some(((1., 2.).into(), (5., 3.).into())));
+
+    let mut empty = square;
+    empty.contours.clear();
+    assert_eq!(empty.bounds(), None);
+  }
+
+  #[test]
+  fn append() {
+    let square = |x: f32| {
+      shape(vec![
+        (x, 0.).into(),
+        (x + 2., 0.).into(),
+        (x + 2., 2.).into(),
+        (x, 2.).into(),
+        (x, 0.).into(),
+      ])
+    };
+    let mut both = square(0.);
+    both.append(&square(4.));
+    assert_eq!(both.contours.len(), 2);
+    assert_eq!(both.contours[1].spline_range, 1..2);
+    assert_eq!(both.splines[1].segments_range, 4..8);
+    assert_eq!(both.bounds(), Some(((0., 0.).into(), (6., 2.).into())));
+    assert_eq!(both.sample_single_channel((5., 1.)), 1.);
+  }
+
+  #[test]
+  fn cull_degenerate_contours() {
+    let mut square = shape(vec![
+      (0., 0.).into(),
+      (2., 0.).into(),
+      (2., 2.).into(),
+      (0., 2.).into(),
+      (0., 0.).into(),
+    ]);
+    assert_eq!(square.cull_degenerate_contours(), Vec::<usize>::new());
+    assert_eq!(square.contours.len(), 1);
+
+    let mut folded =
+      shape(vec![(0., 0.).into(), (2., 2.).into(), (0., 0.).into()]);
+    assert_eq!(folded.cull_degenerate_contours(), vec![0]);
+    assert!(folded.contours.is_empty());
+    assert!(folded.splines.is_empty());
+    assert!(folded.segments.is_empty());
+    assert!(folded.points.is_empty());
+
+    // the contours around a culled one keep their data
+    let mut mixed = folded.clone();
+    let square = |x: f32| {
+      shape(vec![
+        (x, 0.).into(),
+        (x + 2., 0.).into(),
+        (x + 2., 2.).into(),
+        (x, 2.).into(),
+        (x, 0.).into(),
+      ])
+    };
+    mixed.append(&square(0.));
+    mixed.append(&shape(vec![
+      (5., 0.).into(),
+      (6., 1.).into(),
+      (5., 0.).into(),
+    ]));
+    mixed.append(&square(8.));
+    let expected = {
+      let mut both = square(0.);
+      both.append(&square(8.));
+      both
+    };
+    assert_eq!(mixed.cull_degenerate_contours(), vec![1]);
+    assert_eq!(mixed.points, expected.points);
+    assert_eq!(mixed.checksum(), expected.checksum());
+
+    let mut open = shape(vec![(0., 0.).into(), (2., 2.).into()]);
+    assert_eq!(open.cull_degenerate_contours(), Vec::<usize>::new());
+    assert_eq!(open.contours.len(), 1);
+  }
+}
@@ -0,0 +1,84 @@
+use crate::*;
+use std::fmt::Write;
+
+impl Shape {
+  /// Draw the shape's outline as an SVG image, overlaid with a curvature
+  /// comb along each segment, for debugging
+  ///
+  /// Each segment is sampled `teeth + 1` times, and a tooth drawn away from
+  /// the centre of the curve at each sample, `scale` times the curvature
+  /// long, as measured by `Segment::curvature` (see [`Shape::get_segment`]).
+  /// Splines are drawn in their colours. Jumps in the comb show where the
+  /// outline bends sharply, and where its corners are found, or missed,
+  /// when colouring imported outlines.
+  ///
+  /// ```
+  /// # use rsdf_builder::ShapeBuilder;
+  /// # use rsdf_core::*;
+  /// let shape = ShapeBuilder::new()
+  ///   .contour((0., 0.))
+  ///   .quadratic_bezier((10., 0.), (10., 10.))
+  ///   .line((0., 0.))
+  ///   .end_contour()
+  ///   .build();
+  /// let svg = shape.curvature_comb_svg(16, 20.);
+  /// assert!(svg.starts_with("<svg"));
+  /// ```
+  #[must_use]
+  pub fn curvature_comb_svg(&self, teeth: usize, scale: f32) -> String {
+    let teeth = teeth.max(1);
+    let (min, max) = self.bounds().unwrap_or((Point::ZERO, Point::ZERO));
+    let margin = ((max - min).abs() * 0.1).max(1.);
+    let (x, y) = (min.x - margin, min.y - margin);
+    let [width, height] =
+      [max.x - min.x, max.y - min.y].map(|d| d + 2. * margin);
+    let mut svg = String::new();
+    let _ = writeln!(
+      svg,
+      r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{x} {y} {width} {height}">"#,
+    );
+    let _ = writeln!(
+      svg,
+      r#"<rect x="{x}" y="{y}" width="{width}" height="{height}" fill="grey"/>"#,
+    );
+    let stroke_width = margin / 20.;
+
+    for spline in &self.splines {
+      let colour = format!("{:?}", spline.colour).to_lowercase();
+      for &segment_ref in &self.segments[spline.segments_range.clone()] {
+        let segment = self.get_segment(segment_ref);
+        let samples: Vec<_> = (0..=teeth)
+          .map(|i| {
+            let t = i as f32 / teeth as f32;
+            let point = segment.sample(t);
+            let tangent = segment.sample_derivative(t).norm();
+            // the centre of the curve lies along the normal when the
+            // curvature is positive
+            let normal = Vector::new(-tangent.y, tangent.x);
+            let length = (segment.curvature(t) * scale).clamp(-1e4, 1e4);
+            (point, point - normal * length)
+          })
+          .collect();
+
+        let _ =
+          write!(svg, r#"<g stroke-width="{stroke_width}" fill="none">"#);
+        let _ = write!(svg, r#"<polyline stroke="{colour}" points=""#);
+        for (point, _) in &samples {
+          let _ = write!(svg, "{},{} ", point.x, point.y);
+        }
+        let _ = write!(svg, r#""/><path stroke="black" d=""#);
+        for (point, tooth) in &samples {
+          let _ =
+            write!(svg, "M{},{}L{},{}", point.x, point.y, tooth.x, tooth.y);
+        }
+        let _ = write!(svg, r#""/><polyline stroke="black" points=""#);
+        for (_, tooth) in &samples {
+          let _ = write!(svg, "{},{} ", tooth.x, tooth.y);
+        }
+        let _ = writeln!(svg, r#""/></g>"#);
+      }
+    }
+    svg.push_str("</svg>\n");
+    svg
+  }
+}
@@ -0,0 +1,263 @@
+use crate::*;
+use elliptical_arc::CentreParam;
+use std::fmt::Write;
+
+impl Shape {
+  /// Write the shape in [msdfgen]'s shape description format
+  ///
+  /// Each segment is written as an edge with the colour of its spline, so
+  /// the shape can be fed to msdfgen with its colouring intact, to cross
+  /// check the two implementations. msdfgen descriptions have y pointing up,
+  /// so y is negated, which also keeps the inside of the shape the same.
+  ///
+  /// msdfgen has no elliptical arcs, so they are approximated by a cubic
+  /// bezier per quarter turn, and colours other than cyan, magenta, yellow
+  /// & white can't be written, so those edges are left uncoloured.
+  ///
+  /// [msdfgen]: https://github.com/Chlumsky/msdfgen
+  ///
+  /// ```
+  /// # use rsdf_builder::ShapeBuilder;
+  /// let triangle = ShapeBuilder::new()
+  ///   .contour((0., 0.))
+  ///   .line((10., 0.))
+  ///   .quadratic_bezier((10., 10.), (0., 10.))
+  ///   .line((0., 0.))
+  ///   .end_contour()
+  ///   .build();
+  /// assert_eq!(
+  ///   triangle.to_shape_description(),
+  ///   "{ 0, 0; m; 10, 0; y (10, -10); 0, -10; c; # }\n",
+  /// );
+  /// ```
+  #[must_use]
+  pub fn to_shape_description(&self) -> String {
+    let mut text = String::new();
+    for contour in &self.contours {
+      let splines = &self.splines[contour.spline_range.clone()];
+      let mut edges = splines.iter().flat_map(|spline| {
+        let segments = &self.segments[spline.segments_range.clone()];
+        segments.iter().map(|&segment| (segment, spline.colour))
+      });
+      let Some((first, colour)) = edges.next() else {
+        continue;
+      };
+      let start = self.segment_start(first);
+      text.push('{');
+      write_point(&mut text, start);
+      for (segment, colour) in std::iter::once((first, colour)).chain(edges) {
+        self.write_edge(&mut text, segment, colour, start);
+      }
+      text.push_str(" }\n");
+    }
+    text
+  }
+
+  /// The first point of a segment; elliptical arcs start at the end of the
+  /// previous segment
+  fn segment_start(&self, segment: SegmentRef) -> Point {
+    match segment.kind {
+      SegmentKind::EllipticalArc => self.points[segment.points_index - 1],
+      _ => self.points[segment.points_index],
+    }
+  }
+
+  /// Write `; colour (controls); end` for a segment, or several for an
+  /// elliptical arc, with `#` for an end at the contour's `start`
+  fn write_edge(
+    &self,
+    text: &mut String,
+    segment: SegmentRef,
+    colour: Colour,
+    start: Point,
+  ) {
+    let i = segment.points_index;
+    let letter = match colour {
+      Cyan => " c",
+      Magenta => " m",
+      Yellow => " y",
+      White => " w",
+      _ => "",
+    };
+    let controls: Vec<(&[Point], Point)> = match segment.kind {
+      SegmentKind::Line => vec![(&[], self.points[i + 1])],
+      SegmentKind::QuadBezier => {
+        vec![(&self.points[i + 1..i + 2], self.points[i + 2])]
+      },
+      SegmentKind::CubicBezier => {
+        vec![(&self.points[i + 1..i + 3], self.points[i + 3])]
+      },
+      SegmentKind::EllipticalArc => {
+        return self.write_arc(text, segment, letter, start);
+      },
+    };
+    for (controls, end) in controls {
+      write_edge(text, letter, controls, end, start);
+    }
+  }
+
+  /// Approximate an elliptical arc with a cubic bezier per quarter turn
+  fn write_arc(
+    &self,
+    text: &mut String,
+    segment: SegmentRef,
+    letter: &str,
+    start: Point,
+  ) {
+    let i = segment.points_index;
+    let arc = CentreParam::from_ps(&self.points[i..i + 4]);
+    let pieces =
+      (arc.delta.abs() / std::f32::consts::FRAC_PI_2).ceil() as usize;
+    let pieces = pieces.max(1);
+    let step = arc.delta / pieces as f32;
+    // the length of the handles, for a bezier through a circular arc
+    let handle = 4. / 3. * (step / 4.).tan();
+    let mut from = self.segment_start(segment);
+    for piece in 0..pieces {
+      let a0 = arc.theta + step * piece as f32;
+      let a1 = a0 + step;
+      // the arc ends exactly where the next segment starts
+      let to = match piece + 1 == pieces {
+        true => self.points[i + 4],
+        false => arc.sample_ellipse(a1),
+      };
+      let controls = [
+        from + arc.sample_ellipse_derivative(a0) * handle,
+        to - arc.sample_ellipse_derivative(a1) * handle,
+      ];
+      write_edge(text, letter, &controls, to, start);
+      from = to;
+    }
+  }
+}
+
+fn write_edge(
+  text: &mut String,
+  letter: &str,
+  controls: &[Point],
+  end: Point,
+  start: Point,
+) {
+  text.push(';');
+  text.push_str(letter);
+  if !controls.is_empty() {
+    text.push_str(" (");
+    for (n, &control) in controls.iter().enumerate() {
+      if n > 0 {
+        text.push_str("; ");
+      }
+      write_coordinates(text, control);
+    }
+    text.push(')');
+  }
+  if !letter.is_empty() || !controls.is_empty() {
+    text.push(';');
+  }
+  if end == start {
+    text.push_str(" #");
+  } else {
+    write_point(text, end);
+  }
+}
+
+fn write_point(text: &mut String, point: Point) {
+  text.push(' ');
+  write_coordinates(text, point);
+}
+
+/// Write `x, y` with y pointing up, without the space before it
+fn write_coordinates(text: &mut String, point: Point) {
+  // subtracting from zero avoids writing negative zeros
+  write!(text, "{}, {}", point.x, 0. - point.y).unwrap();
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn to_shape_description() {
+    let shape = Shape {
+      points: vec![
+        (0., 0.).into(),
+        (4., 0.).into(),
+        (6., 2.).into(),
+        (6., 6.).into(),
+        (0., 6.).into(),
+        (0., 0.).into(),
+      ],
+      segments: vec![
+        SegmentRef {
+          kind: SegmentKind::Line,
+          points_index: 0,
+        },
+        SegmentRef {
+          kind: SegmentKind::CubicBezier,
+          points_index: 1,
+        },
+        SegmentRef {
+          kind: SegmentKind::Line,
+          points_index: 4,
+        },
+      ],
+      splines: vec![
+        Spline {
+          segments_range: 0..2,
+          colour: White,
+        },
+        Spline {
+          segments_range: 2..3,
+          colour: Red,
+        },
+      ],
+      contours: vec![Contour { spline_range: 0..2 }],
+    };
+    assert_eq!(
+      shape.to_shape_description(),
+      "{ 0, 0; w; 4, 0; w (6, -2; 6, -6); 0, -6; # }\n"
+    );
+  }
+
+  #[test]
+  fn arcs() {
+    // a half circle of radius 5 around (5, 0), closed by a line
+    let mut points = vec![Point::new(0., 0.)];
+    points.extend(
+      CentreParam {
+        centre: (5., 0.).into(),
+        r: 5.,
+        k: 1.,
+        phi: 0.,
+        theta: std::f32::consts::PI,
+        delta: std::f32::consts::PI,
+      }
+      .to_ps(),
+    );
+    points.push((10., 0.).into());
+    points.push((0., 0.).into());
+    let shape = Shape {
+      points,
+      segments: vec![
+        SegmentRef {
+          kind: SegmentKind::EllipticalArc,
+          points_index: 1,
+        },
+        SegmentRef {
+          kind: SegmentKind::Line,
+          points_index: 5,
+        },
+      ],
+      splines: vec![Spline {
+        segments_range: 0..2,
+        colour: Yellow,
+      }],
+      contours: vec![Contour { spline_range: 0..1 }],
+    };
+    let text = shape.to_shape_description();
+    // two quarter turns, then the line
+    assert_eq!(text.matches('(').count(), 2);
+    assert!(text.starts_with("{ 0, 0; y ("));
+    assert!(text.contains("; 5, 5; y ("), "{text}");
+    assert!(text.ends_with("; 10, 0; y; # }\n"), "{text}");
+  }
+}
@@ -0,0 +1,154 @@
+//! Deserialized shapes are checked like those decoded by
+//! [`Shape::from_bytes`], so that every reference is in range and the shape
+//! can be sampled without panicking.
+
+use super::*;
+use crate::binary::{check_range, check_segment};
+use crate::DecodeError;
+
+#[derive(serde::Deserialize)]
+pub(super) struct UncheckedSegmentRef {
+  kind: SegmentKind,
+  points_index: usize,
+}
+
+impl TryFrom<UncheckedSegmentRef> for SegmentRef {
+  type Error = DecodeError;
+
+  fn try_from(segment: UncheckedSegmentRef) -> Result<Self, DecodeError> {
+    let segment = SegmentRef {
+      kind: segment.kind,
+      points_index: segment.points_index,
+    };
+    // the points are only known to the shape, but the end must not overflow
+    check_segment(&segment, usize::MAX)?;
+    Ok(segment)
+  }
+}
+
+#[derive(serde::Deserialize)]
+pub(super) struct UncheckedSpline {
+  segments_range: Range<usize>,
+  colour: Colour,
+}
+
+impl TryFrom<UncheckedSpline> for Spline {
+  type Error = DecodeError;
+
+  fn try_from(spline: UncheckedSpline) -> Result<Self, DecodeError> {
+    check_range(&spline.segments_range, usize::MAX)?;
+    Ok(Spline {
+      segments_range: spline.segments_range,
+      colour: spline.colour,
+    })
+  }
+}
+
+#[derive(serde::Deserialize)]
+pub(super) struct UncheckedContour {
+  spline_range: Range<usize>,
+}
+
+impl TryFrom<UncheckedContour> for Contour {
+  type Error = DecodeError;
+
+  fn try_from(contour: UncheckedContour) -> Result<Self, DecodeError> {
+    check_range(&contour.spline_range, usize::MAX)?;
+    Ok(Contour {
+      spline_range: contour.spline_range,
+    })
+  }
+}
+
+#[derive(serde::Deserialize)]
+pub(super) struct UncheckedShape {
+  points: Vec<Point>,
+  segments: Vec<SegmentRef>,
+  splines: Vec<Spline>,
+  contours: Vec<Contour>,
+}
+
+impl TryFrom<UncheckedShape> for Shape {
+  type Error = DecodeError;
+
+  fn try_from(shape: UncheckedShape) -> Result<Self, DecodeError> {
+    for segment in &shape.segments {
+      check_segment(segment, shape.points.len())?;
+    }
+    for spline in &shape.splines {
+      check_range(&spline.segments_range, shape.segments.len())?;
+    }
+    for contour in &shape.contours {
+      check_range(&contour.spline_range, shape.splines.len())?;
+    }
+    Ok(Shape {
+      points: shape.points,
+      segments: shape.segments,
+      splines: shape.splines,
+      contours: shape.contours,
+    })
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  /// A triangle in a single spline
+  fn triangle() -> Shape {
+    Shape {
+      points: vec![
+        Point::new(0., 0.),
+        Point::new(1., 0.),
+        Point::new(0., 1.),
+        Point::new(0., 0.),
+      ],
+      segments: (0..3)
+        .map(|points_index| SegmentRef {
+          kind: SegmentKind::Line,
+          points_index,
+        })
+        .collect(),
+      splines: vec![Spline {
+        segments_range: 0..3,
+        colour: Colour::White,
+      }],
+      contours: vec![Contour { spline_range: 0..1 }],
+    }
+  }
+
+  fn deserialize(value: serde_json::Value) -> Result<Shape, String> {
+    serde_json::from_value(value).map_err(|error| error.to_string())
+  }
+
+  #[test]
+  fn round_trip() {
+    let shape = triangle();
+    let json = serde_json::to_value(&shape).unwrap();
+    assert_eq!(deserialize(json).unwrap().checksum(), shape.checksum());
+
+    let vector = Vector::new(1., -2.);
+    let json = serde_json::to_string(&vector).unwrap();
+    assert_eq!(serde_json::from_str::<Vector>(&json).unwrap(), vector);
+  }
+
+  #[test]
+  fn invalid_references() {
+    let invalid = DecodeError::Invalid.to_string();
+    let check = |edit: fn(&mut serde_json::Value)| {
+      let mut json = serde_json::to_value(triangle()).unwrap();
+      edit(&mut json);
+      let error = deserialize(json).unwrap_err();
+      assert!(error.contains(&invalid), "{error}");
+    };
+
+    // the last line would read past the end of the points
+    check(|json| json["segments"][2]["points_index"] = 3.into());
+    check(|json| json["segments"][0]["points_index"] = usize::MAX.into());
+    // the spline ends after the last segment
+    check(|json| json["splines"][0]["segments_range"]["end"] = 4.into());
+    // the contour's range is backwards, or ends after the last spline
+    check(|json| json["contours"][0]["spline_range"]["start"] = 2.into());
+    check(|json| json["contours"][0]["spline_range"]["end"] = 2.into());
+  }
+}
@@ -1,62 +1,141 @@
+use super::primitives::Segment;
 use crate::*;
-use std::ops::Range;
+use arrayvec::ArrayVec;
+use std::ops::{Deref, Range};
+
+/// The number of segments of a spline that are resolved without allocating
+///
+/// Most splines are made of 1 to 4 segments.
+const INLINE_SEGMENTS: usize = 4;
+
+/// The resolved segments of a [`Spline`], kept on the stack for short splines
+/// so the per-pixel loops don't allocate or resolve them more than once
+pub(crate) enum SplineSegments<'shape> {
+  Inline(ArrayVec<Segment<'shape>, INLINE_SEGMENTS>),
+  Heap(Vec<Segment<'shape>>),
+}
+
+impl<'shape> Deref for SplineSegments<'shape> {
+  type Target = [Segment<'shape>];
+
+  #[inline]
+  fn deref(&self) -> &Self::Target {
+    match self {
+      SplineSegments::Inline(segments) => segments,
+      SplineSegments::Heap(segments) => segments,
+    }
+  }
+}
 
 impl Shape {
+  /// Resolve the segments of a [`Spline`]
+  #[inline]
+  pub(crate) fn spline_segments(
+    &self,
+    segments_range: Range<usize>,
+  ) -> SplineSegments<'_> {
+    let segments = self.segments[segments_range]
+      .iter()
+      .map(|&segment_ref| self.get_segment(segment_ref));
+    match segments.len() <= INLINE_SEGMENTS {
+      true => SplineSegments::Inline(segments.collect()),
+      false => SplineSegments::Heap(segments.collect()),
+    }
+  }
+
   /// Calculate the signed distance and orthogonality of a [`Point`] from a
   /// [`Spline`]
   pub fn spline_distance_orthogonality(
     &self,
     segments_range: Range<usize>,
-    point: Point,
+    point: impl Into<Point>,
   ) -> ((/* dist */ f32, /* orth */ f32), /* end_bias */ Bias) {
-    let mut selected_dist = f32::INFINITY;
-    // initial values don't matter since the first distance will always be set
-    let mut selected_segment = None;
-    let mut selected_t = f32::NAN;
-
-    for &segment_ref in &self.segments[segments_range] {
-      let segment = self.get_segment(segment_ref);
-      let (dist, t) = segment.distance(point);
-      if dist < selected_dist {
-        selected_dist = dist;
-        selected_segment = Some(segment);
-        selected_t = t;
-      }
-    }
-
-    // unwrap is okay since the selected segment will be always be set assuming
-    // any dist < infinity are found above.
-    let selected_segment = selected_segment.unwrap();
-    let orthogonality = selected_segment
-      .sample_derivative(selected_t.clamp(0., 1.))
-      .norm()
-      .signed_area(
-        (point - selected_segment.sample(selected_t.clamp(0., 1.))).norm(),
-      );
+    segments_distance_orthogonality(
+      &self.spline_segments(segments_range),
+      point.into(),
+    )
+  }
 
-    // kind of redundant
-    let signed_dist = selected_dist.copysign(orthogonality);
-
-    // this bias corrects artifacts caused by the pseudo-distance of a spline
-    // looping back on itself
-    let bias = if selected_t <= 0f32 {
-      Bias::Start
-    } else if selected_t >= 1f32 {
-      Bias::End
-    } else {
-      Bias::Centre
-    };
+  /// Calculate the signed distance of a [`Point`] from a [`Spline`], measured
+  /// with the given [`DistanceMetric`]
+  ///
+  /// The nearest point on the spline is still found using the euclidean
+  /// distance; only the length of the offset to it depends on the metric.
+  pub fn spline_distance_with_metric(
+    &self,
+    segments_range: Range<usize>,
+    point: impl Into<Point>,
+    metric: DistanceMetric,
+  ) -> f32 {
+    let point = point.into();
+    let segments = self.spline_segments(segments_range);
+    let nearest = nearest_segment(&segments, point);
+    let ((signed_dist, _), _) = nearest_distance_orthogonality(nearest, point);
+    let (_, segment, t) = nearest;
+    let offset = point - segment.sample(t.clamp(0., 1.));
+    metric.length(offset).copysign(signed_dist)
+  }
 
-    ((signed_dist, orthogonality.abs()), bias)
+  /// A cheap lower bound on the distance from a [`Point`] to a [`Spline`]
+  ///
+  /// Lets the samplers skip splines that can't be closer than the distance
+  /// they have already found.
+  pub fn spline_distance_lower_bound(
+    &self,
+    segments_range: Range<usize>,
+    point: impl Into<Point>,
+  ) -> f32 {
+    segments_distance_lower_bound(
+      &self.spline_segments(segments_range),
+      point.into(),
+    )
   }
 
   /// Calculate the signed pseudo distance of a [`Point`] from a [`Spline`]
+  ///
+  /// The [`Bias`] picks the part of the spline measured against: the spline
+  /// itself for [`Bias::Centre`], or the tangent line extending beyond its
+  /// start or end. Inside is positive, as for [`Shape::sample`].
+  ///
+  /// ```
+  /// # use rsdf_builder::ShapeBuilder;
+  /// # use rsdf_core::*;
+  /// let triangle = ShapeBuilder::from_svg_path("M10,10 H20 L15,20 Z")
+  ///   .unwrap()
+  ///   .build();
+  /// // the top edge, from (10, 10) to (20, 10)
+  /// let top = triangle.splines[0].segments_range.clone();
+  /// let distance = |point, bias| {
+  ///   triangle.spline_pseudo_distance(top.clone(), point, bias)
+  /// };
+  /// assert_eq!(distance((15., 7.), Bias::Centre), -3.);
+  /// // past the end of the edge, where the distance to the end point is 5
+  /// assert_eq!(distance((24., 7.), Bias::End), -3.);
+  /// ```
   pub fn spline_pseudo_distance(
     &self,
     segments_range: Range<usize>,
-    point: Point,
+    point: impl Into<Point>,
     bias: Bias,
   ) -> f32 {
+    self.spline_pseudo_distance_with_metric(
+      segments_range,
+      point,
+      bias,
+      DistanceMetric::Euclidean,
+    )
+  }
+
+  /// Calculate the signed pseudo distance of a [`Point`] from a [`Spline`],
+  /// measured with the given [`DistanceMetric`]
+  pub fn spline_pseudo_distance_with_metric(
+    &self,
+    segments_range: Range<usize>,
+    point: impl Into<Point>,
+    bias: Bias,
+    metric: DistanceMetric,
+  ) -> f32 {
+    let point = point.into();
     let mut selected_dist = f32::INFINITY;
     let mut selected_segment = None;
     let mut selected_t = f32::NAN;
@@ -111,12 +190,99 @@ impl Shape {
       .sample_derivative(selected_t)
       .signed_area(point - selected_segment.sample(selected_t));
 
-    selected_dist.copysign(sign)
+    match metric {
+      DistanceMetric::Euclidean => selected_dist.copysign(sign),
+      _ => {
+        let offset = point - selected_segment.sample(selected_t);
+        metric.length(offset).copysign(sign)
+      },
+    }
+  }
+}
+
+/// The signed distance and orthogonality of a [`Point`] from the resolved
+/// segments of a spline, see [`Shape::spline_distance_orthogonality`]
+pub(crate) fn segments_distance_orthogonality(
+  segments: &[Segment<'_>],
+  point: Point,
+) -> ((/* dist */ f32, /* orth */ f32), /* end_bias */ Bias) {
+  nearest_distance_orthogonality(nearest_segment(segments, point), point)
+}
+
+/// The signed distance and orthogonality of a [`Point`] from the segment
+/// found by [`nearest_segment`]
+fn nearest_distance_orthogonality(
+  (selected_dist, selected_segment, selected_t): (f32, Segment<'_>, f32),
+  point: Point,
+) -> ((/* dist */ f32, /* orth */ f32), /* end_bias */ Bias) {
+  let offset = point - selected_segment.sample(selected_t.clamp(0., 1.));
+  // a point lying exactly on the spline has no direction to it
+  let orthogonality = if offset == Vector::ZERO {
+    0.
+  } else {
+    selected_segment
+      .sample_derivative(selected_t.clamp(0., 1.))
+      .norm()
+      .signed_area(offset.norm())
+  };
+
+  // kind of redundant
+  let signed_dist = selected_dist.copysign(orthogonality);
+
+  // this bias corrects artifacts caused by the pseudo-distance of a spline
+  // looping back on itself
+  let bias = if selected_t <= 0f32 {
+    Bias::Start
+  } else if selected_t >= 1f32 {
+    Bias::End
+  } else {
+    Bias::Centre
+  };
+
+  ((signed_dist, orthogonality.abs()), bias)
+}
+
+/// A cheap lower bound on the distance from a [`Point`] to the resolved
+/// segments of a spline, see [`Shape::spline_distance_lower_bound`]
+#[inline]
+pub(crate) fn segments_distance_lower_bound(
+  segments: &[Segment<'_>],
+  point: Point,
+) -> f32 {
+  segments
+    .iter()
+    .map(|segment| segment.distance_lower_bound(point))
+    .fold(f32::INFINITY, f32::min)
+}
+
+/// Find the segment nearest to a [`Point`], along with the distance and time
+/// `t` of the nearest point on it
+fn nearest_segment<'shape>(
+  segments: &[Segment<'shape>],
+  point: Point,
+) -> (/* dist */ f32, Segment<'shape>, /* t */ f32) {
+  let mut selected_dist = f32::INFINITY;
+  // initial values don't matter since the first distance will always be set
+  let mut selected_segment = None;
+  let mut selected_t = f32::NAN;
+
+  for &segment in segments {
+    let (dist, t) = segment.distance(point);
+    if dist < selected_dist {
+      selected_dist = dist;
+      selected_segment = Some(segment);
+      selected_t = t;
+    }
   }
+
+  // unwrap is okay since the selected segment will be always be set assuming
+  // any dist < infinity are found above.
+  (selected_dist, selected_segment.unwrap(), selected_t)
 }
 
 #[cfg(any(test, doctest))]
 mod tests {
+  use super::*;
   use float_cmp::assert_approx_eq;
 
   #[test]
@@ -175,7 +341,7 @@ mod tests {
     } = shape.splines[0].clone();
 
     {
-      let point = (0., 0.).into();
+      let point = Point::new(0., 0.);
       let dist = shape.spline_pseudo_distance(
         segments_range.clone(),
         point,
@@ -185,7 +351,7 @@ mod tests {
       assert_approx_eq!(f32, dist, expected);
     }
     {
-      let point = (-1., 1.).into();
+      let point = Point::new(-1., 1.);
       let dist = shape.spline_pseudo_distance(
         segments_range.clone(),
         point,
@@ -195,7 +361,7 @@ mod tests {
       assert_approx_eq!(f32, dist, expected);
     }
     {
-      let point = (-1., -1.).into();
+      let point = Point::new(-1., -1.);
       // lies exactly on the curve so the sign is undefined
       let dist = shape.spline_pseudo_distance(
         segments_range.clone(),
@@ -206,7 +372,7 @@ mod tests {
       assert_approx_eq!(f32, dist, expected);
     }
     {
-      let point = (0.5, 1.5).into();
+      let point = Point::new(0.5, 1.5);
       let dist = shape.spline_pseudo_distance(
         segments_range.clone(),
         point,
@@ -216,7 +382,7 @@ mod tests {
       assert_approx_eq!(f32, dist, expected);
     }
     {
-      let point = (2.75, 3.).into();
+      let point = Point::new(2.75, 3.);
       let dist = shape.spline_pseudo_distance(
         segments_range.clone(),
         point,
@@ -226,7 +392,7 @@ mod tests {
       assert_approx_eq!(f32, dist, expected);
     }
     {
-      let point = (2.75, 1.5).into();
+      let point = Point::new(2.75, 1.5);
       let dist = shape.spline_pseudo_distance(
         segments_range.clone(),
         point,
@@ -236,7 +402,7 @@ mod tests {
       assert_approx_eq!(f32, dist, expected);
     }
     {
-      let point = (5., 0.).into();
+      let point = Point::new(5., 0.);
       let dist = shape.spline_pseudo_distance(
         segments_range.clone(),
         point,
@@ -303,21 +469,21 @@ mod tests {
     } = shape.splines[0].clone();
 
     {
-      let point = (0., 0.).into();
+      let point = Point::new(0., 0.);
       let ((dist, _), _) =
         shape.spline_distance_orthogonality(segments_range.clone(), point);
       let expected = 0.;
       assert_approx_eq!(f32, dist, expected);
     }
     {
-      let point = (-1., 1.).into();
+      let point = Point::new(-1., 1.);
       let ((dist, _), _) =
         shape.spline_distance_orthogonality(segments_range.clone(), point);
       let expected = -SQRT_2;
       assert_approx_eq!(f32, dist, expected);
     }
     {
-      let point = (-1., -1.).into();
+      let point = Point::new(-1., -1.);
       // lies exactly on the curve so the sign is undefined
       let dist = shape
         .spline_distance_orthogonality(segments_range.clone(), point)
@@ -328,32 +494,209 @@ mod tests {
       assert_approx_eq!(f32, dist, expected);
     }
     {
-      let point = (0.5, 1.5).into();
+      let point = Point::new(0.5, 1.5);
       let ((dist, _), _) =
         shape.spline_distance_orthogonality(segments_range.clone(), point);
       let expected = -SQRT_2 / 2.;
       assert_approx_eq!(f32, dist, expected);
     }
     {
-      let point = (2.75, 3.).into();
+      let point = Point::new(2.75, 3.);
       let ((dist, _), _) =
         shape.spline_distance_orthogonality(segments_range.clone(), point);
       let expected = -1.;
       assert_approx_eq!(f32, dist, expected);
     }
     {
-      let point = (2.75, 1.5).into();
+      let point = Point::new(2.75, 1.5);
       let ((dist, _), _) =
         shape.spline_distance_orthogonality(segments_range.clone(), point);
       let expected = 0.5;
       assert_approx_eq!(f32, dist, expected);
     }
     {
-      let point = (5., 0.).into();
+      let point = Point::new(5., 0.);
       let ((dist, _), _) =
         shape.spline_distance_orthogonality(segments_range.clone(), point);
       let expected = -1. / 5f32.sqrt();
       assert_approx_eq!(f32, dist, expected);
     }
   }
+
+  /// A shape holding a single spline made of the given segments, each given
+  /// as its kind and the points that follow the previous segment's end
+  fn spline(start: Point, segments: &[(SegmentKind, &[Point])]) -> Shape {
+    let mut points = vec![start];
+    let mut segment_refs = vec![];
+    for &(kind, ps) in segments {
+      let points_index = match kind {
+        // arcs are stored as their centre parameters, followed by their end
+        SegmentKind::EllipticalArc => points.len(),
+        _ => points.len() - 1,
+      };
+      segment_refs.push(SegmentRef { kind, points_index });
+      points.extend_from_slice(ps);
+    }
+    Shape {
+      points,
+      splines: vec![Spline {
+        segments_range: 0..segment_refs.len(),
+        colour: White,
+      }],
+      segments: segment_refs,
+      contours: vec![Contour { spline_range: 0..1 }],
+    }
+  }
+
+  #[test]
+  fn spline_pseudo_distance_curve_extensions() {
+    use super::*;
+    use std::f32::consts::{FRAC_PI_2, SQRT_2};
+    use SegmentKind::*;
+
+    // a single quadratic, whose start extends down and to the left
+    let shape = spline(
+      (0., 0.).into(),
+      &[(QuadBezier, &[(2., 2.).into(), (4., 0.).into()])],
+    );
+    let point = Point::new(-2., -1.);
+    let dist = shape.spline_pseudo_distance(0..1, point, Bias::Start);
+    assert_approx_eq!(f32, dist, SQRT_2 / 2., epsilon = 0.0001);
+    // without the bias the extension isn't used
+    let dist = shape.spline_pseudo_distance(0..1, point, Bias::Centre);
+    assert_approx_eq!(f32, dist, 5f32.sqrt(), epsilon = 0.0001);
+
+    // a single cubic, whose end extends down and to the right
+    let cubic: [Point; 3] =
+      [(1., 2.).into(), (3., 2.).into(), (4., 0.).into()];
+    let shape = spline((0., 0.).into(), &[(CubicBezier, &cubic)]);
+    let point = Point::new(5., -1.);
+    let dist = shape.spline_pseudo_distance(0..1, point, Bias::End);
+    assert_approx_eq!(f32, dist, 1. / 5f32.sqrt(), epsilon = 0.0001);
+    let dist = shape.spline_pseudo_distance(0..1, point, Bias::Centre);
+    assert_approx_eq!(f32, dist, SQRT_2, epsilon = 0.0001);
+
+    // the same cubic ending a spline that starts with a line
+    let shape = spline(
+      (-4., 0.).into(),
+      &[(Line, &[(0., 0.).into()]), (CubicBezier, &cubic)],
+    );
+    let dist = shape.spline_pseudo_distance(0..2, point, Bias::End);
+    assert_approx_eq!(f32, dist, 1. / 5f32.sqrt(), epsilon = 0.0001);
+
+    // an anticlockwise quarter of the unit circle, from (1, 0) to (0, 1),
+    // whose start extends straight down
+    let arc: [Point; 5] = [
+      (0., 0.).into(),
+      (1., 1.).into(),
+      (0., f32::NAN).into(),
+      (0., FRAC_PI_2).into(),
+      (0., 1.).into(),
+    ];
+    let shape = spline((1., 0.).into(), &[(EllipticalArc, &arc)]);
+    let point = Point::new(2., -3.);
+    let dist = shape.spline_pseudo_distance(0..1, point, Bias::Start);
+    assert_approx_eq!(f32, dist, -1., epsilon = 0.0001);
+    let dist = shape.spline_pseudo_distance(0..1, point, Bias::Centre);
+    assert_approx_eq!(f32, dist, -10f32.sqrt(), epsilon = 0.0001);
+
+    // the same arc reversed, so its end extends straight down
+    let reversed: [Point; 5] = [
+      (0., 0.).into(),
+      (1., 1.).into(),
+      (0., f32::NAN).into(),
+      (FRAC_PI_2, -FRAC_PI_2).into(),
+      (1., 0.).into(),
+    ];
+    let shape = spline((0., 1.).into(), &[(EllipticalArc, &reversed)]);
+    let dist = shape.spline_pseudo_distance(0..1, point, Bias::End);
+    assert_approx_eq!(f32, dist, 1., epsilon = 0.0001);
+
+    // the arc starting a spline that continues with a line and a curve
+    let shape = spline(
+      (1., 0.).into(),
+      &[
+        (EllipticalArc, &arc),
+        (Line, &[(-2., 1.).into()]),
+        (QuadBezier, &[(-3., 1.).into(), (-3., 0.).into()]),
+      ],
+    );
+    let dist = shape.spline_pseudo_distance(0..3, point, Bias::Start);
+    assert_approx_eq!(f32, dist, -1., epsilon = 0.0001);
+    let dist = shape.spline_pseudo_distance(0..3, point, Bias::Centre);
+    assert_approx_eq!(f32, dist, -10f32.sqrt(), epsilon = 0.0001);
+  }
+
+  #[test]
+  fn cusp() {
+    use super::*;
+
+    // a cubic with a cusp at (0.5, 0.75), and one with coincident control
+    // points at its ends, each closed by a line
+    let points = vec![
+      (0., 0.).into(),
+      (1., 1.).into(),
+      (0., 1.).into(),
+      (1., 0.).into(),
+      (0., 0.).into(),
+      (4., 4.).into(),
+      (4., 4.).into(),
+      (8., 2.).into(),
+      (8., 2.).into(),
+      (4., 4.).into(),
+    ];
+    let segments = vec![
+      SegmentRef {
+        kind: SegmentKind::CubicBezier,
+        points_index: 0,
+      },
+      SegmentRef {
+        kind: SegmentKind::Line,
+        points_index: 3,
+      },
+      SegmentRef {
+        kind: SegmentKind::CubicBezier,
+        points_index: 5,
+      },
+      SegmentRef {
+        kind: SegmentKind::Line,
+        points_index: 8,
+      },
+    ];
+    let shape = Shape {
+      points,
+      segments,
+      splines: vec![
+        Spline {
+          segments_range: 0..2,
+          colour: White,
+        },
+        Spline {
+          segments_range: 2..4,
+          colour: White,
+        },
+      ],
+      contours: vec![Contour { spline_range: 0..2 }],
+    };
+
+    let cases = [
+      (0..2, (0.5, 0.75)),
+      (0..2, (0.5, 2.)),
+      (0..2, (0.25, 0.75)),
+      (2..4, (3., 3.)),
+      (2..4, (3., 5.)),
+      (2..4, (9., 2.)),
+    ];
+    for (segments_range, point) in cases {
+      let point = Point::from(point);
+      let ((dist, orth), _) =
+        shape.spline_distance_orthogonality(segments_range.clone(), point);
+      assert!(!dist.is_nan() && !orth.is_nan(), "NaN at {point:?}");
+      for bias in [Bias::Start, Bias::Centre, Bias::End] {
+        let dist =
+          shape.spline_pseudo_distance(segments_range.clone(), point, bias);
+        assert!(!dist.is_nan(), "NaN at {point:?}");
+      }
+    }
+  }
 }
@@ -0,0 +1,133 @@
+use crate::*;
+use std::mem::discriminant;
+use std::ops::Range;
+
+impl Shape {
+  /// Interpolate between two shapes with the same structure, where `t = 0`
+  /// gives `a` and `t = 1` gives `b`
+  ///
+  /// The shapes must have the same segments, in the same contours, as for
+  /// the instances of a glyph in a variable font, or the targets of an SVG
+  /// morph. Every point, and every parameter of the elliptical arcs, is
+  /// interpolated linearly. The splines (and so the colouring) are taken
+  /// from whichever shape is nearer, since a corner in one shape may be
+  /// smooth in the other.
+  ///
+  /// Returns `None` if the shapes aren't compatible.
+  ///
+  /// ```
+  /// # use rsdf_builder::ShapeBuilder;
+  /// # use rsdf_core::*;
+  /// let square = |size: f32| {
+  ///   ShapeBuilder::new()
+  ///     .contour((0., 0.))
+  ///     .line((size, 0.))
+  ///     .line((size, size))
+  ///     .line((0., size))
+  ///     .line((0., 0.))
+  ///     .end_contour()
+  ///     .build()
+  /// };
+  /// let halfway = Shape::lerp(&square(4.), &square(8.), 0.5).unwrap();
+  /// assert_eq!(halfway.points[2], Point::new(6., 6.));
+  /// ```
+  #[must_use]
+  pub fn lerp(a: &Shape, b: &Shape, t: f32) -> Option<Shape> {
+    if !a.is_compatible(b) {
+      return None;
+    }
+    let nearer = if t < 0.5 { a } else { b };
+    let points = a
+      .points
+      .iter()
+      .zip(&b.points)
+      .map(|(&p, &q)| {
+        (p.as_vector() * (1. - t) + q.as_vector() * t).as_point()
+      })
+      .collect();
+    Some(Shape {
+      points,
+      segments: a.segments.clone(),
+      splines: nearer.splines.clone(),
+      contours: nearer.contours.clone(),
+    })
+  }
+
+  /// Whether the shapes have the same segments, grouped into the same
+  /// contours, so they can be interpolated
+  fn is_compatible(&self, other: &Shape) -> bool {
+    let same_segments = self.points.len() == other.points.len()
+      && self.segments.len() == other.segments.len()
+      && self.segments.iter().zip(&other.segments).all(|(s, o)| {
+        discriminant(&s.kind) == discriminant(&o.kind)
+          && s.points_index == o.points_index
+      });
+    let contour_segments = |shape: &Shape| -> Vec<Range<usize>> {
+      shape
+        .contours
+        .iter()
+        .map(|contour| {
+          let splines = &shape.splines[contour.spline_range.clone()];
+          match (splines.first(), splines.last()) {
+            (Some(first), Some(last)) => {
+              first.segments_range.start..last.segments_range.end
+            },
+            _ => 0..0,
+          }
+        })
+        .collect()
+    };
+    same_segments && contour_segments(self) == contour_segments(other)
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  /// A square, split into a spline per side when it has sharp corners
+  fn square(size: f32, sharp: bool) -> Shape {
+    let colours: &[Colour] = match sharp {
+      true => &[Magenta, Yellow, Cyan, Yellow],
+      false => &[White],
+    };
+    Shape::rectangle((0., 0.), (size, size), colours)
+  }
+
+  #[test]
+  fn lerp() {
+    let (a, b) = (square(4., true), square(8., false));
+    let start = Shape::lerp(&a, &b, 0.).unwrap();
+    assert_eq!(start.checksum(), a.checksum());
+    let end = Shape::lerp(&a, &b, 1.).unwrap();
+    assert_eq!(end.checksum(), b.checksum());
+
+    let quarter = Shape::lerp(&a, &b, 0.25).unwrap();
+    assert_eq!(quarter.points[2], Point::new(5., 5.));
+    assert_eq!(quarter.splines.len(), 4);
+    let three_quarters = Shape::lerp(&a, &b, 0.75).unwrap();
+    assert_eq!(three_quarters.points[2], Point::new(7., 7.));
+    assert_eq!(three_quarters.splines.len(), 1);
+  }
+
+  #[test]
+  fn incompatible() {
+    let square = square(4., true);
+    let mut triangle = square.clone();
+    triangle.points.pop();
+    triangle.segments.pop();
+    assert!(Shape::lerp(&square, &triangle, 0.5).is_none());
+
+    let mut curved = square.clone();
+    curved.segments[0].kind = SegmentKind::QuadBezier;
+    assert!(Shape::lerp(&square, &curved, 0.5).is_none());
+
+    // the same segments, split into two contours
+    let mut split = square.clone();
+    split.contours = vec![
+      Contour { spline_range: 0..2 },
+      Contour { spline_range: 2..4 },
+    ];
+    assert!(Shape::lerp(&square, &split, 0.5).is_none());
+  }
+}
@@ -13,7 +13,23 @@ use arrayvec::ArrayVec;
 use std::ops::{Bound, RangeBounds};
 
 /// The primitive type of a [`Segment`]
+///
+/// More primitives may be added, so downstream matches need a wildcard arm:
+///
+/// ```compile_fail
+/// # use rsdf_core::SegmentKind;
+/// fn points(kind: SegmentKind) -> usize {
+///   match kind {
+///     SegmentKind::Line => 2,
+///     SegmentKind::QuadBezier => 3,
+///     SegmentKind::CubicBezier => 4,
+///     SegmentKind::EllipticalArc => 5,
+///   }
+/// }
+/// ```
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub enum SegmentKind {
   /// Line, consisting of 2 [`Point`] - starting, ending positions.
   Line,
@@ -35,7 +51,7 @@ impl Shape {
   pub fn get_segment(
     &self,
     SegmentRef{kind, points_index: i}: SegmentRef
-  ) -> Segment {
+  ) -> Segment<'_> {
     match kind {
       SegmentKind::Line => Segment::Line(&self.points[i..i + 2]),
       SegmentKind::QuadBezier => Segment::QuadBezier(&self.points[i..i + 3]),
@@ -72,7 +88,7 @@ impl Segment<'_> {
       let p1 = p0 + self.sample_derivative(clamped_t);
       let line = [p0, p1];
       Line::sample(&line, t)
-    } else if t > 0f32 {
+    } else if t > 1f32 {
       let p1 = sample;
       let p0 = p1 - self.sample_derivative(clamped_t);
       let line = [p0, p1];
@@ -90,10 +106,10 @@ impl Segment<'_> {
     // beyond the end of the curve). ditto for the < 0.
     let t = t.clamp(0f32, 1f32);
     match self {
-      Segment::Line(ps) => Line::sample_derivative(ps, t),
-      Segment::QuadBezier(ps) => QuadBezier::sample_derivative(ps, t),
-      Segment::CubicBezier(ps) => CubicBezier::sample_derivative(ps, t),
-      Segment::EllipticalArc(ps) => EllipticalArc::sample_derivative(ps, t),
+      Segment::Line(ps) => Line::tangent(ps, t),
+      Segment::QuadBezier(ps) => QuadBezier::tangent(ps, t),
+      Segment::CubicBezier(ps) => CubicBezier::tangent(ps, t),
+      Segment::EllipticalArc(ps) => EllipticalArc::tangent(ps, t),
     }
   }
 
@@ -117,6 +133,96 @@ impl Segment<'_> {
     }
   }
 
+  /// A cheap lower bound on the distance from a point to the segment
+  ///
+  /// Bezier curves are contained by the bounding box of their control points,
+  /// and elliptical arcs by the circle of their major radius.
+  #[inline]
+  pub fn distance_lower_bound(self, point: Point) -> f32 {
+    match self {
+      Segment::Line(ps)
+      | Segment::QuadBezier(ps)
+      | Segment::CubicBezier(ps) => {
+        let (min, max) = control_point_bounds(ps);
+        let dx = (min.x - point.x).max(point.x - max.x).max(0.);
+        let dy = (min.y - point.y).max(point.y - max.y).max(0.);
+        Vector::new(dx, dy).abs()
+      },
+      Segment::EllipticalArc(ps) => {
+        let params = CentreParam::from_ps(ps);
+        let major_radius = params.r.abs() * params.k.abs().max(1.);
+        ((point - params.centre).abs() - major_radius).max(0.)
+      },
+    }
+  }
+
+  /// The signed curvature of the segment at time `t`, the reciprocal of the
+  /// radius of the circle that best fits it there
+  ///
+  /// The curvature is positive where the segment turns clockwise (with y
+  /// pointing down), and `0` along lines. It's infinite at a cusp, where the
+  /// derivative vanishes.
+  ///
+  /// ```
+  /// # use rsdf_builder::ShapeBuilder;
+  /// // the parabola y = x^2, with a radius of curvature of 1/2 at its vertex
+  /// let parabola = ShapeBuilder::new()
+  ///   .contour((-1., 1.))
+  ///   .quadratic_bezier((0., -1.), (1., 1.))
+  ///   .end_contour()
+  ///   .build();
+  /// let segment = parabola.get_segment(parabola.segments[0]);
+  /// assert_eq!(segment.curvature(0.5), 2.);
+  /// ```
+  pub fn curvature(self, t: f32) -> f32 {
+    let t = t.clamp(0f32, 1f32);
+    let (first, second) = match self {
+      Segment::Line(_) => return 0.,
+      Segment::QuadBezier(ps) => (
+        QuadBezier::sample_derivative(ps, t),
+        2. * (ps[2].as_vector() - 2. * ps[1].as_vector() + ps[0].as_vector()),
+      ),
+      Segment::CubicBezier(ps) => {
+        let [p0, p1, p2, p3] =
+          [ps[0], ps[1], ps[2], ps[3]].map(|p| p.as_vector());
+        (
+          CubicBezier::sample_derivative(ps, t),
+          6. * ((1. - t) * (p2 - 2. * p1 + p0) + t * (p3 - 2. * p2 + p1)),
+        )
+      },
+      Segment::EllipticalArc(ps) => {
+        let arc = CentreParam::from_ps(ps);
+        let angle = arc.theta + t * arc.delta;
+        // the second derivative of an ellipse points back at its centre
+        let radial = arc.sample_ellipse(angle) - arc.centre;
+        (
+          arc.delta * arc.sample_ellipse_derivative(angle),
+          -(arc.delta * arc.delta) * radial,
+        )
+      },
+    };
+    first.wedge(second) / first.abs().powi(3)
+  }
+
+  /// A box containing the segment, as its `(min, max)` corners
+  ///
+  /// Uses the same conservative bounds as
+  /// [`distance_lower_bound`](Self::distance_lower_bound).
+  #[inline]
+  pub fn bounds(self) -> (Point, Point) {
+    match self {
+      Segment::Line(ps)
+      | Segment::QuadBezier(ps)
+      | Segment::CubicBezier(ps) => control_point_bounds(ps),
+      Segment::EllipticalArc(ps) => {
+        let params = CentreParam::from_ps(ps);
+        let major_radius = params.r.abs() * params.k.abs().max(1.);
+        let radius = Vector::new(major_radius, major_radius);
+        (params.centre - radius, params.centre + radius)
+      },
+    }
+  }
+
   /// Get the distance from a point to the segment at time `t`
   #[inline]
   pub fn distance(self, point: Point) -> (/* dist */ f32, /* t */ f32) {
@@ -139,6 +245,17 @@ pub trait Primitive {
   /// Return a tangent to the primitve at time `t`
   fn sample_derivative(ps: &[Point], t: f32) -> Vector;
 
+  /// Return a tangent to the primitive at time `t`, which is never zero
+  /// unless the primitive has no length
+  ///
+  /// Unlike [`Primitive::sample_derivative`], this doesn't vanish at cusps or
+  /// at ends with coincident control points, where the direction the curve
+  /// leaves `t` in (or arrives at `t` from, when `t` is `1`) is used instead.
+  #[inline]
+  fn tangent(ps: &[Point], t: f32) -> Vector {
+    Self::sample_derivative(ps, t)
+  }
+
   /// Find when normals of the primitive intersect the given point, where the
   /// times returned fall within the given `range`
   fn find_normals<R: RangeBounds<f32> + Clone>(
@@ -169,7 +286,7 @@ pub trait Primitive {
     let (start, end) = range_to_values(range);
     if start < 0. {
       let p0 = Self::sample(ps, 0.);
-      let p1 = p0 + Self::sample_derivative(ps, 0.);
+      let p1 = p0 + Self::tangent(ps, 0.);
       let line = [p0, p1];
       if let Some(t) = Line::find_normals(&line, point, start..0f32) {
         let dist = (point - Line::sample(&line, t)).abs();
@@ -181,7 +298,7 @@ pub trait Primitive {
     }
     if end > 1. {
       let p1 = Self::sample(ps, 1.);
-      let p0 = p1 - Self::sample_derivative(ps, 1.);
+      let p0 = p1 - Self::tangent(ps, 1.);
       let line = [p0, p1];
       if let Some(t) = Line::find_normals(&line, point, 1f32..end) {
         let dist = (point - Line::sample(&line, t)).abs();
@@ -216,6 +333,46 @@ pub trait Primitive {
   }
 }
 
+/// The bounding box of a set of control points, as its `(min, max)` corners
+#[inline]
+fn control_point_bounds(ps: &[Point]) -> (Point, Point) {
+  ps.iter().skip(1).fold((ps[0], ps[0]), |(a, b), p| {
+    (
+      Point::new(a.x.min(p.x), a.y.min(p.y)),
+      Point::new(b.x.max(p.x), b.y.max(p.y)),
+    )
+  })
+}
+
+/// Derivatives shorter than this, relative to the extent of the control
+/// points, are considered to vanish
+const CUSP_TOLERANCE: f32 = 1e-5;
+
+/// Pick a tangent from the successive derivatives of a curve at time `t`
+///
+/// The first derivative that doesn't vanish gives the direction of the curve
+/// either side of `t`, up to a sign: even order derivatives point backwards
+/// along the curve when approaching `t` from below.
+#[inline]
+pub fn one_sided_tangent<const N: usize>(
+  ps: &[Point],
+  derivatives: [Vector; N],
+  t: f32,
+) -> Vector {
+  let extent = ps.iter().map(|&p| (p - ps[0]).abs()).fold(0f32, f32::max);
+  let tolerance = CUSP_TOLERANCE * extent;
+  for (k, &derivative) in derivatives.iter().enumerate() {
+    if derivative.abs() > tolerance {
+      return if t >= 1. && k % 2 == 1 {
+        -derivative
+      } else {
+        derivative
+      };
+    }
+  }
+  derivatives[0]
+}
+
 /// Helps turn a `RangeBounds<f32>` into a pair of `f32`s.
 #[rustfmt::skip]
 #[inline]
@@ -240,6 +397,53 @@ pub fn range_to_values<R: RangeBounds<f32> + Clone>(
 mod tests {
   use super::*;
 
+  #[test]
+  fn sample() {
+    // the parabola y = x^2
+    let quad = [(-1., 1.), (0., -1.), (1., 1.)].map(Point::from);
+    let segment = Segment::QuadBezier(&quad);
+    // on the curve within the segment
+    assert_eq!(segment.sample(0.5), Point::new(0., 0.));
+    assert_eq!(segment.sample(1.), Point::new(1., 1.));
+    // & along the tangents beyond its ends
+    assert_eq!(segment.sample(-0.5), Point::new(-2., 3.));
+    assert_eq!(segment.sample(1.5), Point::new(2., 3.));
+  }
+
+  #[test]
+  fn curvature() {
+    // the parabola y = x^2, turning clockwise with a radius of curvature of
+    // 1/2 at its vertex
+    let quad = [(-1., 1.), (0., -1.), (1., 1.)].map(Point::from);
+    let segment = Segment::QuadBezier(&quad);
+    float_cmp::assert_approx_eq!(f32, segment.curvature(0.5), 2.);
+    // raised to a cubic, the curve is the same
+    let cubic = [
+      (-1., 1.),
+      (-1. / 3., -1. / 3.),
+      (1. / 3., -1. / 3.),
+      (1., 1.),
+    ]
+    .map(Point::from);
+    let segment = Segment::CubicBezier(&cubic);
+    float_cmp::assert_approx_eq!(f32, segment.curvature(0.5), 2.);
+    let line = [Point::ZERO, Point::new(1., 1.)];
+    assert_eq!(Segment::Line(&line).curvature(0.3), 0.);
+
+    // a quarter circle of radius 2, turning anticlockwise
+    let arc = CentreParam {
+      centre: Point::ZERO,
+      r: 2.,
+      k: 1.,
+      phi: 0.,
+      theta: 0.,
+      delta: -std::f32::consts::FRAC_PI_2,
+    }
+    .to_ps();
+    let curvature = Segment::EllipticalArc(&arc).curvature(0.5);
+    float_cmp::assert_approx_eq!(f32, curvature, -0.5, epsilon = 1e-6);
+  }
+
   #[test]
   fn get_segment() {
     use SegmentKind::*;
@@ -327,4 +531,40 @@ mod tests {
       assert_eq!(result, expected);
     }
   }
+
+  #[test]
+  fn distance_lower_bound() {
+    let line = [(0., 0.).into(), (4., 0.).into()];
+    let cubic = [
+      (0., 0.).into(),
+      (2., 4.).into(),
+      (6., 4.).into(),
+      (8., 0.).into(),
+    ];
+    let arc = CentreParam {
+      centre: (2., 2.).into(),
+      r: 2.,
+      k: 0.5,
+      phi: 0.,
+      theta: 0.,
+      delta: 3.,
+    }
+    .to_ps();
+    let segments = [
+      Segment::Line(&line),
+      Segment::CubicBezier(&cubic),
+      Segment::EllipticalArc(&arc),
+    ];
+
+    for x in -4..12 {
+      for y in -4..8 {
+        let point = (x as f32, y as f32).into();
+        for segment in segments {
+          let bound = segment.distance_lower_bound(point);
+          let (dist, _) = segment.distance(point);
+          assert!(bound <= dist + 0.0001, "{segment:?} {point:?}");
+        }
+      }
+    }
+  }
 }
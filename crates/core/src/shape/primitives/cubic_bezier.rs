@@ -25,6 +25,19 @@ impl Primitive for CubicBezier {
         * (ps[3].as_vector() - 3f32*ps[2].as_vector() + 3f32*ps[1].as_vector() - ps[0].as_vector())
   }
 
+  #[inline]
+  #[rustfmt::skip]
+  fn tangent(ps: &[Point], t: f32) -> Vector {
+    let v2 = ps[2].as_vector() - 2f32*ps[1].as_vector() + ps[0].as_vector();
+    let v3 = ps[3].as_vector() - 3f32*ps[2].as_vector() + 3f32*ps[1].as_vector() - ps[0].as_vector();
+    let derivatives = [
+      Self::sample_derivative(ps, t),
+      6f32*(v2 + t*v3),
+      6f32*v3,
+    ];
+    one_sided_tangent(ps, derivatives, t)
+  }
+
   #[rustfmt::skip]
   fn find_normals<R: RangeBounds<f32>>(
     ps: &[Point],
@@ -45,7 +58,8 @@ impl Primitive for CubicBezier {
       v3.dot(v3),
     ];
 
-    roots_in_range(&polynomial, range)
+    // the quintic rarely converges within the default 15 rounds
+    roots_in_range_with_iterations(&polynomial, range, 64)
   }
 }
 
@@ -159,6 +173,34 @@ mod tests {
     }
   }
 
+  #[test]
+  fn tangent() {
+    use super::*;
+    // a cusp at t = 0.5, where the derivative vanishes
+    let cusp = [
+      (0., 0.).into(),
+      (1., 1.).into(),
+      (0., 1.).into(),
+      (1., 0.).into(),
+    ];
+    assert_eq!(CubicBezier::sample_derivative(&cusp, 0.5), Vector::ZERO);
+    let result = CubicBezier::tangent(&cusp, 0.5).norm();
+    assert_approx_eq!(Vector, result, (0., -1.).into());
+
+    // coincident control points at both ends
+    let cubic = [
+      (0., 0.).into(),
+      (0., 0.).into(),
+      (4., 2.).into(),
+      (4., 2.).into(),
+    ];
+    let expected = Vector::from((2., 1.)).norm();
+    let result = CubicBezier::tangent(&cubic, 0.).norm();
+    assert_approx_eq!(Vector, result, expected);
+    let result = CubicBezier::tangent(&cubic, 1.).norm();
+    assert_approx_eq!(Vector, result, expected);
+  }
+
   #[test]
   fn find_normals() {
     use super::*;
@@ -19,17 +19,15 @@ impl Primitive for EllipticalArc {
   #[inline]
   fn sample(ps: &[Point], t: f32) -> Point {
     let params = CentreParam::from_ps(ps);
-    let angle = params.theta + t * params.delta;
-    params.sample_ellipse(angle)
+    params.sample_ellipse_f64(params.angle_at(t))
   }
 
   #[inline]
   fn sample_derivative(ps: &[Point], t: f32) -> Vector {
     let params = CentreParam::from_ps(ps);
-    let angle = params.theta + t * params.delta;
     // we must negate the derivative when the curve is reversed.
     let sign = 1f32.copysign(params.delta);
-    params.sample_ellipse_derivative(angle) * sign
+    params.sample_ellipse_derivative_f64(params.angle_at(t)) * sign
   }
 
   #[inline]
@@ -39,14 +37,19 @@ impl Primitive for EllipticalArc {
     _range: R,
   ) -> Self::Ts {
     // TODO: I hate this.
-    let range = 0f32..=1f32;
+    let range = 0f64..=1f64;
+    // the arc's angles are only given in single precision, so roots that
+    // fall just outside either end of it are snapped onto the end
+    let tolerance = f32::EPSILON as f64;
 
     let params = CentreParam::from_ps(ps);
+    let (theta, delta) = (params.theta as f64, params.delta as f64);
     params
-      .find_normals(point)
+      .find_normal_angles(point)
       .iter()
-      .map(|angle| (angle - params.theta) / params.delta)
-      .filter(|t| range.contains(t))
+      .map(|angle| (angle - theta) / delta)
+      .filter(|t| (-tolerance..=1. + tolerance).contains(t))
+      .map(|t| t.clamp(*range.start(), *range.end()) as f32)
       .collect()
   }
 }
@@ -95,12 +98,15 @@ impl CentreParam {
 
   /// Compose an array of `Point`s from a centre parameterisation of an
   /// ellipse
+  ///
+  /// The unused coordinate is zero rather than NaN, so that shapes can be
+  /// written to formats without NaN, and compared.
   #[inline]
   pub fn to_ps(self) -> [Point; 4] {
     [
       self.centre,
       (self.r, self.k).into(),
-      (self.phi, f32::NAN).into(),
+      (self.phi, 0.).into(),
       (self.theta, self.delta).into(),
     ]
   }
@@ -110,13 +116,7 @@ impl CentreParam {
   ///
   /// Note: does not take `theta` or `delta` into account.
   pub fn sample_ellipse(&self, angle: f32) -> Point {
-    let ry = self.k * self.r;
-    let (phi_sin, phi_cos) = self.phi.sin_cos();
-    let (ang_sin, ang_cos) = angle.sin_cos();
-    Point {
-      x: self.r * phi_cos * ang_cos - ry * phi_sin * ang_sin + self.centre.x,
-      y: self.r * phi_sin * ang_cos + ry * phi_cos * ang_sin + self.centre.y,
-    }
+    self.sample_ellipse_f64(angle as f64)
   }
 
   /// Sample the derivative of the ellipse described by the parameters:
@@ -124,13 +124,7 @@ impl CentreParam {
   ///
   /// Note: does not take `theta` or `delta` into account.
   pub fn sample_ellipse_derivative(&self, angle: f32) -> Vector {
-    let ry = self.k * self.r;
-    let (phi_sin, phi_cos) = self.phi.sin_cos();
-    let (ang_sin, ang_cos) = angle.sin_cos();
-    Vector {
-      x: -self.r * phi_cos * ang_sin - ry * phi_sin * ang_cos,
-      y: -self.r * phi_sin * ang_sin + ry * phi_cos * ang_cos,
-    }
+    self.sample_ellipse_derivative_f64(angle as f64)
   }
 
   /// Find the pseudo angle of the ellipse where a normal points in the
@@ -139,30 +133,79 @@ impl CentreParam {
   /// Note: does not take `theta` or `delta` into account. Just returns angles
   /// for the ellipse itself.
   pub fn find_normals(&self, point: Point) -> [f32; 8] {
+    self.find_normal_angles(point).map(|angle| angle as f32)
+  }
+
+  // The angle math is done in double precision internally, since the terms
+  // cancel catastrophically near multiples of `phi` in single precision.
+
+  /// The pseudo angle of the arc at time `t`
+  #[inline]
+  fn angle_at(&self, t: f32) -> f64 {
+    self.theta as f64 + t as f64 * self.delta as f64
+  }
+
+  #[inline]
+  fn sample_ellipse_f64(&self, angle: f64) -> Point {
+    let (r, ry) = (self.r as f64, (self.k * self.r) as f64);
+    let (phi_sin, phi_cos) = (self.phi as f64).sin_cos();
+    let (ang_sin, ang_cos) = angle.sin_cos();
+    Point {
+      x: (r * phi_cos * ang_cos - ry * phi_sin * ang_sin) as f32
+        + self.centre.x,
+      y: (r * phi_sin * ang_cos + ry * phi_cos * ang_sin) as f32
+        + self.centre.y,
+    }
+  }
+
+  #[inline]
+  fn sample_ellipse_derivative_f64(&self, angle: f64) -> Vector {
+    let (r, ry) = (self.r as f64, (self.k * self.r) as f64);
+    let (phi_sin, phi_cos) = (self.phi as f64).sin_cos();
+    let (ang_sin, ang_cos) = angle.sin_cos();
+    Vector {
+      x: (-r * phi_cos * ang_sin - ry * phi_sin * ang_cos) as f32,
+      y: (-r * phi_sin * ang_sin + ry * phi_cos * ang_cos) as f32,
+    }
+  }
+
+  fn find_normal_angles(&self, point: Point) -> [f64; 8] {
+    use std::f64::consts::{PI, TAU};
+
     // Much like the beziers, we need to find the zeros of the equation:
     //   N(t) = (p(t) - P) dot dp/dt
     // where t_0  N(t_0) = 0 is the pseudo angle of the ellipse that is
     // closest to the point in question.
-    let (a, b) = f32::sin_cos(self.phi);
-    let (c, p, r) = (self.centre, point, Point::new(self.r, self.r * self.k));
-
-    let m = 0.5 * (r.y * r.y - r.x * r.x) * (a * a + b * b);
-    let n = r.x * (a * (p.y - c.y) + b * (p.x - c.x));
-    let o = r.y * (a * (p.x - c.x) + b * (c.y - p.y));
+    let (a, b) = f64::sin_cos(self.phi as f64);
+    let (c, p) = (self.centre, point);
+    let (cx, cy, px, py) = (c.x as f64, c.y as f64, p.x as f64, p.y as f64);
+    let (rx, ry) = (self.r as f64, (self.r * self.k) as f64);
+
+    let m = 0.5 * (ry * ry - rx * rx) * (a * a + b * b);
+    let n = rx * (a * (py - cy) + b * (px - cx));
+    let o = ry * (a * (px - cx) + b * (cy - py));
     // `f` is N(t).
-    let f = |t: f32| m * (2. * t).sin() + n * t.sin() + o * t.cos();
+    let f = |t: f64| m * (2. * t).sin() + n * t.sin() + o * t.cos();
     // easily twice differentiable so we can use Halley's method.
-    let df = |t: f32| 2. * m * (2. * t).cos() + n * t.cos() - o * t.sin();
-    let ddf = |t: f32| -4. * m * (2. * t).sin() - n * t.sin() - o * t.cos();
+    let df = |t: f64| 2. * m * (2. * t).cos() + n * t.cos() - o * t.sin();
+    let ddf = |t: f64| -4. * m * (2. * t).sin() - n * t.sin() - o * t.cos();
     // assume a circle, should give close-ish initial guesses.
-    let mut guess = f32::atan2(c.y - p.y, c.x - p.x) - self.phi;
-    if guess < 0f32 {
+    let mut guess = f64::atan2(cy - py, cx - px) - self.phi as f64;
+    if guess < 0. {
       guess += TAU
     }
-    // modulo shenanigans with TAU is to renormalise the result to [0, TAU].
-    let t0 = (halleys_method(guess, f, df, ddf) % TAU + TAU) % TAU;
-    let t1 =
-      (halleys_method((guess + PI) % TAU, f, df, ddf) % TAU + TAU) % TAU;
+    // renormalise the result to [0, TAU), wrapping roots that fall just short
+    // of TAU back to 0, where they would otherwise round up to TAU in f32
+    let normalise = |t: f64| {
+      let t = t.rem_euclid(TAU);
+      if TAU - t <= f32::EPSILON as f64 {
+        0.
+      } else {
+        t
+      }
+    };
+    let t0 = normalise(halleys_method(guess, f, df, ddf));
+    let t1 = normalise(halleys_method((guess + PI) % TAU, f, df, ddf));
 
     // this is ugly but we need to make sure we include the extra roots incase
     // there's a positive or negative offset for the arc
@@ -1118,9 +1161,7 @@ mod tests {
 
       let point = Point::from((2., 0.));
       let ts = EllipticalArc::find_normals(&ps, point, 0f32..=1f32);
-      // presicision issues reduce the number of results to 2
-      // assert_approx_eq!(&[f32], &ts, &[0., 1., 0.5]);
-      assert_approx_eq!(&[f32], &ts, &[1., 0.5]);
+      assert_approx_eq!(&[f32], &ts, &[0., 1., 0.5]);
     }
   }
 }
@@ -24,6 +24,16 @@ impl Primitive for QuadBezier {
         * (ps[2].as_vector() - 2f32*ps[1].as_vector() + ps[0].as_vector())
   }
 
+  #[inline]
+  #[rustfmt::skip]
+  fn tangent(ps: &[Point], t: f32) -> Vector {
+    let derivatives = [
+      Self::sample_derivative(ps, t),
+      2f32*(ps[2].as_vector() - 2f32*ps[1].as_vector() + ps[0].as_vector()),
+    ];
+    one_sided_tangent(ps, derivatives, t)
+  }
+
   fn find_normals<R: RangeBounds<f32> + Clone>(
     ps: &[Point],
     point: Point,
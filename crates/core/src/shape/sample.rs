@@ -1,15 +1,49 @@
+use super::distance::{
+  segments_distance_lower_bound, segments_distance_orthogonality,
+};
 use crate::*;
-use std::f32::{INFINITY, NEG_INFINITY};
 
-/// Threshold for float comparisons
-const EPSILON: f32 = 0.0001;
-
-type Dist = (/* distance */ f32, /* orthogonality */ f32);
+pub(crate) type Dist = (/* distance */ f32, /* orthogonality */ f32);
 
 impl Shape {
   /// Sample the signed distance of the shape at the given [`Point`]
-  pub fn sample_single_channel(&self, point: Point) -> f32 {
-    let mut selected_dist: Dist = (INFINITY, NEG_INFINITY);
+  ///
+  /// Distances are in the units of the shape's coordinates (pixels, when
+  /// rasterizing), and are positive inside the shape and negative outside.
+  ///
+  /// ```
+  /// # use rsdf_builder::ShapeBuilder;
+  /// # use rsdf_core::*;
+  /// // a 10x10 square, with its top-left corner at (10, 10)
+  /// let square = ShapeBuilder::new()
+  ///   .contour((10., 10.))
+  ///   .line((20., 10.))
+  ///   .line((20., 20.))
+  ///   .line((10., 20.))
+  ///   .line((10., 10.))
+  ///   .end_contour()
+  ///   .build();
+  /// assert_eq!(square.sample_single_channel((15., 15.)), 5.);
+  /// assert_eq!(square.sample_single_channel((15., 12.)), 2.);
+  /// assert_eq!(square.sample_single_channel((15., 10.)), 0.);
+  /// assert_eq!(square.sample_single_channel((15., 7.)), -3.);
+  /// ```
+  #[must_use]
+  pub fn sample_single_channel(&self, point: impl Into<Point>) -> f32 {
+    self.sample_single_channel_with(point, RasterConfig::default())
+  }
+
+  /// Sample the signed distance of the shape at the given [`Point`], using
+  /// the settings in the [`RasterConfig`]
+  #[must_use]
+  pub fn sample_single_channel_with(
+    &self,
+    point: impl Into<Point>,
+    config: RasterConfig,
+  ) -> f32 {
+    let point = point.into();
+    let mut selected_dist: Dist = (f32::INFINITY, f32::NEG_INFINITY);
+    let mut selected = None;
 
     for contour in self.contours.iter() {
       for Spline {
@@ -17,67 +51,455 @@ impl Shape {
         colour: _,
       } in self.splines[contour.spline_range.clone()].iter()
       {
-        let (dist, _) =
-          self.spline_distance_orthogonality(segments_range.clone(), point);
-        if closer(dist, selected_dist) {
+        let segments = self.spline_segments(segments_range.clone());
+        let lower_bound = segments_distance_lower_bound(&segments, point);
+        if lower_bound - selected_dist.0.abs() > config.tie_epsilon {
+          continue;
+        }
+        let (dist, _) = segments_distance_orthogonality(&segments, point);
+        let replace = match compare(dist, selected_dist, config.tie_epsilon) {
+          Closer => true,
+          // prefer the larger signed distance, so the result doesn't depend
+          // on the order of the splines
+          Tie => dist.0 > selected_dist.0,
+          Further => false,
+        };
+        if replace {
           selected_dist = dist;
+          selected = Some(segments_range.clone());
+        }
+      }
+    }
+
+    match (config.metric, selected) {
+      (DistanceMetric::Euclidean, _) | (_, None) => selected_dist.0,
+      (metric, Some(spline)) => {
+        self.spline_distance_with_metric(spline, point, metric)
+      },
+    }
+  }
+
+  /// Sample the unsigned distance of the shape at the given [`Point`]
+  ///
+  /// The shape's contours don't need to be closed, since there is no notion
+  /// of inside or outside; this is useful for fields of strokes, hairlines and
+  /// centrelines. To render a stroke of width `w`, treat `w / 2 - distance` as
+  /// the signed distance.
+  ///
+  /// ```
+  /// # use rsdf_core::*;
+  /// // an open path made of a single line
+  /// let line = Shape {
+  ///   points: vec![(0., 0.).into(), (10., 0.).into()],
+  ///   segments: vec![SegmentRef {
+  ///     kind: SegmentKind::Line,
+  ///     points_index: 0,
+  ///   }],
+  ///   splines: vec![Spline {
+  ///     segments_range: 0..1,
+  ///     colour: White,
+  ///   }],
+  ///   contours: vec![Contour { spline_range: 0..1 }],
+  /// };
+  /// assert_eq!(line.sample_unsigned((5., 3.)), 3.);
+  /// assert_eq!(line.sample_unsigned((5., -3.)), 3.);
+  /// assert_eq!(line.sample_unsigned((13., 4.)), 5.);
+  /// ```
+  #[must_use]
+  pub fn sample_unsigned(&self, point: impl Into<Point>) -> f32 {
+    let point = point.into();
+    let mut selected_dist = f32::INFINITY;
+
+    for contour in self.contours.iter() {
+      for spline in self.splines[contour.spline_range.clone()].iter() {
+        for &segment_ref in &self.segments[spline.segments_range.clone()] {
+          let segment = self.get_segment(segment_ref);
+          if segment.distance_lower_bound(point) >= selected_dist {
+            continue;
+          }
+          let (dist, _) = segment.distance(point);
+          selected_dist = selected_dist.min(dist);
         }
       }
     }
 
-    selected_dist.0
+    selected_dist
   }
 
   /// Sample the multi-channel signed pseudo distance of the shape at the given
   /// [`Point`]
-  pub fn sample(&self, point: Point) -> [f32; 3] {
-    let [mut red_spline, mut green_spline, mut blue_spline] =
-      [None, None, None];
-    let [mut red_dist, mut green_dist, mut blue_dist]: [Dist; 3] =
-      [(INFINITY, NEG_INFINITY); 3];
+  ///
+  /// Each channel holds the distance to the nearest spline of its colour,
+  /// with the same units and sign as [`Shape::sample_single_channel`]. The
+  /// median of the channels recovers the signed distance, while keeping the
+  /// shape's corners sharp.
+  ///
+  /// ```
+  /// # use rsdf_builder::ShapeBuilder;
+  /// # use rsdf_core::*;
+  /// // a right-angled triangle, with its right angle at the origin
+  /// let triangle = ShapeBuilder::from_svg_path("M0,0 H20 L0,20 Z")
+  ///   .unwrap()
+  ///   .build();
+  /// assert_eq!(median(triangle.sample((5., 2.))), 2.);
+  /// assert_eq!(median(triangle.sample((5., -3.))), -3.);
+  /// ```
+  #[must_use]
+  pub fn sample(&self, point: impl Into<Point>) -> [f32; 3] {
+    self.sample_with(point, RasterConfig::default())
+  }
+
+  /// Sample the multi-channel signed pseudo distance of the shape at the given
+  /// [`Point`], using the settings in the [`RasterConfig`]
+  #[must_use]
+  pub fn sample_with(
+    &self,
+    point: impl Into<Point>,
+    config: RasterConfig,
+  ) -> [f32; 3] {
+    let point = point.into();
+    let (selected, selected_dist) = self.select_splines(point, config, false);
+    [0, 1, 2].map(|i| {
+      self.selected_distance(selected[i], selected_dist[i].0, point, config)
+    })
+  }
+
+  /// Sample the single channel signed pseudo distance of the shape at the
+  /// given [`Point`], as in msdfgen's PSDF
+  ///
+  /// The nearest spline is chosen regardless of colour, and its
+  /// pseudo-distance taken, so corners stay sharp when a field is upscaled
+  /// without taking the median of several channels. Outside of the corners
+  /// the field is rounded less than the true distance is.
+  ///
+  /// ```
+  /// # use rsdf_builder::ShapeBuilder;
+  /// // a 20x10 bar, with its top-left corner at the origin
+  /// let bar = ShapeBuilder::from_svg_path("M0,0 H20 V10 H0 Z")
+  ///   .unwrap()
+  ///   .build();
+  /// assert_eq!(bar.sample_pseudo((15., 2.)), 2.);
+  /// // outside of a corner, the distance is to the extension of an edge
+  /// assert_eq!(bar.sample_pseudo((-3., -4.)), -4.);
+  /// assert_eq!(bar.sample_single_channel((-3., -4.)), -5.);
+  /// ```
+  #[must_use]
+  pub fn sample_pseudo(&self, point: impl Into<Point>) -> f32 {
+    self.sample_pseudo_with(point, RasterConfig::default())
+  }
+
+  /// Sample the single channel signed pseudo distance of the shape at the
+  /// given [`Point`], using the settings in the [`RasterConfig`]
+  #[must_use]
+  pub fn sample_pseudo_with(
+    &self,
+    point: impl Into<Point>,
+    config: RasterConfig,
+  ) -> f32 {
+    let point = point.into();
+    let ([selected, ..], [(dist, _), ..]) =
+      self.select_splines(point, config, true);
+    self.selected_distance(selected, dist, point, config)
+  }
+
+  /// The pseudo-distance to a spline chosen by [`Shape::select_splines`],
+  /// whose true distance is `dist`
+  fn selected_distance(
+    &self,
+    selected: Option<(usize, Bias)>,
+    dist: f32,
+    point: Point,
+    config: RasterConfig,
+  ) -> f32 {
+    // far from a spline its pseudo-distance can be wildly wrong, so beyond
+    // the limit we fall back to the true distance
+    let limit = config.pseudo_distance_limit * config.max_distance;
+    selected.map_or(f32::NEG_INFINITY, |(spline, bias)| {
+      let spline = self.splines[spline].segments_range.clone();
+      match config.metric {
+        DistanceMetric::Euclidean if dist.abs() > limit => dist,
+        metric if dist.abs() > limit => {
+          self.spline_distance_with_metric(spline, point, metric)
+        },
+        metric => {
+          self.spline_pseudo_distance_with_metric(spline, point, bias, metric)
+        },
+      }
+    })
+  }
+
+  /// Sample the multi-channel signed pseudo distance of the shape at the given
+  /// [`Point`], followed by its true signed distance, as in msdfgen's MTSDF
+  ///
+  /// The fourth channel is the distance given by
+  /// [`Shape::sample_single_channel`], which effects like outlines & glows
+  /// need far from the edges, where the median of the other channels only
+  /// holds near them.
+  ///
+  /// ```
+  /// # use rsdf_builder::ShapeBuilder;
+  /// # use rsdf_core::*;
+  /// let dot = ShapeBuilder::new().dot((0., 0.), 5.).build();
+  /// let point = Point::new(12., 9.);
+  /// let [r, g, b, a] = dot.sample_mtsdf(point);
+  /// assert_eq!([r, g, b], dot.sample(point));
+  /// assert_eq!(a, dot.sample_single_channel(point));
+  /// ```
+  #[must_use]
+  pub fn sample_mtsdf(&self, point: impl Into<Point>) -> [f32; 4] {
+    self.sample_mtsdf_with(point, RasterConfig::default())
+  }
+
+  /// Sample the multi-channel & true signed distances of the shape at the
+  /// given [`Point`], as in [`Shape::sample_mtsdf`], using the settings in
+  /// the [`RasterConfig`]
+  #[must_use]
+  pub fn sample_mtsdf_with(
+    &self,
+    point: impl Into<Point>,
+    config: RasterConfig,
+  ) -> [f32; 4] {
+    let point = point.into();
+    let [r, g, b] = self.sample_with(point, config);
+    [r, g, b, self.sample_single_channel_with(point, config)]
+  }
+
+  /// Select the spline nearest to the [`Point`] in each channel, as indices
+  /// into [`Shape::splines`], along with their distances
+  ///
+  /// With `ignore_colours` every spline is treated as white, so all three
+  /// channels select the nearest spline overall.
+  pub(crate) fn select_splines(
+    &self,
+    point: Point,
+    config: RasterConfig,
+    ignore_colours: bool,
+  ) -> ([Option<(usize, Bias)>; 3], [Dist; 3]) {
+    let mut selected: [Option<(usize, Bias)>; 3] = [None, None, None];
+    let mut selected_dist: [Dist; 3] = [(f32::INFINITY, f32::NEG_INFINITY); 3];
 
     for Contour { spline_range } in self.contours.iter() {
-      for Spline {
-        segments_range,
-        colour,
-      } in self.splines[spline_range.clone()].iter().cloned()
-      {
-        let (dist, bias) =
-          self.spline_distance_orthogonality(segments_range.clone(), point);
-        if (colour & Red == Red) && closer(dist, red_dist) {
-          red_dist = dist;
-          red_spline = Some((segments_range.clone(), bias));
-        }
-        if (colour & Green == Green) && closer(dist, green_dist) {
-          green_dist = dist;
-          green_spline = Some((segments_range.clone(), bias));
+      for index in spline_range.clone() {
+        let Spline {
+          segments_range,
+          colour,
+        } = self.splines[index].clone();
+        let colour = if ignore_colours { White } else { colour };
+        // skip the spline early if it can't be closer than the distance
+        // already selected in any of its channels. Once every channel holds
+        // a distance, most splines are skipped in the large empty areas of
+        // the canvas.
+        let furthest = [Red, Green, Blue]
+          .into_iter()
+          .zip(selected_dist)
+          .filter(|&(channel, _)| colour & channel == channel)
+          .map(|(_, (dist, _))| dist.abs())
+          .fold(f32::NEG_INFINITY, f32::max);
+        let segments = self.spline_segments(segments_range.clone());
+        let lower_bound = segments_distance_lower_bound(&segments, point);
+        if lower_bound - furthest > config.tie_epsilon {
+          continue;
         }
-        if (colour & Blue == Blue) && closer(dist, blue_dist) {
-          blue_dist = dist;
-          blue_spline = Some((segments_range.clone(), bias));
+
+        let (dist, bias) = segments_distance_orthogonality(&segments, point);
+        for (i, channel) in [Red, Green, Blue].into_iter().enumerate() {
+          if colour & channel != channel {
+            continue;
+          }
+          let replace =
+            match compare(dist, selected_dist[i], config.tie_epsilon) {
+              Closer => true,
+              // prefer the spline with the larger pseudo-distance, so the
+              // result doesn't depend on the order of the splines
+              Tie => selected[i].is_none_or(|(other, other_bias)| {
+                self.spline_pseudo_distance(
+                  segments_range.clone(),
+                  point,
+                  bias,
+                ) > self.spline_pseudo_distance(
+                  self.splines[other].segments_range.clone(),
+                  point,
+                  other_bias,
+                )
+              }),
+              Further => false,
+            };
+          if replace {
+            selected_dist[i] = dist;
+            selected[i] = Some((index, bias));
+          }
         }
       }
     }
 
-    [red_spline, green_spline, blue_spline].map(|r| {
-      r.map_or(NEG_INFINITY, |(spline, bias)| {
-        self.spline_pseudo_distance(spline, point, bias)
-      })
-    })
+    (selected, selected_dist)
   }
 }
 
+/// The result of comparing a pair of distances
+enum Comparison {
+  Closer,
+  Tie,
+  Further,
+}
+use Comparison::*;
+
 /// Comparison function for pairs of distances
-fn closer(
+///
+/// Distances within `epsilon` of each other are resolved by orthogonality;
+/// the more orthogonal distance is considered closer. If the orthogonality is
+/// also within `epsilon` then the distances are a [`Tie`].
+fn compare(
   (distance_a, orthogonality_a): Dist,
   (distance_b, orthogonality_b): Dist,
-) -> bool {
-  distance_b.abs() - distance_a.abs() > EPSILON
-    || (orthogonality_a.abs() > orthogonality_b.abs()
-      && float_cmp::approx_eq!(
-        f32,
-        distance_a.abs(),
-        distance_b.abs(),
-        epsilon = EPSILON
-      ))
+  epsilon: f32,
+) -> Comparison {
+  let (distance_a, distance_b) = (distance_a.abs(), distance_b.abs());
+  let (orthogonality_a, orthogonality_b) =
+    (orthogonality_a.abs(), orthogonality_b.abs());
+  if distance_b - distance_a > epsilon {
+    Closer
+  } else if distance_a - distance_b > epsilon {
+    Further
+  } else if orthogonality_a - orthogonality_b > epsilon {
+    Closer
+  } else if orthogonality_b - orthogonality_a > epsilon {
+    Further
+  } else {
+    Tie
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  const TRIANGLE: [(f32, f32); 3] = [(0., 0.), (4., 0.), (0., 4.)];
+
+  /// A triangle where each edge is its own spline, with the splines starting
+  /// from the edge at index `rotation`
+  fn triangle(rotation: usize) -> Shape {
+    let corners = TRIANGLE.map(Point::from);
+    let colours = [Yellow, Cyan, Magenta];
+    let points = (0..4).map(|i| corners[(i + rotation) % 3]).collect();
+    Shape {
+      points,
+      segments: (0..3)
+        .map(|points_index| SegmentRef {
+          kind: SegmentKind::Line,
+          points_index,
+        })
+        .collect(),
+      splines: (0..3)
+        .map(|i| Spline {
+          segments_range: i..i + 1,
+          colour: colours[(i + rotation) % 3],
+        })
+        .collect(),
+      contours: vec![Contour { spline_range: 0..3 }],
+    }
+  }
+
+  #[test]
+  fn corner_bisectors() {
+    // points on the bisectors of each corner, both inside and outside
+    let corners = TRIANGLE.map(Point::from);
+    let points = (0..3).flat_map(|i| {
+      let corner = corners[i];
+      let [a, b] = [corners[(i + 1) % 3], corners[(i + 2) % 3]];
+      let bisector = ((a - corner).norm() + (b - corner).norm()).norm();
+      [corner + bisector, corner - bisector]
+    });
+    let shapes = [triangle(0), triangle(1), triangle(2)];
+    for point in points {
+      let single = shapes.each_ref().map(|s| s.sample_single_channel(point));
+      assert_eq!(single[0], single[1], "{point:?}");
+      assert_eq!(single[0], single[2], "{point:?}");
+
+      let multi = shapes.each_ref().map(|s| s.sample(point));
+      assert_eq!(multi[0], multi[1], "{point:?}");
+      assert_eq!(multi[0], multi[2], "{point:?}");
+    }
+  }
+
+  #[test]
+  fn sample_unsigned() {
+    // an open path made of a single line
+    let shape = Shape {
+      points: vec![(0., 0.).into(), (4., 0.).into()],
+      segments: vec![SegmentRef {
+        kind: SegmentKind::Line,
+        points_index: 0,
+      }],
+      splines: vec![Spline {
+        segments_range: 0..1,
+        colour: White,
+      }],
+      contours: vec![Contour { spline_range: 0..1 }],
+    };
+
+    assert_eq!(shape.sample_unsigned(Point::new(2., 0.)), 0.);
+    assert_eq!(shape.sample_unsigned(Point::new(2., 1.)), 1.);
+    assert_eq!(shape.sample_unsigned(Point::new(2., -1.)), 1.);
+    assert_eq!(shape.sample_unsigned(Point::new(7., 4.)), 5.);
+  }
+
+  #[test]
+  fn metric() {
+    let shape = triangle(0);
+    let config = |metric| RasterConfig {
+      metric,
+      ..Default::default()
+    };
+    let euclidean = config(DistanceMetric::Euclidean);
+    let manhattan = config(DistanceMetric::Manhattan);
+    let chebyshev = config(DistanceMetric::Chebyshev);
+
+    // diagonally out from the corner at the origin
+    let point = Point::new(-1., -1.);
+    let single = |config| shape.sample_single_channel_with(point, config);
+    float_cmp::assert_approx_eq!(f32, single(euclidean), -(2f32.sqrt()));
+    float_cmp::assert_approx_eq!(f32, single(manhattan), -2.);
+    float_cmp::assert_approx_eq!(f32, single(chebyshev), -1.);
+
+    // pseudo-distances perpendicular to the axis aligned edges are unchanged
+    let point = Point::new(1., -1.);
+    let multi = |config| shape.sample_with(point, config);
+    assert_eq!(multi(euclidean), multi(manhattan));
+    assert_eq!(multi(euclidean), multi(chebyshev));
+
+    // but not those perpendicular to the diagonal edge
+    let point = Point::new(3., 3.);
+    let expected = (Point::from((3., 3.)) - Point::from((2., 2.))).abs();
+    let [_, green, _] = shape.sample_with(point, euclidean);
+    float_cmp::assert_approx_eq!(f32, green, -expected);
+    let [_, green, _] = shape.sample_with(point, manhattan);
+    float_cmp::assert_approx_eq!(f32, green, -2.);
+    let [_, green, _] = shape.sample_with(point, chebyshev);
+    float_cmp::assert_approx_eq!(f32, green, -1.);
+  }
+
+  #[test]
+  fn pseudo_distance_limit() {
+    // the extension of the bottom edge passes close to points that are far
+    // away from the shape itself
+    let shape = triangle(0);
+    let config = RasterConfig {
+      pseudo_distance_limit: 1.,
+      ..Default::default()
+    };
+
+    // close to the shape the limit has no effect
+    let point = Point::new(1., -1.);
+    assert_eq!(shape.sample(point), shape.sample_with(point, config));
+
+    // far away, the pseudo-distance along the extension of the bottom edge
+    // is replaced by the true distance
+    let point = Point::new(40., -1.);
+    let [red, _, _] = shape.sample(point);
+    float_cmp::assert_approx_eq!(f32, red, -1.);
+    let [red, _, _] = shape.sample_with(point, config);
+    let expected = -(Point::from((40., -1.)) - Point::from((4., 0.))).abs();
+    float_cmp::assert_approx_eq!(f32, red, expected);
+  }
 }
@@ -0,0 +1,348 @@
+use crate::*;
+use arrayvec::ArrayVec;
+use std::f32::consts::PI;
+
+impl Shape {
+  /// Scale the shape uniformly about the origin by `scale`, then move it by
+  /// `offset`
+  ///
+  /// A negative scale turns the shape half way around the origin, which
+  /// keeps the winding of the contours, so the inside stays positive.
+  ///
+  /// Panics if `scale` is zero, since the shape would collapse to a point.
+  pub fn scale_translate(&mut self, scale: f32, offset: Vector) {
+    assert!(scale != 0., "scale must be non-zero");
+    let transform = |p: Point| (p.as_vector() * scale + offset).as_point();
+
+    // the parameters stored after an elliptical arc's centre aren't points
+    let mut is_point = vec![true; self.points.len()];
+    for segment in &self.segments {
+      if let SegmentKind::EllipticalArc = segment.kind {
+        let i = segment.points_index;
+        is_point[i + 1..i + 4].fill(false);
+        // only the radius needs scaling; the aspect ratio & angles don't,
+        // other than turning the start angle with a negative scale
+        self.points[i + 1].x *= scale.abs();
+        if scale < 0. {
+          self.points[i + 3].x += PI;
+        }
+      }
+    }
+    for (point, is_point) in self.points.iter_mut().zip(is_point) {
+      if is_point {
+        *point = transform(*point);
+      }
+    }
+  }
+
+  /// Replicate the shape into a grid of `rows` by `cols` copies, each
+  /// `spacing` apart, with the original in the top-left cell
+  ///
+  /// The copies keep their splines and colours, so patterns can be sampled
+  /// as a single shape without duplicating the geometry by hand. Sampling
+  /// time grows with the number of copies.
+  ///
+  /// ```
+  /// # use rsdf_builder::ShapeBuilder;
+  /// # use rsdf_core::*;
+  /// // a 2x2 square
+  /// let dot = ShapeBuilder::new()
+  ///   .contour((0., 0.))
+  ///   .line((2., 0.))
+  ///   .line((2., 2.))
+  ///   .line((0., 2.))
+  ///   .end_contour()
+  ///   .build();
+  /// let pattern = dot.repeat(3, 4, Vector::new(5., 5.));
+  /// assert_eq!(pattern.contours.len(), 12);
+  /// assert_eq!(pattern.sample_single_channel((16., 11.)), 1.);
+  /// ```
+  #[must_use]
+  pub fn repeat(&self, rows: usize, cols: usize, spacing: Vector) -> Shape {
+    let mut pattern = Shape {
+      points: vec![],
+      segments: vec![],
+      splines: vec![],
+      contours: vec![],
+    };
+    for row in 0..rows {
+      for col in 0..cols {
+        let mut copy = self.clone();
+        let offset =
+          Vector::new(col as f32 * spacing.x, row as f32 * spacing.y);
+        copy.scale_translate(1., offset);
+        pattern.append(&copy);
+      }
+    }
+    pattern
+  }
+
+  /// Mirror the shape across the vertical line `x = axis`
+  ///
+  /// Mirroring reverses the winding of the contours, so they're reversed
+  /// afterwards to keep the inside of the shape positive. Symmetric icons
+  /// can be built from one half, appended to its mirror image, see
+  /// [`Shape::append`].
+  ///
+  /// ```
+  /// # use rsdf_builder::ShapeBuilder;
+  /// # use rsdf_core::*;
+  /// // the left half of an arrowhead, pointing up along x = 10
+  /// let half = ShapeBuilder::new()
+  ///   .contour((10., 0.))
+  ///   .line((10., 10.))
+  ///   .line((0., 10.))
+  ///   .end_contour()
+  ///   .build();
+  /// let mut other_half = half.clone();
+  /// other_half.mirror_x(10.);
+  /// assert_eq!(other_half.sample_single_channel((12., 9.)), 1.);
+  /// assert_eq!(
+  ///   other_half.sample_single_channel((12., 9.)),
+  ///   half.sample_single_channel((8., 9.))
+  /// );
+  /// ```
+  pub fn mirror_x(&mut self, axis: f32) {
+    self.mirror(|p| Point::new(2. * axis - p.x, p.y), |theta| PI - theta);
+  }
+
+  /// Mirror the shape across the horizontal line `y = axis`, as
+  /// [`Shape::mirror_x`] does across a vertical one
+  pub fn mirror_y(&mut self, axis: f32) {
+    self.mirror(|p| Point::new(p.x, 2. * axis - p.y), |theta| -theta);
+  }
+
+  /// Mirror the points with `reflect`, and the start angles of elliptical
+  /// arcs with `reflect_angle`, then reverse the contours
+  fn mirror(
+    &mut self,
+    reflect: impl Fn(Point) -> Point,
+    reflect_angle: impl Fn(f32) -> f32,
+  ) {
+    let mut is_point = vec![true; self.points.len()];
+    for segment in &self.segments {
+      if let SegmentKind::EllipticalArc = segment.kind {
+        let i = segment.points_index;
+        is_point[i + 1..i + 4].fill(false);
+        // the rotation & sweep of the ellipse are reflected too
+        self.points[i + 2].x = -self.points[i + 2].x;
+        let Point { x: theta, y: delta } = self.points[i + 3];
+        self.points[i + 3] = Point::new(reflect_angle(theta), -delta);
+      }
+    }
+    for (point, is_point) in self.points.iter_mut().zip(is_point) {
+      if is_point {
+        *point = reflect(*point);
+      }
+    }
+    self.reverse_contours();
+  }
+
+  /// Reverse the direction of every contour, along with the order of their
+  /// splines & segments
+  fn reverse_contours(&mut self) {
+    let mut points = Vec::with_capacity(self.points.len());
+    let mut segments = Vec::with_capacity(self.segments.len());
+    let mut splines = Vec::with_capacity(self.splines.len());
+    for contour in &mut self.contours {
+      let first_spline = splines.len();
+      let mut first_segment = true;
+      for spline in self.splines[contour.spline_range.clone()].iter().rev() {
+        let start = segments.len();
+        for &SegmentRef {
+          kind,
+          points_index: i,
+        } in self.segments[spline.segments_range.clone()].iter().rev()
+        {
+          let ps = &self.points;
+          let (reversed, points_index): (ArrayVec<Point, 6>, _) = match kind {
+            SegmentKind::Line => ([ps[i + 1], ps[i]].into_iter().collect(), 0),
+            SegmentKind::QuadBezier => {
+              ([ps[i + 2], ps[i + 1], ps[i]].into_iter().collect(), 0)
+            },
+            SegmentKind::CubicBezier => (
+              [ps[i + 3], ps[i + 2], ps[i + 1], ps[i]]
+                .into_iter()
+                .collect(),
+              0,
+            ),
+            SegmentKind::EllipticalArc => {
+              // the same ellipse, swept back from the end angle
+              let Point { x: theta, y: delta } = ps[i + 3];
+              let sweep = Point::new(theta + delta, -delta);
+              (
+                [ps[i + 4], ps[i], ps[i + 1], ps[i + 2], sweep, ps[i - 1]]
+                  .into_iter()
+                  .collect(),
+                1,
+              )
+            },
+          };
+          // each segment starts where the one before it ended
+          if first_segment {
+            points.push(reversed[0]);
+            first_segment = false;
+          }
+          segments.push(SegmentRef {
+            kind,
+            points_index: points.len() - 1 + points_index,
+          });
+          points.extend_from_slice(&reversed[1..]);
+        }
+        splines.push(Spline {
+          segments_range: start..segments.len(),
+          colour: spline.colour,
+        });
+      }
+      contour.spline_range = first_spline..splines.len();
+    }
+    self.points = points;
+    self.segments = segments;
+    self.splines = splines;
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+  use float_cmp::assert_approx_eq;
+
+  #[test]
+  fn scale_translate() {
+    // a line followed by a half circle of radius 1 around (1, 0)
+    let mut shape = Shape {
+      points: vec![
+        (2., 0.).into(),
+        (0., 0.).into(),
+        (1., 0.).into(),
+        (1., 1.).into(),
+        (0., 0.).into(),
+        (std::f32::consts::PI, -std::f32::consts::PI).into(),
+        (2., 0.).into(),
+      ],
+      segments: vec![
+        SegmentRef {
+          kind: SegmentKind::Line,
+          points_index: 0,
+        },
+        SegmentRef {
+          kind: SegmentKind::EllipticalArc,
+          points_index: 2,
+        },
+      ],
+      splines: vec![Spline {
+        segments_range: 0..2,
+        colour: White,
+      }],
+      contours: vec![Contour { spline_range: 0..1 }],
+    };
+    let before = shape.sample_single_channel(Point::new(1., 0.5));
+    let area = shape.contour_area(&shape.contours[0]);
+
+    shape.scale_translate(3., Vector::new(10., 20.));
+    assert_eq!(shape.points[0], Point::new(16., 20.));
+    assert_eq!(shape.points[1], Point::new(10., 20.));
+    assert_eq!(shape.points[2], Point::new(13., 20.));
+    assert_eq!(shape.points[3], Point::new(3., 1.));
+    assert_eq!(shape.points[6], Point::new(16., 20.));
+    let arc = shape.get_segment(shape.segments[1]);
+    assert_approx_eq!(Point, arc.sample(0.5), Point::new(13., 23.));
+    assert_approx_eq!(
+      f32,
+      shape.sample_single_channel(Point::new(13., 21.5)),
+      before * 3.
+    );
+    assert_approx_eq!(
+      f32,
+      shape.contour_area(&shape.contours[0]),
+      area * 9.,
+      epsilon = 0.0001
+    );
+
+    // a negative scale turns the shape around, rather than turning it
+    // inside out
+    let mut turned = shape.clone();
+    turned.scale_translate(-1., Vector::new(32., 46.));
+    assert!(turned.is_contour_closed(&turned.contours[0]));
+    assert_approx_eq!(
+      f32,
+      turned.contour_area(&turned.contours[0]),
+      area * 9.,
+      epsilon = 0.0001
+    );
+    let arc = turned.get_segment(turned.segments[1]);
+    assert_approx_eq!(Point, arc.sample(0.5), Point::new(19., 23.));
+    for (x, y) in [(13., 21.5), (13., 19.), (20., 21.)] {
+      assert_approx_eq!(
+        f32,
+        turned.sample_single_channel((32. - x, 46. - y)),
+        shape.sample_single_channel((x, y)),
+        epsilon = 0.0001
+      );
+    }
+  }
+
+  #[test]
+  fn mirror() {
+    // a line followed by a half circle of radius 1 around (1, 0), bulging
+    // up from the line
+    let shape = Shape {
+      points: vec![
+        (2., 0.).into(),
+        (0., 0.).into(),
+        (1., 0.).into(),
+        (1., 1.).into(),
+        (0., 0.).into(),
+        (std::f32::consts::PI, -std::f32::consts::PI).into(),
+        (2., 0.).into(),
+      ],
+      segments: vec![
+        SegmentRef {
+          kind: SegmentKind::Line,
+          points_index: 0,
+        },
+        SegmentRef {
+          kind: SegmentKind::EllipticalArc,
+          points_index: 2,
+        },
+      ],
+      splines: vec![
+        Spline {
+          segments_range: 0..1,
+          colour: Magenta,
+        },
+        Spline {
+          segments_range: 1..2,
+          colour: Yellow,
+        },
+      ],
+      contours: vec![Contour { spline_range: 0..2 }],
+    };
+    let mut mirrored = shape.clone();
+    mirrored.mirror_y(5.);
+    assert_eq!(mirrored.points[0], Point::new(2., 10.));
+    assert_eq!(mirrored.splines[0].colour, Yellow);
+    assert!(mirrored.is_contour_closed(&mirrored.contours[0]));
+    assert_approx_eq!(
+      f32,
+      mirrored.contour_area(&mirrored.contours[0]),
+      shape.contour_area(&shape.contours[0]),
+      epsilon = 0.0001
+    );
+    for (x, y) in [(1., -0.5), (1., 0.5), (1.5, -2.), (3., 0.)] {
+      let before = shape.sample_single_channel((x, y));
+      let after = mirrored.sample_single_channel((x, 10. - y));
+      assert_approx_eq!(f32, after, before, epsilon = 0.0001);
+    }
+
+    mirrored = shape.clone();
+    mirrored.mirror_x(0.);
+    for (x, y) in [(1., -0.5), (1., 0.5), (1.5, -2.), (3., 0.)] {
+      let before = shape.sample(Point::new(x, y));
+      let after = mirrored.sample(Point::new(-x, y));
+      for (after, before) in after.into_iter().zip(before) {
+        assert_approx_eq!(f32, after, before, epsilon = 0.0001);
+      }
+    }
+  }
+}
@@ -0,0 +1,190 @@
+use crate::*;
+
+/// The spline that won a channel of a sample, see [`Shape::sample_winners`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Winner {
+  /// The index of the spline in [`Shape::splines`]
+  pub spline: usize,
+  /// The index of the spline's contour in [`Shape::contours`]
+  pub contour: usize,
+}
+
+impl Winner {
+  /// A colour to tell the winner apart from the other splines in a false
+  /// colour map, see [`Shape::write_winner_map`]
+  ///
+  /// Splines of the same contour share a hue, with neighbouring splines
+  /// alternating in brightness.
+  #[must_use]
+  pub fn false_colour(self) -> [u8; 3] {
+    // step around the colour wheel by the golden angle, so that nearby
+    // contours get distinct hues
+    let hue = (self.contour as f32 * 0.381_966).fract() * 6.;
+    let value = [255., 160., 96.][self.spline % 3];
+    let x = 1. - (hue % 2. - 1.).abs();
+    let [r, g, b] = match hue as usize {
+      0 => [1., x, 0.],
+      1 => [x, 1., 0.],
+      2 => [0., 1., x],
+      3 => [0., x, 1.],
+      4 => [x, 0., 1.],
+      _ => [1., 0., x],
+    };
+    [r, g, b].map(|c: f32| (c * value) as u8)
+  }
+}
+
+impl Shape {
+  /// Find the spline that determines each channel of the multi-channel
+  /// sample at the given [`Point`], using the settings in the
+  /// [`RasterConfig`]
+  ///
+  /// This is the spline [`Shape::sample_with`] takes each channel's distance
+  /// from, or `None` for a channel that no spline is coloured with. It's
+  /// meant for debugging: when a pixel shows the wrong channel, the winners
+  /// around it point at the spline responsible.
+  ///
+  /// ```
+  /// # use rsdf_builder::ShapeBuilder;
+  /// # use rsdf_core::*;
+  /// let triangle = ShapeBuilder::from_svg_path("M10,10 H30 L20,30 Z")
+  ///   .unwrap()
+  ///   .build();
+  /// let config = RasterConfig::default();
+  /// let winners = triangle.sample_winners((20., 12.), config);
+  /// // the top edge is nearest, in every channel it's coloured with
+  /// let top = triangle.splines[0].colour;
+  /// for (winner, channel) in winners.into_iter().zip([Red, Green, Blue]) {
+  ///   if top & channel == channel {
+  ///     assert_eq!(winner.unwrap().spline, 0);
+  ///   }
+  /// }
+  /// ```
+  #[must_use]
+  pub fn sample_winners(
+    &self,
+    point: impl Into<Point>,
+    config: RasterConfig,
+  ) -> [Option<Winner>; 3] {
+    let (selected, _) = self.select_splines(point.into(), config, false);
+    selected.map(|selected| {
+      let (spline, _) = selected?;
+      let contour = self
+        .contours
+        .iter()
+        .position(|contour| contour.spline_range.contains(&spline))?;
+      Some(Winner { spline, contour })
+    })
+  }
+
+  /// Find the winning splines, as in [`Shape::sample_winners`], for every
+  /// pixel on a canvas of the given `size`
+  ///
+  /// The winners are returned in row-major order, sampled at the same points
+  /// as [`Shape::sample_field`].
+  #[must_use]
+  pub fn winner_map(
+    &self,
+    size: [usize; 2],
+    config: RasterConfig,
+  ) -> Vec<[Option<Winner>; 3]> {
+    (0..size[1])
+      .flat_map(|y| (0..size[0]).map(move |x| [x, y]))
+      .map(|[x, y]| {
+        self.sample_winners(Point::from((x as f32, y as f32)), config)
+      })
+      .collect()
+  }
+
+  /// Write a false colour map of the winning splines of one `channel` (0 to
+  /// 2, for red, green & blue) of every pixel to a PNG at `path`
+  ///
+  /// Each spline gets its own colour, see [`Winner::false_colour`], and
+  /// pixels without a winner are black. Requires the `png` feature.
+  #[cfg(feature = "png")]
+  pub fn write_winner_map(
+    &self,
+    path: &str,
+    size: [usize; 2],
+    channel: usize,
+    config: RasterConfig,
+  ) {
+    let mut image = Image::new(path, size);
+    for (i, winners) in self.winner_map(size, config).into_iter().enumerate() {
+      let colour = winners[channel].map_or([0; 3], Winner::false_colour);
+      image.set_pixel([i % size[0], i / size[0]], colour);
+    }
+    image.flush();
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn sample_winners() {
+    // a pair of separate squares, with each edge a spline of its own
+    let splines = |splines: usize| -> [Spline; 4] {
+      std::array::from_fn(|i| Spline {
+        segments_range: splines + i..splines + i + 1,
+        colour: [Yellow, Cyan, Magenta, Cyan][i],
+      })
+    };
+    let corners = |x0: f32| {
+      [(x0, 0.), (x0 + 4., 0.), (x0 + 4., 4.), (x0, 4.), (x0, 0.)]
+        .map(Point::from)
+    };
+    let shape = Shape {
+      points: corners(0.).into_iter().chain(corners(10.)).collect(),
+      segments: [0, 1, 2, 3, 5, 6, 7, 8]
+        .map(|points_index| SegmentRef {
+          kind: SegmentKind::Line,
+          points_index,
+        })
+        .to_vec(),
+      splines: splines(0).into_iter().chain(splines(4)).collect(),
+      contours: vec![
+        Contour { spline_range: 0..4 },
+        Contour { spline_range: 4..8 },
+      ],
+    };
+    let config = RasterConfig::default();
+
+    // just above the top edge of the right square; blue isn't on the top
+    // edge, so is won by one of the sides
+    let [red, green, blue] = shape.sample_winners((12., -1.), config);
+    assert_eq!(
+      red,
+      Some(Winner {
+        spline: 4,
+        contour: 1
+      })
+    );
+    assert_eq!(
+      green,
+      Some(Winner {
+        spline: 4,
+        contour: 1
+      })
+    );
+    assert_eq!(blue.unwrap().contour, 1);
+    assert_ne!(blue.unwrap().spline, 4);
+
+    let map = shape.winner_map([16, 6], config);
+    assert_eq!(map.len(), 16 * 6);
+    // the left edge is the nearest green spline
+    assert_eq!(
+      map[2 * 16 + 1][1],
+      Some(Winner {
+        spline: 3,
+        contour: 0
+      })
+    );
+
+    // splines of different contours don't share a colour
+    let colour = |spline, contour| Winner { spline, contour }.false_colour();
+    assert_ne!(colour(0, 0), colour(0, 1));
+    assert_ne!(colour(0, 0), colour(1, 0));
+  }
+}
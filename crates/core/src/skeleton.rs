@@ -0,0 +1,74 @@
+use crate::*;
+
+impl Shape {
+  /// Approximate the medial axis of the shape on a canvas of the given `size`
+  ///
+  /// **Experimental**, requires the `unstable` feature.
+  ///
+  /// The interior distance is sampled at each pixel, and pixels that form a
+  /// ridge in the field, i.e. are no closer to the edge than their neighbours
+  /// on both sides along some direction (and further than at least one of
+  /// them), are returned.
+  ///
+  /// The result is a set of pixel coordinates approximating the centrelines
+  /// of the shape, and isn't guaranteed to be connected or one pixel thick.
+  #[must_use]
+  pub fn medial_axis(&self, size: [usize; 2]) -> Vec<[usize; 2]> {
+    let [width, height] = size;
+    let field: Vec<f32> = (0..height)
+      .flat_map(|y| (0..width).map(move |x| (x, y)))
+      .map(|(x, y)| self.sample_single_channel((x as f32, y as f32)))
+      .collect();
+    let at = |x: usize, y: usize| field[y * width + x];
+
+    // horizontal, vertical and both diagonals
+    const DIRECTIONS: [(isize, isize); 4] = [(1, 0), (0, 1), (1, 1), (1, -1)];
+
+    let mut axis = vec![];
+    for y in 1..height.saturating_sub(1) {
+      for x in 1..width.saturating_sub(1) {
+        let distance = at(x, y);
+        if distance <= 0. {
+          continue;
+        }
+        let is_ridge = DIRECTIONS.iter().any(|&(dx, dy)| {
+          let before =
+            at(x.wrapping_add_signed(-dx), y.wrapping_add_signed(-dy));
+          let after = at(x.wrapping_add_signed(dx), y.wrapping_add_signed(dy));
+          // ignore plateaus running parallel to an edge
+          distance >= before
+            && distance >= after
+            && (distance > before || distance > after)
+        });
+        if is_ridge {
+          axis.push([x, y]);
+        }
+      }
+    }
+    axis
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn medial_axis() {
+    // a rectangle spanning 1..=15 horizontally and 1..=7 vertically, whose
+    // medial axis runs along y = 4 between the diagonals from the corners
+    let colours = [Magenta, Yellow, Magenta, Yellow];
+    let shape = Shape::rectangle((1., 1.), (15., 7.), &colours);
+
+    let axis = shape.medial_axis([17, 9]);
+    for x in 4..=12 {
+      assert!(axis.contains(&[x, 4]), "missing [{x}, 4]");
+    }
+    // the diagonals from the corners
+    assert!(axis.contains(&[2, 2]));
+    assert!(axis.contains(&[14, 6]));
+    // pixels next to the edges aren't part of the axis
+    assert!(!axis.contains(&[8, 2]));
+    assert!(!axis.contains(&[8, 6]));
+  }
+}
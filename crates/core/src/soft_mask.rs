@@ -0,0 +1,140 @@
+use crate::*;
+
+impl Field<3> {
+  /// A conservative coverage for each pixel, from `0` (outside) to `1`
+  /// (inside), for compositing previews without a reconstruction shader
+  ///
+  /// The coverage ramps up over `smoothing` pixels inside the edge given by
+  /// the median of the channels, so it never bleeds outside of the shape. A
+  /// `smoothing` of `0` gives a hard mask.
+  ///
+  /// ```
+  /// # use rsdf_core::*;
+  /// let field = Field::from_samples([3, 1], vec![[-1.; 3], [0.5; 3], [2.; 3]]);
+  /// let mask = field.soft_mask(1.);
+  /// assert_eq!(mask.into_samples(), [[0.], [0.5], [1.]]);
+  /// ```
+  #[must_use]
+  pub fn soft_mask(&self, smoothing: f32) -> Field<1> {
+    let samples = self
+      .samples()
+      .iter()
+      .map(|&sample| [coverage(median(sample), smoothing)])
+      .collect();
+    Field::from_samples(self.size(), samples)
+  }
+
+  /// The field, with its [`soft_mask`](Self::soft_mask) as a fourth channel
+  #[must_use]
+  pub fn with_soft_mask(&self, smoothing: f32) -> Field<4> {
+    let samples = self
+      .samples()
+      .iter()
+      .map(|&[r, g, b]| [r, g, b, coverage(median([r, g, b]), smoothing)])
+      .collect();
+    Field::from_samples(self.size(), samples)
+  }
+}
+
+fn coverage(distance: f32, smoothing: f32) -> f32 {
+  if smoothing > 0. {
+    (distance / smoothing).clamp(0., 1.)
+  } else if distance > 0. {
+    1.
+  } else {
+    0.
+  }
+}
+
+impl Quantization {
+  /// Write a coverage, from `0` to `1`, into `out`, which must be
+  /// [`channel_size`](Self::channel_size) bytes long
+  ///
+  /// The fixed point formats map the coverage to their full range, as an
+  /// alpha channel.
+  pub fn write_coverage(self, coverage: f32, out: &mut [u8]) {
+    let coverage = coverage.clamp(0., 1.);
+    match self {
+      Quantization::U8 => out[0] = (coverage * u8::MAX as f32).round() as u8,
+      Quantization::U16 => {
+        let value = (coverage * u16::MAX as f32).round() as u16;
+        out.copy_from_slice(&value.to_ne_bytes());
+      },
+      Quantization::F16 => {
+        out.copy_from_slice(&math::f32_to_f16_bits(coverage).to_ne_bytes())
+      },
+      Quantization::F32 => out.copy_from_slice(&coverage.to_ne_bytes()),
+    }
+  }
+}
+
+impl Shape {
+  /// Rasterize the multi-channel field of the shape into a region of a
+  /// caller-provided buffer, as [`Shape::rasterize_into`] does, followed by
+  /// its [`soft_mask`](Field::soft_mask) as an alpha channel
+  ///
+  /// The [`RegionDesc::pixel_stride`] must fit 4 channels of the
+  /// [`RasterConfig::quantization`]. Returns the number of channels that had
+  /// to be scrubbed, see [`scrub_non_finite`].
+  pub fn rasterize_soft_mask_into(
+    &self,
+    buffer: &mut [u8],
+    region: RegionDesc,
+    config: RasterConfig,
+    smoothing: f32,
+  ) -> usize {
+    let channel_size = config.quantization.channel_size();
+    assert!(
+      region.pixel_stride >= 4 * channel_size,
+      "pixel stride is too small for the quantization"
+    );
+    if region.width == 0 || region.height == 0 {
+      return 0;
+    }
+    let end = region.offset
+      + (region.height - 1) * region.row_pitch
+      + (region.width - 1) * region.pixel_stride
+      + 4 * channel_size;
+    assert!(end <= buffer.len(), "region is outside of the buffer");
+
+    let mut field =
+      self.sample_field::<3>([region.width, region.height], config);
+    let scrubbed = scrub_non_finite(field.samples_mut(), config.max_distance);
+    for (y, row) in field.with_soft_mask(smoothing).rows().enumerate() {
+      for (x, &[r, g, b, coverage]) in row.iter().enumerate() {
+        let location =
+          region.offset + y * region.row_pitch + x * region.pixel_stride;
+        let pixel = &mut buffer[location..location + 4 * channel_size];
+        let mut channels = pixel.chunks_exact_mut(channel_size);
+        for distance in [r, g, b] {
+          config.quantization.write_with_range(
+            distance,
+            config.max_distance,
+            channels.next().unwrap(),
+          );
+        }
+        config
+          .quantization
+          .write_coverage(coverage, channels.next().unwrap());
+      }
+    }
+    scrubbed
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn soft_mask() {
+    let field =
+      Field::from_samples([2, 1], vec![[3., -1., 0.2], [1., 1., -2.]]);
+    assert_eq!(field.soft_mask(0.).into_samples(), [[1.], [1.]]);
+    assert_eq!(field.with_soft_mask(2.).get([0, 0]), [3., -1., 0.2, 0.1]);
+
+    let mut out = [0; 2];
+    Quantization::U16.write_coverage(1.5, &mut out);
+    assert_eq!(out, u16::MAX.to_ne_bytes());
+  }
+}
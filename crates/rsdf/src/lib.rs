@@ -0,0 +1,126 @@
+//! A **raster signed distance field** generator
+//!
+//! This crate re-exports the rsdf workspace crates, so that only a single
+//! dependency is needed:
+//! - [`rsdf_core`] is re-exported at the root.
+//! - [`builder`] is enabled by the `builder` feature (on by default), its
+//!   conversion of [`swash`](https://docs.rs/swash) outlines by the `swash`
+//!   feature, its shaping of text with
+//!   [`rustybuzz`](https://docs.rs/rustybuzz) and error-resilient glyph
+//!   pipeline by the `rustybuzz` feature, its conversion of
+//!   [`kurbo`](https://docs.rs/kurbo) paths by the `kurbo` feature, of
+//!   [`zeno`](https://docs.rs/zeno) commands by the `zeno` feature, of
+//!   [`tiny-skia`](https://docs.rs/tiny-skia) paths by the `tiny-skia`
+//!   feature, of
+//!   [FreeType](https://docs.rs/freetype-rs) outlines by the `freetype`
+//!   feature, of [`skrifa`](https://docs.rs/skrifa) outlines by the `skrifa`
+//!   feature, and its selection of system fonts by name with
+//!   [`font-kit`](https://docs.rs/font-kit) by the `font-kit` feature.
+//! - [`svg`] is enabled by the `svg` feature, and its conversion of
+//!   [`usvg`](https://docs.rs/usvg) trees by the `usvg` feature.
+//! - [`Atlas`]es, and their export to BMFont & msdf-atlas-gen's JSON along
+//!   with [`Provenance`] records, are enabled by the `atlas` feature (on by
+//!   default).
+//! - The `cli` feature enables everything a command line front end needs:
+//!   `builder`, `svg`, `atlas` & `png`, so that tools only list one feature
+//!   and report the rest through [`FEATURES`].
+//! - Shapes, and the builder's [`Op`](builder::Op)s, implement serde's
+//!   `Serialize` & `Deserialize` with the `serde` feature.
+//! - [`Field`]s can be written to OpenEXR images with the `exr` feature.
+//! - [`Bitmap`]s can be encoded as PNG images, and drawn to with [`Image`],
+//!   with the `png` feature (on by default).
+//! - KTX2 textures can be supercompressed with zlib with the `zlib` feature.
+//!
+//! The [`prelude`] brings the types needed to get started into scope with a
+//! single import.
+//!
+//! Experimental APIs are only available with the `unstable` feature, and may
+//! change or be removed without a breaking release.
+
+pub use rsdf_core::*;
+
+#[cfg(feature = "builder")]
+pub use rsdf_builder as builder;
+#[cfg(feature = "svg")]
+pub use rsdf_svg as svg;
+
+/// Each optional feature of the crate, along with whether it was compiled in
+///
+/// Tools built on rsdf can report these, e.g. from a `--print-config` flag,
+/// so that users can check their build has the features they expect.
+///
+/// ```
+/// let builder = rsdf::FEATURES.iter().find(|(name, _)| *name == "builder");
+/// assert_eq!(builder, Some(&("builder", cfg!(feature = "builder"))));
+/// ```
+pub const FEATURES: &[(&str, bool)] = &[
+  ("builder", cfg!(feature = "builder")),
+  ("swash", cfg!(feature = "swash")),
+  ("rustybuzz", cfg!(feature = "rustybuzz")),
+  ("kurbo", cfg!(feature = "kurbo")),
+  ("freetype", cfg!(feature = "freetype")),
+  ("zeno", cfg!(feature = "zeno")),
+  ("tiny-skia", cfg!(feature = "tiny-skia")),
+  ("font-kit", cfg!(feature = "font-kit")),
+  ("skrifa", cfg!(feature = "skrifa")),
+  ("svg", cfg!(feature = "svg")),
+  ("serde", cfg!(feature = "serde")),
+  ("usvg", cfg!(feature = "usvg")),
+  ("atlas", cfg!(feature = "atlas")),
+  ("cli", cfg!(feature = "cli")),
+  ("unstable", cfg!(feature = "unstable")),
+  ("assert-finite", cfg!(feature = "assert-finite")),
+  ("exr", cfg!(feature = "exr")),
+  ("png", cfg!(feature = "png")),
+  ("zlib", cfg!(feature = "zlib")),
+];
+
+/// The types needed by most users, to be glob imported
+///
+/// ```
+/// use rsdf::prelude::*;
+///
+/// # #[cfg(feature = "builder")] {
+/// let shape: Shape = ShapeBuilder::new()
+///   .contour((2., 2.))
+///   .line((14., 2.))
+///   .line((14., 14.))
+///   .line((2., 14.))
+///   .line((2., 2.))
+///   .end_contour()
+///   .build();
+/// let config = RasterConfig::default();
+/// let field: Field<3> = shape.sample_field([16, 16], config);
+/// assert!(field.get([8, 8])[0] > 0.);
+/// assert_eq!(shape.sample_single_channel(Point::new(8., 0.)), -2.);
+/// # }
+/// ```
+pub mod prelude {
+  #[cfg(feature = "builder")]
+  pub use rsdf_builder::ShapeBuilder;
+  pub use rsdf_core::{Field, Point, RasterConfig, Shape};
+}
+
+#[cfg(test)]
+mod tests {
+  #[test]
+  fn features() {
+    // every feature in the manifest, except the default set, is reported
+    let manifest = include_str!("../Cargo.toml");
+    let features = manifest
+      .split("[features]")
+      .nth(1)
+      .and_then(|rest| rest.split("\n[").next())
+      .unwrap();
+    let mut names: Vec<_> = features
+      .lines()
+      .filter_map(|line| line.split_once(" = "))
+      .map(|(name, _)| name)
+      .filter(|&name| name != "default")
+      .collect();
+    let mut reported: Vec<_> = super::FEATURES.iter().map(|f| f.0).collect();
+    names.sort_unstable();
+    reported.sort_unstable();
+    assert_eq!(names, reported);
+  }
+}
@@ -1,3 +1,723 @@
+//! Load [`Shape`]s from SVG documents
+//!
+//! The `<path>`, `<rect>`, `<circle>`, `<ellipse>` and `<polygon>` elements
+//! are converted, along with the `transform` attributes of them and their
+//! ancestors. Elements are filled unless their `fill` is `none`, and their
+//! `stroke` is outlined with the stroker of [`ShapeBuilder::dashed_path`],
+//! following the `stroke-width`, `stroke-linecap`, `stroke-linejoin`,
+//! `stroke-miterlimit`, `stroke-dasharray` & `stroke-dashoffset`
+//! properties. These are read from presentation attributes & `style`
+//! declarations, and inherited from ancestors. Everything else, such as
+//! colours & stylesheets, is ignored. As for [`ShapeBuilder`], filled
+//! contours should be wound clockwise (as displayed) for the inside to be
+//! positive; strokes always are.
+//!
+//! With the `usvg` feature, [`tree_shapes`] & [`tree_shape`] instead convert
+//! a [`usvg::Tree`], which handles the rest of SVG's semantics, such as
+//! strokes, `<use>` references and styling, and corrects the winding.
+
+use rsdf_builder::*;
 use rsdf_core::*;
+use std::path::Path;
+
+mod transform;
+#[cfg(feature = "usvg")]
+mod tree;
+
+pub use transform::Transform;
+#[cfg(feature = "usvg")]
+pub use tree::{tree_shape, tree_shapes};
+
+/// An error found while loading an SVG document
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SvgError {
+  Io(std::io::Error),
+  Xml(roxmltree::Error),
+  /// Invalid data in the `d` attribute of a `<path>`, or the `points` of a
+  /// `<polygon>`
+  Path(PathError),
+  /// An attribute that couldn't be understood
+  Attribute {
+    element: String,
+    attribute: &'static str,
+  },
+  /// The document's shape is over the limits given to
+  /// [`parse_shape_with_limits`]
+  Limit(LimitError),
+}
+
+impl std::fmt::Display for SvgError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      SvgError::Io(error) => write!(f, "couldn't read the document: {error}"),
+      SvgError::Xml(error) => write!(f, "invalid XML: {error}"),
+      SvgError::Path(error) => write!(f, "invalid path data: {error}"),
+      SvgError::Attribute { element, attribute } => {
+        write!(f, "invalid `{attribute}` attribute on a <{element}>")
+      },
+      SvgError::Limit(error) => write!(f, "shape too complex: {error}"),
+    }
+  }
+}
+
+impl std::error::Error for SvgError {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      SvgError::Io(error) => Some(error),
+      SvgError::Xml(error) => Some(error),
+      SvgError::Path(error) => Some(error),
+      SvgError::Limit(error) => Some(error),
+      SvgError::Attribute { .. } => None,
+    }
+  }
+}
+
+impl From<std::io::Error> for SvgError {
+  fn from(error: std::io::Error) -> Self {
+    SvgError::Io(error)
+  }
+}
+
+impl From<roxmltree::Error> for SvgError {
+  fn from(error: roxmltree::Error) -> Self {
+    SvgError::Xml(error)
+  }
+}
+
+impl From<PathError> for SvgError {
+  fn from(error: PathError) -> Self {
+    SvgError::Path(error)
+  }
+}
+
+impl From<LimitError> for SvgError {
+  fn from(error: LimitError) -> Self {
+    SvgError::Limit(error)
+  }
+}
+
+/// Load an SVG file, with one [`Shape`] per fill or stroke, see
+/// [`parse_shapes`]
+pub fn load_shapes(path: impl AsRef<Path>) -> Result<Vec<Shape>, SvgError> {
+  parse_shapes(&std::fs::read_to_string(path)?)
+}
+
+/// Load an SVG file into a single [`Shape`], see [`parse_shape`]
+pub fn load_shape(path: impl AsRef<Path>) -> Result<Shape, SvgError> {
+  parse_shape(&std::fs::read_to_string(path)?)
+}
+
+/// Parse an SVG document, with one [`Shape`] per fill or stroke, in document
+/// order
+///
+/// Elements that don't draw anything, such as a `<rect>` with no width, are
+/// left out.
+///
+/// ```
+/// let svg = r#"
+///   <svg xmlns="http://www.w3.org/2000/svg">
+///     <rect x="2" y="2" width="10" height="10" stroke="black" />
+///     <path d="M 20 0 V 14" fill="none" style="stroke: black; stroke-width: 4" />
+///   </svg>
+/// "#;
+/// let shapes = rsdf_svg::parse_shapes(svg).unwrap();
+/// // the rect's fill & stroke, and the path's stroke
+/// assert_eq!(shapes.len(), 3);
+/// assert_eq!(shapes[2].sample_single_channel((20., 7.)), 2.);
+/// ```
+pub fn parse_shapes(svg: &str) -> Result<Vec<Shape>, SvgError> {
+  Ok(
+    element_paths(svg)?
+      .into_iter()
+      .map(|painted| painted.add_to(ShapeBuilder::new()).build())
+      .collect(),
+  )
+}
+
+/// Parse an SVG document into a single [`Shape`] holding all of its elements
+///
+/// ```
+/// let svg = r#"
+///   <svg xmlns="http://www.w3.org/2000/svg">
+///     <rect x="2" y="2" width="10" height="10" />
+///     <circle cx="20" cy="7" r="5" transform="translate(0 10)" />
+///   </svg>
+/// "#;
+/// let shape = rsdf_svg::parse_shape(svg).unwrap();
+/// assert_eq!(shape.contours.len(), 2);
+/// assert_eq!(shape.sample_single_channel((20., 17.)), 5.);
+/// ```
+pub fn parse_shape(svg: &str) -> Result<Shape, SvgError> {
+  Ok(
+    element_paths(svg)?
+      .into_iter()
+      .fold(ShapeBuilder::new(), |builder, painted| {
+        painted.add_to(builder)
+      })
+      .build(),
+  )
+}
+
+/// Parse an SVG document into a single [`Shape`], as for [`parse_shape`],
+/// unless it's over the [`ShapeLimits`]
+///
+/// Use this for untrusted documents, so a huge one can't tie up the time it
+/// takes to sample its field.
+///
+/// ```
+/// # use rsdf_core::*;
+/// let svg = r#"
+///   <svg xmlns="http://www.w3.org/2000/svg">
+///     <rect x="2" y="2" width="10" height="10" />
+///     <rect x="20" y="2" width="10" height="10" />
+///   </svg>
+/// "#;
+/// let limits = ShapeLimits {
+///   max_segments: 4,
+///   ..ShapeLimits::default()
+/// };
+/// let error = rsdf_svg::parse_shape_with_limits(svg, limits).unwrap_err();
+/// assert!(matches!(error, rsdf_svg::SvgError::Limit(_)));
+/// ```
+pub fn parse_shape_with_limits(
+  svg: &str,
+  limits: ShapeLimits,
+) -> Result<Shape, SvgError> {
+  Ok(
+    element_paths(svg)?
+      .into_iter()
+      .fold(ShapeBuilder::new(), |builder, painted| {
+        painted.add_to(builder)
+      })
+      .try_build(limits)?,
+  )
+}
+
+/// Elements whose children aren't drawn directly
+const NON_RENDERED: &[&str] = &[
+  "defs",
+  "clipPath",
+  "mask",
+  "marker",
+  "pattern",
+  "symbol",
+  "linearGradient",
+  "radialGradient",
+];
+
+/// A path drawn by an element, in the coordinates of the document
+enum Painted {
+  /// The area inside a path, wound as in the document
+  Fill(Vec<PathCommand>),
+  /// The outline of a stroke, in either winding direction
+  Stroke(Vec<PathCommand>),
+}
+
+impl Painted {
+  fn add_to(self, builder: ShapeBuilder) -> ShapeBuilder {
+    match self {
+      Painted::Fill(path) => builder.path(path),
+      Painted::Stroke(outline) => builder.filled_path(outline),
+    }
+  }
+}
+
+/// The painting properties of an element, inherited by its children
+#[derive(Debug, Clone)]
+struct Paint {
+  fill: bool,
+  stroke: bool,
+  style: StrokeStyle,
+  dashes: Vec<f32>,
+  dash_offset: f32,
+}
+
+impl Default for Paint {
+  fn default() -> Self {
+    Paint {
+      fill: true,
+      stroke: false,
+      style: StrokeStyle::default(),
+      dashes: vec![],
+      dash_offset: 0.,
+    }
+  }
+}
+
+impl Paint {
+  /// The properties of an element, given those of its parent
+  fn inherit(&self, node: roxmltree::Node) -> Result<Paint, SvgError> {
+    let mut paint = self.clone();
+    for attribute in [
+      "fill",
+      "stroke",
+      "stroke-width",
+      "stroke-linecap",
+      "stroke-linejoin",
+      "stroke-miterlimit",
+      "stroke-dasharray",
+      "stroke-dashoffset",
+    ] {
+      let Some(value) = property(node, attribute) else {
+        continue;
+      };
+      let invalid = || invalid(node, attribute);
+      let style = &mut paint.style;
+      match attribute {
+        "fill" => paint.fill = value != "none",
+        "stroke" => paint.stroke = value != "none",
+        "stroke-width" => {
+          style.width = parse_length(value).ok_or_else(invalid)?;
+        },
+        "stroke-linecap" => {
+          style.cap = match value {
+            "butt" => LineCap::Butt,
+            "round" => LineCap::Round,
+            "square" => LineCap::Square,
+            _ => return Err(invalid()),
+          };
+        },
+        "stroke-linejoin" => {
+          style.join = match value {
+            "miter" | "miter-clip" | "arcs" => LineJoin::Miter,
+            "round" => LineJoin::Round,
+            "bevel" => LineJoin::Bevel,
+            _ => return Err(invalid()),
+          };
+        },
+        "stroke-miterlimit" => {
+          style.miter_limit = value
+            .parse()
+            .ok()
+            .filter(|&limit| limit >= 1.)
+            .ok_or_else(invalid)?;
+        },
+        "stroke-dasharray" if value == "none" => paint.dashes.clear(),
+        "stroke-dasharray" => {
+          paint.dashes = value
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|length| !length.is_empty())
+            .map(parse_length)
+            .collect::<Option<_>>()
+            .ok_or_else(invalid)?;
+        },
+        "stroke-dashoffset" => {
+          paint.dash_offset = parse_length(value).ok_or_else(invalid)?;
+        },
+        _ => unreachable!(),
+      }
+    }
+    Ok(paint)
+  }
+}
+
+/// The value of a property from an element's `style` declarations, or else
+/// its presentation attribute, unless it's inherited
+fn property<'a>(node: roxmltree::Node<'a, '_>, name: &str) -> Option<&'a str> {
+  let declared = node.attribute("style").and_then(|style| {
+    style.split(';').rev().find_map(|declaration| {
+      let (property, value) = declaration.split_once(':')?;
+      (property.trim() == name).then(|| value.trim())
+    })
+  });
+  declared
+    .or_else(|| node.attribute(name).map(str::trim))
+    .filter(|&value| value != "inherit")
+}
+
+/// The fill & stroke of each drawn element, in document order, transformed
+/// into the coordinates of the document
+fn element_paths(svg: &str) -> Result<Vec<Painted>, SvgError> {
+  let document = roxmltree::Document::parse(svg)?;
+  let mut painted = vec![];
+  walk(
+    document.root_element(),
+    Transform::IDENTITY,
+    &Paint::default(),
+    &mut painted,
+  )?;
+  Ok(painted)
+}
+
+fn walk(
+  node: roxmltree::Node,
+  parent_transform: Transform,
+  parent_paint: &Paint,
+  painted: &mut Vec<Painted>,
+) -> Result<(), SvgError> {
+  let name = node.tag_name().name();
+  if NON_RENDERED.contains(&name) {
+    return Ok(());
+  }
+  let transform = match node.attribute("transform") {
+    Some(value) => parent_transform.then_after(
+      Transform::parse(value).ok_or_else(|| invalid(node, "transform"))?,
+    ),
+    None => parent_transform,
+  };
+  let paint = parent_paint.inherit(node)?;
+
+  let path = element_path(node)?;
+  let apply = |path: Vec<PathCommand>| -> Vec<PathCommand> {
+    path
+      .into_iter()
+      .map(|command| transform.apply_to_command(command))
+      .collect()
+  };
+  if !path.is_empty() && paint.fill {
+    painted.push(Painted::Fill(apply(path.clone())));
+  }
+  if !path.is_empty() && paint.stroke && paint.style.width > 0. {
+    // the stroke is outlined in the element's own coordinates, where its
+    // width is defined, with the tolerance scaled to that of the document
+    let scale = (transform.a * transform.d - transform.b * transform.c)
+      .abs()
+      .sqrt();
+    let mut style = paint.style;
+    if scale > 0. {
+      style.tolerance /= scale;
+    }
+    let outline =
+      dashed_outline(path, style, &paint.dashes, paint.dash_offset);
+    if !outline.is_empty() {
+      painted.push(Painted::Stroke(apply(outline)));
+    }
+  }
+  for child in node.children().filter(roxmltree::Node::is_element) {
+    walk(child, transform, &paint, painted)?;
+  }
+  Ok(())
+}
+
+/// The untransformed path of a single element, which is empty if the element
+/// doesn't draw anything
+fn element_path(node: roxmltree::Node) -> Result<Vec<PathCommand>, SvgError> {
+  let number = |attribute| length(node, attribute);
+  let path = match node.tag_name().name() {
+    "path" => parse_svg_path(node.attribute("d").unwrap_or_default())?,
+    "polygon" => {
+      let points = node.attribute("points").unwrap_or_default();
+      if points.trim().is_empty() {
+        vec![]
+      } else {
+        parse_svg_path(&format!("M{points}Z"))?
+      }
+    },
+    "rect" => {
+      let [x, y] = [number("x")?, number("y")?].map(Option::unwrap_or_default);
+      let width = number("width")?.unwrap_or_default();
+      let height = number("height")?.unwrap_or_default();
+      // a missing radius takes the value of the other one
+      let (rx, ry) = match (number("rx")?, number("ry")?) {
+        (Some(rx), Some(ry)) => (rx, ry),
+        (Some(r), None) | (None, Some(r)) => (r, r),
+        (None, None) => (0., 0.),
+      };
+      if width <= 0. || height <= 0. {
+        return Ok(vec![]);
+      }
+      rect(x, y, width, height, rx.min(width / 2.), ry.min(height / 2.))
+    },
+    "circle" => {
+      let r = number("r")?.unwrap_or_default();
+      ellipse(number("cx")?, number("cy")?, r, r)
+    },
+    "ellipse" => {
+      let rx = number("rx")?.unwrap_or_default();
+      let ry = number("ry")?.unwrap_or_default();
+      ellipse(number("cx")?, number("cy")?, rx, ry)
+    },
+    _ => vec![],
+  };
+  Ok(path)
+}
+
+fn rect(x: f32, y: f32, w: f32, h: f32, rx: f32, ry: f32) -> Vec<PathCommand> {
+  use PathCommand::*;
+  let corner = |end: (f32, f32)| ArcTo {
+    rx,
+    ry,
+    phi: 0.,
+    large_arc: false,
+    sweep: true,
+    end: Point::new(end.0, end.1),
+  };
+  let line = |end: (f32, f32)| LineTo(Point::new(end.0, end.1));
+  if rx <= 0. || ry <= 0. {
+    return vec![
+      MoveTo(Point::new(x, y)),
+      line((x + w, y)),
+      line((x + w, y + h)),
+      line((x, y + h)),
+      ClosePath,
+    ];
+  }
+  vec![
+    MoveTo(Point::new(x + rx, y)),
+    line((x + w - rx, y)),
+    corner((x + w, y + ry)),
+    line((x + w, y + h - ry)),
+    corner((x + w - rx, y + h)),
+    line((x + rx, y + h)),
+    corner((x, y + h - ry)),
+    line((x, y + ry)),
+    corner((x + rx, y)),
+    ClosePath,
+  ]
+}
+
+fn ellipse(
+  cx: Option<f32>,
+  cy: Option<f32>,
+  rx: f32,
+  ry: f32,
+) -> Vec<PathCommand> {
+  if rx <= 0. || ry <= 0. {
+    return vec![];
+  }
+  let [cx, cy] = [cx, cy].map(Option::unwrap_or_default);
+  let half = |end| PathCommand::ArcTo {
+    rx,
+    ry,
+    phi: 0.,
+    large_arc: false,
+    sweep: true,
+    end,
+  };
+  vec![
+    PathCommand::MoveTo(Point::new(cx + rx, cy)),
+    half(Point::new(cx - rx, cy)),
+    half(Point::new(cx + rx, cy)),
+    PathCommand::ClosePath,
+  ]
+}
+
+/// Read a length attribute in user units, allowing an explicit `px` suffix
+fn length(
+  node: roxmltree::Node,
+  attribute: &'static str,
+) -> Result<Option<f32>, SvgError> {
+  let Some(value) = node.attribute(attribute) else {
+    return Ok(None);
+  };
+  parse_length(value)
+    .map(Some)
+    .ok_or_else(|| invalid(node, attribute))
+}
+
+fn parse_length(value: &str) -> Option<f32> {
+  let value = value.trim();
+  value.strip_suffix("px").unwrap_or(value).parse().ok()
+}
+
+fn invalid(node: roxmltree::Node, attribute: &'static str) -> SvgError {
+  SvgError::Attribute {
+    element: node.tag_name().name().to_owned(),
+    attribute,
+  }
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+  use float_cmp::assert_approx_eq;
+
+  fn svg(body: &str) -> String {
+    format!(r#"<svg xmlns="http://www.w3.org/2000/svg">{body}</svg>"#)
+  }
+
+  #[test]
+  fn elements() {
+    let shapes = parse_shapes(&svg(
+      r#"
+      <path d="M 10 10 H 20 V 20 H 10 Z" />
+      <polygon points="10,10 20,10 20,20 10,20" />
+      <rect x="10" y="10" width="10" height="10" />
+      <rect x="10" y="10" width="10px" height="0" />
+      <circle cx="15" cy="15" r="5" />
+      <ellipse cx="15" cy="15" rx="5" ry="5" />
+      <defs><rect x="10" y="10" width="10" height="10" /></defs>
+    "#,
+    ))
+    .unwrap();
+    assert_eq!(shapes.len(), 5);
+
+    for square in &shapes[..3] {
+      assert_eq!(square.checksum(), shapes[0].checksum());
+    }
+    for circle in &shapes[3..] {
+      assert_approx_eq!(f32, circle.sample_single_channel((15., 15.)), 5.);
+      assert_approx_eq!(f32, circle.sample_single_channel((15., 7.)), -3.);
+      assert_approx_eq!(f32, circle.sample_single_channel((18., 19.)), 0.);
+    }
+  }
+
+  #[test]
+  fn rounded_rect() {
+    let shape = parse_shape(&svg(
+      r#"<rect x="0" y="0" width="20" height="10" rx="5" />"#,
+    ))
+    .unwrap();
+    // fully rounded ends, since the radius is half the height
+    assert_eq!(shape.segments.len(), 6);
+    assert_approx_eq!(f32, shape.sample_single_channel((10., 5.)), 5.);
+    assert_approx_eq!(f32, shape.sample_single_channel((5., 5.)), 5.);
+    assert_approx_eq!(f32, shape.sample_single_channel((-1., 5.)), -1.);
+    let corner = 5. - 50f32.sqrt();
+    assert_approx_eq!(f32, shape.sample_single_channel((0., 0.)), corner);
+  }
+
+  #[test]
+  fn transforms() {
+    let shape = parse_shape(&svg(
+      r#"
+      <g transform="translate(100 0)">
+        <rect width="10" height="10" transform="rotate(45 5 5)" />
+        <circle r="5" transform="translate(0 50) scale(2 1)" />
+      </g>
+    "#,
+    ))
+    .unwrap();
+    // the rotated square
+    let half_diagonal = 50f32.sqrt();
+    let top = Point::new(105., 5. - half_diagonal);
+    assert_approx_eq!(
+      f32,
+      shape.sample_single_channel(top),
+      0.,
+      epsilon = 0.0001
+    );
+    assert_approx_eq!(
+      f32,
+      shape.sample_single_channel((105., 5.)),
+      5.,
+      epsilon = 0.0001
+    );
+    // the stretched circle
+    assert_approx_eq!(
+      f32,
+      shape.sample_single_channel((100., 52.)),
+      3.,
+      epsilon = 0.0001
+    );
+    assert_approx_eq!(
+      f32,
+      shape.sample_single_channel((110., 50.)),
+      0.,
+      epsilon = 0.0001
+    );
+    assert_approx_eq!(
+      f32,
+      shape.sample_single_channel((100., 55.)),
+      0.,
+      epsilon = 0.0001
+    );
+  }
+
+  #[test]
+  fn strokes() {
+    let shapes = parse_shapes(&svg(
+      r#"
+      <path d="M 10 10 H 30" stroke="black" stroke-width="4" fill="none" />
+      <g fill="none" style="stroke: black; stroke-width: 2">
+        <rect x="10" y="10" width="20" height="20" />
+        <path d="M 10 10 H 30" stroke-width="4" stroke-linecap="round"
+          stroke-dasharray="5 5" stroke-dashoffset="0" />
+      </g>
+      <path d="M 0 0 H 2" fill="none" stroke="none" />
+    "#,
+    ))
+    .unwrap();
+    assert_eq!(shapes.len(), 3);
+
+    // an open path is stroked rather than filled
+    let line = &shapes[0];
+    assert_eq!(line.contours.len(), 1);
+    assert_approx_eq!(f32, line.sample_single_channel((20., 10.)), 2.);
+    assert_approx_eq!(f32, line.sample_single_channel((20., 15.)), -3.);
+
+    // the outline of a closed stroke has a hole
+    let frame = &shapes[1];
+    assert_eq!(frame.contours.len(), 2);
+    assert_approx_eq!(f32, frame.sample_single_channel((20., 10.)), 1.);
+    assert_approx_eq!(f32, frame.sample_single_channel((20., 20.)), -9.);
+
+    // dashes from 10 to 15 & 20 to 25, with round caps
+    let dashes = &shapes[2];
+    assert_eq!(dashes.contours.len(), 2);
+    assert_approx_eq!(f32, dashes.sample_single_channel((12., 10.)), 2.);
+    assert_approx_eq!(
+      f32,
+      dashes.sample_single_channel((17.5, 10.)),
+      -0.5,
+      epsilon = 0.0001
+    );
+    assert_approx_eq!(
+      f32,
+      dashes.sample_single_channel((26., 10.)),
+      1.,
+      epsilon = 0.0001
+    );
+  }
+
+  #[test]
+  fn transformed_strokes() {
+    // the width is scaled along with the path
+    let shape = parse_shape(&svg(
+      r#"<path d="M 0 5 H 10" transform="scale(2)" fill="none"
+        stroke="black" stroke-width="2" />"#,
+    ))
+    .unwrap();
+    assert_approx_eq!(
+      f32,
+      shape.sample_single_channel((10., 10.)),
+      2.,
+      epsilon = 0.0001
+    );
+    assert_approx_eq!(
+      f32,
+      shape.sample_single_channel((10., 15.)),
+      -3.,
+      epsilon = 0.0001
+    );
+  }
 
-// TODO: create svg front-end
+  #[test]
+  fn errors() {
+    let error = |body| parse_shapes(&svg(body)).unwrap_err();
+    assert!(matches!(
+      error(r#"<path d="L 1 1" />"#),
+      SvgError::Path(PathError::MissingMoveTo)
+    ));
+    assert!(matches!(
+      error(r#"<rect width="10%" height="1" />"#),
+      SvgError::Attribute {
+        attribute: "width",
+        ..
+      }
+    ));
+    assert!(matches!(
+      error(r#"<g transform="spin(1)" />"#),
+      SvgError::Attribute {
+        attribute: "transform",
+        ..
+      }
+    ));
+    assert!(matches!(
+      error(r#"<path d="M 0 0 H 1" stroke-linecap="pointy" />"#),
+      SvgError::Attribute {
+        attribute: "stroke-linecap",
+        ..
+      }
+    ));
+    assert!(matches!(
+      error(r#"<path d="M 0 0 H 1" style="stroke-dasharray: 1 x" />"#),
+      SvgError::Attribute {
+        attribute: "stroke-dasharray",
+        ..
+      }
+    ));
+    assert!(matches!(parse_shapes("<svg>"), Err(SvgError::Xml(_))));
+  }
+}
@@ -0,0 +1,255 @@
+use crate::*;
+
+/// An affine transform, as given by an SVG `transform` attribute
+///
+/// Maps `(x, y)` to `(a x + c y + e, b x + d y + f)`, following the SVG
+/// `matrix(a b c d e f)` convention.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+  pub a: f32,
+  pub b: f32,
+  pub c: f32,
+  pub d: f32,
+  pub e: f32,
+  pub f: f32,
+}
+
+impl Default for Transform {
+  fn default() -> Self {
+    Self::IDENTITY
+  }
+}
+
+impl Transform {
+  pub const IDENTITY: Transform = Transform {
+    a: 1.,
+    b: 0.,
+    c: 0.,
+    d: 1.,
+    e: 0.,
+    f: 0.,
+  };
+
+  pub fn translate(x: f32, y: f32) -> Self {
+    Transform {
+      e: x,
+      f: y,
+      ..Self::IDENTITY
+    }
+  }
+
+  pub fn scale(x: f32, y: f32) -> Self {
+    Transform {
+      a: x,
+      d: y,
+      ..Self::IDENTITY
+    }
+  }
+
+  /// A rotation by `angle` radians, clockwise with y pointing down
+  pub fn rotate(angle: f32) -> Self {
+    let (sin, cos) = angle.sin_cos();
+    Transform {
+      a: cos,
+      b: sin,
+      c: -sin,
+      d: cos,
+      ..Self::IDENTITY
+    }
+  }
+
+  /// The transform that applies `other` first, then `self`
+  #[must_use]
+  pub fn then_after(self, other: Transform) -> Transform {
+    Transform {
+      a: self.a * other.a + self.c * other.b,
+      b: self.b * other.a + self.d * other.b,
+      c: self.a * other.c + self.c * other.d,
+      d: self.b * other.c + self.d * other.d,
+      e: self.a * other.e + self.c * other.f + self.e,
+      f: self.b * other.e + self.d * other.f + self.f,
+    }
+  }
+
+  pub fn apply(self, point: Point) -> Point {
+    Point::new(
+      self.a * point.x + self.c * point.y + self.e,
+      self.b * point.x + self.d * point.y + self.f,
+    )
+  }
+
+  /// Transform a path command
+  ///
+  /// Arcs are refitted to the transformed ellipse. A transform that mirrors
+  /// the plane reverses the direction of the path, and so the winding of its
+  /// contours.
+  pub fn apply_to_command(self, command: PathCommand) -> PathCommand {
+    let p = |point| self.apply(point);
+    match command {
+      PathCommand::MoveTo(point) => PathCommand::MoveTo(p(point)),
+      PathCommand::LineTo(point) => PathCommand::LineTo(p(point)),
+      PathCommand::QuadTo(control, end) => {
+        PathCommand::QuadTo(p(control), p(end))
+      },
+      PathCommand::CubicTo(control_1, control_2, end) => {
+        PathCommand::CubicTo(p(control_1), p(control_2), p(end))
+      },
+      PathCommand::ArcTo {
+        rx,
+        ry,
+        phi,
+        large_arc,
+        sweep,
+        end,
+      } => {
+        // the columns of `m` are the axes of the transformed ellipse, which
+        // are no longer orthogonal in general; its singular values and left
+        // singular vectors give the new radii and rotation
+        let (sin, cos) = phi.sin_cos();
+        let m00 = (self.a * cos + self.c * sin) * rx;
+        let m10 = (self.b * cos + self.d * sin) * rx;
+        let m01 = (-self.a * sin + self.c * cos) * ry;
+        let m11 = (-self.b * sin + self.d * cos) * ry;
+        let p = m00 * m00 + m01 * m01;
+        let q = m00 * m10 + m01 * m11;
+        let r = m10 * m10 + m11 * m11;
+        let mean = (p + r) / 2.;
+        let spread = (((p - r) / 2.).powi(2) + q * q).sqrt();
+        let determinant = self.a * self.d - self.b * self.c;
+        PathCommand::ArcTo {
+          rx: (mean + spread).sqrt(),
+          ry: (mean - spread).max(0.).sqrt(),
+          phi: 0.5 * (2. * q).atan2(p - r),
+          large_arc,
+          sweep: sweep != (determinant < 0.),
+          end: self.apply(end),
+        }
+      },
+      PathCommand::ClosePath => PathCommand::ClosePath,
+    }
+  }
+
+  /// Parse the value of a `transform` attribute
+  pub fn parse(value: &str) -> Option<Transform> {
+    let mut transform = Transform::IDENTITY;
+    let mut rest = value.trim();
+    while !rest.is_empty() {
+      let open = rest.find('(')?;
+      let close = rest.find(')')?;
+      let name = rest[..open].trim();
+      let args = parse_numbers(&rest[open + 1..close])?;
+      let next = match (name, args.as_slice()) {
+        ("matrix", &[a, b, c, d, e, f]) => Transform { a, b, c, d, e, f },
+        ("translate", &[x]) => Transform::translate(x, 0.),
+        ("translate", &[x, y]) => Transform::translate(x, y),
+        ("scale", &[s]) => Transform::scale(s, s),
+        ("scale", &[x, y]) => Transform::scale(x, y),
+        ("rotate", &[angle]) => Transform::rotate(angle.to_radians()),
+        ("rotate", &[angle, x, y]) => Transform::translate(x, y)
+          .then_after(Transform::rotate(angle.to_radians()))
+          .then_after(Transform::translate(-x, -y)),
+        ("skewX", &[angle]) => Transform {
+          c: angle.to_radians().tan(),
+          ..Transform::IDENTITY
+        },
+        ("skewY", &[angle]) => Transform {
+          b: angle.to_radians().tan(),
+          ..Transform::IDENTITY
+        },
+        _ => return None,
+      };
+      transform = transform.then_after(next);
+      rest = rest[close + 1..]
+        .trim_start_matches(|c: char| c.is_ascii_whitespace() || c == ',');
+    }
+    Some(transform)
+  }
+}
+
+fn parse_numbers(list: &str) -> Option<Vec<f32>> {
+  list
+    .split(|c: char| c.is_ascii_whitespace() || c == ',')
+    .filter(|number| !number.is_empty())
+    .map(|number| number.parse().ok())
+    .collect()
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+  use float_cmp::assert_approx_eq;
+
+  #[test]
+  fn parse() {
+    let transform = Transform::parse("translate(10 20) scale(2)").unwrap();
+    assert_eq!(transform.apply(Point::new(1., 1.)), Point::new(12., 22.));
+
+    let transform = Transform::parse("rotate(90, 5 5)").unwrap();
+    assert_approx_eq!(
+      Point,
+      transform.apply(Point::new(10., 5.)),
+      Point::new(5., 10.)
+    );
+
+    assert_eq!(
+      Transform::parse(" matrix(1,2,3,4,5,6) ").unwrap(),
+      Transform {
+        a: 1.,
+        b: 2.,
+        c: 3.,
+        d: 4.,
+        e: 5.,
+        f: 6.
+      }
+    );
+    assert_eq!(Transform::parse(""), Some(Transform::IDENTITY));
+    assert_eq!(Transform::parse("scale(1 2 3)"), None);
+    assert_eq!(Transform::parse("shear(1)"), None);
+  }
+
+  #[test]
+  fn arcs() {
+    let arc = PathCommand::ArcTo {
+      rx: 2.,
+      ry: 1.,
+      phi: 0.,
+      large_arc: false,
+      sweep: true,
+      end: Point::new(4., 0.),
+    };
+    let rotated = Transform::rotate(std::f32::consts::FRAC_PI_2);
+    let PathCommand::ArcTo {
+      rx,
+      ry,
+      phi,
+      sweep,
+      end,
+      ..
+    } = rotated.apply_to_command(arc)
+    else {
+      unreachable!()
+    };
+    assert_approx_eq!(f32, rx, 2.);
+    assert_approx_eq!(f32, ry, 1.);
+    // an ellipse rotated by half a turn is the same ellipse
+    let phi = phi.rem_euclid(std::f32::consts::PI);
+    assert_approx_eq!(f32, phi, std::f32::consts::FRAC_PI_2);
+    assert!(sweep);
+    assert_approx_eq!(Point, end, Point::new(0., 4.), epsilon = 0.0001);
+
+    // stretching the short axis makes it the long one, and mirroring
+    // reverses the sweep
+    let stretched = Transform::scale(-1., 4.);
+    let PathCommand::ArcTo {
+      rx, ry, phi, sweep, ..
+    } = stretched.apply_to_command(arc)
+    else {
+      unreachable!()
+    };
+    assert_approx_eq!(f32, rx, 4.);
+    assert_approx_eq!(f32, ry, 2.);
+    let phi = phi.rem_euclid(std::f32::consts::PI);
+    assert_approx_eq!(f32, phi, std::f32::consts::FRAC_PI_2);
+    assert!(!sweep);
+  }
+}
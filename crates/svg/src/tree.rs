@@ -0,0 +1,192 @@
+use crate::*;
+use usvg::tiny_skia_path::{self, PathSegment, PathStroker};
+
+/// Convert the painted paths of a [`usvg::Tree`], with one [`Shape`] per fill
+/// or stroke, in document order
+///
+/// usvg has already resolved the document's styling, `<use>` references and
+/// transforms, and converted its basic shapes into paths. Strokes, including
+/// dashes, are converted into the outline they cover by
+/// [`dashed_outline`], as for [`parse_shapes`](crate::parse_shapes), and
+/// each outline is wound so that its inside has positive distances. Text is included if
+/// usvg flattened it into paths. Clip paths, masks and images are ignored.
+pub fn tree_shapes(tree: &usvg::Tree) -> Vec<Shape> {
+  tree_outlines(tree)
+    .into_iter()
+    .map(|outline| ShapeBuilder::new().filled_path(outline).build())
+    .collect()
+}
+
+/// Convert the painted paths of a [`usvg::Tree`] into a single [`Shape`],
+/// see [`tree_shapes`]
+///
+/// ```
+/// let svg = r#"
+///   <svg xmlns="http://www.w3.org/2000/svg">
+///     <rect x="2" y="2" width="10" height="10" />
+///     <path d="M 20 0 V 14" stroke="black" stroke-width="4" />
+///   </svg>
+/// "#;
+/// let tree = usvg::Tree::from_str(svg, &usvg::Options::default()).unwrap();
+/// let shape = rsdf_svg::tree_shape(&tree);
+/// assert_eq!(shape.contours.len(), 2);
+/// assert_eq!(shape.sample_single_channel((20., 7.)), 2.);
+/// ```
+pub fn tree_shape(tree: &usvg::Tree) -> Shape {
+  tree_outlines(tree)
+    .into_iter()
+    .fold(ShapeBuilder::new(), ShapeBuilder::filled_path)
+    .build()
+}
+
+/// The outline of every fill & stroke, in the coordinates of the document,
+/// in either winding direction
+fn tree_outlines(tree: &usvg::Tree) -> Vec<Vec<PathCommand>> {
+  let mut outlines = vec![];
+  walk_group(tree.root(), &mut outlines);
+  outlines
+}
+
+fn walk_group(group: &usvg::Group, outlines: &mut Vec<Vec<PathCommand>>) {
+  for node in group.children() {
+    match node {
+      usvg::Node::Group(group) => walk_group(group, outlines),
+      usvg::Node::Path(path) => path_outlines(path, outlines),
+      usvg::Node::Text(text) => walk_group(text.flattened(), outlines),
+      usvg::Node::Image(_) => {},
+    }
+  }
+}
+
+fn path_outlines(path: &usvg::Path, outlines: &mut Vec<Vec<PathCommand>>) {
+  if !path.is_visible() {
+    return;
+  }
+  let transform = path.abs_transform();
+  if path.fill().is_some() {
+    if let Some(data) = path.data().clone().transform(transform) {
+      outlines.push(commands(&data));
+    }
+  }
+  let Some(stroke) = path.stroke() else {
+    return;
+  };
+  // stroke in the path's own coordinates, where the width is defined, with
+  // enough precision for the size it will be drawn at
+  let resolution = PathStroker::compute_resolution_scale(&transform);
+  let style = StrokeStyle {
+    width: stroke.width().get(),
+    cap: match stroke.linecap() {
+      usvg::LineCap::Butt => LineCap::Butt,
+      usvg::LineCap::Round => LineCap::Round,
+      usvg::LineCap::Square => LineCap::Square,
+    },
+    join: match stroke.linejoin() {
+      usvg::LineJoin::Miter | usvg::LineJoin::MiterClip => LineJoin::Miter,
+      usvg::LineJoin::Round => LineJoin::Round,
+      usvg::LineJoin::Bevel => LineJoin::Bevel,
+    },
+    miter_limit: stroke.miterlimit().get(),
+    tolerance: StrokeStyle::default().tolerance / resolution,
+  };
+  let dashes = stroke.dasharray().unwrap_or_default();
+  let outline =
+    dashed_outline(commands(path.data()), style, dashes, stroke.dashoffset());
+  if !outline.is_empty() {
+    let transform = Transform {
+      a: transform.sx,
+      b: transform.ky,
+      c: transform.kx,
+      d: transform.sy,
+      e: transform.tx,
+      f: transform.ty,
+    };
+    outlines.push(
+      outline
+        .into_iter()
+        .map(|command| transform.apply_to_command(command))
+        .collect(),
+    );
+  }
+}
+
+fn commands(data: &tiny_skia_path::Path) -> Vec<PathCommand> {
+  let point = |p: tiny_skia_path::Point| Point::new(p.x, p.y);
+  data
+    .segments()
+    .map(|segment| match segment {
+      PathSegment::MoveTo(p) => PathCommand::MoveTo(point(p)),
+      PathSegment::LineTo(p) => PathCommand::LineTo(point(p)),
+      PathSegment::QuadTo(c, p) => PathCommand::QuadTo(point(c), point(p)),
+      PathSegment::CubicTo(c1, c2, p) => {
+        PathCommand::CubicTo(point(c1), point(c2), point(p))
+      },
+      PathSegment::Close => PathCommand::ClosePath,
+    })
+    .collect()
+}
+
+#[cfg(any(test, doctest))]
+mod tests {
+  use super::*;
+  use float_cmp::assert_approx_eq;
+
+  fn tree(body: &str) -> usvg::Tree {
+    let svg = format!(
+      r#"<svg xmlns="http://www.w3.org/2000/svg" width="40" height="40">
+        {body}
+      </svg>"#
+    );
+    usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap()
+  }
+
+  #[test]
+  fn fills() {
+    let shapes = tree_shapes(&tree(
+      r#"
+      <path d="M 10 10 H 20 V 20 H 10 Z" />
+      <path d="M 10 10 V 20 H 20 V 10 Z" />
+      <g transform="translate(10 10)">
+        <rect width="10" height="10" transform="scale(-1 1) translate(-10)" />
+      </g>
+      <rect x="10" y="10" width="10" height="10" fill="none" />
+      <rect x="10" y="10" width="10" height="10" visibility="hidden" />
+    "#,
+    ));
+    assert_eq!(shapes.len(), 3);
+    // both windings, and mirrored transforms, have a positive inside
+    for shape in &shapes {
+      assert_approx_eq!(f32, shape.sample_single_channel((15., 15.)), 5.);
+      assert_approx_eq!(f32, shape.sample_single_channel((15., 8.)), -2.);
+    }
+  }
+
+  #[test]
+  fn strokes() {
+    let shapes = tree_shapes(&tree(
+      r#"
+      <path d="M 10 10 H 30" stroke="black" stroke-width="4" fill="none" />
+      <rect x="10" y="10" width="20" height="20" fill="none"
+        stroke="black" stroke-width="2" />
+      <path d="M 10 10 H 30" stroke="black" stroke-width="4"
+        stroke-dasharray="5 5" fill="none" />
+    "#,
+    ));
+    assert_eq!(shapes.len(), 3);
+
+    let line = &shapes[0];
+    assert_approx_eq!(f32, line.sample_single_channel((20., 10.)), 2.);
+    assert_approx_eq!(f32, line.sample_single_channel((20., 15.)), -3.);
+
+    // the outline of a closed stroke has a hole
+    let frame = &shapes[1];
+    assert_eq!(frame.contours.len(), 2);
+    assert_approx_eq!(f32, frame.sample_single_channel((20., 10.)), 1.);
+    assert_approx_eq!(f32, frame.sample_single_channel((20., 20.)), -9.);
+
+    let dashes = &shapes[2];
+    assert_eq!(dashes.contours.len(), 2);
+    assert_approx_eq!(f32, dashes.sample_single_channel((12., 10.)), 2.);
+    assert_approx_eq!(f32, dashes.sample_single_channel((17.5, 10.)), -2.5);
+  }
+}
@@ -0,0 +1,30 @@
+//! Decode arbitrary bytes as a binary shape, then sample it, and check that
+//! it survives being encoded again.
+//!
+//! Run with `cargo fuzz run binary` from the repository root.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rsdf_core::*;
+
+fuzz_target!(|data: &[u8]| {
+  let Ok(shape) = Shape::from_bytes(data) else {
+    return;
+  };
+  let limits = ShapeLimits::default();
+  let counts = (shape.contours.len(), shape.segments.len(), shape.points.len());
+  if limits.check(counts.0, counts.1, counts.2).is_err() {
+    return;
+  }
+  let encoded = shape.to_bytes();
+  let decoded = Shape::from_bytes(&encoded).expect("re-encoded shape");
+  assert_eq!(decoded.to_bytes(), encoded);
+  for y in -2..6 {
+    for x in -2..6 {
+      let point = Point::new(x as f32 * 16., y as f32 * 16.);
+      let _ = shape.sample(point);
+      let _ = shape.sample_single_channel(point);
+    }
+  }
+});
@@ -0,0 +1,80 @@
+//! Feed arbitrary sequences of builder operations into the [`ShapeBuilder`],
+//! then sample the resulting shape.
+//!
+//! Run with `cargo fuzz run builder` from the repository root.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rsdf_builder::*;
+use rsdf_core::*;
+
+/// Reads coordinates out of the fuzzer's input
+struct Input<'a>(&'a [u8]);
+
+impl Input<'_> {
+  fn byte(&mut self) -> Option<u8> {
+    let (&first, rest) = self.0.split_first()?;
+    self.0 = rest;
+    Some(first)
+  }
+
+  /// Coordinates are kept to a modest range, since the interesting failures
+  /// come from the geometry rather than from float overflow
+  fn coord(&mut self) -> Option<f32> {
+    let bytes = [self.byte()?, self.byte()?];
+    Some(i16::from_le_bytes(bytes) as f32 / 256.)
+  }
+
+  fn point(&mut self) -> Option<(f32, f32)> {
+    Some((self.coord()?, self.coord()?))
+  }
+}
+
+fn build(input: &mut Input) -> Option<Shape> {
+  let mut shape = ShapeBuilder::new();
+  loop {
+    let mut contour = shape.contour(input.point()?);
+    loop {
+      contour = match input.byte()? % 6 {
+        0 => contour.line(input.point()?),
+        1 => contour.quadratic_bezier(input.point()?, input.point()?),
+        2 => {
+          contour.cubic_bezier(input.point()?, input.point()?, input.point()?)
+        },
+        3 => {
+          let (rx, ry) = input.point()?;
+          let flags = input.byte()?;
+          contour.elliptical_arc(
+            rx.abs() + 0.1,
+            ry.abs() + 0.1,
+            input.coord()?,
+            flags & 1 == 1,
+            flags & 2 == 2,
+            input.point()?,
+          )
+        },
+        4 => break shape = contour.end_contour(),
+        _ => break shape = contour.end_open_contour(),
+      }
+    }
+    if input.byte()? % 4 == 0 {
+      return Some(shape.build());
+    }
+  }
+}
+
+fuzz_target!(|data: &[u8]| {
+  let mut input = Input(data);
+  let Some(shape) = build(&mut input) else {
+    return;
+  };
+  for y in -2..6 {
+    for x in -2..6 {
+      let point = Point::new(x as f32 * 16., y as f32 * 16.);
+      let _ = shape.sample(point);
+      let _ = shape.sample_single_channel(point);
+      let _ = shape.sample_unsigned(point);
+    }
+  }
+});
@@ -0,0 +1,28 @@
+//! Parse arbitrary strings as msdfgen shape descriptions, then sample the
+//! resulting shape.
+//!
+//! Run with `cargo fuzz run shape_description` from the repository root.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rsdf_builder::parse_shape_description;
+use rsdf_core::*;
+
+fuzz_target!(|data: &str| {
+  let Ok(shape) = parse_shape_description(data) else {
+    return;
+  };
+  let limits = ShapeLimits::default();
+  let counts = (shape.contours.len(), shape.segments.len(), shape.points.len());
+  if limits.check(counts.0, counts.1, counts.2).is_err() {
+    return;
+  }
+  for y in -2..6 {
+    for x in -2..6 {
+      let point = Point::new(x as f32 * 16., -y as f32 * 16.);
+      let _ = shape.sample(point);
+      let _ = shape.sample_single_channel(point);
+    }
+  }
+});
@@ -0,0 +1,29 @@
+//! Sample shapes from the procedural stress corpus, picking the pathology and
+//! seed from the fuzzer's input.
+//!
+//! Run with `cargo fuzz run stress` from the repository root.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rsdf_builder::stress::{generate, Pathology, Rng};
+use rsdf_core::Point;
+
+fuzz_target!(|data: &[u8]| {
+  let Some((&kind, rest)) = data.split_first() else {
+    return;
+  };
+  let pathology = Pathology::ALL[kind as usize % Pathology::ALL.len()];
+  let mut seed = [0; 8];
+  let len = rest.len().min(8);
+  seed[..len].copy_from_slice(&rest[..len]);
+  let seed = u64::from_le_bytes(seed);
+  let shape = generate(pathology, &mut Rng::new(seed));
+  for y in (-8..72).step_by(4) {
+    for x in (-8..72).step_by(4) {
+      let point = Point::new(x as f32, y as f32);
+      let _ = shape.sample(point);
+      let _ = shape.sample_single_channel(point);
+    }
+  }
+});
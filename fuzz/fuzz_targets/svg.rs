@@ -0,0 +1,23 @@
+//! Parse arbitrary strings as SVG documents, then sample the resulting
+//! shape.
+//!
+//! Run with `cargo fuzz run svg` from the repository root.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rsdf_core::*;
+
+fuzz_target!(|data: &str| {
+  let limits = ShapeLimits::default();
+  let Ok(shape) = rsdf_svg::parse_shape_with_limits(data, limits) else {
+    return;
+  };
+  for y in -2..6 {
+    for x in -2..6 {
+      let point = Point::new(x as f32 * 16., y as f32 * 16.);
+      let _ = shape.sample(point);
+      let _ = shape.sample_single_channel(point);
+    }
+  }
+});
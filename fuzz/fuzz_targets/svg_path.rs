@@ -0,0 +1,26 @@
+//! Parse arbitrary strings as SVG path data, then sample the resulting
+//! shape.
+//!
+//! Run with `cargo fuzz run svg_path` from the repository root.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rsdf_builder::*;
+use rsdf_core::*;
+
+fuzz_target!(|data: &str| {
+  let Ok(builder) = ShapeBuilder::from_svg_path(data) else {
+    return;
+  };
+  let Ok(shape) = builder.try_build(ShapeLimits::default()) else {
+    return;
+  };
+  for y in -2..6 {
+    for x in -2..6 {
+      let point = Point::new(x as f32 * 16., y as f32 * 16.);
+      let _ = shape.sample(point);
+      let _ = shape.sample_single_channel(point);
+    }
+  }
+});